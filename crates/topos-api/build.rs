@@ -69,6 +69,14 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             ".topos.tce.v1.Ready",
             "#[derive(serde::Deserialize, serde::Serialize)]",
         )
+        .type_attribute(
+            ".topos.tce.v1.CheckpointSummary",
+            "#[derive(serde::Deserialize, serde::Serialize)]",
+        )
+        .type_attribute(
+            ".topos.tce.v1.CheckpointSignature",
+            "#[derive(serde::Deserialize, serde::Serialize)]",
+        )
         .type_attribute(
             ".topos.tce.v1.DoubleEchoRequest",
             "#[derive(serde::Deserialize, serde::Serialize)]",
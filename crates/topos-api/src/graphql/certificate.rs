@@ -23,6 +23,24 @@ pub struct Certificate {
     pub verifier: u32,
 }
 
+/// Certificate along with its position in the delivery order of the node
+#[derive(Debug, Default, Serialize, Deserialize, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct SequencedCertificate {
+    pub sequence_number: u64,
+    pub certificate: Certificate,
+}
+
+/// Certificate along with the times it was received and delivered by the node,
+/// as unix timestamps in milliseconds
+#[derive(Debug, Default, Serialize, Deserialize, SimpleObject)]
+#[serde(rename_all = "camelCase")]
+pub struct TimestampedCertificate {
+    pub received_at: u64,
+    pub delivered_at: u64,
+    pub certificate: Certificate,
+}
+
 impl From<&topos_uci::Certificate> for Certificate {
     fn from(uci_cert: &topos_uci::Certificate) -> Self {
         Self {
@@ -20,4 +20,7 @@ pub enum GraphQLServerError {
 
     #[error("Internal API error: {0}")]
     InternalError(&'static str),
+
+    #[error("At most {max} certificates can be requested at once, {requested} were requested")]
+    PageTooLarge { requested: usize, max: usize },
 }
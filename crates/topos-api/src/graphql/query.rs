@@ -1,6 +1,9 @@
-use crate::graphql::certificate::{Certificate, CertificateId};
+use crate::graphql::certificate::{
+    Certificate, CertificateId, SequencedCertificate, TimestampedCertificate,
+};
 use crate::graphql::checkpoint::SourceCheckpoint;
 use crate::graphql::errors::GraphQLServerError;
+use crate::graphql::filter::SubnetFilter;
 
 use async_graphql::Context;
 use async_trait::async_trait;
@@ -17,4 +20,18 @@ pub trait CertificateQuery {
         ctx: &Context<'_>,
         certificate_id: CertificateId,
     ) -> Result<Certificate, GraphQLServerError>;
+
+    async fn certificates_from_sequence_number(
+        ctx: &Context<'_>,
+        from_sequence_number: u64,
+        first: usize,
+    ) -> Result<Vec<SequencedCertificate>, GraphQLServerError>;
+
+    async fn certificates_by_delivery_time(
+        ctx: &Context<'_>,
+        filter: SubnetFilter,
+        from: u64,
+        to: u64,
+        first: usize,
+    ) -> Result<Vec<TimestampedCertificate>, GraphQLServerError>;
 }
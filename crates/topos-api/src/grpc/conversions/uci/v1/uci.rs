@@ -45,8 +45,14 @@ impl TryFrom<proto_v1::Certificate> for topos_uci::Certificate {
                 .value
                 .as_slice()
                 .try_into()?,
-            proof: certificate.proof.expect("valid proof").value,
-            signature: certificate.signature.expect("valid frost signature").value,
+            proof: certificate
+                .proof
+                .ok_or(Error::MissingField("certificate.proof"))?
+                .value,
+            signature: certificate
+                .signature
+                .ok_or(Error::MissingField("certificate.signature"))?
+                .value,
         })
     }
 }
@@ -134,18 +140,17 @@ fn test_proto_uci_certificate_conversion_id_random_0x() {
 }
 
 #[test]
-fn test_proto_uci_certificate_conversion_id_starts_with_0x() {
+fn test_proto_uci_certificate_conversion_id_starts_with_0x() -> Result<(), hex::FromHexError> {
     use crate::grpc::shared::v1::{CertificateId, Frost, StarkProof, SubnetId};
     let mut prev_id = vec![b'0', b'x'];
-    prev_id.append(
-        &mut hex::decode("aac03cadfff6846c9ce72956eee2498011dd7b08689565d6f29e25c0a967ef14")
-            .expect("Valid id"),
-    );
+    prev_id.append(&mut hex::decode(
+        "aac03cadfff6846c9ce72956eee2498011dd7b08689565d6f29e25c0a967ef14",
+    )?);
     let id = "504b5d01948bc777ba1510ba92a901f516408e4b2a1a5b97fed719430acc9ec9";
     let valid_cert = proto_v1::Certificate {
         prev_id: Some(CertificateId { value: prev_id }),
         id: Some(CertificateId {
-            value: hex::decode(id).expect("Valid id"),
+            value: hex::decode(id)?,
         }),
         source_subnet_id: Some(SubnetId::from([0u8; 32])),
         state_root: [0u8; 32].to_vec(),
@@ -194,4 +199,41 @@ fn test_proto_uci_certificate_conversion_id_starts_with_0x() {
         "Second certificate converted prev_id={}, id={}",
         cert_2.prev_id, cert_2.id
     );
+
+    Ok(())
+}
+
+#[test]
+fn test_proto_uci_certificate_conversion_missing_fields() {
+    use crate::grpc::shared::v1::{CertificateId, Frost, StarkProof, SubnetId};
+    let certificate = proto_v1::Certificate {
+        prev_id: Some(CertificateId {
+            value: [0u8; 32].to_vec(),
+        }),
+        id: Some(CertificateId {
+            value: [1u8; 32].to_vec(),
+        }),
+        source_subnet_id: Some(SubnetId::from([0u8; 32])),
+        state_root: [0u8; 32].to_vec(),
+        tx_root_hash: [0u8; 32].to_vec(),
+        receipts_root_hash: [0u8; 32].to_vec(),
+        proof: Some(StarkProof { value: Vec::new() }),
+        signature: Some(Frost { value: Vec::new() }),
+        ..Default::default()
+    };
+
+    assert!(matches!(
+        topos_uci::Certificate::try_from(proto_v1::Certificate {
+            proof: None,
+            ..certificate.clone()
+        }),
+        Err(Error::MissingField("certificate.proof"))
+    ));
+    assert!(matches!(
+        topos_uci::Certificate::try_from(proto_v1::Certificate {
+            signature: None,
+            ..certificate
+        }),
+        Err(Error::MissingField("certificate.signature"))
+    ));
 }
@@ -7,6 +7,12 @@ pub struct CheckpointRequest {
     pub request_id: ::core::option::Option<super::super::shared::v1::Uuid>,
     #[prost(message, repeated, tag = "2")]
     pub checkpoint: ::prost::alloc::vec::Vec<ProofOfDelivery>,
+    /// Maximum number of Proofs of Delivery to return, 0 lets the server use its own limit
+    #[prost(uint32, tag = "3")]
+    pub limit: u32,
+    /// Token returned by a previous response to fetch the next page of the diff
+    #[prost(bytes = "vec", tag = "4")]
+    pub continuation_token: ::prost::alloc::vec::Vec<u8>,
 }
 #[derive(serde::Deserialize, serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -17,6 +23,9 @@ pub struct CheckpointResponse {
     pub request_id: ::core::option::Option<super::super::shared::v1::Uuid>,
     #[prost(message, repeated, tag = "2")]
     pub checkpoint_diff: ::prost::alloc::vec::Vec<CheckpointMapFieldEntry>,
+    /// Token to send back to fetch the next page, empty once the diff is complete
+    #[prost(bytes = "vec", tag = "3")]
+    pub continuation_token: ::prost::alloc::vec::Vec<u8>,
 }
 #[derive(serde::Deserialize, serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -45,6 +54,23 @@ pub struct FetchCertificatesResponse {
     #[prost(message, repeated, tag = "2")]
     pub certificates: ::prost::alloc::vec::Vec<super::super::uci::v1::Certificate>,
 }
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PendingCertificateIdsRequest {
+    /// Provide a request_id to track response
+    #[prost(message, optional, tag = "1")]
+    pub request_id: ::core::option::Option<super::super::shared::v1::Uuid>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PendingCertificateIdsResponse {
+    /// Provide a request_id to track response
+    #[prost(message, optional, tag = "1")]
+    pub request_id: ::core::option::Option<super::super::shared::v1::Uuid>,
+    /// Ids of the certificates in the pending pools, each certificate following the one it depends on
+    #[prost(message, repeated, tag = "2")]
+    pub certificate_ids: ::prost::alloc::vec::Vec<super::super::shared::v1::CertificateId>,
+}
 #[derive(serde::Deserialize, serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
@@ -212,6 +238,66 @@ pub mod synchronizer_service_client {
                 );
             self.inner.unary(req, path, codec).await
         }
+        pub async fn fetch_pending_certificate_ids(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PendingCertificateIdsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::PendingCertificateIdsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/topos.tce.v1.SynchronizerService/fetch_pending_certificate_ids",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "topos.tce.v1.SynchronizerService",
+                        "fetch_pending_certificate_ids",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn fetch_pending_certificates(
+            &mut self,
+            request: impl tonic::IntoRequest<super::FetchCertificatesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::FetchCertificatesResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/topos.tce.v1.SynchronizerService/fetch_pending_certificates",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "topos.tce.v1.SynchronizerService",
+                        "fetch_pending_certificates",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -235,6 +321,20 @@ pub mod synchronizer_service_server {
             tonic::Response<super::FetchCertificatesResponse>,
             tonic::Status,
         >;
+        async fn fetch_pending_certificate_ids(
+            &self,
+            request: tonic::Request<super::PendingCertificateIdsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::PendingCertificateIdsResponse>,
+            tonic::Status,
+        >;
+        async fn fetch_pending_certificates(
+            &self,
+            request: tonic::Request<super::FetchCertificatesRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::FetchCertificatesResponse>,
+            tonic::Status,
+        >;
     }
     #[derive(Debug)]
     pub struct SynchronizerServiceServer<T: SynchronizerService> {
@@ -415,6 +515,106 @@ pub mod synchronizer_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/topos.tce.v1.SynchronizerService/fetch_pending_certificate_ids" => {
+                    #[allow(non_camel_case_types)]
+                    struct fetch_pending_certificate_idsSvc<T: SynchronizerService>(pub Arc<T>);
+                    impl<
+                        T: SynchronizerService,
+                    > tonic::server::UnaryService<super::PendingCertificateIdsRequest>
+                    for fetch_pending_certificate_idsSvc<T> {
+                        type Response = super::PendingCertificateIdsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::PendingCertificateIdsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as SynchronizerService>::fetch_pending_certificate_ids(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = fetch_pending_certificate_idsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/topos.tce.v1.SynchronizerService/fetch_pending_certificates" => {
+                    #[allow(non_camel_case_types)]
+                    struct fetch_pending_certificatesSvc<T: SynchronizerService>(pub Arc<T>);
+                    impl<
+                        T: SynchronizerService,
+                    > tonic::server::UnaryService<super::FetchCertificatesRequest>
+                    for fetch_pending_certificatesSvc<T> {
+                        type Response = super::FetchCertificatesResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::FetchCertificatesRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as SynchronizerService>::fetch_pending_certificates(
+                                        &inner,
+                                        request,
+                                    )
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = fetch_pending_certificatesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -1157,6 +1357,179 @@ pub struct StatusRequest {}
 pub struct StatusResponse {
     #[prost(bool, tag = "1")]
     pub has_active_sample: bool,
+    /// Progress of the synchronization with the other nodes
+    #[prost(message, optional, tag = "2")]
+    pub sync_status: ::core::option::Option<SyncStatus>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SyncStatus {
+    /// Whether the node has every certificate known by the peers it synchronized with
+    #[prost(bool, tag = "1")]
+    pub caught_up: bool,
+    /// Number of certificates missing per subnet, compared to the heads of the peers
+    #[prost(message, repeated, tag = "2")]
+    pub subnet_lags: ::prost::alloc::vec::Vec<SubnetLag>,
+    /// Number of synchronized Proofs of Delivery whose certificate isn't delivered yet
+    #[prost(uint64, tag = "3")]
+    pub pending_proofs: u64,
+    /// Number of certificates fetched from the peers since the start of the node
+    #[prost(uint64, tag = "4")]
+    pub certificates_fetched: u64,
+    /// Peers of the last synchronization round
+    #[prost(string, repeated, tag = "5")]
+    pub peers: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SubnetLag {
+    #[prost(message, optional, tag = "1")]
+    pub subnet_id: ::core::option::Option<super::super::shared::v1::SubnetId>,
+    #[prost(uint64, tag = "2")]
+    pub missing_certificates: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetMisbehavioursRequest {
+    /// Only return the misbehaviours of this validator if provided
+    #[prost(message, optional, tag = "1")]
+    pub validator_id: ::core::option::Option<super::super::shared::v1::ValidatorId>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetMisbehavioursResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub validators: ::prost::alloc::vec::Vec<ValidatorMisbehaviours>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ValidatorMisbehaviours {
+    #[prost(message, optional, tag = "1")]
+    pub validator_id: ::core::option::Option<super::super::shared::v1::ValidatorId>,
+    #[prost(message, repeated, tag = "2")]
+    pub misbehaviours: ::prost::alloc::vec::Vec<Misbehaviour>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetStorageStatisticsRequest {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetStorageStatisticsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub column_families: ::prost::alloc::vec::Vec<ColumnFamilyStatistics>,
+}
+/// Statistics of one column family of the storage
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ColumnFamilyStatistics {
+    /// Name of the database holding the column family
+    #[prost(string, tag = "1")]
+    pub database: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub column_family: ::prost::alloc::string::String,
+    /// Estimated number of keys
+    #[prost(uint64, tag = "3")]
+    pub estimated_keys: u64,
+    /// Total size of the live SST files, in bytes
+    #[prost(uint64, tag = "4")]
+    pub sst_files_size: u64,
+    /// Size of the memtables, in bytes
+    #[prost(uint64, tag = "5")]
+    pub memtable_size: u64,
+    /// Estimated number of bytes the compactions have to rewrite
+    #[prost(uint64, tag = "6")]
+    pub pending_compaction_bytes: u64,
+}
+/// Echo or Ready message signed by a validator
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SignedMessage {
+    #[prost(enumeration = "signed_message::Kind", tag = "1")]
+    pub kind: i32,
+    #[prost(message, optional, tag = "2")]
+    pub certificate_id: ::core::option::Option<super::super::shared::v1::CertificateId>,
+    /// Hex encoded ECDSA signature of the message
+    #[prost(string, tag = "3")]
+    pub signature: ::prost::alloc::string::String,
+}
+/// Nested message and enum types in `SignedMessage`.
+pub mod signed_message {
+    #[derive(
+        Clone,
+        Copy,
+        Debug,
+        PartialEq,
+        Eq,
+        Hash,
+        PartialOrd,
+        Ord,
+        ::prost::Enumeration
+    )]
+    #[repr(i32)]
+    pub enum Kind {
+        Echo = 0,
+        Ready = 1,
+    }
+    impl Kind {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                Kind::Echo => "ECHO",
+                Kind::Ready => "READY",
+            }
+        }
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "ECHO" => Some(Self::Echo),
+                "READY" => Some(Self::Ready),
+                _ => None,
+            }
+        }
+    }
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Misbehaviour {
+    #[prost(oneof = "misbehaviour::Kind", tags = "1, 2")]
+    pub kind: ::core::option::Option<misbehaviour::Kind>,
+}
+/// Nested message and enum types in `Misbehaviour`.
+pub mod misbehaviour {
+    /// Messages of the same kind signed for two different certificates
+    /// claiming the same source stream position
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct ConflictingMessages {
+        #[prost(message, optional, tag = "1")]
+        pub subnet_id: ::core::option::Option<super::super::super::shared::v1::SubnetId>,
+        #[prost(message, optional, tag = "2")]
+        pub prev_certificate_id: ::core::option::Option<
+            super::super::super::shared::v1::CertificateId,
+        >,
+        #[prost(message, optional, tag = "3")]
+        pub first: ::core::option::Option<super::SignedMessage>,
+        #[prost(message, optional, tag = "4")]
+        pub second: ::core::option::Option<super::SignedMessage>,
+    }
+    /// Too many messages signed for certificates unknown to the node
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Message)]
+    pub struct UnknownCertificatesFlood {
+        #[prost(message, repeated, tag = "1")]
+        pub messages: ::prost::alloc::vec::Vec<super::SignedMessage>,
+    }
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Kind {
+        #[prost(message, tag = "1")]
+        ConflictingMessages(ConflictingMessages),
+        #[prost(message, tag = "2")]
+        UnknownCertificatesFlood(UnknownCertificatesFlood),
+    }
 }
 /// Generated client implementations.
 pub mod console_service_client {
@@ -1265,6 +1638,63 @@ pub mod console_service_client {
                 .insert(GrpcMethod::new("topos.tce.v1.ConsoleService", "Status"));
             self.inner.unary(req, path, codec).await
         }
+        pub async fn get_misbehaviours(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetMisbehavioursRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetMisbehavioursResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/topos.tce.v1.ConsoleService/GetMisbehaviours",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("topos.tce.v1.ConsoleService", "GetMisbehaviours"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        pub async fn get_storage_statistics(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetStorageStatisticsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetStorageStatisticsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/topos.tce.v1.ConsoleService/GetStorageStatistics",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "topos.tce.v1.ConsoleService",
+                        "GetStorageStatistics",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -1278,6 +1708,20 @@ pub mod console_service_server {
             &self,
             request: tonic::Request<super::StatusRequest>,
         ) -> std::result::Result<tonic::Response<super::StatusResponse>, tonic::Status>;
+        async fn get_misbehaviours(
+            &self,
+            request: tonic::Request<super::GetMisbehavioursRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetMisbehavioursResponse>,
+            tonic::Status,
+        >;
+        async fn get_storage_statistics(
+            &self,
+            request: tonic::Request<super::GetStorageStatisticsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::GetStorageStatisticsResponse>,
+            tonic::Status,
+        >;
     }
     #[derive(Debug)]
     pub struct ConsoleServiceServer<T: ConsoleService> {
@@ -1404,6 +1848,100 @@ pub mod console_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/topos.tce.v1.ConsoleService/GetMisbehaviours" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetMisbehavioursSvc<T: ConsoleService>(pub Arc<T>);
+                    impl<
+                        T: ConsoleService,
+                    > tonic::server::UnaryService<super::GetMisbehavioursRequest>
+                    for GetMisbehavioursSvc<T> {
+                        type Response = super::GetMisbehavioursResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetMisbehavioursRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ConsoleService>::get_misbehaviours(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetMisbehavioursSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/topos.tce.v1.ConsoleService/GetStorageStatistics" => {
+                    #[allow(non_camel_case_types)]
+                    struct GetStorageStatisticsSvc<T: ConsoleService>(pub Arc<T>);
+                    impl<
+                        T: ConsoleService,
+                    > tonic::server::UnaryService<super::GetStorageStatisticsRequest>
+                    for GetStorageStatisticsSvc<T> {
+                        type Response = super::GetStorageStatisticsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetStorageStatisticsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                <T as ConsoleService>::get_storage_statistics(&inner, request)
+                                    .await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = GetStorageStatisticsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -1474,11 +2012,42 @@ pub struct Ready {
     #[prost(message, optional, tag = "3")]
     pub validator_id: ::core::option::Option<super::super::shared::v1::ValidatorId>,
 }
+/// Heads of the source streams agreed on by the validators at some point of an epoch
+#[derive(serde::Deserialize, serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckpointSummary {
+    #[prost(uint64, tag = "1")]
+    pub epoch: u64,
+    #[prost(uint64, tag = "2")]
+    pub sequence_number: u64,
+    /// Digest of the previous checkpoint, empty for the first checkpoint of the chain
+    #[prost(bytes = "vec", tag = "3")]
+    pub previous_digest: ::prost::alloc::vec::Vec<u8>,
+    #[prost(message, repeated, tag = "4")]
+    pub heads: ::prost::alloc::vec::Vec<
+        super::super::shared::v1::positions::SourceStreamPosition,
+    >,
+    /// Validator set of the next epoch, empty if the checkpoint doesn't end its epoch
+    #[prost(message, repeated, tag = "5")]
+    pub next_validators: ::prost::alloc::vec::Vec<super::super::shared::v1::ValidatorId>,
+}
+#[derive(serde::Deserialize, serde::Serialize)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CheckpointSignature {
+    #[prost(message, optional, tag = "1")]
+    pub summary: ::core::option::Option<CheckpointSummary>,
+    #[prost(message, optional, tag = "2")]
+    pub signature: ::core::option::Option<super::super::shared::v1::EcdsaSignature>,
+    #[prost(message, optional, tag = "3")]
+    pub validator_id: ::core::option::Option<super::super::shared::v1::ValidatorId>,
+}
 #[derive(serde::Deserialize, serde::Serialize)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DoubleEchoRequest {
-    #[prost(oneof = "double_echo_request::Request", tags = "1, 2, 3")]
+    #[prost(oneof = "double_echo_request::Request", tags = "1, 2, 3, 4")]
     pub request: ::core::option::Option<double_echo_request::Request>,
 }
 /// Nested message and enum types in `DoubleEchoRequest`.
@@ -1493,5 +2062,7 @@ pub mod double_echo_request {
         Echo(super::Echo),
         #[prost(message, tag = "3")]
         Ready(super::Ready),
+        #[prost(message, tag = "4")]
+        CheckpointSignature(super::CheckpointSignature),
     }
 }
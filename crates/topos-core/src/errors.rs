@@ -1,5 +1,7 @@
 use topos_api::grpc::checkpoints::StreamPositionError;
 
+use crate::types::ValidatorId;
+
 #[derive(Debug, thiserror::Error)]
 pub enum GrpcParsingError {
     #[error("Malformed gRPC object: {0}")]
@@ -7,3 +9,19 @@ pub enum GrpcParsingError {
     #[error(transparent)]
     PositionParsing(#[from] StreamPositionError),
 }
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProofOfDeliveryError {
+    #[error("Unable to parse the validator id of a Ready message: {0}")]
+    InvalidValidatorId(String),
+    #[error("Unable to parse the signature of the Ready message from {0}")]
+    MalformedSignature(ValidatorId),
+    #[error("Invalid signature for the Ready message from {0}")]
+    InvalidSignature(ValidatorId),
+    #[error("Duplicated Ready message from {0}")]
+    DuplicatedReady(ValidatorId),
+    #[error("Ready message from unknown validator {0}")]
+    UnknownValidator(ValidatorId),
+    #[error("Not enough Ready messages to reach the threshold: expected {expected}, got {got}")]
+    ThresholdNotReached { expected: u64, got: u64 },
+}
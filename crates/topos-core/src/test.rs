@@ -1,4 +1,12 @@
-use crate::types::stream::Position;
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use topos_crypto::messages::MessageSigner;
+use topos_uci::{CertificateId, SubnetId};
+
+use crate::errors::ProofOfDeliveryError;
+use crate::types::stream::{CertificateSourceStreamPosition, Position};
+use crate::types::{ProofOfDelivery, SignedMessageKind, ValidatorId};
 
 #[test]
 fn test_position() {
@@ -25,3 +33,130 @@ fn position_from_integer() {
 
     assert_eq!(*position, 0);
 }
+
+fn create_signed_proof(
+    signers: &[MessageSigner],
+    threshold: u64,
+) -> (ProofOfDelivery, HashSet<ValidatorId>) {
+    let certificate_id = CertificateId::from([1u8; 32]);
+    let mut validators = HashSet::new();
+
+    let readies = signers
+        .iter()
+        .map(|signer| {
+            let validator_id = ValidatorId::from(signer.public_address);
+            validators.insert(validator_id);
+
+            let payload = SignedMessageKind::Ready.signing_payload(&certificate_id, &validator_id);
+
+            let signature = signer.sign_message(&payload).unwrap();
+
+            (validator_id.to_string(), signature.to_string())
+        })
+        .collect();
+
+    let proof = ProofOfDelivery {
+        certificate_id,
+        delivery_position: CertificateSourceStreamPosition {
+            subnet_id: SubnetId::from([2u8; 32]),
+            position: Position::ZERO,
+        },
+        readies,
+        threshold,
+    };
+
+    (proof, validators)
+}
+
+fn create_signers(count: u8) -> Vec<MessageSigner> {
+    (1..=count)
+        .map(|i| MessageSigner::new(&[i; 32]).unwrap())
+        .collect()
+}
+
+#[test]
+fn proof_of_delivery_verification() {
+    let (proof, validators) = create_signed_proof(&create_signers(4), 3);
+
+    assert!(proof.verify(&validators, 3).is_ok());
+    assert!(proof.verify(&validators, 4).is_ok());
+    assert!(matches!(
+        proof.verify(&validators, 5),
+        Err(ProofOfDeliveryError::ThresholdNotReached {
+            expected: 5,
+            got: 4
+        })
+    ));
+}
+
+#[test]
+fn proof_of_delivery_from_unknown_validator() {
+    let (proof, mut validators) = create_signed_proof(&create_signers(4), 3);
+    let unknown = ValidatorId::from_str(&proof.readies[0].0).unwrap();
+    validators.remove(&unknown);
+
+    assert!(matches!(
+        proof.verify(&validators, 3),
+        Err(ProofOfDeliveryError::UnknownValidator(validator_id)) if validator_id == unknown
+    ));
+}
+
+#[test]
+fn proof_of_delivery_with_duplicated_readies() {
+    let (mut proof, validators) = create_signed_proof(&create_signers(2), 3);
+    proof.readies.push(proof.readies[0].clone());
+
+    assert!(matches!(
+        proof.verify(&validators, 3),
+        Err(ProofOfDeliveryError::DuplicatedReady(_))
+    ));
+}
+
+#[test]
+fn proof_of_delivery_with_invalid_signatures() {
+    let (mut proof, validators) = create_signed_proof(&create_signers(4), 3);
+
+    // Signature of another validator
+    proof.readies[0].1 = proof.readies[1].1.clone();
+    assert!(matches!(
+        proof.verify(&validators, 3),
+        Err(ProofOfDeliveryError::InvalidSignature(_))
+    ));
+
+    // Signature over another certificate
+    let (mut proof, validators) = create_signed_proof(&create_signers(4), 3);
+    proof.certificate_id = CertificateId::from([3u8; 32]);
+    assert!(matches!(
+        proof.verify(&validators, 3),
+        Err(ProofOfDeliveryError::InvalidSignature(_))
+    ));
+
+    // Signature of the Echo of the validator
+    let (mut proof, validators) = create_signed_proof(&create_signers(4), 3);
+    let signer = MessageSigner::new(&[1u8; 32]).unwrap();
+    let validator_id = ValidatorId::from(signer.public_address);
+    let payload = SignedMessageKind::Echo.signing_payload(&proof.certificate_id, &validator_id);
+    proof.readies[0] = (
+        validator_id.to_string(),
+        signer.sign_message(&payload).unwrap().to_string(),
+    );
+    assert!(matches!(
+        proof.verify(&validators, 3),
+        Err(ProofOfDeliveryError::InvalidSignature(_))
+    ));
+
+    // Placeholder signature
+    let (mut proof, validators) = create_signed_proof(&create_signers(4), 3);
+    proof.readies[0].1 = "signature".to_string();
+    assert!(matches!(
+        proof.verify(&validators, 3),
+        Err(ProofOfDeliveryError::MalformedSignature(_))
+    ));
+
+    // Malformed validator id
+    proof.readies[0].0 = "ready".to_string();
+    assert!(matches!(
+        proof.verify(&validators, 3),
+        Err(ProofOfDeliveryError::InvalidValidatorId(_))
+    ));
+}
@@ -1,12 +1,17 @@
 use serde::{Deserialize, Serialize};
-use topos_uci::{Certificate, CertificateId};
+use std::collections::HashSet;
+use std::str::FromStr;
+use topos_uci::{Certificate, CertificateId, SubnetId};
 
-use crate::errors::GrpcParsingError;
+use crate::errors::{GrpcParsingError, ProofOfDeliveryError};
 
 use self::stream::CertificateSourceStreamPosition;
 use topos_api::grpc::{
     checkpoints::SourceStreamPosition,
-    tce::v1::{ProofOfDelivery as GrpcProofOfDelivery, SignedReady},
+    tce::v1::{
+        misbehaviour, signed_message, Misbehaviour as GrpcMisbehaviour,
+        ProofOfDelivery as GrpcProofOfDelivery, SignedMessage as GrpcSignedMessage, SignedReady,
+    },
 };
 
 pub mod stream;
@@ -43,12 +48,65 @@ pub struct ProofOfDelivery {
     pub certificate_id: CertificateId,
     /// The position of the certificate in the source stream
     pub delivery_position: CertificateSourceStreamPosition,
-    /// The list of Ready messages used to prove the certificate's delivery
+    /// The list of Ready messages used to prove the certificate's delivery,
+    /// each one being the validator id and its hex encoded ECDSA signature
     pub readies: Vec<(Ready, Signature)>,
     /// The threshold of Ready messages required to consider the certificate as delivered
     pub threshold: u64,
 }
 
+impl ProofOfDelivery {
+    /// Verify that this Proof of Delivery has been agreed on by the given validator set
+    ///
+    /// Every Ready message needs to come from a distinct and known validator and
+    /// to carry a valid Ready signature over the certificate id. The number of Ready
+    /// messages needs to reach the provided `threshold`, which is the one expected
+    /// by the caller and not the one advertised by the proof itself.
+    ///
+    /// The `delivery_position` isn't signed by the validators, the proof doesn't
+    /// attest it and it has to be checked against the source stream by the caller.
+    pub fn verify(
+        &self,
+        validators: &HashSet<ValidatorId>,
+        threshold: u64,
+    ) -> Result<(), ProofOfDeliveryError> {
+        let mut signers = HashSet::with_capacity(self.readies.len());
+
+        for (ready, signature) in &self.readies {
+            let validator_id = ValidatorId::from_str(ready)
+                .map_err(|_| ProofOfDeliveryError::InvalidValidatorId(ready.clone()))?;
+
+            if !validators.contains(&validator_id) {
+                return Err(ProofOfDeliveryError::UnknownValidator(validator_id));
+            }
+
+            if !signers.insert(validator_id) {
+                return Err(ProofOfDeliveryError::DuplicatedReady(validator_id));
+            }
+
+            let signature = topos_crypto::messages::Signature::from_str(signature)
+                .map_err(|_| ProofOfDeliveryError::MalformedSignature(validator_id))?;
+
+            let payload =
+                SignedMessageKind::Ready.signing_payload(&self.certificate_id, &validator_id);
+
+            signature
+                .verify(payload, validator_id.address())
+                .map_err(|_| ProofOfDeliveryError::InvalidSignature(validator_id))?;
+        }
+
+        let got = signers.len() as u64;
+        if got < threshold {
+            return Err(ProofOfDeliveryError::ThresholdNotReached {
+                expected: threshold,
+                got,
+            });
+        }
+
+        Ok(())
+    }
+}
+
 impl From<SourceStreamPosition> for CertificateSourceStreamPosition {
     fn from(value: SourceStreamPosition) -> Self {
         Self {
@@ -106,3 +164,101 @@ impl From<ProofOfDelivery> for GrpcProofOfDelivery {
         }
     }
 }
+
+/// Kind of a double echo message signed by a validator
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SignedMessageKind {
+    Echo,
+    Ready,
+}
+
+impl SignedMessageKind {
+    /// Payload signed by a validator sending a message of this kind, tagged with the
+    /// kind so that the signature of an Echo can't be replayed as the one of a Ready
+    pub fn signing_payload(
+        &self,
+        certificate_id: &CertificateId,
+        validator_id: &ValidatorId,
+    ) -> Vec<u8> {
+        let tag: &[u8] = match self {
+            Self::Echo => b"echo",
+            Self::Ready => b"ready",
+        };
+
+        let mut payload = Vec::new();
+        payload.extend_from_slice(tag);
+        payload.extend_from_slice(certificate_id.as_array());
+        payload.extend_from_slice(validator_id.as_bytes());
+
+        payload
+    }
+}
+
+/// Echo or Ready message signed by a validator
+///
+/// The signature covers the kind of the message, the certificate id and the
+/// validator id, which makes the message verifiable by any third party.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SignedMessage {
+    pub kind: SignedMessageKind,
+    pub certificate_id: CertificateId,
+    /// The hex encoded ECDSA signature of the message
+    pub signature: Signature,
+}
+
+/// Misbehaviour of a validator, along with the signed messages proving it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum Misbehaviour {
+    /// The validator signed messages of the same kind for two different
+    /// certificates claiming the same source stream position
+    ConflictingMessages {
+        subnet_id: SubnetId,
+        prev_certificate_id: CertificateId,
+        first: SignedMessage,
+        second: SignedMessage,
+    },
+    /// The validator sent too many messages for certificates unknown to the node
+    UnknownCertificatesFlood { messages: Vec<SignedMessage> },
+}
+
+impl From<SignedMessage> for GrpcSignedMessage {
+    fn from(value: SignedMessage) -> Self {
+        let kind = match value.kind {
+            SignedMessageKind::Echo => signed_message::Kind::Echo,
+            SignedMessageKind::Ready => signed_message::Kind::Ready,
+        };
+
+        Self {
+            kind: kind.into(),
+            certificate_id: Some(value.certificate_id.into()),
+            signature: value.signature,
+        }
+    }
+}
+
+impl From<Misbehaviour> for GrpcMisbehaviour {
+    fn from(value: Misbehaviour) -> Self {
+        let kind = match value {
+            Misbehaviour::ConflictingMessages {
+                subnet_id,
+                prev_certificate_id,
+                first,
+                second,
+            } => misbehaviour::Kind::ConflictingMessages(misbehaviour::ConflictingMessages {
+                subnet_id: Some(subnet_id.into()),
+                prev_certificate_id: Some(prev_certificate_id.into()),
+                first: Some(first.into()),
+                second: Some(second.into()),
+            }),
+            Misbehaviour::UnknownCertificatesFlood { messages } => {
+                misbehaviour::Kind::UnknownCertificatesFlood(
+                    misbehaviour::UnknownCertificatesFlood {
+                        messages: messages.into_iter().map(Into::into).collect(),
+                    },
+                )
+            }
+        };
+
+        Self { kind: Some(kind) }
+    }
+}
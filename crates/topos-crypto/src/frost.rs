@@ -0,0 +1,321 @@
+//! Threshold Schnorr signatures over secp256k1, following the two-round
+//! FROST protocol as specified by RFC 9591 with the FROST(secp256k1, SHA-256)
+//! ciphersuite.
+//!
+//! A subnet is identified on the TCE side by its [`GroupPublicKey`]. Its signers
+//! each hold a [`KeyShare`] and any `threshold` of them can produce a signature
+//! which verifies against the group key:
+//!   1. Each signer creates [`SigningNonces`] and shares the matching [`SigningCommitment`]
+//!   2. Each signer produces a [`SignatureShare`] over the message and all commitments
+//!   3. The shares are checked against the [`GroupKeys`] and combined into the final
+//!      signature with [`aggregate`]
+//!
+//! A subnet having a single signer can use [`sign`] with its secret key directly,
+//! its group public key being the matching secp256k1 public key.
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use frost_secp256k1 as frost;
+use rand_core::{CryptoRng, RngCore};
+
+use crate::Error;
+
+/// Length of a serialized threshold signature: compressed group commitment followed by
+/// the response scalar
+pub const SIGNATURE_LENGTH: usize = 65;
+
+/// Length of a serialized group public key, in compressed SEC1 encoding
+pub const PUBLIC_KEY_LENGTH: usize = 33;
+
+/// Public key of a group of signers
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GroupPublicKey(frost::VerifyingKey);
+
+impl GroupPublicKey {
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let bytes = bytes.try_into().map_err(|_| {
+            Error::InvalidKeyError(format!(
+                "expected {PUBLIC_KEY_LENGTH} bytes, got {}",
+                bytes.len()
+            ))
+        })?;
+
+        frost::VerifyingKey::deserialize(bytes)
+            .map(Self)
+            .map_err(|e| Error::InvalidKeyError(e.to_string()))
+    }
+
+    /// Returns the compressed SEC1 encoding of the key
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.serialize().to_vec()
+    }
+
+    /// Derives the group public key of a single signer from its secret key
+    pub fn from_secret_key(secret_key: &[u8]) -> Result<Self, Error> {
+        Ok(Self(frost::VerifyingKey::from(&signing_key(secret_key)?)))
+    }
+}
+
+impl FromStr for GroupPublicKey {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let bytes = hex::decode(s.strip_prefix("0x").unwrap_or(s))
+            .map_err(|e| Error::InvalidKeyError(e.to_string()))?;
+
+        Self::from_bytes(&bytes)
+    }
+}
+
+impl std::fmt::Display for GroupPublicKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0x{}", hex::encode(self.to_bytes()))
+    }
+}
+
+/// Public keys of a group: the group public key and the key of each signer,
+/// used to check the signature shares before aggregating them
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroupKeys(frost::keys::PublicKeyPackage);
+
+impl GroupKeys {
+    pub fn group_public_key(&self) -> GroupPublicKey {
+        GroupPublicKey(*self.0.verifying_key())
+    }
+}
+
+/// Secret share of the group key held by one signer
+#[derive(Clone)]
+pub struct KeyShare {
+    identifier: u16,
+    key_package: frost::keys::KeyPackage,
+}
+
+/// Nonces generated by a signer for a single signing session, must never be reused
+pub struct SigningNonces(frost::round1::SigningNonces);
+
+/// Public commitment to the [`SigningNonces`] of a signer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SigningCommitment {
+    pub identifier: u16,
+    commitments: frost::round1::SigningCommitments,
+}
+
+/// Partial signature produced by a signer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SignatureShare {
+    pub identifier: u16,
+    share: frost::round2::SignatureShare,
+}
+
+/// Splits a new group key into `participants` shares, any `threshold` of which can sign.
+/// The signers are identified from 1 to `participants`.
+pub fn generate_with_dealer<R: RngCore + CryptoRng>(
+    threshold: u16,
+    participants: u16,
+    rng: &mut R,
+) -> Result<(GroupKeys, Vec<KeyShare>), Error> {
+    let (secret_shares, public_key_package) = frost::keys::generate_with_dealer(
+        participants,
+        threshold,
+        frost::keys::IdentifierList::Default,
+        rng,
+    )
+    .map_err(threshold_error)?;
+
+    let shares = (1..=participants)
+        .map(|identifier| {
+            let secret_share = secret_shares
+                .get(&frost_identifier(identifier)?)
+                .cloned()
+                .ok_or_else(|| {
+                    Error::ThresholdSigningError(format!("missing share of signer {identifier}"))
+                })?;
+
+            Ok(KeyShare {
+                identifier,
+                key_package: frost::keys::KeyPackage::try_from(secret_share)
+                    .map_err(threshold_error)?,
+            })
+        })
+        .collect::<Result<_, Error>>()?;
+
+    Ok((GroupKeys(public_key_package), shares))
+}
+
+impl KeyShare {
+    pub fn identifier(&self) -> u16 {
+        self.identifier
+    }
+
+    pub fn group_public_key(&self) -> GroupPublicKey {
+        GroupPublicKey(*self.key_package.verifying_key())
+    }
+
+    /// First round: generates the nonces of a signing session and their commitment
+    pub fn commit<R: RngCore + CryptoRng>(
+        &self,
+        rng: &mut R,
+    ) -> (SigningNonces, SigningCommitment) {
+        let (nonces, commitments) = frost::round1::commit(self.key_package.signing_share(), rng);
+
+        (
+            SigningNonces(nonces),
+            SigningCommitment {
+                identifier: self.identifier,
+                commitments,
+            },
+        )
+    }
+
+    /// Second round: signs the message given the commitments of every signer of the session
+    pub fn sign(
+        &self,
+        nonces: SigningNonces,
+        message: &[u8],
+        commitments: &[SigningCommitment],
+    ) -> Result<SignatureShare, Error> {
+        let signing_package = signing_package(message, commitments)?;
+
+        let share = frost::round2::sign(&signing_package, &nonces.0, &self.key_package)
+            .map_err(threshold_error)?;
+
+        Ok(SignatureShare {
+            identifier: self.identifier,
+            share,
+        })
+    }
+}
+
+/// Combines the signature shares of a session into a signature verifiable against the
+/// group key. When the combined signature is invalid, the shares are verified and the
+/// first signer whose share is invalid is reported in the error.
+pub fn aggregate(
+    group_keys: &GroupKeys,
+    message: &[u8],
+    commitments: &[SigningCommitment],
+    shares: &[SignatureShare],
+) -> Result<Vec<u8>, Error> {
+    let signing_package = signing_package(message, commitments)?;
+
+    let mut signature_shares = BTreeMap::new();
+    for share in shares {
+        if signature_shares
+            .insert(frost_identifier(share.identifier)?, share.share)
+            .is_some()
+        {
+            return Err(Error::ThresholdSigningError(format!(
+                "more than one signature share from signer {}",
+                share.identifier
+            )));
+        }
+    }
+
+    if signature_shares.len() != commitments.len()
+        || commitments
+            .iter()
+            .any(|c| !shares.iter().any(|s| s.identifier == c.identifier))
+    {
+        return Err(Error::ThresholdSigningError(
+            "signature shares don't match the commitments".to_string(),
+        ));
+    }
+
+    let signature =
+        frost::aggregate(&signing_package, &signature_shares, &group_keys.0).map_err(|error| {
+            match error.culprit() {
+                Some(culprit) => Error::ThresholdSigningError(format!(
+                    "invalid signature shares from signers {:?}",
+                    shares
+                        .iter()
+                        .filter(|share| frost_identifier(share.identifier).ok() == Some(culprit))
+                        .map(|share| share.identifier)
+                        .collect::<Vec<_>>()
+                )),
+                None => threshold_error(error),
+            }
+        })?;
+
+    Ok(signature.serialize().to_vec())
+}
+
+/// Signs a message as the single member of a group
+pub fn sign<R: RngCore + CryptoRng>(
+    secret_key: &[u8],
+    message: &[u8],
+    rng: &mut R,
+) -> Result<Vec<u8>, Error> {
+    Ok(signing_key(secret_key)?
+        .sign(rng, message)
+        .serialize()
+        .to_vec())
+}
+
+/// Verifies a threshold signature against the public key of the group
+pub fn verify(group_public_key: &[u8], message: &[u8], signature: &[u8]) -> Result<(), Error> {
+    let group_public_key = GroupPublicKey::from_bytes(group_public_key)?;
+
+    if signature.len() != SIGNATURE_LENGTH {
+        return Err(Error::InvalidSignature(format!(
+            "expected {SIGNATURE_LENGTH} bytes, got {}",
+            signature.len()
+        )));
+    }
+
+    let signature = signature
+        .try_into()
+        .map_err(|_| Error::InvalidSignature("invalid signature length".to_string()))?;
+    let signature = frost::Signature::deserialize(signature)
+        .map_err(|e| Error::InvalidSignature(e.to_string()))?;
+
+    group_public_key
+        .0
+        .verify(message, &signature)
+        .map_err(|e| Error::InvalidSignature(e.to_string()))
+}
+
+fn signing_package(
+    message: &[u8],
+    commitments: &[SigningCommitment],
+) -> Result<frost::SigningPackage, Error> {
+    let mut signing_commitments = BTreeMap::new();
+
+    for commitment in commitments {
+        if signing_commitments
+            .insert(
+                frost_identifier(commitment.identifier)?,
+                commitment.commitments,
+            )
+            .is_some()
+        {
+            return Err(Error::ThresholdSigningError(
+                "commitments must be unique per signer".to_string(),
+            ));
+        }
+    }
+
+    if signing_commitments.is_empty() {
+        return Err(Error::ThresholdSigningError(
+            "commitments must be non empty".to_string(),
+        ));
+    }
+
+    Ok(frost::SigningPackage::new(signing_commitments, message))
+}
+
+fn frost_identifier(identifier: u16) -> Result<frost::Identifier, Error> {
+    frost::Identifier::try_from(identifier).map_err(threshold_error)
+}
+
+fn signing_key(secret_key: &[u8]) -> Result<frost::SigningKey, Error> {
+    let secret_key = secret_key
+        .try_into()
+        .map_err(|_| Error::InvalidKeyError("expected a 32 bytes secret key".to_string()))?;
+
+    frost::SigningKey::deserialize(secret_key).map_err(|e| Error::InvalidKeyError(e.to_string()))
+}
+
+fn threshold_error(error: frost::Error) -> Error {
+    Error::ThresholdSigningError(error.to_string())
+}
@@ -1,5 +1,6 @@
 use thiserror::Error;
 
+pub mod frost;
 pub mod hash;
 pub mod keys;
 pub mod keystore;
@@ -23,4 +24,7 @@ pub enum Error {
 
     #[error("Invalid signature: {0}")]
     InvalidSignature(String),
+
+    #[error("Threshold signing error: {0}")]
+    ThresholdSigningError(String),
 }
@@ -0,0 +1,119 @@
+use rstest::*;
+use topos_crypto::frost::{self, GroupKeys, GroupPublicKey, KeyShare};
+
+const MESSAGE: &[u8] = b"certificate payload";
+const PRIVATE_KEY: &str = "122f3ae6ade1fd136b292cea4f6243c7811160352c8821528547a1fe7c459daf";
+
+fn threshold_sign(group_keys: &GroupKeys, signers: &[&KeyShare]) -> Vec<u8> {
+    let mut rng = rand::thread_rng();
+    let (nonces, commitments): (Vec<_>, Vec<_>) =
+        signers.iter().map(|signer| signer.commit(&mut rng)).unzip();
+
+    let shares: Vec<_> = signers
+        .iter()
+        .zip(nonces)
+        .map(|(signer, nonces)| signer.sign(nonces, MESSAGE, &commitments).unwrap())
+        .collect();
+
+    frost::aggregate(group_keys, MESSAGE, &commitments, &shares).unwrap()
+}
+
+#[rstest]
+pub fn threshold_of_signers_can_sign() {
+    let (group_keys, shares) = frost::generate_with_dealer(2, 3, &mut rand::thread_rng()).unwrap();
+    let group_public_key = group_keys.group_public_key();
+
+    for signers in [
+        [&shares[0], &shares[1]],
+        [&shares[1], &shares[2]],
+        [&shares[2], &shares[0]],
+    ] {
+        let signature = threshold_sign(&group_keys, &signers);
+
+        assert!(frost::verify(&group_public_key.to_bytes(), MESSAGE, &signature).is_ok());
+        assert!(frost::verify(&group_public_key.to_bytes(), b"other payload", &signature).is_err());
+    }
+}
+
+#[rstest]
+pub fn fails_to_sign_below_threshold() {
+    let (_, shares) = frost::generate_with_dealer(3, 4, &mut rand::thread_rng()).unwrap();
+
+    let mut rng = rand::thread_rng();
+    let signers = [&shares[0], &shares[1]];
+    let (nonces, commitments): (Vec<_>, Vec<_>) =
+        signers.iter().map(|signer| signer.commit(&mut rng)).unzip();
+    let signature_shares: Result<Vec<_>, _> = signers
+        .iter()
+        .zip(nonces)
+        .map(|(signer, nonces)| signer.sign(nonces, MESSAGE, &commitments))
+        .collect();
+
+    // Signers refuse to sign a session having fewer commitments than the threshold
+    assert!(signature_shares.is_err());
+}
+
+#[rstest]
+pub fn invalid_signature_share_is_rejected_before_aggregation() {
+    let (group_keys, shares) = frost::generate_with_dealer(2, 3, &mut rand::thread_rng()).unwrap();
+
+    let mut rng = rand::thread_rng();
+    let signers = [&shares[0], &shares[1]];
+    let (nonces, commitments): (Vec<_>, Vec<_>) =
+        signers.iter().map(|signer| signer.commit(&mut rng)).unzip();
+    let mut nonces = nonces.into_iter();
+
+    let honest_share = signers[0]
+        .sign(nonces.next().unwrap(), MESSAGE, &commitments)
+        .unwrap();
+    // The second signer signs another message than the one being aggregated
+    let faulty_share = signers[1]
+        .sign(nonces.next().unwrap(), b"other payload", &commitments)
+        .unwrap();
+
+    let error = frost::aggregate(
+        &group_keys,
+        MESSAGE,
+        &commitments,
+        &[honest_share, faulty_share],
+    )
+    .unwrap_err();
+
+    assert!(error
+        .to_string()
+        .contains(&format!("[{}]", signers[1].identifier())));
+}
+
+#[rstest]
+pub fn single_signer_matches_its_public_key() {
+    let private_key = hex::decode(PRIVATE_KEY).unwrap();
+    let group_public_key = GroupPublicKey::from_secret_key(&private_key).unwrap();
+    let public_key = topos_crypto::keys::derive_public_key(&private_key).unwrap();
+
+    assert_eq!(group_public_key.to_bytes(), public_key);
+
+    let signature = frost::sign(&private_key, MESSAGE, &mut rand::thread_rng()).unwrap();
+    assert!(frost::verify(&public_key, MESSAGE, &signature).is_ok());
+
+    let other_key = frost::generate_with_dealer(2, 2, &mut rand::thread_rng())
+        .unwrap()
+        .0
+        .group_public_key();
+    assert!(frost::verify(&other_key.to_bytes(), MESSAGE, &signature).is_err());
+}
+
+#[rstest]
+pub fn group_public_key_roundtrip() {
+    let group_public_key = frost::generate_with_dealer(2, 3, &mut rand::thread_rng())
+        .unwrap()
+        .0
+        .group_public_key();
+
+    assert_eq!(
+        group_public_key
+            .to_string()
+            .parse::<GroupPublicKey>()
+            .unwrap(),
+        group_public_key
+    );
+}
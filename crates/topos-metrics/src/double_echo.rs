@@ -55,4 +55,18 @@ lazy_static! {
             TOPOS_METRIC_REGISTRY
         )
         .unwrap();
+    pub static ref DOUBLE_ECHO_BROADCAST_FAILED_TOTAL: IntCounter =
+        register_int_counter_with_registry!(
+            "double_echo_broadcast_failed_total",
+            "Number of broadcast abandoned before delivery.",
+            TOPOS_METRIC_REGISTRY
+        )
+        .unwrap();
+    pub static ref DOUBLE_ECHO_EQUIVOCATION_TOTAL: IntCounter =
+        register_int_counter_with_registry!(
+            "double_echo_equivocation_total",
+            "Number of conflicting certificates detected for the same source stream position.",
+            TOPOS_METRIC_REGISTRY
+        )
+        .unwrap();
 }
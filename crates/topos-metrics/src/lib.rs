@@ -10,11 +10,13 @@ mod api;
 mod double_echo;
 mod p2p;
 mod storage;
+mod synchronizer;
 
 pub use api::*;
 pub use double_echo::*;
 pub use p2p::*;
 pub use storage::*;
+pub use synchronizer::*;
 
 lazy_static! {
     pub static ref TOPOS_METRIC_REGISTRY: Registry = Registry::new_custom(
@@ -99,9 +101,11 @@ pub fn init_metrics() {
     DOUBLE_ECHO_BUFFERED_MESSAGE_COUNT.set(0);
     DOUBLE_ECHO_BROADCAST_CREATED_TOTAL.reset();
     DOUBLE_ECHO_BROADCAST_FINISHED_TOTAL.reset();
+    DOUBLE_ECHO_BROADCAST_FAILED_TOTAL.reset();
     CERTIFICATE_PROCESSING_TOTAL.reset();
     CERTIFICATE_PROCESSING_FROM_GOSSIP_TOTAL.reset();
     CERTIFICATE_PROCESSING_FROM_API_TOTAL.reset();
     CERTIFICATE_DELIVERED_TOTAL.reset();
     STORAGE_COMMAND_CHANNEL_CAPACITY_TOTAL.reset();
+    SYNCHRONIZER_CERTIFICATES_FETCHED_TOTAL.reset();
 }
@@ -1,6 +1,6 @@
 use prometheus::{
-    self, register_histogram_with_registry, register_int_counter_with_registry, Histogram,
-    IntCounter,
+    self, register_histogram_with_registry, register_int_counter_with_registry,
+    register_int_gauge_vec_with_registry, Histogram, IntCounter, IntGaugeVec,
 };
 
 use lazy_static::lazy_static;
@@ -31,4 +31,36 @@ lazy_static! {
             TOPOS_METRIC_REGISTRY
         )
         .unwrap();
+    pub static ref STORAGE_COLUMN_FAMILY_ESTIMATED_KEYS: IntGaugeVec =
+        register_int_gauge_vec_with_registry!(
+            "storage_column_family_estimated_keys",
+            "Estimated number of keys of each column family.",
+            &["database", "column_family"],
+            TOPOS_METRIC_REGISTRY
+        )
+        .unwrap();
+    pub static ref STORAGE_COLUMN_FAMILY_SST_FILES_SIZE_BYTES: IntGaugeVec =
+        register_int_gauge_vec_with_registry!(
+            "storage_column_family_sst_files_size_bytes",
+            "Total size of the live SST files of each column family.",
+            &["database", "column_family"],
+            TOPOS_METRIC_REGISTRY
+        )
+        .unwrap();
+    pub static ref STORAGE_COLUMN_FAMILY_MEMTABLE_SIZE_BYTES: IntGaugeVec =
+        register_int_gauge_vec_with_registry!(
+            "storage_column_family_memtable_size_bytes",
+            "Size of the memtables of each column family.",
+            &["database", "column_family"],
+            TOPOS_METRIC_REGISTRY
+        )
+        .unwrap();
+    pub static ref STORAGE_COLUMN_FAMILY_PENDING_COMPACTION_BYTES: IntGaugeVec =
+        register_int_gauge_vec_with_registry!(
+            "storage_column_family_pending_compaction_bytes",
+            "Estimated number of bytes the compactions of each column family have to rewrite.",
+            &["database", "column_family"],
+            TOPOS_METRIC_REGISTRY
+        )
+        .unwrap();
 }
@@ -0,0 +1,45 @@
+use prometheus::{
+    self, register_int_counter_vec_with_registry, register_int_gauge_vec_with_registry,
+    register_int_gauge_with_registry, IntCounterVec, IntGauge, IntGaugeVec,
+};
+
+use lazy_static::lazy_static;
+
+use crate::TOPOS_METRIC_REGISTRY;
+
+lazy_static! {
+    pub static ref SYNCHRONIZER_SUBNET_LAG: IntGaugeVec = register_int_gauge_vec_with_registry!(
+        "synchronizer_subnet_lag",
+        "Number of certificates missing for each subnet, compared to the heads of the peers.",
+        &["subnet_id"],
+        TOPOS_METRIC_REGISTRY
+    )
+    .unwrap();
+    pub static ref SYNCHRONIZER_PENDING_PROOFS: IntGauge = register_int_gauge_with_registry!(
+        "synchronizer_pending_proofs",
+        "Number of synchronized proofs of delivery whose certificate isn't delivered yet.",
+        TOPOS_METRIC_REGISTRY
+    )
+    .unwrap();
+    pub static ref SYNCHRONIZER_CAUGHT_UP: IntGauge = register_int_gauge_with_registry!(
+        "synchronizer_caught_up",
+        "Whether the node has every certificate known by the peers it synchronized with.",
+        TOPOS_METRIC_REGISTRY
+    )
+    .unwrap();
+    pub static ref SYNCHRONIZER_PEERS: IntGaugeVec = register_int_gauge_vec_with_registry!(
+        "synchronizer_peers",
+        "Peers of the last synchronization round.",
+        &["peer_id"],
+        TOPOS_METRIC_REGISTRY
+    )
+    .unwrap();
+    pub static ref SYNCHRONIZER_CERTIFICATES_FETCHED_TOTAL: IntCounterVec =
+        register_int_counter_vec_with_registry!(
+            "synchronizer_certificates_fetched_total",
+            "Number of certificates fetched from each peer.",
+            &["peer_id"],
+            TOPOS_METRIC_REGISTRY
+        )
+        .unwrap();
+}
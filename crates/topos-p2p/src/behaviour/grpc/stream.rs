@@ -102,9 +102,12 @@ impl GrpcStream {
 }
 
 impl Connected for GrpcStream {
-    type ConnectInfo = ();
+    /// The remote peer is exposed to the gRPC services as a request extension
+    type ConnectInfo = PeerId;
 
-    fn connect_info(&self) -> Self::ConnectInfo {}
+    fn connect_info(&self) -> Self::ConnectInfo {
+        self.peer_id
+    }
 }
 
 impl AsyncRead for GrpcStream {
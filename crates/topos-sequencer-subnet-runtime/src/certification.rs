@@ -1,3 +1,4 @@
+use crate::signing::SigningCoordinator;
 use crate::Error;
 use std::collections::{HashSet, LinkedList};
 use std::fmt::{Debug, Formatter};
@@ -17,6 +18,9 @@ pub struct Certification {
     pub verifier: u32,
     /// Key for signing certificates, currently secp256k1
     signing_key: Vec<u8>,
+    /// Signers of the subnet, the certificates carry their threshold signature
+    /// instead of the ECDSA signature of `signing_key` if provided
+    signing_coordinator: Option<Arc<dyn SigningCoordinator>>,
     /// Optional synchronization from particular block number
     pub start_block: Option<u64>,
 }
@@ -35,6 +39,7 @@ impl Certification {
         source_head_certificate_id: Option<CertificateId>,
         verifier: u32,
         signing_key: Vec<u8>,
+        signing_coordinator: Option<Arc<dyn SigningCoordinator>>,
         start_block: Option<u64>,
     ) -> Result<Arc<Mutex<Certification>>, crate::Error> {
         Ok(Arc::new(Mutex::from(Self {
@@ -43,6 +48,7 @@ impl Certification {
             subnet_id: *subnet_id,
             verifier,
             signing_key,
+            signing_coordinator,
             start_block,
         })))
     }
@@ -91,9 +97,7 @@ impl Certification {
                 proof,
             )
             .map_err(|e| Error::CertificateGenerationError(e.to_string()))?;
-            certificate
-                .update_signature(self.get_signing_key())
-                .map_err(Error::CertificateSigningError)?;
+            self.sign(&mut certificate).await?;
             generated_certificates.push(certificate);
         }
 
@@ -135,6 +139,25 @@ impl Certification {
         self.signing_key.as_slice()
     }
 
+    async fn sign(&self, certificate: &mut Certificate) -> Result<(), Error> {
+        match &self.signing_coordinator {
+            Some(coordinator) => {
+                let (commitments, shares) = coordinator
+                    .collect_signature_shares(certificate.get_payload().as_slice())
+                    .await
+                    .map_err(|e| Error::CertificateSigningError(e.into()))?;
+
+                certificate.update_threshold_signature(
+                    coordinator.group_keys(),
+                    &commitments,
+                    &shares,
+                )
+            }
+            None => certificate.update_signature(self.get_signing_key()),
+        }
+        .map_err(Error::CertificateSigningError)
+    }
+
     /// Expand short block history. Remove older blocks
     pub fn append_blocks(&mut self, blocks: Vec<BlockInfo>) {
         self.finalized_blocks.extend(blocks);
@@ -4,6 +4,7 @@
 //! Abstracted from actual storage implementation.
 //!
 use proxy::SubnetRuntimeProxy;
+use signing::SigningCoordinator;
 use std::sync::Arc;
 use thiserror::Error;
 use tokio::sync::Mutex;
@@ -15,6 +16,7 @@ pub type Peer = String;
 
 pub mod certification;
 pub mod proxy;
+pub mod signing;
 
 use crate::proxy::{SubnetRuntimeProxyCommand, SubnetRuntimeProxyEvent};
 
@@ -84,6 +86,8 @@ pub struct SubnetRuntimeProxyConfig {
     pub source_head_certificate_id: Option<CertificateId>,
     pub verifier: u32,
     pub start_block: Option<u64>,
+    /// Signers of the subnet producing the threshold signature of the certificates
+    pub signing_coordinator: Option<Arc<dyn SigningCoordinator>>,
 }
 
 /// Thread safe client to the protocol aggregate
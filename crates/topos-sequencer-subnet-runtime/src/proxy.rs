@@ -87,6 +87,7 @@ impl SubnetRuntimeProxy {
             None,
             config.verifier,
             signing_key.clone(),
+            config.signing_coordinator.clone(),
             config.start_block,
         )?;
 
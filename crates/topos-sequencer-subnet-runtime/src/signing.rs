@@ -0,0 +1,71 @@
+use async_trait::async_trait;
+use std::fmt::{Debug, Formatter};
+use topos_crypto::frost::{GroupKeys, KeyShare, SignatureShare, SigningCommitment};
+
+/// Coordinates the two rounds of the threshold signature of the certificates
+/// with the signers of the subnet
+#[async_trait]
+pub trait SigningCoordinator: Debug + Send + Sync {
+    /// Public keys of the subnet signers, used to verify their signature shares
+    fn group_keys(&self) -> &GroupKeys;
+
+    /// Collects the commitments and the signature shares of a threshold of signers
+    /// over the certificate payload
+    async fn collect_signature_shares(
+        &self,
+        payload: &[u8],
+    ) -> Result<(Vec<SigningCommitment>, Vec<SignatureShare>), topos_crypto::Error>;
+}
+
+/// Signers whose key shares are all held by the sequencer
+pub struct LocalSigners {
+    group_keys: GroupKeys,
+    key_shares: Vec<KeyShare>,
+}
+
+impl LocalSigners {
+    /// `key_shares` must hold at least the threshold of signers of the group
+    pub fn new(group_keys: GroupKeys, key_shares: Vec<KeyShare>) -> Self {
+        Self {
+            group_keys,
+            key_shares,
+        }
+    }
+}
+
+impl Debug for LocalSigners {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LocalSigners")
+            .field("group_public_key", &self.group_keys.group_public_key())
+            .field("signers", &self.key_shares.len())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl SigningCoordinator for LocalSigners {
+    fn group_keys(&self) -> &GroupKeys {
+        &self.group_keys
+    }
+
+    async fn collect_signature_shares(
+        &self,
+        payload: &[u8],
+    ) -> Result<(Vec<SigningCommitment>, Vec<SignatureShare>), topos_crypto::Error> {
+        let mut rng = rand::thread_rng();
+        let (nonces, commitments): (Vec<_>, Vec<_>) = self
+            .key_shares
+            .iter()
+            .map(|key_share| key_share.commit(&mut rng))
+            .unzip();
+
+        let shares = self
+            .key_shares
+            .iter()
+            .zip(nonces)
+            .map(|(key_share, nonces)| key_share.sign(nonces, payload, &commitments))
+            .collect::<Result<_, _>>()?;
+
+        Ok((commitments, shares))
+    }
+}
@@ -647,6 +647,7 @@ async fn test_create_runtime() -> Result<(), Box<dyn std::error::Error>> {
             verifier: 0,
             source_head_certificate_id: None,
             start_block: None,
+            signing_coordinator: None,
         },
         test_private_key,
     )
@@ -680,6 +681,7 @@ async fn test_subnet_certificate_push_call(
             verifier: 0,
             source_head_certificate_id: None,
             start_block: None,
+            signing_coordinator: None,
         },
         admin_key.clone(),
     )
@@ -969,6 +971,7 @@ async fn test_subnet_send_token_processing(
             verifier: 0,
             source_head_certificate_id: None,
             start_block: None,
+            signing_coordinator: None,
         },
         test_private_key.clone(),
     )
@@ -1095,6 +1098,7 @@ async fn test_sync_from_genesis_and_particular_source_head(
             verifier: 0,
             source_head_certificate_id: None,
             start_block: None,
+            signing_coordinator: None,
         },
         test_private_key.clone(),
     )
@@ -1164,6 +1168,7 @@ async fn test_sync_from_genesis_and_particular_source_head(
             verifier: 0,
             source_head_certificate_id: None,
             start_block: None,
+            signing_coordinator: None,
         },
         test_private_key.clone(),
     )
@@ -1261,6 +1266,7 @@ async fn test_sync_from_start_block(
             verifier: 0,
             source_head_certificate_id: None,
             start_block: Some(start_block),
+            signing_coordinator: None,
         },
         test_private_key.clone(),
     )
@@ -1350,6 +1356,7 @@ async fn test_subnet_multiple_send_token_in_a_block(
             verifier: 0,
             source_head_certificate_id: None,
             start_block: None,
+            signing_coordinator: None,
         },
         test_private_key.clone(),
     )
@@ -1,5 +1,6 @@
 use crate::app_context::AppContext;
 use std::io::ErrorKind::InvalidInput;
+use std::sync::Arc;
 use tokio::{
     spawn,
     sync::{
@@ -9,7 +10,9 @@ use tokio::{
 };
 use tokio_util::sync::CancellationToken;
 use topos_core::uci::{CertificateId, SubnetId};
-use topos_sequencer_subnet_runtime::{SubnetRuntimeProxyConfig, SubnetRuntimeProxyWorker};
+use topos_sequencer_subnet_runtime::{
+    signing::SigningCoordinator, SubnetRuntimeProxyConfig, SubnetRuntimeProxyWorker,
+};
 use topos_tce_proxy::{worker::TceProxyWorker, TceProxyConfig};
 use topos_wallet::SecretKey;
 use tracing::{debug, info, warn};
@@ -27,6 +30,8 @@ pub struct SequencerConfiguration {
     pub signing_key: SecretKey,
     pub verifier: u32,
     pub start_block: Option<u64>,
+    /// Signers of the subnet, the certificates are signed with `signing_key` if not provided
+    pub signing_coordinator: Option<Arc<dyn SigningCoordinator>>,
 }
 
 pub async fn launch(
@@ -93,6 +98,7 @@ pub async fn launch(
             source_head_certificate_id: None, // Must be acquired later after TCE proxy is connected
             verifier: config.verifier,
             start_block: config.start_block,
+            signing_coordinator: config.signing_coordinator.clone(),
         },
         config.signing_key.clone(),
     )
@@ -8,7 +8,7 @@ use tokio::sync::{mpsc, oneshot};
 use topos_api::graphql::errors::GraphQLServerError;
 use topos_api::graphql::filter::SubnetFilter;
 use topos_api::graphql::{
-    certificate::{Certificate, CertificateId},
+    certificate::{Certificate, CertificateId, SequencedCertificate, TimestampedCertificate},
     checkpoint::SourceCheckpoint,
     query::CertificateQuery,
 };
@@ -23,6 +23,21 @@ use crate::stream::TransientStream;
 
 use super::filter::FilterIs;
 
+/// Maximum number of certificates returned by a single query
+pub(crate) const MAX_PAGE_SIZE: usize = 1000;
+
+/// Checks the number of certificates requested by a query against [`MAX_PAGE_SIZE`]
+pub(crate) fn page_size(first: usize) -> Result<usize, GraphQLServerError> {
+    if first > MAX_PAGE_SIZE {
+        return Err(GraphQLServerError::PageTooLarge {
+            requested: first,
+            max: MAX_PAGE_SIZE,
+        });
+    }
+
+    Ok(first)
+}
+
 pub struct QueryRoot;
 pub(crate) type ServiceSchema = Schema<QueryRoot, EmptyMutation, SubscriptionRoot>;
 
@@ -92,6 +107,67 @@ impl CertificateQuery for QueryRoot {
                     .ok_or(GraphQLServerError::StorageError)
             })
     }
+
+    async fn certificates_from_sequence_number(
+        ctx: &Context<'_>,
+        from_sequence_number: u64,
+        first: usize,
+    ) -> Result<Vec<SequencedCertificate>, GraphQLServerError> {
+        let store = ctx.data::<Arc<FullNodeStore>>().map_err(|_| {
+            tracing::error!("Failed to get store from context");
+
+            GraphQLServerError::ParseDataConnector
+        })?;
+
+        Ok(store
+            .get_certificates_from_sequence_number(from_sequence_number, first)
+            .map_err(|_| GraphQLServerError::StorageError)?
+            .into_iter()
+            .map(|(c, sequence_number)| SequencedCertificate {
+                sequence_number,
+                certificate: c.certificate.as_ref().into(),
+            })
+            .collect())
+    }
+
+    async fn certificates_by_delivery_time(
+        ctx: &Context<'_>,
+        filter: SubnetFilter,
+        from: u64,
+        to: u64,
+        first: usize,
+    ) -> Result<Vec<TimestampedCertificate>, GraphQLServerError> {
+        let store = ctx.data::<Arc<FullNodeStore>>().map_err(|_| {
+            tracing::error!("Failed to get store from context");
+
+            GraphQLServerError::ParseDataConnector
+        })?;
+
+        let certificates = match filter {
+            SubnetFilter::Source(id) => store.get_source_certificates_delivered_between(
+                &topos_core::uci::SubnetId::try_from(&id)?,
+                from,
+                to,
+                first,
+            ),
+            SubnetFilter::Target(id) => store.get_target_certificates_delivered_between(
+                &topos_core::uci::SubnetId::try_from(&id)?,
+                from,
+                to,
+                first,
+            ),
+        }
+        .map_err(|_| GraphQLServerError::StorageError)?;
+
+        Ok(certificates
+            .into_iter()
+            .map(|(c, timestamps)| TimestampedCertificate {
+                received_at: timestamps.received_at,
+                delivered_at: timestamps.delivered_at,
+                certificate: c.certificate.as_ref().into(),
+            })
+            .collect())
+    }
 }
 
 #[Object]
@@ -103,7 +179,7 @@ impl QueryRoot {
         from_source_checkpoint: SourceCheckpoint,
         first: usize,
     ) -> Result<Vec<Certificate>, GraphQLServerError> {
-        Self::certificates_per_subnet(ctx, from_source_checkpoint, first).await
+        Self::certificates_per_subnet(ctx, from_source_checkpoint, page_size(first)?).await
     }
 
     async fn certificate(
@@ -113,6 +189,31 @@ impl QueryRoot {
     ) -> Result<Certificate, GraphQLServerError> {
         Self::certificate_by_id(ctx, certificate_id).await
     }
+
+    /// Returns the certificates in the order they were delivered by this node,
+    /// starting from a sequence number, `first` being at most [`MAX_PAGE_SIZE`]
+    async fn certificates_since(
+        &self,
+        ctx: &Context<'_>,
+        from_sequence_number: u64,
+        first: usize,
+    ) -> Result<Vec<SequencedCertificate>, GraphQLServerError> {
+        Self::certificates_from_sequence_number(ctx, from_sequence_number, page_size(first)?).await
+    }
+
+    /// Returns the certificates of a source subnet, or for a target subnet,
+    /// delivered between two unix timestamps in milliseconds included, `first` being
+    /// at most [`MAX_PAGE_SIZE`]
+    async fn certificates_delivered_between(
+        &self,
+        ctx: &Context<'_>,
+        filter: SubnetFilter,
+        from: u64,
+        to: u64,
+        first: usize,
+    ) -> Result<Vec<TimestampedCertificate>, GraphQLServerError> {
+        Self::certificates_by_delivery_time(ctx, filter, from, to, page_size(first)?).await
+    }
 }
 
 pub struct SubscriptionRoot;
@@ -1,7 +1,7 @@
 use std::{sync::Arc, time::Duration};
 
 use crate::{
-    graphql::query::{QueryRoot, SubscriptionRoot},
+    graphql::query::{page_size, QueryRoot, SubscriptionRoot, MAX_PAGE_SIZE},
     runtime::InternalRuntimeCommand,
     stream::TransientStream,
 };
@@ -10,6 +10,7 @@ use futures::{SinkExt, StreamExt};
 use rstest::rstest;
 use test_log::test;
 use tokio::sync::{mpsc, oneshot};
+use topos_api::graphql::errors::GraphQLServerError;
 use topos_core::uci::{Certificate, INITIAL_CERTIFICATE_ID};
 use topos_test_sdk::constants::{SOURCE_SUBNET_ID_2, TARGET_SUBNET_ID_3};
 use uuid::Uuid;
@@ -148,3 +149,13 @@ async fn open_watch_certificate_delivered() {
         }),
     );
 }
+
+#[test]
+fn page_size_is_bounded() {
+    assert_eq!(page_size(MAX_PAGE_SIZE).unwrap(), MAX_PAGE_SIZE);
+    assert!(matches!(
+        page_size(MAX_PAGE_SIZE + 1),
+        Err(GraphQLServerError::PageTooLarge { requested, max })
+            if requested == MAX_PAGE_SIZE + 1 && max == MAX_PAGE_SIZE
+    ));
+}
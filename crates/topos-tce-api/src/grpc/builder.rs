@@ -7,6 +7,7 @@ use topos_core::api::grpc::tce::v1::{
     api_service_server::ApiServiceServer, console_service_server::ConsoleServiceServer,
     StatusResponse,
 };
+use topos_core::uci::verifier::VerifierRegistry;
 use topos_tce_storage::validator::ValidatorStore;
 
 use crate::runtime::InternalRuntimeCommand;
@@ -16,6 +17,7 @@ use super::{console::TceConsoleService, TceGrpcService};
 #[derive(Default)]
 pub struct ServerBuilder {
     store: Option<Arc<ValidatorStore>>,
+    verifiers: Option<Arc<dyn VerifierRegistry>>,
     local_peer_id: String,
     command_sender: Option<Sender<InternalRuntimeCommand>>,
     serve_addr: Option<SocketAddr>,
@@ -28,6 +30,12 @@ impl ServerBuilder {
         self
     }
 
+    pub(crate) fn with_verifiers(mut self, verifiers: Arc<dyn VerifierRegistry>) -> Self {
+        self.verifiers = Some(verifiers);
+
+        self
+    }
+
     pub(crate) fn with_peer_id(mut self, local_peer_id: String) -> Self {
         self.local_peer_id = local_peer_id;
 
@@ -63,20 +71,28 @@ impl ServerBuilder {
         // So as soon as the node starts it is ready to send and receive ECHO messages.
         let status = Arc::new(RwLock::new(StatusResponse {
             has_active_sample: true,
+            sync_status: None,
         }));
 
+        let store = self
+            .store
+            .take()
+            .expect("Cannot build GraphQL server without a FullNode store");
+
         let console = ConsoleServiceServer::new(TceConsoleService {
             command_sender: command_sender.clone(),
             status: status.clone(),
+            store: store.clone(),
         });
 
-        let store = self
-            .store
+        let verifiers = self
+            .verifiers
             .take()
-            .expect("Cannot build GraphQL server without a FullNode store");
+            .expect("Cannot build gRPC without a proof verifier registry");
 
         let service = ApiServiceServer::new(TceGrpcService {
             store,
+            verifiers,
             command_sender,
         });
 
@@ -5,8 +5,12 @@ use crate::runtime::InternalRuntimeCommand;
 use tokio::sync::RwLock;
 use tonic::{Request, Response, Status};
 use topos_core::api::grpc::tce::v1::{
-    console_service_server::ConsoleService, StatusRequest, StatusResponse,
+    console_service_server::ConsoleService, GetMisbehavioursRequest, GetMisbehavioursResponse,
+    GetStorageStatisticsRequest, GetStorageStatisticsResponse, StatusRequest, StatusResponse,
+    ValidatorMisbehaviours,
 };
+use topos_core::types::ValidatorId;
+use topos_tce_storage::validator::ValidatorStore;
 
 pub(crate) struct TceConsoleService {
     // We want to allow this unused command_sender, because we need it in the future again.
@@ -15,6 +19,7 @@ pub(crate) struct TceConsoleService {
     #[allow(dead_code)]
     pub(crate) command_sender: Sender<InternalRuntimeCommand>,
     pub(crate) status: Arc<RwLock<StatusResponse>>,
+    pub(crate) store: Arc<ValidatorStore>,
 }
 
 #[tonic::async_trait]
@@ -27,4 +32,53 @@ impl ConsoleService for TceConsoleService {
 
         Ok(Response::new(status.clone()))
     }
+
+    async fn get_misbehaviours(
+        &self,
+        request: Request<GetMisbehavioursRequest>,
+    ) -> Result<Response<GetMisbehavioursResponse>, Status> {
+        let ledgers = match request.into_inner().validator_id {
+            Some(validator_id) => {
+                let validator_id: ValidatorId = validator_id
+                    .try_into()
+                    .map_err(|_| Status::invalid_argument("Invalid validator id"))?;
+
+                let misbehaviours = self
+                    .store
+                    .get_misbehaviours(&validator_id)
+                    .map_err(|e| Status::internal(e.to_string()))?;
+
+                vec![(validator_id, misbehaviours)]
+            }
+            None => self
+                .store
+                .get_all_misbehaviours()
+                .map_err(|e| Status::internal(e.to_string()))?,
+        };
+
+        let validators = ledgers
+            .into_iter()
+            .filter(|(_, misbehaviours)| !misbehaviours.is_empty())
+            .map(|(validator_id, misbehaviours)| ValidatorMisbehaviours {
+                validator_id: Some(validator_id.into()),
+                misbehaviours: misbehaviours.into_iter().map(Into::into).collect(),
+            })
+            .collect();
+
+        Ok(Response::new(GetMisbehavioursResponse { validators }))
+    }
+
+    async fn get_storage_statistics(
+        &self,
+        _request: Request<GetStorageStatisticsRequest>,
+    ) -> Result<Response<GetStorageStatisticsResponse>, Status> {
+        let statistics = self
+            .store
+            .get_storage_statistics()
+            .map_err(|e| Status::internal(e.to_string()))?;
+
+        Ok(Response::new(GetStorageStatisticsResponse {
+            column_families: statistics.into_iter().map(Into::into).collect(),
+        }))
+    }
 }
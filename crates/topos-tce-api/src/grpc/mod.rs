@@ -12,7 +12,7 @@ use topos_core::api::grpc::tce::v1::{
     SubmitCertificateRequest, SubmitCertificateResponse, WatchCertificatesRequest,
     WatchCertificatesResponse,
 };
-use topos_core::uci::SubnetId;
+use topos_core::uci::{verifier::VerifierRegistry, Certificate, SubnetId};
 use topos_metrics::API_GRPC_CERTIFICATE_RECEIVED_TOTAL;
 use topos_tce_storage::validator::ValidatorStore;
 use tracing::{error, info, Span};
@@ -36,6 +36,7 @@ pub(crate) mod messaging;
 
 pub(crate) struct TceGrpcService {
     store: Arc<ValidatorStore>,
+    verifiers: Arc<dyn VerifierRegistry>,
     command_sender: mpsc::Sender<InternalRuntimeCommand>,
 }
 
@@ -91,7 +92,7 @@ impl ApiService for TceGrpcService {
                     let (sender, receiver) = oneshot::channel();
                     // FIXME: remove certificate cloning (may be a lot of data) when we
                     // resolve the issue with invalid certificate error
-                    let certificate = match certificate.clone().try_into() {
+                    let certificate: Certificate = match certificate.clone().try_into() {
                         Ok(c) => c,
                         Err(e) => {
                             error!(
@@ -103,6 +104,13 @@ impl ApiService for TceGrpcService {
                         }
                     };
 
+                    if let Err(e) = certificate.check_proof(&*self.verifiers) {
+                        error!("Invalid proof for the certificate {}: {e}", certificate.id);
+                        return Err(Status::invalid_argument(format!(
+                            "Can't submit certificate with invalid proof: {e}"
+                        )));
+                    }
+
                     if self
                         .command_sender
                         .send(InternalRuntimeCommand::CertificateSubmitted {
@@ -120,6 +128,9 @@ impl ApiService for TceGrpcService {
                     receiver
                         .map(|value| match value {
                             Ok(Ok(_)) => Ok(Response::new(SubmitCertificateResponse {})),
+                            Ok(Err(crate::RuntimeError::ReadOnly)) => Err(Status::unavailable(
+                                "Can't submit certificate: the node only serves reads",
+                            )),
                             Ok(Err(_)) => Err(Status::internal("Can't submit certificate")),
                             Err(_) => Err(Status::internal("Can't submit certificate")),
                         })
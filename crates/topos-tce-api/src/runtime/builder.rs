@@ -6,6 +6,7 @@ use tokio::{
 };
 use tokio_stream::wrappers::ReceiverStream;
 use topos_core::api::grpc::tce::v1::StatusResponse;
+use topos_core::uci::verifier::{VerifierRegistry, Verifiers};
 use topos_tce_storage::{
     types::CertificateDeliveredWithPositions, validator::ValidatorStore, StorageClient,
 };
@@ -20,6 +21,7 @@ use crate::{
 pub struct RuntimeBuilder {
     storage: Option<StorageClient>,
     store: Option<Arc<ValidatorStore>>,
+    verifiers: Option<Arc<dyn VerifierRegistry>>,
     broadcast_stream: Option<broadcast::Receiver<CertificateDeliveredWithPositions>>,
     local_peer_id: String,
     grpc_socket_addr: Option<SocketAddr>,
@@ -74,6 +76,14 @@ impl RuntimeBuilder {
         self
     }
 
+    /// Registry used to verify the proof of the submitted certificates,
+    /// defaults to [`Verifiers::default`]
+    pub fn verifiers(mut self, verifiers: Arc<dyn VerifierRegistry>) -> Self {
+        self.verifiers = Some(verifiers);
+
+        self
+    }
+
     pub fn storage(mut self, storage: StorageClient) -> Self {
         self.storage = Some(storage);
 
@@ -98,6 +108,11 @@ impl RuntimeBuilder {
                     .take()
                     .expect("Unable to build gRPC Server, Store is missing"),
             )
+            .with_verifiers(
+                self.verifiers
+                    .take()
+                    .unwrap_or_else(|| Arc::new(Verifiers::default())),
+            )
             .with_peer_id(self.local_peer_id)
             .command_sender(internal_runtime_command_sender.clone())
             .serve_addr(self.grpc_socket_addr)
@@ -6,7 +6,10 @@ use futures::Future;
 use tokio::sync::{mpsc, oneshot, RwLock};
 use topos_core::api::grpc::checkpoints::TargetStreamPosition;
 use topos_core::uci::SubnetId;
-use topos_core::{api::grpc::tce::v1::StatusResponse, uci::Certificate};
+use topos_core::{
+    api::grpc::tce::v1::{StatusResponse, SyncStatus},
+    uci::Certificate,
+};
 use tracing::error;
 
 #[derive(Clone, Debug)]
@@ -47,6 +50,12 @@ impl RuntimeClient {
         status.has_active_sample = value;
     }
 
+    pub async fn set_sync_status(&self, sync_status: SyncStatus) {
+        let mut status = self.tce_status.write().await;
+
+        status.sync_status = Some(sync_status);
+    }
+
     pub async fn shutdown(&self) -> Result<(), Box<dyn std::error::Error>> {
         let (sender, receiver) = oneshot::channel();
         self.shutdown_channel.send(sender).await?;
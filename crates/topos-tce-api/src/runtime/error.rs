@@ -14,6 +14,9 @@ pub enum RuntimeError {
     #[error("Unknown subnet with subnet id {0}")]
     UnknownSubnet(SubnetId),
 
+    #[error("The node only serves reads, certificates can't be submitted")]
+    ReadOnly,
+
     #[error("Unexpected store error: {0}")]
     Store(#[from] StorageError),
 }
@@ -1,6 +1,7 @@
 use futures::Stream;
 use rstest::rstest;
 use serde::Deserialize;
+use std::str::FromStr;
 use std::time::Duration;
 use test_log::test;
 use tokio::sync::{broadcast, mpsc};
@@ -11,12 +12,16 @@ use tonic::transport::Uri;
 use topos_core::api::graphql::certificate::Certificate as GraphQLCertificate;
 use topos_core::api::grpc::shared::v1::checkpoints::TargetCheckpoint;
 use topos_core::api::grpc::shared::v1::positions::TargetStreamPosition;
-use topos_core::types::CertificateDelivered;
+use topos_core::types::{
+    CertificateDelivered, Misbehaviour, SignedMessage, SignedMessageKind, ValidatorId,
+};
 use topos_core::{
     api::grpc::tce::v1::{
         api_service_client::ApiServiceClient,
+        console_service_client::ConsoleServiceClient,
         watch_certificates_request::OpenStream,
         watch_certificates_response::{CertificatePushed, Event},
+        GetMisbehavioursRequest,
     },
     uci::Certificate,
 };
@@ -616,3 +621,84 @@ async fn can_query_graphql_endpoint_for_certificates(
         graphql_certificate.source_subnet_id
     );
 }
+
+#[rstest]
+#[timeout(Duration::from_secs(4))]
+#[test(tokio::test)]
+async fn can_query_validators_misbehaviours(
+    broadcast_stream: broadcast::Receiver<CertificateDeliveredWithPositions>,
+) {
+    let addr = get_available_addr();
+    let graphql_addr = get_available_addr();
+    let metrics_addr = get_available_addr();
+
+    let fullnode_store = create_fullnode_store::default().await;
+    let store = create_validator_store(Vec::new(), futures::future::ready(fullnode_store)).await;
+
+    let faulty_validator =
+        ValidatorId::from_str("0xb4973cdb10894d1d1547673bd758589034c2bba5").unwrap();
+    let honest_validator =
+        ValidatorId::from_str("0x0d4e8e9e3c8e0b0a4e5e4c0b0a0e8e9e3c8e0b0a").unwrap();
+
+    let misbehaviour = Misbehaviour::UnknownCertificatesFlood {
+        messages: vec![SignedMessage {
+            kind: SignedMessageKind::Echo,
+            certificate_id: PREV_CERTIFICATE_ID,
+            signature: "signature".to_string(),
+        }],
+    };
+    store
+        .insert_misbehaviour(&faulty_validator, misbehaviour.clone())
+        .unwrap();
+
+    let storage_client = StorageClient::new(store.clone());
+
+    let (_runtime_client, _launcher, _ctx) = Runtime::builder()
+        .with_broadcast_stream(broadcast_stream)
+        .storage(storage_client)
+        .store(store)
+        .serve_grpc_addr(addr)
+        .serve_graphql_addr(graphql_addr)
+        .serve_metrics_addr(metrics_addr)
+        .build_and_launch()
+        .await;
+
+    // Wait for server to boot
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let uri = Uri::builder()
+        .path_and_query("/")
+        .authority(addr.to_string())
+        .scheme("http")
+        .build()
+        .unwrap();
+
+    let channel = channel::Channel::builder(uri).connect_lazy();
+    let mut client = ConsoleServiceClient::new(channel);
+
+    let response = client
+        .get_misbehaviours(GetMisbehavioursRequest { validator_id: None })
+        .await
+        .unwrap()
+        .into_inner();
+
+    assert_eq!(response.validators.len(), 1);
+    assert_eq!(
+        response.validators[0].validator_id,
+        Some(faulty_validator.into())
+    );
+    assert_eq!(
+        response.validators[0].misbehaviours,
+        vec![misbehaviour.into()]
+    );
+
+    let response = client
+        .get_misbehaviours(GetMisbehavioursRequest {
+            validator_id: Some(honest_validator.into()),
+        })
+        .await
+        .unwrap()
+        .into_inner();
+
+    assert!(response.validators.is_empty());
+}
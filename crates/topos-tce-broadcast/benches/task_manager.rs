@@ -3,9 +3,11 @@ use std::str::FromStr;
 use std::sync::Arc;
 use tce_transport::ReliableBroadcastParams;
 use tokio::sync::{broadcast, mpsc, oneshot};
-use topos_core::types::ValidatorId;
+use topos_core::types::{SignedMessageKind, ValidatorId};
+use topos_core::uci::verifier::Verifiers;
 use topos_crypto::messages::MessageSigner;
 use topos_tce_broadcast::double_echo::DoubleEcho;
+use topos_tce_broadcast::SignatureVerification;
 use topos_tce_storage::validator::ValidatorStore;
 use topos_test_sdk::certificates::create_certificate_chain;
 use topos_test_sdk::constants::{SOURCE_SUBNET_ID_1, TARGET_SUBNET_ID_1};
@@ -57,6 +59,8 @@ pub async fn processing_double_echo(n: u64, validator_store: Arc<ValidatorStore>
         event_sender,
         double_echo_shutdown_receiver,
         validator_store,
+        Arc::new(Verifiers::default()),
+        SignatureVerification::Disabled,
         broadcast_sender,
     );
 
@@ -86,12 +90,13 @@ pub async fn processing_double_echo(n: u64, validator_store: Arc<ValidatorStore>
     }
 
     for cert in &certificates {
-        let mut payload = Vec::new();
-        payload.extend_from_slice(cert.certificate.id.as_array());
-        payload.extend_from_slice(validator_id.as_bytes());
+        let echo_payload =
+            SignedMessageKind::Echo.signing_payload(&cert.certificate.id, &validator_id);
+        let ready_payload =
+            SignedMessageKind::Ready.signing_payload(&cert.certificate.id, &validator_id);
 
         for _ in &double_echo_selected_echo {
-            let signature = message_signer.sign_message(&payload).unwrap();
+            let signature = message_signer.sign_message(&echo_payload).unwrap();
 
             double_echo
                 .handle_echo(cert.certificate.id, validator_id, signature)
@@ -99,7 +104,7 @@ pub async fn processing_double_echo(n: u64, validator_store: Arc<ValidatorStore>
         }
 
         for _ in &double_echo_selected_ready {
-            let signature = message_signer.sign_message(&payload).unwrap();
+            let signature = message_signer.sign_message(&ready_payload).unwrap();
 
             double_echo
                 .handle_ready(cert.certificate.id, validator_id, signature)
@@ -1,4 +1,5 @@
 use lazy_static::lazy_static;
+use std::time::Duration;
 
 lazy_static! {
     /// Size of the double echo command channel
@@ -45,4 +46,39 @@ lazy_static! {
             .ok()
             .and_then(|s| s.parse().ok())
             .unwrap_or(crate::double_echo::DoubleEcho::MAX_BUFFER_SIZE);
+    /// Delay without delivery after which a broadcast task gossips its certificate again
+    pub static ref BROADCAST_TASK_REGOSSIP_INTERVAL: Duration =
+        std::env::var("TOPOS_BROADCAST_TASK_REGOSSIP_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(crate::TaskDeadlines::REGOSSIP_INTERVAL);
+    /// Number of times a broadcast task gossips its certificate again before giving up
+    pub static ref BROADCAST_TASK_MAX_REGOSSIP: usize =
+        std::env::var("TOPOS_BROADCAST_TASK_MAX_REGOSSIP")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(crate::TaskDeadlines::MAX_REGOSSIP);
+    /// Delay after which messages received for an unknown certificate are dropped
+    pub static ref BROADCAST_BUFFERED_MESSAGES_TTL: Duration =
+        std::env::var("TOPOS_BROADCAST_BUFFERED_MESSAGES_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(crate::TaskDeadlines::BUFFERED_MESSAGES_TTL);
+    /// Number of unknown certificates a validator can send messages for before being
+    /// recorded as flooding
+    pub static ref MISBEHAVIOUR_MAX_UNKNOWN_CERTIFICATES: usize =
+        std::env::var("TOPOS_MISBEHAVIOUR_MAX_UNKNOWN_CERTIFICATES")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(crate::double_echo::misbehaviour_ledger::MisbehaviourLedger::MAX_UNKNOWN_CERTIFICATES);
+    /// Delay after which the messages sent by a validator for an unknown certificate
+    /// no longer count towards a flood
+    pub static ref MISBEHAVIOUR_UNKNOWN_MESSAGES_TTL: Duration =
+        std::env::var("TOPOS_MISBEHAVIOUR_UNKNOWN_MESSAGES_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .map(Duration::from_secs)
+            .unwrap_or(crate::double_echo::misbehaviour_ledger::MisbehaviourLedger::UNKNOWN_MESSAGES_TTL);
 }
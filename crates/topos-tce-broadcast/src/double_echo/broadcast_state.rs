@@ -1,27 +1,34 @@
 use crate::sampler::SubscriptionsView;
+use std::fmt::{Debug, Formatter};
+use std::str::FromStr;
 use std::sync::Arc;
-use std::{collections::HashSet, time};
-use tce_transport::ProtocolEvents;
+use std::{collections::HashMap, time};
+use tce_transport::{BroadcastFailureReason, ProtocolEvents};
 use tokio::sync::mpsc;
 use topos_core::{
     types::{
         stream::{CertificateSourceStreamPosition, Position},
-        CertificateDelivered, ProofOfDelivery, Ready, ValidatorId,
+        CertificateDelivered, ProofOfDelivery, SignedMessageKind, ValidatorId,
     },
     uci::Certificate,
 };
-use topos_crypto::messages::MessageSigner;
-use topos_metrics::DOUBLE_ECHO_BROADCAST_FINISHED_TOTAL;
-use tracing::{debug, info, warn};
+use topos_crypto::messages::{MessageSigner, Signature};
+use topos_metrics::{DOUBLE_ECHO_BROADCAST_FAILED_TOTAL, DOUBLE_ECHO_BROADCAST_FINISHED_TOTAL};
+use topos_tce_storage::types::{
+    BroadcastMessage, BroadcastState as PersistedBroadcastState, EpochId,
+};
+use topos_tce_storage::validator::ValidatorStore;
+use tracing::{debug, error, info, warn};
 mod status;
 
 pub use status::Status;
 
-#[derive(Debug)]
 pub struct BroadcastState {
     subscriptions_view: SubscriptionsView,
     status: Status,
     pub(crate) certificate: Certificate,
+    /// Epoch in which the broadcast started, whose validator set drives it
+    pub(crate) epoch: EpochId,
     validator_id: ValidatorId,
     echo_threshold: usize,
     ready_threshold: usize,
@@ -29,14 +36,37 @@ pub struct BroadcastState {
     message_signer: Arc<MessageSigner>,
     event_sender: mpsc::Sender<ProtocolEvents>,
     delivery_time: time::Instant,
-    readies: HashSet<Ready>,
+    /// Echo messages received for this certificate along with their signature
+    echoes: HashMap<ValidatorId, Signature>,
+    /// Ready messages received for this certificate along with their signature
+    readies: HashMap<ValidatorId, Signature>,
+    /// Signature of the Echo message sent by the local validator
+    echo_sent: Option<Signature>,
+    /// Signature of the Ready message sent by the local validator
+    ready_sent: Option<Signature>,
+    /// Store of the progress of the broadcast, every message is persisted
+    /// as soon as it is received or before being sent
+    validator_store: Arc<ValidatorStore>,
     pub(crate) expected_position: Option<Position>,
 }
 
+impl Debug for BroadcastState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BroadcastState")
+            .field("certificate_id", &self.certificate.id)
+            .field("epoch", &self.epoch)
+            .field("status", &self.status)
+            .field("echoes", &self.echoes.len())
+            .field("readies", &self.readies.len())
+            .finish_non_exhaustive()
+    }
+}
+
 impl BroadcastState {
     #[allow(clippy::too_many_arguments)]
     pub fn new(
         certificate: Certificate,
+        epoch: EpochId,
         validator_id: ValidatorId,
         echo_threshold: usize,
         ready_threshold: usize,
@@ -45,11 +75,13 @@ impl BroadcastState {
         subscriptions_view: SubscriptionsView,
         need_gossip: bool,
         message_signer: Arc<MessageSigner>,
+        validator_store: Arc<ValidatorStore>,
     ) -> Self {
         let mut state = Self {
             subscriptions_view,
             status: Status::Pending,
             certificate,
+            epoch,
             validator_id,
             echo_threshold,
             ready_threshold,
@@ -57,10 +89,31 @@ impl BroadcastState {
             message_signer,
             event_sender,
             delivery_time: time::Instant::now(),
-            readies: HashSet::new(),
+            echoes: HashMap::new(),
+            readies: HashMap::new(),
+            echo_sent: None,
+            ready_sent: None,
+            validator_store,
             expected_position: None,
         };
 
+        if let Err(error) = state
+            .validator_store
+            .insert_broadcast_state(&PersistedBroadcastState {
+                certificate: state.certificate.clone(),
+                epoch,
+                echoes: Vec::new(),
+                readies: Vec::new(),
+                echo_sent: None,
+                ready_sent: None,
+            })
+        {
+            error!(
+                "Unable to persist the broadcast state of {}: {:?}",
+                state.certificate.id, error
+            );
+        }
+
         _ = state.event_sender.try_send(ProtocolEvents::Broadcast {
             certificate_id: state.certificate.id,
         });
@@ -77,6 +130,140 @@ impl BroadcastState {
         state
     }
 
+    /// Rebuilds the state of a broadcast from its persisted progress
+    ///
+    /// The messages already sent by the local validator are sent again with
+    /// their original signature instead of being signed again.
+    #[allow(clippy::too_many_arguments)]
+    pub fn restore(
+        persisted: PersistedBroadcastState,
+        validator_id: ValidatorId,
+        echo_threshold: usize,
+        ready_threshold: usize,
+        delivery_threshold: usize,
+        event_sender: mpsc::Sender<ProtocolEvents>,
+        subscriptions_view: SubscriptionsView,
+        message_signer: Arc<MessageSigner>,
+        validator_store: Arc<ValidatorStore>,
+    ) -> Self {
+        let parse_messages = |messages: Vec<(ValidatorId, String)>| {
+            messages
+                .into_iter()
+                .filter_map(|(validator_id, signature)| {
+                    Some((validator_id, Signature::from_str(&signature).ok()?))
+                })
+                .collect::<HashMap<_, _>>()
+        };
+
+        let mut state = Self {
+            subscriptions_view,
+            status: Status::Pending,
+            certificate: persisted.certificate,
+            epoch: persisted.epoch,
+            validator_id,
+            echo_threshold,
+            ready_threshold,
+            delivery_threshold,
+            message_signer,
+            event_sender,
+            delivery_time: time::Instant::now(),
+            echoes: parse_messages(persisted.echoes),
+            readies: parse_messages(persisted.readies),
+            echo_sent: None,
+            ready_sent: None,
+            validator_store,
+            expected_position: None,
+        };
+
+        for validator_id in state.echoes.keys() {
+            state.subscriptions_view.echo.remove(validator_id);
+        }
+        for validator_id in state.readies.keys() {
+            state.subscriptions_view.ready.remove(validator_id);
+        }
+
+        _ = state.event_sender.try_send(ProtocolEvents::Broadcast {
+            certificate_id: state.certificate.id,
+        });
+
+        if let Some(signature) = persisted
+            .echo_sent
+            .and_then(|signature| Signature::from_str(&signature).ok())
+        {
+            let _ = state.event_sender.try_send(ProtocolEvents::Echo {
+                certificate_id: state.certificate.id,
+                signature,
+                validator_id,
+            });
+            state.echo_sent = Some(signature);
+            state.status = Status::EchoSent;
+        }
+
+        if let Some(signature) = persisted
+            .ready_sent
+            .and_then(|signature| Signature::from_str(&signature).ok())
+        {
+            let _ = state.event_sender.try_send(ProtocolEvents::Ready {
+                certificate_id: state.certificate.id,
+                signature,
+                validator_id,
+            });
+            state.ready_sent = Some(signature);
+            state.status = state.status.ready_sent();
+        }
+
+        while state.update_status().is_some() {}
+
+        state
+    }
+
+    pub(crate) fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Persists one message of the broadcast, returns whether it succeeded
+    fn persist(&self, message: BroadcastMessage, signature: &Signature) -> bool {
+        match self.validator_store.insert_broadcast_message(
+            &self.certificate.id,
+            message,
+            &signature.to_string(),
+        ) {
+            Ok(()) => true,
+            Err(error) => {
+                error!(
+                    "Unable to persist the {:?} message of the Certificate {}: {:?}",
+                    message, self.certificate.id, error
+                );
+
+                false
+            }
+        }
+    }
+
+    /// Gossips the certificate again, used when the broadcast is stalling
+    pub fn regossip(&self) {
+        warn!(
+            "📣 Gossiping again the Certificate {}",
+            &self.certificate.id
+        );
+        let _ = self.event_sender.try_send(ProtocolEvents::Gossip {
+            cert: self.certificate.clone(),
+        });
+    }
+
+    /// Notifies that the broadcast of the certificate is abandoned
+    pub fn fail(&self, reason: BroadcastFailureReason) {
+        warn!(
+            "Abandoning the broadcast of the Certificate {}: {:?}",
+            &self.certificate.id, reason
+        );
+        DOUBLE_ECHO_BROADCAST_FAILED_TOTAL.inc();
+        let _ = self.event_sender.try_send(ProtocolEvents::BroadcastFailed {
+            certificate_id: self.certificate.id,
+            reason,
+        });
+    }
+
     pub fn into_delivered(&self) -> CertificateDelivered {
         CertificateDelivered {
             certificate: self.certificate.clone(),
@@ -92,25 +279,37 @@ impl BroadcastState {
                 readies: self
                     .readies
                     .iter()
-                    .cloned()
-                    .map(|r| (r, "signature".to_string()))
+                    .map(|(validator_id, signature)| {
+                        (validator_id.to_string(), signature.to_string())
+                    })
                     .collect(),
                 threshold: self.delivery_threshold as u64,
             },
         }
     }
 
-    pub fn apply_echo(&mut self, validator_id: ValidatorId) -> Option<Status> {
+    pub fn apply_echo(
+        &mut self,
+        validator_id: ValidatorId,
+        signature: Signature,
+    ) -> Option<Status> {
         if self.subscriptions_view.echo.remove(&validator_id) {
+            self.persist(BroadcastMessage::Echo(validator_id), &signature);
+            self.echoes.insert(validator_id, signature);
             self.update_status()
         } else {
             None
         }
     }
 
-    pub fn apply_ready(&mut self, validator_id: ValidatorId) -> Option<Status> {
+    pub fn apply_ready(
+        &mut self,
+        validator_id: ValidatorId,
+        signature: Signature,
+    ) -> Option<Status> {
         if self.subscriptions_view.ready.remove(&validator_id) {
-            self.readies.insert(validator_id.to_string());
+            self.persist(BroadcastMessage::Ready(validator_id), &signature);
+            self.readies.insert(validator_id, signature);
             self.update_status()
         } else {
             None
@@ -122,16 +321,22 @@ impl BroadcastState {
         // any Echo or Ready messages
         // Sending our Echo message
         if let Status::Pending = self.status {
-            let mut payload = Vec::new();
-            payload.extend_from_slice(self.certificate.id.as_array());
-            payload.extend_from_slice(self.validator_id.as_bytes());
+            let payload =
+                SignedMessageKind::Echo.signing_payload(&self.certificate.id, &self.validator_id);
+
+            let signature = self.message_signer.sign_message(&payload).ok()?;
+            // Sent only once persisted, to be sent again as is after a restart
+            if !self.persist(BroadcastMessage::EchoSent, &signature) {
+                return None;
+            }
 
             let _ = self.event_sender.try_send(ProtocolEvents::Echo {
                 certificate_id: self.certificate.id,
-                signature: self.message_signer.sign_message(&payload).ok()?,
+                signature,
                 validator_id: self.validator_id,
             });
 
+            self.echo_sent = Some(signature);
             self.status = Status::EchoSent;
             debug!(
                 "📝 Certificate {} is now {}",
@@ -146,19 +351,24 @@ impl BroadcastState {
         // If the status was EchoSent, we update it to ReadySent
         // If the status was Delivered, we update it to DeliveredWithReadySent
         if !self.status.is_ready_sent() && self.reached_ready_threshold() {
-            let mut payload = Vec::new();
-            payload.extend_from_slice(self.certificate.id.as_array());
-            payload.extend_from_slice(self.validator_id.as_bytes());
+            let payload =
+                SignedMessageKind::Ready.signing_payload(&self.certificate.id, &self.validator_id);
+
+            let signature = self.message_signer.sign_message(&payload).ok()?;
+            if !self.persist(BroadcastMessage::ReadySent, &signature) {
+                return None;
+            }
 
             let event = ProtocolEvents::Ready {
                 certificate_id: self.certificate.id,
-                signature: self.message_signer.sign_message(&payload).ok()?,
+                signature,
                 validator_id: self.validator_id,
             };
             if let Err(e) = self.event_sender.try_send(event) {
                 warn!("Error sending Ready message: {}", e);
             }
 
+            self.ready_sent = Some(signature);
             self.status = self.status.ready_sent();
 
             debug!(
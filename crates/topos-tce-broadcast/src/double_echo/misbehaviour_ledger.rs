@@ -0,0 +1,277 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use topos_core::{
+    types::{Misbehaviour, SignedMessage, SignedMessageKind, ValidatorId},
+    uci::{Certificate, CertificateId, SubnetId},
+};
+use topos_tce_storage::{store::ReadStore, validator::ValidatorStore};
+use tracing::{error, warn};
+
+/// Source stream position claimed by a certificate, identified by
+/// its source subnet and its previous certificate
+type ClaimedPosition = (SubnetId, CertificateId);
+
+/// First message of each kind signed by a validator for a claimed position,
+/// along with the certificates for which a conflicting message was already recorded
+struct SignedPosition {
+    first: SignedMessage,
+    reported: HashSet<CertificateId>,
+}
+
+/// Messages signed by a validator for a certificate unknown to the node
+struct UnknownMessages {
+    received_at: Instant,
+    messages: Vec<SignedMessage>,
+}
+
+/// Keeps track of the messages signed by each validator in order to detect
+/// and persist misbehaviours:
+///   - Echo or Ready messages signed for two different certificates claiming
+///     the same source stream position
+///   - Floods of messages signed for certificates unknown to this node
+///
+/// Messages whose signature is invalid can't be attributed to their claimed sender,
+/// they are only counted per claimed sender and never persisted as misbehaviours.
+pub struct MisbehaviourLedger {
+    validator_store: Arc<ValidatorStore>,
+    /// Claimed position of the certificates known by the node
+    known_certificates: HashMap<CertificateId, ClaimedPosition>,
+    /// Messages signed by each validator for each claimed position
+    signed_positions: HashMap<(ValidatorId, SignedMessageKind, ClaimedPosition), SignedPosition>,
+    /// Messages received for certificates that aren't known yet, per validator
+    unknown_messages: HashMap<ValidatorId, HashMap<CertificateId, UnknownMessages>>,
+    /// Unknown certificates ordered by the reception of their first message
+    unknown_receptions: VecDeque<(Instant, ValidatorId, CertificateId)>,
+    /// Number of messages received with an invalid signature, per claimed sender
+    invalid_signatures: HashMap<ValidatorId, u64>,
+    /// Number of distinct unknown certificates a validator can sign messages for
+    /// before being considered as flooding
+    pub(crate) max_unknown_certificates: usize,
+    /// Delay after which the messages signed for an unknown certificate are forgotten
+    pub(crate) unknown_messages_ttl: Duration,
+}
+
+impl MisbehaviourLedger {
+    pub const MAX_UNKNOWN_CERTIFICATES: usize = 1024;
+    pub const UNKNOWN_MESSAGES_TTL: Duration = Duration::from_secs(60);
+
+    pub fn new(
+        validator_store: Arc<ValidatorStore>,
+        max_unknown_certificates: usize,
+        unknown_messages_ttl: Duration,
+    ) -> Self {
+        Self {
+            validator_store,
+            known_certificates: Default::default(),
+            signed_positions: Default::default(),
+            unknown_messages: Default::default(),
+            unknown_receptions: Default::default(),
+            invalid_signatures: Default::default(),
+            max_unknown_certificates,
+            unknown_messages_ttl,
+        }
+    }
+
+    /// Registers a certificate as known, checking the messages previously
+    /// received for it
+    pub fn certificate_known(&mut self, certificate: &Certificate) {
+        let position = (certificate.source_subnet_id, certificate.prev_id);
+        self.known_certificates.insert(certificate.id, position);
+
+        let pending: Vec<(ValidatorId, Vec<SignedMessage>)> = self
+            .unknown_messages
+            .iter_mut()
+            .filter_map(|(validator_id, unknown)| {
+                unknown
+                    .remove(&certificate.id)
+                    .map(|unknown| (*validator_id, unknown.messages))
+            })
+            .collect();
+
+        for (validator_id, messages) in pending {
+            for message in messages {
+                self.check_position(validator_id, position, message);
+            }
+        }
+    }
+
+    /// Forgets about the claimed position of a delivered certificate
+    pub fn certificate_delivered(&mut self, certificate_id: &CertificateId) {
+        if let Some(position) = self.known_certificates.remove(certificate_id) {
+            self.known_certificates
+                .retain(|_, known_position| *known_position != position);
+            self.signed_positions
+                .retain(|(_, _, signed_position), _| *signed_position != position);
+        }
+    }
+
+    /// Records a message signed by a validator, the signature is expected
+    /// to be verified beforehand
+    pub fn record(&mut self, validator_id: ValidatorId, message: SignedMessage) {
+        if let Some(position) = self.known_certificates.get(&message.certificate_id) {
+            return self.check_position(validator_id, *position, message);
+        }
+
+        let now = Instant::now();
+        self.expire_unknown_messages(now);
+
+        let unknown = self.unknown_messages.entry(validator_id).or_default();
+        let certificate_messages = unknown.entry(message.certificate_id).or_insert_with(|| {
+            self.unknown_receptions
+                .push_back((now, validator_id, message.certificate_id));
+
+            UnknownMessages {
+                received_at: now,
+                messages: Vec::new(),
+            }
+        });
+
+        // Only the first message of each kind is kept for a certificate
+        if certificate_messages
+            .messages
+            .iter()
+            .all(|known| known.kind != message.kind)
+        {
+            certificate_messages.messages.push(message);
+        }
+
+        if unknown.len() > self.max_unknown_certificates {
+            self.check_flood(validator_id);
+        }
+    }
+
+    /// Counts a message received with an invalid signature. At most
+    /// `max_unknown_certificates` claimed senders are tracked, the claimed
+    /// sender of such a message being chosen by whoever sent it.
+    pub fn record_invalid_signature(&mut self, validator_id: ValidatorId) {
+        if let Some(count) = self.invalid_signatures.get_mut(&validator_id) {
+            *count += 1;
+        } else if self.invalid_signatures.len() < self.max_unknown_certificates {
+            self.invalid_signatures.insert(validator_id, 1);
+        }
+    }
+
+    /// Number of messages received with an invalid signature claiming to come
+    /// from the given validator
+    pub fn invalid_signatures(&self, validator_id: &ValidatorId) -> u64 {
+        self.invalid_signatures
+            .get(validator_id)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Records a flood once the unknown certificates of the validator are confirmed
+    /// not to be delivered, messages for delivered certificates being late ones
+    fn check_flood(&mut self, validator_id: ValidatorId) {
+        let Some(unknown) = self.unknown_messages.get_mut(&validator_id) else {
+            return;
+        };
+
+        let mut delivered = Vec::new();
+        for certificate_id in unknown.keys() {
+            match self.validator_store.get_certificate(certificate_id) {
+                Ok(Some(_)) => delivered.push(*certificate_id),
+                Ok(None) => {}
+                Err(error) => {
+                    return error!(
+                        "Unable to check the Certificate {} for misbehaviour: {:?}",
+                        certificate_id, error
+                    );
+                }
+            }
+        }
+
+        for certificate_id in delivered {
+            unknown.remove(&certificate_id);
+        }
+
+        if unknown.len() > self.max_unknown_certificates {
+            let messages = self
+                .unknown_messages
+                .remove(&validator_id)
+                .map(|unknown| {
+                    unknown
+                        .into_values()
+                        .flat_map(|unknown| unknown.messages)
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            self.persist(
+                validator_id,
+                Misbehaviour::UnknownCertificatesFlood { messages },
+            );
+        }
+    }
+
+    /// Forgets the messages of the unknown certificates received for too long
+    fn expire_unknown_messages(&mut self, now: Instant) {
+        while let Some(&(received_at, validator_id, certificate_id)) =
+            self.unknown_receptions.front()
+        {
+            if now.duration_since(received_at) < self.unknown_messages_ttl {
+                break;
+            }
+
+            self.unknown_receptions.pop_front();
+
+            if let Some(unknown) = self.unknown_messages.get_mut(&validator_id) {
+                // Ignores the certificates forgotten and received again since then
+                if matches!(
+                    unknown.get(&certificate_id),
+                    Some(messages) if messages.received_at == received_at
+                ) {
+                    unknown.remove(&certificate_id);
+                }
+
+                if unknown.is_empty() {
+                    self.unknown_messages.remove(&validator_id);
+                }
+            }
+        }
+    }
+
+    fn check_position(
+        &mut self,
+        validator_id: ValidatorId,
+        position: ClaimedPosition,
+        message: SignedMessage,
+    ) {
+        let signed = self
+            .signed_positions
+            .entry((validator_id, message.kind, position))
+            .or_insert_with(|| SignedPosition {
+                first: message.clone(),
+                reported: HashSet::new(),
+            });
+
+        if signed.first.certificate_id != message.certificate_id
+            && signed.reported.insert(message.certificate_id)
+        {
+            let misbehaviour = Misbehaviour::ConflictingMessages {
+                subnet_id: position.0,
+                prev_certificate_id: position.1,
+                first: signed.first.clone(),
+                second: message,
+            };
+
+            self.persist(validator_id, misbehaviour);
+        }
+    }
+
+    fn persist(&self, validator_id: ValidatorId, misbehaviour: Misbehaviour) {
+        warn!("Misbehaviour detected for the validator {}", validator_id);
+
+        if let Err(error) = self
+            .validator_store
+            .insert_misbehaviour(&validator_id, misbehaviour)
+        {
+            error!(
+                "Unable to persist the misbehaviour of {}: {:?}",
+                validator_id, error
+            );
+        }
+    }
+}
@@ -1,19 +1,25 @@
 use crate::TaskStatus;
-use crate::{DoubleEchoCommand, SubscriptionsView};
-use std::collections::HashSet;
+use crate::{DoubleEchoCommand, SignatureVerification, SubscriptionsView, TaskDeadlines};
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::sync::Arc;
-use tce_transport::{ProtocolEvents, ReliableBroadcastParams};
+use std::time::Instant;
+use tce_transport::{BroadcastFailureReason, ProtocolEvents, ReliableBroadcastParams};
 use tokio::sync::{broadcast, mpsc, oneshot};
 use topos_core::{
-    types::ValidatorId,
-    uci::{Certificate, CertificateId},
+    types::{SignedMessage, SignedMessageKind, ValidatorId},
+    uci::{verifier::VerifierRegistry, Certificate, CertificateId, SubnetId},
 };
 use topos_crypto::messages::{MessageSigner, Signature};
-use topos_tce_storage::types::CertificateDeliveredWithPositions;
+use topos_metrics::DOUBLE_ECHO_EQUIVOCATION_TOTAL;
+use topos_tce_storage::store::ReadStore;
+use topos_tce_storage::types::{CertificateDeliveredWithPositions, EpochId, EquivocationEvidence};
 use topos_tce_storage::validator::ValidatorStore;
 use tracing::{error, info, warn};
 
 pub mod broadcast_state;
+pub mod misbehaviour_ledger;
+
+use misbehaviour_ledger::MisbehaviourLedger;
 
 pub struct DoubleEcho {
     /// Channel to receive commands
@@ -24,6 +30,20 @@ pub struct DoubleEcho {
     pub(crate) shutdown: mpsc::Receiver<oneshot::Sender<()>>,
     /// Delivered certificate ids to avoid processing twice the same certificate
     delivered_certificates: HashSet<CertificateId>,
+    /// Certificates currently broadcast or whose broadcast failed recently,
+    /// indexed by their source subnet and previous certificate id
+    certificates_in_broadcast: HashMap<(SubnetId, CertificateId), Certificate>,
+    /// Source subnet and previous certificate id of each certificate of `certificates_in_broadcast`
+    broadcast_prev_ids: HashMap<CertificateId, (SubnetId, CertificateId)>,
+    /// Epoch in which the broadcast of each certificate of `certificates_in_broadcast` started
+    broadcast_epochs: HashMap<CertificateId, EpochId>,
+    /// Validator sets of the past epochs in which the tracked broadcasts started
+    epoch_validators: HashMap<EpochId, HashSet<ValidatorId>>,
+    /// Failure time of the broadcasts which failed, their certificate keeps
+    /// conflicting with the other ones as long as the pending pools retain it
+    failed_at: HashMap<CertificateId, Instant>,
+    /// Failed broadcasts ordered by failure time
+    failed_broadcasts: VecDeque<(Instant, CertificateId)>,
     /// The threshold parameters for the double echo
     pub params: ReliableBroadcastParams,
     /// The connection to the TaskManager to forward DoubleEchoCommand messages
@@ -36,7 +56,20 @@ pub struct DoubleEcho {
     pub message_signer: Arc<MessageSigner>,
     /// List of approved validators through smart contract and/or genesis
     pub validators: HashSet<ValidatorId>,
+    /// Validators of the previous epoch, still allowed to take part
+    /// in the broadcasts started before the last epoch change
+    previous_validators: HashSet<ValidatorId>,
+    /// Current epoch, `validators` being its validator set
+    pub epoch: EpochId,
     pub validator_store: Arc<ValidatorStore>,
+    /// Ledger of the validators' misbehaviours
+    pub(crate) misbehaviour_ledger: MisbehaviourLedger,
+    /// Registry of the backends used to verify the certificates' proof
+    pub verifiers: Arc<dyn VerifierRegistry>,
+    /// Verification of the certificates' signature
+    pub signature_verification: SignatureVerification,
+    /// Deadlines applied to the broadcast tasks
+    pub task_deadlines: TaskDeadlines,
     pub broadcast_sender: broadcast::Sender<CertificateDeliveredWithPositions>,
 }
 
@@ -54,6 +87,8 @@ impl DoubleEcho {
         event_sender: mpsc::Sender<ProtocolEvents>,
         shutdown: mpsc::Receiver<oneshot::Sender<()>>,
         validator_store: Arc<ValidatorStore>,
+        verifiers: Arc<dyn VerifierRegistry>,
+        signature_verification: SignatureVerification,
         broadcast_sender: broadcast::Sender<CertificateDeliveredWithPositions>,
     ) -> Self {
         Self {
@@ -61,6 +96,8 @@ impl DoubleEcho {
             validator_id,
             message_signer,
             validators: validators.clone(),
+            previous_validators: Default::default(),
+            epoch: 0,
             task_manager_message_sender,
             command_receiver,
             event_sender,
@@ -71,7 +108,21 @@ impl DoubleEcho {
             },
             shutdown,
             delivered_certificates: Default::default(),
+            certificates_in_broadcast: Default::default(),
+            broadcast_prev_ids: Default::default(),
+            broadcast_epochs: Default::default(),
+            epoch_validators: Default::default(),
+            failed_at: Default::default(),
+            failed_broadcasts: Default::default(),
+            misbehaviour_ledger: MisbehaviourLedger::new(
+                validator_store.clone(),
+                *crate::constant::MISBEHAVIOUR_MAX_UNKNOWN_CERTIFICATES,
+                *crate::constant::MISBEHAVIOUR_UNKNOWN_MESSAGES_TTL,
+            ),
             validator_store,
+            verifiers,
+            signature_verification,
+            task_deadlines: TaskDeadlines::default(),
             broadcast_sender,
         }
     }
@@ -93,6 +144,8 @@ impl DoubleEcho {
             self.message_signer.clone(),
             self.validator_store.clone(),
             self.broadcast_sender.clone(),
+            self.task_deadlines,
+            self.epoch,
         );
 
         tokio::spawn(task_manager.run(shutdown_receiver));
@@ -116,6 +169,7 @@ impl DoubleEcho {
             self.message_signer.clone(),
             self.params.clone(),
             self.validator_store.clone(),
+            self.epoch,
         );
 
         tokio::spawn(task_manager.run(shutdown_receiver));
@@ -134,7 +188,9 @@ impl DoubleEcho {
         mut self,
         task_manager_message_receiver: mpsc::Receiver<DoubleEchoCommand>,
     ) {
+        self.restore_broadcasts();
         let mut task_completion = self.spawn_task_manager(task_manager_message_receiver);
+        let mut evictions = self.validator_store.subscribe_evictions();
 
         info!("DoubleEcho started");
 
@@ -151,39 +207,19 @@ impl DoubleEcho {
 
                         DoubleEchoCommand::Broadcast { need_gossip, cert } => self.broadcast(cert, need_gossip).await,
 
+                        DoubleEchoCommand::EpochChange { epoch } => self.change_epoch(epoch).await,
+
                         command if self.subscriptions.is_some() => {
                             match command {
                                 DoubleEchoCommand::Echo { certificate_id, validator_id, signature } => {
-                                    // Check if source is part of known_validators
-                                    if !self.validators.contains(&validator_id) {
-                                        return error!("ECHO message comes from non-validator: {}", validator_id);
-                                    }
-
-                                    let mut payload = Vec::new();
-                                    payload.extend_from_slice(certificate_id.as_array());
-                                    payload.extend_from_slice(validator_id.as_bytes());
-
-                                    if let Err(e) = self.message_signer.verify_signature(signature, &payload, validator_id.address()) {
-                                        return error!("ECHO messag signature cannot be verified from: {}", e);
+                                    if self.accept_message(SignedMessageKind::Echo, certificate_id, validator_id, signature) {
+                                        self.handle_echo(certificate_id, validator_id, signature).await
                                     }
-
-                                    self.handle_echo(certificate_id, validator_id, signature).await
                                 },
                                 DoubleEchoCommand::Ready { certificate_id, validator_id, signature } => {
-                                    // Check if source is part of known_validators
-                                    if !self.validators.contains(&validator_id) {
-                                        return error!("READY message comes from non-validator: {}", validator_id);
+                                    if self.accept_message(SignedMessageKind::Ready, certificate_id, validator_id, signature) {
+                                        self.handle_ready(certificate_id, validator_id, signature).await
                                     }
-
-                                    let mut payload = Vec::new();
-                                    payload.extend_from_slice(certificate_id.as_array());
-                                    payload.extend_from_slice(validator_id.as_bytes());
-
-                                    if let Err(e) = self.message_signer.verify_signature(signature, &payload, validator_id.address()) {
-                                        return error!("READY message signature cannot be verified from: {}", e);
-                                    }
-
-                                    self.handle_ready(certificate_id, validator_id, signature).await
                                 },
                                 _ => {}
                             }
@@ -196,9 +232,11 @@ impl DoubleEcho {
                 }
 
                 Some((certificate_id, status)) = task_completion.recv() => {
-                    if let TaskStatus::Success = status {
-                        self.delivered_certificates.insert(certificate_id);
-                    }
+                    self.task_completed(certificate_id, status);
+                }
+
+                Ok(evicted) = evictions.recv() => {
+                    self.certificate_evicted(&evicted.certificate_id);
                 }
 
                 else => {
@@ -224,24 +262,39 @@ impl DoubleEcho {
         info!("🙌 Starting broadcasting the Certificate {}", &cert.id);
         if self.cert_pre_broadcast_check(&cert).is_err() {
             error!("Failure on the pre-check for the Certificate {}", &cert.id);
-            self.event_sender
-                .try_send(ProtocolEvents::BroadcastFailed {
-                    certificate_id: cert.id,
-                })
-                .unwrap();
+            if let Err(error) = self.event_sender.try_send(ProtocolEvents::BroadcastFailed {
+                certificate_id: cert.id,
+                reason: BroadcastFailureReason::InvalidCertificate,
+            }) {
+                error!("Unable to send the BroadcastFailed event: {:?}", error);
+            }
             return;
         }
 
         if self.delivered_certificates.get(&cert.id).is_some() {
-            self.event_sender
+            if let Err(error) = self
+                .event_sender
                 .try_send(ProtocolEvents::AlreadyDelivered {
                     certificate_id: cert.id,
                 })
-                .unwrap();
+            {
+                error!("Unable to send the AlreadyDelivered event: {:?}", error);
+            }
+
+            return;
+        }
+
+        self.expire_failed_broadcasts();
+
+        if let Some(known) = self.find_conflicting_certificate(&cert) {
+            self.report_equivocation(known, cert);
 
             return;
         }
 
+        self.misbehaviour_ledger.certificate_known(&cert);
+        self.track_broadcast(cert.clone(), self.epoch);
+
         if self
             .delivery_state_for_new_cert(cert, origin)
             .await
@@ -252,6 +305,215 @@ impl DoubleEcho {
         }
     }
 
+    /// Switches to the validator set of the new epoch, the broadcasts already
+    /// started keep the validator set of the epoch they started in
+    pub async fn change_epoch(&mut self, epoch: EpochId) {
+        if epoch <= self.epoch {
+            return;
+        }
+
+        info!("Entering the epoch {epoch}");
+        let previous_epoch = std::mem::replace(&mut self.epoch, epoch);
+
+        // The broadcasts started in the previous epoch keep its validator set
+        self.epoch_validators
+            .entry(previous_epoch)
+            .or_insert_with(|| self.validators.clone());
+
+        if let Some(validators) = crate::load_epoch_validators(&self.validator_store, epoch) {
+            if validators != self.validators {
+                info!(
+                    "Validator set of the epoch {epoch} has {} validators",
+                    validators.len()
+                );
+                self.params = ReliableBroadcastParams::new(validators.len());
+                self.subscriptions = SubscriptionsView::from_validators(&validators);
+                self.previous_validators = std::mem::replace(&mut self.validators, validators);
+            }
+        }
+
+        let broadcast_epochs = &self.broadcast_epochs;
+        self.epoch_validators
+            .retain(|epoch, _| broadcast_epochs.values().any(|started| started == epoch));
+
+        _ = self
+            .task_manager_message_sender
+            .send(DoubleEchoCommand::EpochChange { epoch })
+            .await;
+    }
+
+    /// Whether the validator belongs to the current or to the previous validator set
+    fn is_validator(&self, validator_id: &ValidatorId) -> bool {
+        self.validators.contains(validator_id) || self.previous_validators.contains(validator_id)
+    }
+
+    /// Whether the validator belongs to the validator set of the epoch in which the broadcast
+    /// of the certificate started, or to the current or previous set when the certificate
+    /// isn't broadcast yet
+    fn is_validator_for(
+        &mut self,
+        certificate_id: &CertificateId,
+        validator_id: &ValidatorId,
+    ) -> bool {
+        let Some(&epoch) = self.broadcast_epochs.get(certificate_id) else {
+            return self.is_validator(validator_id);
+        };
+
+        if epoch == self.epoch {
+            return self.validators.contains(validator_id);
+        }
+
+        let validator_store = &self.validator_store;
+        self.epoch_validators
+            .entry(epoch)
+            .or_insert_with(|| {
+                crate::load_epoch_validators(validator_store, epoch).unwrap_or_default()
+            })
+            .contains(validator_id)
+    }
+
+    /// Checks the signature and the sender of an Echo or Ready message before handling it.
+    /// The rejected messages are recorded in the misbehaviour ledger and dropped.
+    pub(crate) fn accept_message(
+        &mut self,
+        kind: SignedMessageKind,
+        certificate_id: CertificateId,
+        validator_id: ValidatorId,
+        signature: Signature,
+    ) -> bool {
+        let payload = kind.signing_payload(&certificate_id, &validator_id);
+
+        if let Err(error) =
+            self.message_signer
+                .verify_signature(signature, &payload, validator_id.address())
+        {
+            warn!("{kind:?} message signature from {validator_id} cannot be verified: {error}");
+            self.misbehaviour_ledger
+                .record_invalid_signature(validator_id);
+
+            return false;
+        }
+
+        if !self.is_validator_for(&certificate_id, &validator_id) {
+            warn!(
+                "{kind:?} message for the Certificate {certificate_id} comes from non-validator: \
+                 {validator_id}"
+            );
+            self.misbehaviour_ledger.record(
+                validator_id,
+                SignedMessage {
+                    kind,
+                    certificate_id,
+                    signature: signature.to_string(),
+                },
+            );
+
+            return false;
+        }
+
+        true
+    }
+
+    /// Keeps track of the certificates whose broadcast will be resumed
+    /// by the task manager, to detect the conflicting ones
+    fn restore_broadcasts(&mut self) {
+        match self.validator_store.get_broadcast_states() {
+            Ok(states) => {
+                for state in states {
+                    if matches!(
+                        self.validator_store.get_certificate(&state.certificate.id),
+                        Ok(Some(_))
+                    ) {
+                        continue;
+                    }
+
+                    if state.epoch != self.epoch {
+                        if let Some(validators) =
+                            crate::load_epoch_validators(&self.validator_store, state.epoch)
+                        {
+                            self.previous_validators.extend(validators);
+                        }
+                    }
+
+                    self.misbehaviour_ledger
+                        .certificate_known(&state.certificate);
+                    self.track_broadcast(state.certificate, state.epoch);
+                }
+            }
+            Err(error) => error!("Unable to read the persisted broadcast states: {:?}", error),
+        }
+    }
+
+    /// Updates the certificates known to be broadcast once a broadcast task is over
+    pub(crate) fn task_completed(&mut self, certificate_id: CertificateId, status: TaskStatus) {
+        match status {
+            TaskStatus::Success => {
+                self.untrack_broadcast(&certificate_id);
+                self.misbehaviour_ledger
+                    .certificate_delivered(&certificate_id);
+                self.delivered_certificates.insert(certificate_id);
+            }
+            // The certificate is still awaited at its position of the source stream
+            TaskStatus::Failure => {
+                let now = Instant::now();
+                self.failed_at.insert(certificate_id, now);
+                self.failed_broadcasts.push_back((now, certificate_id));
+            }
+        }
+
+        self.expire_failed_broadcasts();
+    }
+
+    /// Forgets a failed broadcast once its certificate leaves the pools, the
+    /// certificates of the ongoing broadcasts are forgotten when they are over
+    pub(crate) fn certificate_evicted(&mut self, certificate_id: &CertificateId) {
+        if self.failed_at.contains_key(certificate_id) {
+            self.untrack_broadcast(certificate_id);
+        }
+    }
+
+    fn track_broadcast(&mut self, cert: Certificate, epoch: EpochId) {
+        let certificate_id = cert.id;
+        self.failed_at.remove(&certificate_id);
+        let key = (cert.source_subnet_id, cert.prev_id);
+        self.broadcast_prev_ids.insert(certificate_id, key);
+        self.broadcast_epochs.insert(certificate_id, epoch);
+
+        if let Some(replaced) = self.certificates_in_broadcast.insert(key, cert) {
+            if replaced.id != certificate_id {
+                self.broadcast_prev_ids.remove(&replaced.id);
+                self.broadcast_epochs.remove(&replaced.id);
+                self.failed_at.remove(&replaced.id);
+            }
+        }
+    }
+
+    fn untrack_broadcast(&mut self, certificate_id: &CertificateId) {
+        if let Some(key) = self.broadcast_prev_ids.remove(certificate_id) {
+            self.certificates_in_broadcast.remove(&key);
+        }
+        self.broadcast_epochs.remove(certificate_id);
+        self.failed_at.remove(certificate_id);
+    }
+
+    /// Forgets the failed broadcasts older than the retention of the pending pools
+    fn expire_failed_broadcasts(&mut self) {
+        let max_age = self.validator_store.pool_retention().max_age;
+
+        while let Some(&(failed_at, certificate_id)) = self.failed_broadcasts.front() {
+            if failed_at.elapsed() < max_age {
+                break;
+            }
+
+            self.failed_broadcasts.pop_front();
+
+            // Ignores the broadcasts started again since then
+            if self.failed_at.get(&certificate_id) == Some(&failed_at) {
+                self.untrack_broadcast(&certificate_id);
+            }
+        }
+    }
+
     /// Build initial delivery state
     async fn delivery_state_for_new_cert(
         &mut self,
@@ -281,14 +543,86 @@ impl DoubleEcho {
         }
     }
 
+    /// Looks for a different certificate already known at the source stream position
+    /// of the given certificate, either broadcast, held in the pending pools or delivered
+    fn find_conflicting_certificate(&self, cert: &Certificate) -> Option<Certificate> {
+        if let Some(known) = self
+            .certificates_in_broadcast
+            .get(&(cert.source_subnet_id, cert.prev_id))
+        {
+            if known.id != cert.id {
+                return Some(known.clone());
+            }
+        }
+
+        let known = self
+            .validator_store
+            .get_conflicting_pool_certificate(cert)
+            .and_then(|known| match known {
+                Some(known) => Ok(Some(known)),
+                None => self
+                    .validator_store
+                    .get_conflicting_delivered_certificate(cert),
+            });
+
+        match known {
+            Ok(known) => known,
+            Err(error) => {
+                error!(
+                    "Unable to check the source stream position of the Certificate {}: {:?}",
+                    cert.id, error
+                );
+
+                None
+            }
+        }
+    }
+
+    /// Persists the evidence of an equivocation and notifies about it
+    fn report_equivocation(&mut self, known: Certificate, conflicting: Certificate) {
+        warn!(
+            "Equivocation detected on subnet {}: Certificate {} conflicts with {}",
+            conflicting.source_subnet_id, conflicting.id, known.id
+        );
+        DOUBLE_ECHO_EQUIVOCATION_TOTAL.inc();
+        // Messages signed for the conflicting certificate are checked against the known one
+        self.misbehaviour_ledger.certificate_known(&conflicting);
+
+        let event = ProtocolEvents::Equivocation {
+            subnet_id: conflicting.source_subnet_id,
+            known_certificate_id: known.id,
+            conflicting_certificate_id: conflicting.id,
+        };
+
+        if let Err(error) = self
+            .validator_store
+            .insert_equivocation_evidence(&EquivocationEvidence { known, conflicting })
+        {
+            error!("Unable to persist the equivocation evidence: {:?}", error);
+        }
+
+        if let Err(error) = self.event_sender.try_send(event) {
+            error!("Unable to send the Equivocation event: {:?}", error);
+        }
+    }
+
     /// Checks done before starting to broadcast
     fn cert_pre_broadcast_check(&self, cert: &Certificate) -> Result<(), ()> {
-        if cert.check_signature().is_err() {
-            error!("Error on the signature");
+        if let SignatureVerification::Enabled(subnet_group_keys) = &self.signature_verification {
+            let verification = match subnet_group_keys.get(&cert.source_subnet_id) {
+                Some(group_public_key) => cert.check_threshold_signature(group_public_key),
+                None => cert.check_signature(),
+            };
+
+            if let Err(error) = verification {
+                error!("Error on the signature: {error}");
+                return Err(());
+            }
         }
 
-        if cert.check_proof().is_err() {
-            error!("Error on the proof");
+        if let Err(error) = cert.check_proof(&*self.verifiers) {
+            error!("Error on the proof: {error}");
+            return Err(());
         }
 
         Ok(())
@@ -303,6 +637,15 @@ impl DoubleEcho {
         signature: Signature,
     ) {
         if self.delivered_certificates.get(&certificate_id).is_none() {
+            self.misbehaviour_ledger.record(
+                validator_id,
+                SignedMessage {
+                    kind: SignedMessageKind::Echo,
+                    certificate_id,
+                    signature: signature.to_string(),
+                },
+            );
+
             let _ = self
                 .task_manager_message_sender
                 .send(DoubleEchoCommand::Echo {
@@ -321,6 +664,15 @@ impl DoubleEcho {
         signature: Signature,
     ) {
         if self.delivered_certificates.get(&certificate_id).is_none() {
+            self.misbehaviour_ledger.record(
+                validator_id,
+                SignedMessage {
+                    kind: SignedMessageKind::Ready,
+                    certificate_id,
+                    signature: signature.to_string(),
+                },
+            );
+
             let _ = self
                 .task_manager_message_sender
                 .send(DoubleEchoCommand::Ready {
@@ -34,8 +34,9 @@
 //!
 use double_echo::DoubleEcho;
 use futures::Stream;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::Duration;
 use tce_transport::{ProtocolEvents, ReliableBroadcastParams};
 use thiserror::Error;
 use tokio::spawn;
@@ -43,17 +44,31 @@ use tokio::sync::mpsc::Sender;
 use tokio::sync::{broadcast, mpsc, oneshot};
 use tokio_stream::wrappers::ReceiverStream;
 use topos_core::types::ValidatorId;
-use topos_core::uci::{Certificate, CertificateId};
+use topos_core::uci::{verifier::VerifierRegistry, Certificate, CertificateId, SubnetId};
+use topos_crypto::frost::GroupPublicKey;
 use topos_crypto::messages::{MessageSigner, Signature};
 use topos_metrics::DOUBLE_ECHO_COMMAND_CHANNEL_CAPACITY_TOTAL;
-use topos_tce_storage::types::CertificateDeliveredWithPositions;
+use topos_tce_storage::types::{CertificateDeliveredWithPositions, EpochId};
 use topos_tce_storage::validator::ValidatorStore;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 pub use topos_core::uci;
 
 pub type Peer = String;
 
+/// Group public keys of the subnets signing their certificates with a threshold signature
+pub type SubnetGroupKeys = HashMap<SubnetId, GroupPublicKey>;
+
+/// Verification of the certificates' signature before broadcasting them
+#[derive(Debug, Clone)]
+pub enum SignatureVerification {
+    /// The certificates of the subnets having a group public key must carry a threshold
+    /// signature of the group, the other ones an ECDSA signature of their subnet key
+    Enabled(SubnetGroupKeys),
+    /// No verification at all, only meant for tests
+    Disabled,
+}
+
 mod constant;
 pub mod double_echo;
 pub mod sampler;
@@ -76,12 +91,53 @@ pub enum TaskStatus {
     Failure,
 }
 
+/// Deadlines applied to the broadcast tasks
+///
+/// A task which doesn't deliver its certificate within `regossip_interval` gossips
+/// it again, up to `max_regossip` times before being abandoned. Tasks waiting for the
+/// delivery of their previous certificate are abandoned after the same total delay.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskDeadlines {
+    pub regossip_interval: Duration,
+    pub max_regossip: usize,
+    /// Delay after which messages received for an unknown certificate are dropped
+    pub buffered_messages_ttl: Duration,
+}
+
+impl TaskDeadlines {
+    pub const REGOSSIP_INTERVAL: Duration = Duration::from_secs(30);
+    pub const MAX_REGOSSIP: usize = 3;
+    pub const BUFFERED_MESSAGES_TTL: Duration = Duration::from_secs(120);
+
+    /// Delay after which a task is abandoned
+    pub fn task_timeout(&self) -> Duration {
+        self.regossip_interval
+            .saturating_mul((self.max_regossip as u32).saturating_add(1))
+    }
+}
+
+impl Default for TaskDeadlines {
+    fn default() -> Self {
+        Self {
+            regossip_interval: *constant::BROADCAST_TASK_REGOSSIP_INTERVAL,
+            max_regossip: *constant::BROADCAST_TASK_MAX_REGOSSIP,
+            buffered_messages_ttl: *constant::BROADCAST_BUFFERED_MESSAGES_TTL,
+        }
+    }
+}
+
 /// Configuration of TCE implementation
 pub struct ReliableBroadcastConfig {
     pub tce_params: ReliableBroadcastParams,
     pub validator_id: ValidatorId,
     pub validators: HashSet<ValidatorId>,
     pub message_signer: Arc<MessageSigner>,
+    /// Registry used to verify the proof of the certificates before broadcasting them
+    pub verifiers: Arc<dyn VerifierRegistry>,
+    /// Verification of the certificates' signature before broadcasting them
+    pub signature_verification: SignatureVerification,
+    /// Current epoch, `validators` being its validator set
+    pub epoch: EpochId,
 }
 
 #[derive(Debug, Clone)]
@@ -105,6 +161,29 @@ pub enum DoubleEchoCommand {
         certificate_id: CertificateId,
         signature: Signature,
     },
+
+    /// Start of a new epoch, the next broadcasts use its validator set
+    EpochChange { epoch: EpochId },
+}
+
+/// Loads the validator set in effect during the given epoch
+pub(crate) fn load_epoch_validators(
+    validator_store: &ValidatorStore,
+    epoch: EpochId,
+) -> Option<HashSet<ValidatorId>> {
+    match validator_store.get_epoch_validators(epoch) {
+        Ok(Some(validators)) if !validators.is_empty() => Some(validators),
+        Ok(_) => {
+            warn!("No validator set registered for the epoch {epoch}");
+
+            None
+        }
+        Err(error) => {
+            error!("Unable to load the validator set of the epoch {epoch}: {error:?}");
+
+            None
+        }
+    }
 }
 
 /// Thread safe client to the protocol aggregate
@@ -132,7 +211,7 @@ impl ReliableBroadcastClient {
         let (task_manager_message_sender, task_manager_message_receiver) =
             mpsc::channel(*constant::BROADCAST_TASK_MANAGER_CHANNEL_SIZE);
 
-        let double_echo = DoubleEcho::new(
+        let mut double_echo = DoubleEcho::new(
             config.tce_params,
             config.validator_id,
             config.message_signer,
@@ -142,8 +221,11 @@ impl ReliableBroadcastClient {
             event_sender,
             double_echo_shutdown_receiver,
             validator_store,
+            config.verifiers,
+            config.signature_verification,
             broadcast_sender,
         );
+        double_echo.epoch = config.epoch;
 
         spawn(double_echo.run(task_manager_message_receiver));
 
@@ -187,6 +269,15 @@ impl ReliableBroadcastClient {
         Ok(())
     }
 
+    /// Notifies the start of a new epoch, the certificates already in broadcast
+    /// keep the validator set of the epoch they started in
+    pub async fn change_epoch(&self, epoch: EpochId) -> Result<(), Errors> {
+        self.command_sender
+            .send(DoubleEchoCommand::EpochChange { epoch })
+            .await
+            .map_err(|error| Errors::DoubleEchoSend(Box::new(error)))
+    }
+
     pub async fn shutdown(&self) -> Result<(), Errors> {
         debug!("Shutting down reliable broadcast client");
         let (double_echo_sender, double_echo_receiver) = oneshot::channel();
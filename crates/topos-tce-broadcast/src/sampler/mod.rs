@@ -14,6 +14,15 @@ pub struct SubscriptionsView {
 }
 
 impl SubscriptionsView {
+    /// Listens for Echo and Ready messages from the whole validator set
+    pub fn from_validators(validators: &HashSet<ValidatorId>) -> Self {
+        Self {
+            echo: validators.clone(),
+            ready: validators.clone(),
+            network_size: validators.len(),
+        }
+    }
+
     pub fn is_some(&self) -> bool {
         !self.is_none()
     }
@@ -17,6 +17,7 @@ use topos_metrics::{
     CERTIFICATE_PROCESSING_FROM_API_TOTAL, CERTIFICATE_PROCESSING_FROM_GOSSIP_TOTAL,
     CERTIFICATE_PROCESSING_TOTAL,
 };
+use topos_tce_storage::types::EpochId;
 use topos_tce_storage::validator::ValidatorStore;
 
 /// The TaskManager is responsible for receiving messages from the network and distributing them
@@ -36,6 +37,8 @@ pub struct TaskManager {
     pub thresholds: ReliableBroadcastParams,
     pub shutdown_sender: mpsc::Sender<()>,
     pub validator_store: Arc<ValidatorStore>,
+    /// Current epoch, new broadcasts are bound to it
+    pub epoch: EpochId,
 }
 
 impl TaskManager {
@@ -49,6 +52,7 @@ impl TaskManager {
         message_signer: Arc<MessageSigner>,
         thresholds: ReliableBroadcastParams,
         validator_store: Arc<ValidatorStore>,
+        epoch: EpochId,
     ) -> (Self, mpsc::Receiver<()>) {
         let (task_completion_sender, task_completion_receiver) =
             mpsc::channel(*constant::BROADCAST_TASK_COMPLETION_CHANNEL_SIZE);
@@ -69,6 +73,7 @@ impl TaskManager {
                 thresholds,
                 shutdown_sender,
                 validator_store,
+                epoch,
             },
             shutdown_receiver,
         )
@@ -93,6 +98,7 @@ impl TaskManager {
                                 std::collections::hash_map::Entry::Vacant(entry) => {
                                     let broadcast_state = BroadcastState::new(
                                         cert.clone(),
+                                        self.epoch,
                                         self.validator_id,
                                         self.thresholds.echo_threshold,
                                         self.thresholds.ready_threshold,
@@ -101,6 +107,7 @@ impl TaskManager {
                                         self.subscriptions.clone(),
                                         need_gossip,
                                         self.message_signer.clone(),
+                                        self.validator_store.clone(),
                                     );
 
                                     let (task, task_context) = Task::new(cert.id, self.task_completion_sender.clone(), broadcast_state, self.validator_store.clone());
@@ -128,6 +135,15 @@ impl TaskManager {
                                 std::collections::hash_map::Entry::Occupied(_) => {},
                             }
                         }
+                        DoubleEchoCommand::EpochChange { epoch } => {
+                            self.epoch = epoch;
+                            if let Some(validators) = crate::load_epoch_validators(&self.validator_store, epoch) {
+                                if validators != self.subscriptions.echo {
+                                    self.thresholds = ReliableBroadcastParams::new(validators.len());
+                                    self.subscriptions = SubscriptionsView::from_validators(&validators);
+                                }
+                            }
+                        }
                     }
                 }
 
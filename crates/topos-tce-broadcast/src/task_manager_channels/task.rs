@@ -65,9 +65,9 @@ impl Task {
             tokio::select! {
                 Some(msg) = self.message_receiver.recv() => {
                     match msg {
-                        DoubleEchoCommand::Echo { validator_id, .. } => {
+                        DoubleEchoCommand::Echo { validator_id, signature, .. } => {
                             if let Some(Status::DeliveredWithReadySent) =
-                                self.broadcast_state.apply_echo(validator_id)
+                                self.broadcast_state.apply_echo(validator_id, signature)
                             {
                                 let _ = self
                                     .completion_sender
@@ -77,9 +77,9 @@ impl Task {
                                 break;
                             }
                         }
-                        DoubleEchoCommand::Ready { validator_id, .. } => {
+                        DoubleEchoCommand::Ready { validator_id, signature, .. } => {
                             if let Some(Status::DeliveredWithReadySent) =
-                                self.broadcast_state.apply_ready(validator_id)
+                                self.broadcast_state.apply_ready(validator_id, signature)
                             {
                                 let _ = self
                                     .completion_sender
@@ -5,8 +5,9 @@ use std::collections::HashMap;
 use std::future::IntoFuture;
 use std::pin::Pin;
 use std::sync::Arc;
-use tce_transport::{ProtocolEvents, ReliableBroadcastParams};
+use tce_transport::{BroadcastFailureReason, ProtocolEvents, ReliableBroadcastParams};
 use tokio::sync::broadcast;
+use tokio::time::Instant;
 use tokio::{spawn, sync::mpsc};
 use topos_core::types::ValidatorId;
 use topos_core::uci::CertificateId;
@@ -15,15 +16,16 @@ use topos_metrics::CERTIFICATE_PROCESSING_FROM_GOSSIP_TOTAL;
 use topos_metrics::CERTIFICATE_PROCESSING_TOTAL;
 use topos_metrics::DOUBLE_ECHO_ACTIVE_TASKS_COUNT;
 use topos_tce_storage::store::ReadStore;
-use topos_tce_storage::types::CertificateDeliveredWithPositions;
+use topos_tce_storage::types::{CertificateDeliveredWithPositions, EpochId};
 use topos_tce_storage::validator::ValidatorStore;
-use tracing::warn;
+use tracing::{error, info, warn};
 
 pub mod task;
 
 use crate::double_echo::broadcast_state::BroadcastState;
 use crate::sampler::SubscriptionsView;
 use crate::DoubleEchoCommand;
+use crate::TaskDeadlines;
 use crate::TaskStatus;
 use task::{Task, TaskContext};
 use topos_crypto::messages::MessageSigner;
@@ -43,12 +45,17 @@ pub struct TaskManager {
     pub message_signer: Arc<MessageSigner>,
     #[allow(clippy::type_complexity)]
     pub running_tasks: RunningTasks,
-    pub buffered_messages: HashMap<CertificateId, Vec<DoubleEchoCommand>>,
+    /// Messages received for certificates without task, along with the instant
+    /// at which the first one was received
+    pub buffered_messages: HashMap<CertificateId, (Instant, Vec<DoubleEchoCommand>)>,
     pub thresholds: ReliableBroadcastParams,
     pub validator_id: ValidatorId,
     pub shutdown_sender: mpsc::Sender<()>,
     pub validator_store: Arc<ValidatorStore>,
     pub broadcast_sender: broadcast::Sender<CertificateDeliveredWithPositions>,
+    pub deadlines: TaskDeadlines,
+    /// Current epoch, new broadcasts are bound to it
+    pub epoch: EpochId,
 
     pub precedence: HashMap<CertificateId, Task>,
 }
@@ -65,6 +72,8 @@ impl TaskManager {
         message_signer: Arc<MessageSigner>,
         validator_store: Arc<ValidatorStore>,
         broadcast_sender: broadcast::Sender<CertificateDeliveredWithPositions>,
+        deadlines: TaskDeadlines,
+        epoch: EpochId,
     ) -> (Self, mpsc::Receiver<()>) {
         let (shutdown_sender, shutdown_receiver) = mpsc::channel(1);
 
@@ -83,6 +92,8 @@ impl TaskManager {
                 shutdown_sender,
                 validator_store,
                 broadcast_sender,
+                deadlines,
+                epoch,
                 precedence: HashMap::new(),
             },
             shutdown_receiver,
@@ -90,6 +101,14 @@ impl TaskManager {
     }
 
     pub async fn run(mut self, mut shutdown_receiver: mpsc::Receiver<()>) {
+        self.restore_tasks();
+
+        let mut cleanup_interval = tokio::time::interval(
+            self.deadlines
+                .regossip_interval
+                .min(self.deadlines.buffered_messages_ttl),
+        );
+
         loop {
             tokio::select! {
                 biased;
@@ -102,58 +121,42 @@ impl TaskManager {
                             } else {
                                 self.buffered_messages
                                     .entry(certificate_id)
-                                    .or_default()
+                                    .or_insert_with(|| (Instant::now(), Vec::new()))
+                                    .1
                                     .push(msg);
                             };
                         }
                         DoubleEchoCommand::Broadcast { ref cert, need_gossip } => {
-                            match self.tasks.entry(cert.id) {
-                                std::collections::hash_map::Entry::Vacant(entry) => {
-                                    let broadcast_state = BroadcastState::new(
-                                        cert.clone(),
-                                        self.validator_id,
-                                        self.thresholds.echo_threshold,
-                                        self.thresholds.ready_threshold,
-                                        self.thresholds.delivery_threshold,
-                                        self.event_sender.clone(),
-                                        self.subscriptions.clone(),
-                                        need_gossip,
-                                        self.message_signer.clone(),
-                                    );
-
-                                    let (task, task_context) = Task::new(
-                                        cert.id,
-                                        broadcast_state,
-                                        self.validator_store.clone(),
-                                        self.broadcast_sender.clone()
-                                    );
-
-                                    let prev = self.validator_store.get_certificate(&cert.prev_id);
-                                    if matches!(prev, Ok(Some(_))) || cert.prev_id == topos_core::uci::INITIAL_CERTIFICATE_ID  {
-                                        Self::start_task(
-                                            &self.running_tasks,
-                                            task,
-                                            task_context.sink.clone(),
-                                            self.buffered_messages.remove(&cert.id),
-                                            need_gossip
-                                        );
-                                    } else {
-                                        self.precedence.insert(cert.prev_id, task);
-                                    }
-                                    entry.insert(task_context);
-                                }
-                                std::collections::hash_map::Entry::Occupied(_) => {},
+                            if !self.tasks.contains_key(&cert.id) {
+                                let broadcast_state = BroadcastState::new(
+                                    cert.clone(),
+                                    self.epoch,
+                                    self.validator_id,
+                                    self.thresholds.echo_threshold,
+                                    self.thresholds.ready_threshold,
+                                    self.thresholds.delivery_threshold,
+                                    self.event_sender.clone(),
+                                    self.subscriptions.clone(),
+                                    need_gossip,
+                                    self.message_signer.clone(),
+                                    self.validator_store.clone(),
+                                );
+
+                                self.schedule_task(broadcast_state, need_gossip);
                             }
                         }
+                        DoubleEchoCommand::EpochChange { epoch } => self.change_epoch(epoch),
                     }
                 }
 
 
                 Some((certificate_id, status)) = self.running_tasks.next() => {
-                    if let TaskStatus::Success = status {
-                        self.tasks.remove(&certificate_id);
-                        DOUBLE_ECHO_ACTIVE_TASKS_COUNT.dec();
-                        let _ = self.task_completion_sender.send((certificate_id, status)).await;
+                    self.tasks.remove(&certificate_id);
+                    DOUBLE_ECHO_ACTIVE_TASKS_COUNT.dec();
+                    let delivered = matches!(status, TaskStatus::Success);
+                    let _ = self.task_completion_sender.send((certificate_id, status)).await;
+
+                    if delivered {
                         if let Some(task) = self.precedence.remove(&certificate_id) {
                             if let Some(context) = self.tasks.get(&task.certificate_id) {
 
@@ -162,7 +165,7 @@ impl TaskManager {
                                     &self.running_tasks,
                                     task,
                                     context.sink.clone(),
-                                    self.buffered_messages.remove(&certificate_id),
+                                    self.buffered_messages.remove(&certificate_id).map(|(_, messages)| messages),
                                     false
                                 );
                             }
@@ -172,6 +175,10 @@ impl TaskManager {
                     }
                 }
 
+                _ = cleanup_interval.tick() => {
+                    self.expire().await;
+                }
+
                 _ = shutdown_receiver.recv() => {
                     warn!("Task Manager shutting down");
 
@@ -185,6 +192,139 @@ impl TaskManager {
         }
     }
 
+    /// Rebuilds the tasks of the broadcasts which were in progress
+    /// before the last shutdown of the validator
+    fn restore_tasks(&mut self) {
+        let states = match self.validator_store.get_broadcast_states() {
+            Ok(states) => states,
+            Err(error) => {
+                error!("Unable to read the persisted broadcast states: {:?}", error);
+
+                return;
+            }
+        };
+
+        for state in states {
+            let certificate_id = state.certificate.id;
+            if matches!(
+                self.validator_store.get_certificate(&certificate_id),
+                Ok(Some(_))
+            ) {
+                _ = self.validator_store.delete_broadcast_state(&certificate_id);
+
+                continue;
+            }
+
+            info!(
+                "Resuming the broadcast of the Certificate {}",
+                certificate_id
+            );
+            let (subscriptions, thresholds) = self.epoch_parameters(state.epoch);
+            let broadcast_state = BroadcastState::restore(
+                state,
+                self.validator_id,
+                thresholds.echo_threshold,
+                thresholds.ready_threshold,
+                thresholds.delivery_threshold,
+                self.event_sender.clone(),
+                subscriptions,
+                self.message_signer.clone(),
+                self.validator_store.clone(),
+            );
+
+            self.schedule_task(broadcast_state, false);
+        }
+    }
+
+    /// Uses the validator set of the new epoch for the next broadcasts
+    fn change_epoch(&mut self, epoch: EpochId) {
+        self.epoch = epoch;
+
+        if let Some(validators) = crate::load_epoch_validators(&self.validator_store, epoch) {
+            if validators != self.subscriptions.echo {
+                self.thresholds = ReliableBroadcastParams::new(validators.len());
+                self.subscriptions = SubscriptionsView::from_validators(&validators);
+            }
+        }
+    }
+
+    /// Subscriptions and thresholds of the broadcasts started in the given epoch
+    fn epoch_parameters(&self, epoch: EpochId) -> (SubscriptionsView, ReliableBroadcastParams) {
+        if epoch != self.epoch {
+            if let Some(validators) = crate::load_epoch_validators(&self.validator_store, epoch) {
+                if validators != self.subscriptions.echo {
+                    return (
+                        SubscriptionsView::from_validators(&validators),
+                        ReliableBroadcastParams::new(validators.len()),
+                    );
+                }
+            }
+        }
+
+        (self.subscriptions.clone(), self.thresholds.clone())
+    }
+
+    /// Creates the task driving the broadcast, the task is started right away
+    /// if the previous certificate is delivered
+    fn schedule_task(&mut self, broadcast_state: BroadcastState, need_gossip: bool) {
+        let certificate_id = broadcast_state.certificate.id;
+        let prev_id = broadcast_state.certificate.prev_id;
+
+        let (task, task_context) = Task::new(
+            certificate_id,
+            broadcast_state,
+            self.validator_store.clone(),
+            self.broadcast_sender.clone(),
+            self.deadlines,
+        );
+
+        let prev = self.validator_store.get_certificate(&prev_id);
+        if matches!(prev, Ok(Some(_))) || prev_id == topos_core::uci::INITIAL_CERTIFICATE_ID {
+            Self::start_task(
+                &self.running_tasks,
+                task,
+                task_context.sink.clone(),
+                self.buffered_messages
+                    .remove(&certificate_id)
+                    .map(|(_, messages)| messages),
+                need_gossip,
+            );
+        } else {
+            self.precedence.insert(prev_id, task);
+        }
+
+        self.tasks.insert(certificate_id, task_context);
+    }
+
+    /// Drops the messages buffered for too long and abandons the tasks
+    /// whose previous certificate wasn't delivered in time
+    async fn expire(&mut self) {
+        let buffered_messages_ttl = self.deadlines.buffered_messages_ttl;
+        self.buffered_messages
+            .retain(|_, (received_at, _)| received_at.elapsed() < buffered_messages_ttl);
+
+        let task_timeout = self.deadlines.task_timeout();
+        let expired: Vec<CertificateId> = self
+            .precedence
+            .iter()
+            .filter(|(_, task)| task.created_at.elapsed() >= task_timeout)
+            .map(|(prev_id, _)| *prev_id)
+            .collect();
+
+        for prev_id in expired {
+            if let Some(task) = self.precedence.remove(&prev_id) {
+                let certificate_id = task.certificate_id;
+                self.tasks.remove(&certificate_id);
+                task.abandon(BroadcastFailureReason::PrecedenceNotDelivered);
+
+                let _ = self
+                    .task_completion_sender
+                    .send((certificate_id, TaskStatus::Failure))
+                    .await;
+            }
+        }
+    }
+
     fn start_task(
         running_tasks: &RunningTasks,
         task: Task,
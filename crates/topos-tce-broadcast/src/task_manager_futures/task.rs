@@ -1,7 +1,9 @@
 use std::future::{Future, IntoFuture};
 use std::pin::Pin;
 use std::sync::Arc;
+use tce_transport::BroadcastFailureReason;
 use tokio::sync::{broadcast, mpsc};
+use tokio::time::Instant;
 
 use topos_core::types::stream::Position;
 use topos_core::uci::CertificateId;
@@ -9,10 +11,10 @@ use topos_tce_storage::errors::StorageError;
 use topos_tce_storage::store::{ReadStore, WriteStore};
 use topos_tce_storage::types::CertificateDeliveredWithPositions;
 use topos_tce_storage::validator::ValidatorStore;
-use tracing::warn;
+use tracing::{error, warn};
 
 use crate::double_echo::broadcast_state::{BroadcastState, Status};
-use crate::{DoubleEchoCommand, TaskStatus};
+use crate::{DoubleEchoCommand, TaskDeadlines, TaskStatus};
 
 #[derive(Debug)]
 pub struct TaskContext {
@@ -26,6 +28,10 @@ pub struct Task {
     pub certificate_id: CertificateId,
     pub broadcast_state: BroadcastState,
     pub shutdown_receiver: mpsc::Receiver<()>,
+    /// Instant at which the task was created, used to expire the tasks
+    /// waiting for their previous certificate
+    pub created_at: Instant,
+    deadlines: TaskDeadlines,
     broadcast_sender: broadcast::Sender<CertificateDeliveredWithPositions>,
 }
 
@@ -35,6 +41,7 @@ impl Task {
         broadcast_state: BroadcastState,
         validator_store: Arc<ValidatorStore>,
         broadcast_sender: broadcast::Sender<CertificateDeliveredWithPositions>,
+        deadlines: TaskDeadlines,
     ) -> (Task, TaskContext) {
         let (message_sender, message_receiver) = mpsc::channel(10_024);
         let (shutdown_sender, shutdown_receiver) = mpsc::channel(1);
@@ -50,12 +57,44 @@ impl Task {
             certificate_id,
             broadcast_state,
             shutdown_receiver,
+            created_at: Instant::now(),
+            deadlines,
             broadcast_sender,
         };
 
         (task, task_context)
     }
 
+    /// Gives up on the broadcast, its progress is not kept
+    pub fn abandon(&self, reason: BroadcastFailureReason) {
+        self.broadcast_state.fail(reason);
+
+        if let Err(error) = self
+            .validator_store
+            .delete_broadcast_state(&self.certificate_id)
+        {
+            error!(
+                "Unable to delete the broadcast state of {}: {:?}",
+                self.certificate_id, error
+            );
+        }
+    }
+
+    async fn deliver(&self) -> TaskStatus {
+        match self.persist().await {
+            Ok(delivered) => {
+                _ = self.broadcast_sender.send(delivered);
+
+                TaskStatus::Success
+            }
+            Err(error) => {
+                error!("Unable to persist one delivered certificate: {:?}", error);
+
+                TaskStatus::Failure
+            }
+        }
+    }
+
     pub async fn persist(&self) -> Result<CertificateDeliveredWithPositions, StorageError> {
         let certificate_delivered = self.broadcast_state.into_delivered();
 
@@ -94,44 +133,43 @@ impl IntoFuture for Task {
             );
             self.broadcast_state.expected_position = Some(expected_position);
 
+            // A restored broadcast may have reached the delivery before the restart
+            if let Status::DeliveredWithReadySent = self.broadcast_state.status() {
+                return (self.certificate_id, self.deliver().await);
+            }
+
+            let deadline = tokio::time::sleep(self.deadlines.regossip_interval);
+            tokio::pin!(deadline);
+            let mut regossip_count = 0;
+
             loop {
                 tokio::select! {
                     Some(msg) = self.message_receiver.recv() => {
-                        match msg {
-                            DoubleEchoCommand::Echo { validator_id, .. } => {
-                                if let Some(Status::DeliveredWithReadySent) = self.broadcast_state.apply_echo(validator_id) {
-                                    match self.persist().await {
-                                        Ok(delivered) => {
-                                            _ = self.broadcast_sender.send(delivered);
-
-                                            return (self.certificate_id, TaskStatus::Success);
-                                        }
-                                        Err(error) => {
-                                            tracing::error!("Unable to persist one delivered certificate: {:?}", error);
-                                            return (self.certificate_id, TaskStatus::Failure);
-                                        }
-                                    }
-
-                                }
+                        let status = match msg {
+                            DoubleEchoCommand::Echo { validator_id, signature, .. } => {
+                                self.broadcast_state.apply_echo(validator_id, signature)
                             }
-                            DoubleEchoCommand::Ready { validator_id, .. } => {
-                                if let Some(Status::DeliveredWithReadySent) = self.broadcast_state.apply_ready(validator_id) {
-                                    match self.persist().await {
-                                        Ok(delivered) => {
-                                            _ = self.broadcast_sender.send(delivered);
-
-                                            return (self.certificate_id, TaskStatus::Success);
-                                        }
-                                        Err(error) => {
-                                            tracing::error!("Unable to persist one delivered certificate: {:?}", error);
-                                            return (self.certificate_id, TaskStatus::Failure);
-                                        }
-                                    }
-                                }
+                            DoubleEchoCommand::Ready { validator_id, signature, .. } => {
+                                self.broadcast_state.apply_ready(validator_id, signature)
                             }
-                            _ => {}
+                            _ => None,
+                        };
+
+                        if let Some(Status::DeliveredWithReadySent) = status {
+                            return (self.certificate_id, self.deliver().await);
                         }
                     }
+                    _ = &mut deadline => {
+                        if regossip_count >= self.deadlines.max_regossip {
+                            self.abandon(BroadcastFailureReason::DeadlineExceeded);
+
+                            return (self.certificate_id, TaskStatus::Failure);
+                        }
+
+                        regossip_count += 1;
+                        self.broadcast_state.regossip();
+                        deadline.as_mut().reset(Instant::now() + self.deadlines.regossip_interval);
+                    }
                     _ = self.shutdown_receiver.recv() => {
                         warn!("Received shutdown, shutting down task {:?}", self.certificate_id);
                         return (self.certificate_id, TaskStatus::Failure)
@@ -1,12 +1,16 @@
 use crate::double_echo::*;
 use crate::*;
 use rstest::*;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 use std::time::Duration;
-use tce_transport::ReliableBroadcastParams;
+use tce_transport::{BroadcastFailureReason, ReliableBroadcastParams};
 use tokio::sync::mpsc::Receiver;
+use topos_core::types::{Misbehaviour, SignedMessage, SignedMessageKind};
+use topos_core::uci::verifier::{HashCommitmentVerifier, Verifiers, HASH_COMMITMENT_VERIFIER_ID};
+use topos_crypto::frost;
 use topos_crypto::messages::MessageSigner;
+use topos_tce_storage::types::BroadcastState as PersistedBroadcastState;
 use topos_test_sdk::constants::*;
 use topos_test_sdk::storage::create_validator_store;
 
@@ -49,7 +53,30 @@ struct Context {
 }
 
 async fn create_context(params: TceParams) -> (DoubleEcho, Context) {
+    create_context_with_deadlines(params, TaskDeadlines::default()).await
+}
+
+async fn create_context_with_deadlines(
+    params: TceParams,
+    task_deadlines: TaskDeadlines,
+) -> (DoubleEcho, Context) {
     let validator_store = create_validator_store::partial_1(vec![]).await;
+
+    build_context(params, task_deadlines, validator_store).await
+}
+
+async fn create_context_with_store(
+    params: TceParams,
+    validator_store: Arc<ValidatorStore>,
+) -> (DoubleEcho, Context) {
+    build_context(params, TaskDeadlines::default(), validator_store).await
+}
+
+async fn build_context(
+    params: TceParams,
+    task_deadlines: TaskDeadlines,
+    validator_store: Arc<ValidatorStore>,
+) -> (DoubleEcho, Context) {
     let (_cmd_sender, cmd_receiver) = mpsc::channel(CHANNEL_SIZE);
     let (event_sender, event_receiver) = mpsc::channel(CHANNEL_SIZE);
     let (_double_echo_shutdown_sender, double_echo_shutdown_receiver) =
@@ -79,9 +106,12 @@ async fn create_context(params: TceParams) -> (DoubleEcho, Context) {
         event_sender,
         double_echo_shutdown_receiver,
         validator_store,
+        Arc::new(Verifiers::default()),
+        SignatureVerification::Disabled,
         broadcast_sender,
     );
 
+    double_echo.task_deadlines = task_deadlines;
     double_echo.spawn_task_manager(task_manager_message_receiver);
 
     (
@@ -105,9 +135,7 @@ async fn reach_echo_threshold(double_echo: &mut DoubleEcho, cert: &Certificate)
     let message_signer = Arc::new(MessageSigner::from_str(PRIVATE_KEY).unwrap());
     let validator_id = ValidatorId::from(message_signer.public_address);
 
-    let mut payload = Vec::new();
-    payload.extend_from_slice(cert.id.as_array());
-    payload.extend_from_slice(validator_id.as_bytes());
+    let payload = SignedMessageKind::Echo.signing_payload(&cert.id, &validator_id);
 
     let signature = message_signer.sign_message(&payload).unwrap();
 
@@ -129,9 +157,7 @@ async fn reach_ready_threshold(double_echo: &mut DoubleEcho, cert: &Certificate)
 
     let validator_id = ValidatorId::from(message_signer.public_address);
 
-    let mut payload = Vec::new();
-    payload.extend_from_slice(cert.id.as_array());
-    payload.extend_from_slice(validator_id.as_bytes());
+    let payload = SignedMessageKind::Ready.signing_payload(&cert.id, &validator_id);
 
     let signature = message_signer.sign_message(&payload).unwrap();
 
@@ -152,9 +178,7 @@ async fn reach_delivery_threshold(double_echo: &mut DoubleEcho, cert: &Certifica
     let message_signer = Arc::new(MessageSigner::from_str(PRIVATE_KEY).unwrap());
     let validator_id = ValidatorId::from(message_signer.public_address);
 
-    let mut payload = Vec::new();
-    payload.extend_from_slice(cert.id.as_array());
-    payload.extend_from_slice(validator_id.as_bytes());
+    let payload = SignedMessageKind::Ready.signing_payload(&cert.id, &validator_id);
 
     let signature = message_signer.sign_message(&payload).unwrap();
 
@@ -254,3 +278,1014 @@ async fn trigger_ready_when_reached_enough_ready(#[case] params: TceParams) {
         Some(ProtocolEvents::Ready { .. })
     ));
 }
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(10))]
+async fn proof_of_delivery_contains_ready_signatures(#[case] params: TceParams) {
+    // One signer per validator, as set up by the context
+    let signers: HashMap<ValidatorId, MessageSigner> = (1..params.nb_peers)
+        .map(|i| MessageSigner::new(&[i as u8; 32]).unwrap())
+        .chain(std::iter::once(
+            MessageSigner::from_str(PRIVATE_KEY).unwrap(),
+        ))
+        .map(|signer| (ValidatorId::from(signer.public_address), signer))
+        .collect();
+
+    let (mut double_echo, mut ctx) = create_context(params).await;
+
+    let dummy_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[])
+            .expect("Dummy certificate");
+
+    double_echo.broadcast(dummy_cert.clone(), true).await;
+
+    reach_echo_threshold(&mut double_echo, &dummy_cert).await;
+
+    let selected = double_echo
+        .subscriptions
+        .ready
+        .iter()
+        .take(double_echo.params.delivery_threshold)
+        .cloned()
+        .collect::<Vec<_>>();
+
+    for validator_id in selected {
+        let payload = SignedMessageKind::Ready.signing_payload(&dummy_cert.id, &validator_id);
+        let signature = signers[&validator_id].sign_message(&payload).unwrap();
+
+        double_echo
+            .handle_ready(dummy_cert.id, validator_id, signature)
+            .await;
+    }
+
+    let CertificateDeliveredWithPositions(delivered, _) =
+        ctx.broadcast_receiver.recv().await.unwrap();
+    let proof = delivered.proof_of_delivery;
+
+    assert_eq!(proof.readies.len(), double_echo.params.delivery_threshold);
+    assert!(proof
+        .verify(
+            &double_echo.validators,
+            double_echo.params.delivery_threshold as u64
+        )
+        .is_ok());
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn refuse_to_echo_conflicting_certificate(#[case] params: TceParams) {
+    let (mut double_echo, mut ctx) = create_context(params).await;
+
+    let known_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[])
+            .expect("Dummy certificate");
+    let conflicting_cert = Certificate::new_with_default_fields(
+        PREV_CERTIFICATE_ID,
+        SOURCE_SUBNET_ID_1,
+        &[TARGET_SUBNET_ID_1],
+    )
+    .expect("Dummy certificate");
+
+    double_echo.broadcast(known_cert.clone(), true).await;
+
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Broadcast { certificate_id }) if certificate_id == known_cert.id
+    ));
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Gossip { .. })
+    ));
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Echo { .. })
+    ));
+
+    double_echo.broadcast(conflicting_cert.clone(), false).await;
+
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Equivocation {
+            subnet_id,
+            known_certificate_id,
+            conflicting_certificate_id,
+        }) if subnet_id == SOURCE_SUBNET_ID_1
+            && known_certificate_id == known_cert.id
+            && conflicting_certificate_id == conflicting_cert.id
+    ));
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert!(matches!(
+        ctx.event_receiver.try_recv(),
+        Err(mpsc::error::TryRecvError::Empty)
+    ));
+
+    let evidence = double_echo
+        .validator_store
+        .get_equivocation_evidence(&conflicting_cert.id)
+        .unwrap()
+        .expect("Equivocation evidence");
+
+    assert_eq!(evidence.known, known_cert);
+    assert_eq!(evidence.conflicting, conflicting_cert);
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn refuse_conflicting_certificate_after_failed_broadcast(#[case] params: TceParams) {
+    let (mut double_echo, mut ctx) = create_context(params).await;
+
+    let known_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[])
+            .expect("Dummy certificate");
+    let conflicting_cert = Certificate::new_with_default_fields(
+        PREV_CERTIFICATE_ID,
+        SOURCE_SUBNET_ID_1,
+        &[TARGET_SUBNET_ID_1],
+    )
+    .expect("Dummy certificate");
+
+    double_echo.broadcast(known_cert.clone(), true).await;
+    for _ in 0..3 {
+        ctx.event_receiver.recv().await.unwrap();
+    }
+
+    double_echo.task_completed(known_cert.id, TaskStatus::Failure);
+
+    // Another subnet's certificate following the same certificate id isn't conflicting
+    let other_subnet_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_2, &[])
+            .expect("Dummy certificate");
+    double_echo.broadcast(other_subnet_cert.clone(), true).await;
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Broadcast { certificate_id }) if certificate_id == other_subnet_cert.id
+    ));
+    for _ in 0..2 {
+        ctx.event_receiver.recv().await.unwrap();
+    }
+
+    double_echo.broadcast(conflicting_cert.clone(), false).await;
+
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Equivocation {
+            known_certificate_id,
+            conflicting_certificate_id,
+            ..
+        }) if known_certificate_id == known_cert.id
+            && conflicting_certificate_id == conflicting_cert.id
+    ));
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn accept_certificate_conflicting_with_evicted_failed_broadcast(#[case] params: TceParams) {
+    let (mut double_echo, mut ctx) = create_context(params).await;
+
+    let known_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[])
+            .expect("Dummy certificate");
+    let conflicting_cert = Certificate::new_with_default_fields(
+        PREV_CERTIFICATE_ID,
+        SOURCE_SUBNET_ID_1,
+        &[TARGET_SUBNET_ID_1],
+    )
+    .expect("Dummy certificate");
+
+    double_echo.broadcast(known_cert.clone(), true).await;
+    for _ in 0..3 {
+        ctx.event_receiver.recv().await.unwrap();
+    }
+
+    double_echo.task_completed(known_cert.id, TaskStatus::Failure);
+    double_echo.certificate_evicted(&known_cert.id);
+
+    double_echo.broadcast(conflicting_cert.clone(), true).await;
+
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Broadcast { certificate_id }) if certificate_id == conflicting_cert.id
+    ));
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn refuse_certificate_conflicting_with_pending_pool(#[case] params: TceParams) {
+    let (mut double_echo, mut ctx) = create_context(params).await;
+
+    let known_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[])
+            .expect("Dummy certificate");
+    let conflicting_cert = Certificate::new_with_default_fields(
+        PREV_CERTIFICATE_ID,
+        SOURCE_SUBNET_ID_1,
+        &[TARGET_SUBNET_ID_1],
+    )
+    .expect("Dummy certificate");
+
+    double_echo
+        .validator_store
+        .insert_pending_certificate(&known_cert)
+        .unwrap();
+
+    double_echo.broadcast(conflicting_cert.clone(), false).await;
+
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Equivocation {
+            known_certificate_id,
+            conflicting_certificate_id,
+            ..
+        }) if known_certificate_id == known_cert.id
+            && conflicting_certificate_id == conflicting_cert.id
+    ));
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn record_conflicting_echoes_in_misbehaviour_ledger(#[case] params: TceParams) {
+    let (mut double_echo, _ctx) = create_context(params).await;
+
+    let known_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[])
+            .expect("Dummy certificate");
+    let conflicting_cert = Certificate::new_with_default_fields(
+        PREV_CERTIFICATE_ID,
+        SOURCE_SUBNET_ID_1,
+        &[TARGET_SUBNET_ID_1],
+    )
+    .expect("Dummy certificate");
+
+    let faulty_signer = MessageSigner::new(&[1u8; 32]).unwrap();
+    let faulty_validator = ValidatorId::from(faulty_signer.public_address);
+
+    let sign = |cert: &Certificate| {
+        let payload = SignedMessageKind::Echo.signing_payload(&cert.id, &faulty_validator);
+
+        faulty_signer.sign_message(&payload).unwrap()
+    };
+
+    // The Echo for the conflicting certificate is received before its gossip
+    double_echo
+        .handle_echo(
+            conflicting_cert.id,
+            faulty_validator,
+            sign(&conflicting_cert),
+        )
+        .await;
+    double_echo.broadcast(known_cert.clone(), true).await;
+    double_echo
+        .handle_echo(known_cert.id, faulty_validator, sign(&known_cert))
+        .await;
+
+    assert!(double_echo
+        .validator_store
+        .get_misbehaviours(&faulty_validator)
+        .unwrap()
+        .is_empty());
+
+    double_echo.broadcast(conflicting_cert.clone(), false).await;
+
+    // The same conflicting message received again isn't recorded twice
+    double_echo
+        .handle_echo(
+            conflicting_cert.id,
+            faulty_validator,
+            sign(&conflicting_cert),
+        )
+        .await;
+
+    let misbehaviours = double_echo
+        .validator_store
+        .get_misbehaviours(&faulty_validator)
+        .unwrap();
+
+    assert_eq!(
+        misbehaviours,
+        vec![Misbehaviour::ConflictingMessages {
+            subnet_id: SOURCE_SUBNET_ID_1,
+            prev_certificate_id: PREV_CERTIFICATE_ID,
+            first: SignedMessage {
+                kind: SignedMessageKind::Echo,
+                certificate_id: known_cert.id,
+                signature: sign(&known_cert).to_string(),
+            },
+            second: SignedMessage {
+                kind: SignedMessageKind::Echo,
+                certificate_id: conflicting_cert.id,
+                signature: sign(&conflicting_cert).to_string(),
+            },
+        }]
+    );
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn record_unknown_certificates_flood_in_misbehaviour_ledger(#[case] params: TceParams) {
+    let (mut double_echo, _ctx) = create_context(params).await;
+    double_echo.misbehaviour_ledger.max_unknown_certificates = 2;
+
+    let message_signer = MessageSigner::from_str(PRIVATE_KEY).unwrap();
+    let validator_id = ValidatorId::from(message_signer.public_address);
+
+    let unknown_certs: Vec<Certificate> =
+        [TARGET_SUBNET_ID_1, TARGET_SUBNET_ID_2, SOURCE_SUBNET_ID_2]
+            .iter()
+            .map(|target| {
+                Certificate::new_with_default_fields(
+                    PREV_CERTIFICATE_ID,
+                    SOURCE_SUBNET_ID_1,
+                    &[*target],
+                )
+                .expect("Dummy certificate")
+            })
+            .collect();
+
+    for (index, cert) in unknown_certs.iter().enumerate() {
+        let payload = SignedMessageKind::Ready.signing_payload(&cert.id, &validator_id);
+        let signature = message_signer.sign_message(&payload).unwrap();
+
+        double_echo
+            .handle_ready(cert.id, validator_id, signature)
+            .await;
+
+        let misbehaviours = double_echo
+            .validator_store
+            .get_misbehaviours(&validator_id)
+            .unwrap();
+
+        if index < 2 {
+            assert!(misbehaviours.is_empty());
+        } else {
+            assert!(matches!(
+                &misbehaviours[..],
+                [Misbehaviour::UnknownCertificatesFlood { messages }] if messages.len() == 3
+            ));
+        }
+    }
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn expired_unknown_messages_do_not_count_as_flood(#[case] params: TceParams) {
+    let (mut double_echo, _ctx) = create_context(params).await;
+    double_echo.misbehaviour_ledger.max_unknown_certificates = 2;
+    double_echo.misbehaviour_ledger.unknown_messages_ttl = Duration::ZERO;
+
+    let message_signer = MessageSigner::from_str(PRIVATE_KEY).unwrap();
+    let validator_id = ValidatorId::from(message_signer.public_address);
+
+    for target in [TARGET_SUBNET_ID_1, TARGET_SUBNET_ID_2, SOURCE_SUBNET_ID_2] {
+        let cert = Certificate::new_with_default_fields(
+            PREV_CERTIFICATE_ID,
+            SOURCE_SUBNET_ID_1,
+            &[target],
+        )
+        .expect("Dummy certificate");
+
+        let payload = SignedMessageKind::Ready.signing_payload(&cert.id, &validator_id);
+        let signature = message_signer.sign_message(&payload).unwrap();
+
+        double_echo
+            .handle_ready(cert.id, validator_id, signature)
+            .await;
+    }
+
+    assert!(double_echo
+        .validator_store
+        .get_misbehaviours(&validator_id)
+        .unwrap()
+        .is_empty());
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn reject_certificate_with_invalid_proof(#[case] params: TceParams) {
+    let (mut double_echo, mut ctx) = create_context(params).await;
+
+    let create_cert = |proof: Vec<u8>| {
+        Certificate::new(
+            PREV_CERTIFICATE_ID,
+            SOURCE_SUBNET_ID_1,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            &[TARGET_SUBNET_ID_1],
+            HASH_COMMITMENT_VERIFIER_ID,
+            proof,
+        )
+        .expect("Dummy certificate")
+    };
+
+    let invalid_cert = create_cert(vec![1, 2, 3]);
+    let valid_cert = create_cert(HashCommitmentVerifier::commitment(&invalid_cert).to_vec());
+
+    double_echo.broadcast(invalid_cert.clone(), true).await;
+
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::BroadcastFailed { certificate_id, .. }) if certificate_id == invalid_cert.id
+    ));
+
+    double_echo.broadcast(valid_cert.clone(), true).await;
+
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Broadcast { certificate_id }) if certificate_id == valid_cert.id
+    ));
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn reject_certificate_with_full_event_channel(#[case] params: TceParams) {
+    let (mut double_echo, mut ctx) = create_context(params).await;
+
+    let invalid_cert = Certificate::new(
+        PREV_CERTIFICATE_ID,
+        SOURCE_SUBNET_ID_1,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        &[TARGET_SUBNET_ID_1],
+        HASH_COMMITMENT_VERIFIER_ID,
+        vec![1, 2, 3],
+    )
+    .expect("Dummy certificate");
+
+    for _ in 0..CHANNEL_SIZE {
+        double_echo.broadcast(invalid_cert.clone(), true).await;
+    }
+
+    // The failure can't be reported while the channel is full
+    double_echo.broadcast(invalid_cert.clone(), true).await;
+
+    for _ in 0..CHANNEL_SIZE {
+        assert!(matches!(
+            ctx.event_receiver.recv().await,
+            Some(ProtocolEvents::BroadcastFailed { .. })
+        ));
+    }
+    assert!(ctx.event_receiver.try_recv().is_err());
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn reject_certificate_with_unauthorized_signature(#[case] params: TceParams) {
+    let (mut double_echo, mut ctx) = create_context(params).await;
+
+    let mut rng = rand::thread_rng();
+    let (group_keys, key_shares) = frost::generate_with_dealer(2, 3, &mut rng).unwrap();
+    double_echo.signature_verification = SignatureVerification::Enabled(
+        [(SOURCE_SUBNET_ID_1, group_keys.group_public_key())]
+            .into_iter()
+            .collect(),
+    );
+
+    let mut forged_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[])
+            .expect("Dummy certificate");
+    forged_cert.update_signature(&[1u8; 32]).unwrap();
+
+    let mut unknown_subnet_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_2, &[])
+            .expect("Dummy certificate");
+    unknown_subnet_cert.update_signature(&[1u8; 32]).unwrap();
+
+    for cert in [forged_cert, unknown_subnet_cert] {
+        double_echo.broadcast(cert.clone(), true).await;
+
+        assert!(matches!(
+            ctx.event_receiver.recv().await,
+            Some(ProtocolEvents::BroadcastFailed { certificate_id, .. }) if certificate_id == cert.id
+        ));
+    }
+
+    // Subnets without group public key sign with the key their id is derived from
+    let (secret_key, subnet_id) = (1u8..)
+        .map(|i| [i; 32])
+        .find_map(|secret_key| {
+            let public_key = topos_crypto::keys::derive_public_key(&secret_key).unwrap();
+            (public_key[0] == 0x02).then(|| {
+                (
+                    secret_key,
+                    SubnetId::from_array(public_key[1..].try_into().unwrap()),
+                )
+            })
+        })
+        .unwrap();
+
+    let mut ecdsa_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, subnet_id, &[TARGET_SUBNET_ID_1])
+            .expect("Dummy certificate");
+    ecdsa_cert.update_signature(&secret_key).unwrap();
+
+    double_echo.broadcast(ecdsa_cert.clone(), true).await;
+
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Broadcast { certificate_id }) if certificate_id == ecdsa_cert.id
+    ));
+    for _ in 0..2 {
+        ctx.event_receiver.recv().await.unwrap();
+    }
+
+    let mut cert = Certificate::new_with_default_fields(
+        PREV_CERTIFICATE_ID,
+        SOURCE_SUBNET_ID_1,
+        &[TARGET_SUBNET_ID_1],
+    )
+    .expect("Dummy certificate");
+
+    let signers = [&key_shares[0], &key_shares[2]];
+    let (nonces, commitments): (Vec<_>, Vec<_>) =
+        signers.iter().map(|signer| signer.commit(&mut rng)).unzip();
+    let shares: Vec<_> = signers
+        .iter()
+        .zip(nonces)
+        .map(|(signer, nonces)| {
+            signer
+                .sign(nonces, &cert.get_payload(), &commitments)
+                .unwrap()
+        })
+        .collect();
+    cert.update_threshold_signature(&group_keys, &commitments, &shares)
+        .unwrap();
+
+    double_echo.broadcast(cert.clone(), true).await;
+
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Broadcast { certificate_id }) if certificate_id == cert.id
+    ));
+}
+
+fn short_deadlines() -> TaskDeadlines {
+    TaskDeadlines {
+        regossip_interval: Duration::from_millis(100),
+        max_regossip: 2,
+        buffered_messages_ttl: Duration::from_millis(100),
+    }
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn abandon_broadcast_after_regossips(#[case] params: TceParams) {
+    let (mut double_echo, mut ctx) = create_context_with_deadlines(params, short_deadlines()).await;
+
+    let dummy_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[])
+            .expect("Dummy certificate");
+
+    double_echo.broadcast(dummy_cert.clone(), true).await;
+
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Broadcast { certificate_id }) if certificate_id == dummy_cert.id
+    ));
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Gossip { .. })
+    ));
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Echo { .. })
+    ));
+
+    // Without enough Echo, the certificate is gossiped again until the limit is reached
+    for _ in 0..2 {
+        assert!(matches!(
+            ctx.event_receiver.recv().await,
+            Some(ProtocolEvents::Gossip { cert }) if cert == dummy_cert
+        ));
+    }
+
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::BroadcastFailed {
+            certificate_id,
+            reason: BroadcastFailureReason::DeadlineExceeded
+        }) if certificate_id == dummy_cert.id
+    ));
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn abandon_broadcast_waiting_for_unknown_precedence(#[case] params: TceParams) {
+    let (mut double_echo, mut ctx) = create_context_with_deadlines(params, short_deadlines()).await;
+
+    let dummy_cert =
+        Certificate::new_with_default_fields(CERTIFICATE_ID_1, SOURCE_SUBNET_ID_1, &[])
+            .expect("Dummy certificate");
+
+    double_echo.broadcast(dummy_cert.clone(), false).await;
+
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Broadcast { certificate_id }) if certificate_id == dummy_cert.id
+    ));
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Echo { .. })
+    ));
+
+    // The previous certificate is never delivered
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::BroadcastFailed {
+            certificate_id,
+            reason: BroadcastFailureReason::PrecedenceNotDelivered
+        }) if certificate_id == dummy_cert.id
+    ));
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn persist_broadcast_progress(#[case] params: TceParams) {
+    let (mut double_echo, mut ctx) = create_context(params).await;
+
+    let dummy_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[])
+            .expect("Dummy certificate");
+
+    double_echo.broadcast(dummy_cert.clone(), true).await;
+
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Broadcast { certificate_id }) if certificate_id == dummy_cert.id
+    ));
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Gossip { .. })
+    ));
+    let Some(ProtocolEvents::Echo {
+        signature: echo_signature,
+        ..
+    }) = ctx.event_receiver.recv().await
+    else {
+        panic!("Expected the Echo of the local validator");
+    };
+
+    // The Echo is persisted before being sent
+    assert_eq!(
+        double_echo
+            .validator_store
+            .get_broadcast_state(&dummy_cert.id)
+            .unwrap()
+            .and_then(|state| state.echo_sent),
+        Some(echo_signature.to_string())
+    );
+
+    reach_echo_threshold(&mut double_echo, &dummy_cert).await;
+
+    let Some(ProtocolEvents::Ready {
+        signature: ready_signature,
+        ..
+    }) = ctx.event_receiver.recv().await
+    else {
+        panic!("Expected the Ready of the local validator");
+    };
+
+    let state = loop {
+        match double_echo
+            .validator_store
+            .get_broadcast_state(&dummy_cert.id)
+        {
+            Ok(Some(state)) if state.ready_sent.is_some() => break state,
+            _ => tokio::time::sleep(Duration::from_millis(10)).await,
+        }
+    };
+
+    assert_eq!(state.certificate, dummy_cert);
+    assert_eq!(state.echoes.len(), double_echo.params.echo_threshold);
+    assert_eq!(state.echo_sent, Some(echo_signature.to_string()));
+    assert_eq!(state.ready_sent, Some(ready_signature.to_string()));
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn resume_broadcast_from_persisted_state(#[case] params: TceParams) {
+    let validator_store = create_validator_store::partial_1(vec![]).await;
+
+    let dummy_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[])
+            .expect("Dummy certificate");
+
+    let message_signer = MessageSigner::from_str(PRIVATE_KEY).unwrap();
+    let validator_id = ValidatorId::from(message_signer.public_address);
+    let payload = SignedMessageKind::Echo.signing_payload(&dummy_cert.id, &validator_id);
+    let echo_signature = message_signer.sign_message(&payload).unwrap();
+
+    validator_store
+        .insert_broadcast_state(&PersistedBroadcastState {
+            certificate: dummy_cert.clone(),
+            epoch: 0,
+            echoes: vec![],
+            readies: vec![],
+            echo_sent: Some(echo_signature.to_string()),
+            ready_sent: None,
+        })
+        .unwrap();
+
+    let (mut double_echo, mut ctx) =
+        create_context_with_store(params, validator_store.clone()).await;
+
+    // The Echo already sent before the restart is sent again as is
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Broadcast { certificate_id }) if certificate_id == dummy_cert.id
+    ));
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Echo { signature, .. }) if signature == echo_signature
+    ));
+
+    reach_echo_threshold(&mut double_echo, &dummy_cert).await;
+
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Ready { .. })
+    ));
+
+    reach_delivery_threshold(&mut double_echo, &dummy_cert).await;
+
+    assert!(matches!(
+        ctx.broadcast_receiver.recv().await,
+        Ok(CertificateDeliveredWithPositions(topos_core::types::CertificateDelivered { certificate, .. }, _)) if certificate == dummy_cert
+    ));
+    assert!(validator_store
+        .get_broadcast_state(&dummy_cert.id)
+        .unwrap()
+        .is_none());
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn rotate_validator_set_on_epoch_change(#[case] params: TceParams) {
+    let (mut double_echo, mut ctx) = create_context(params).await;
+
+    let local_validator_id = double_echo.validator_id;
+    let validators = double_echo
+        .validators
+        .iter()
+        .filter(|validator_id| **validator_id != local_validator_id)
+        .take(3)
+        .cloned()
+        .chain(std::iter::once(local_validator_id))
+        .collect::<HashSet<_>>();
+    double_echo
+        .validator_store
+        .insert_epoch_validators(1, &validators)
+        .unwrap();
+
+    double_echo.change_epoch(1).await;
+
+    let expected = ReliableBroadcastParams::new(validators.len());
+    assert_eq!(double_echo.epoch, 1);
+    assert_eq!(double_echo.validators, validators);
+    assert_eq!(double_echo.subscriptions.network_size, validators.len());
+    assert_eq!(double_echo.params.echo_threshold, expected.echo_threshold);
+    assert_eq!(double_echo.params.ready_threshold, expected.ready_threshold);
+
+    let dummy_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[])
+            .expect("Dummy certificate");
+
+    double_echo.broadcast(dummy_cert.clone(), true).await;
+
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Broadcast { certificate_id }) if certificate_id == dummy_cert.id
+    ));
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Gossip { .. })
+    ));
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Echo { .. })
+    ));
+
+    // The Ready is sent once the echo threshold of the new validator set is reached
+    reach_echo_threshold(&mut double_echo, &dummy_cert).await;
+
+    assert!(matches!(
+        ctx.event_receiver.recv().await,
+        Some(ProtocolEvents::Ready { .. })
+    ));
+    assert_eq!(
+        double_echo
+            .validator_store
+            .get_broadcast_state(&dummy_cert.id)
+            .unwrap()
+            .map(|state| state.epoch),
+        Some(1)
+    );
+}
+
+fn sign_message_for(
+    kind: SignedMessageKind,
+    message_signer: &MessageSigner,
+    cert: &Certificate,
+) -> Signature {
+    let validator_id = ValidatorId::from(message_signer.public_address);
+
+    let payload = kind.signing_payload(&cert.id, &validator_id);
+
+    message_signer.sign_message(&payload).unwrap()
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn drop_messages_from_non_validators(#[case] params: TceParams) {
+    let (mut double_echo, _ctx) = create_context(params).await;
+    double_echo.misbehaviour_ledger.max_unknown_certificates = 0;
+
+    let dummy_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[])
+            .expect("Dummy certificate");
+
+    let outsider = MessageSigner::new(&[200u8; 32]).unwrap();
+    let outsider_id = ValidatorId::from(outsider.public_address);
+    let signature = sign_message_for(SignedMessageKind::Echo, &outsider, &dummy_cert);
+
+    assert!(!double_echo.accept_message(
+        SignedMessageKind::Echo,
+        dummy_cert.id,
+        outsider_id,
+        signature
+    ));
+    assert!(!double_echo
+        .validator_store
+        .get_misbehaviours(&outsider_id)
+        .unwrap()
+        .is_empty());
+
+    // The messages of the validators keep being accepted afterwards
+    let message_signer = MessageSigner::from_str(PRIVATE_KEY).unwrap();
+    let validator_id = ValidatorId::from(message_signer.public_address);
+    let signature = sign_message_for(SignedMessageKind::Ready, &message_signer, &dummy_cert);
+
+    assert!(double_echo.accept_message(
+        SignedMessageKind::Ready,
+        dummy_cert.id,
+        validator_id,
+        signature
+    ));
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn drop_messages_with_invalid_signature(#[case] params: TceParams) {
+    let (mut double_echo, _ctx) = create_context(params).await;
+
+    let dummy_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[])
+            .expect("Dummy certificate");
+
+    let message_signer = MessageSigner::from_str(PRIVATE_KEY).unwrap();
+    let validator_id = ValidatorId::from(message_signer.public_address);
+
+    // Signed by another key than the one of the claimed sender
+    let signature = sign_message_for(
+        SignedMessageKind::Echo,
+        &MessageSigner::new(&[1u8; 32]).unwrap(),
+        &dummy_cert,
+    );
+
+    assert!(!double_echo.accept_message(
+        SignedMessageKind::Echo,
+        dummy_cert.id,
+        validator_id,
+        signature
+    ));
+    assert_eq!(
+        double_echo
+            .misbehaviour_ledger
+            .invalid_signatures(&validator_id),
+        1
+    );
+
+    // The signature of an Echo can't be replayed as the one of a Ready
+    let echo_signature = sign_message_for(SignedMessageKind::Echo, &message_signer, &dummy_cert);
+
+    assert!(!double_echo.accept_message(
+        SignedMessageKind::Ready,
+        dummy_cert.id,
+        validator_id,
+        echo_signature
+    ));
+    assert_eq!(
+        double_echo
+            .misbehaviour_ledger
+            .invalid_signatures(&validator_id),
+        2
+    );
+    assert!(double_echo
+        .validator_store
+        .get_misbehaviours(&validator_id)
+        .unwrap()
+        .is_empty());
+}
+
+#[rstest]
+#[case::small_config(small_config())]
+#[test_log::test(tokio::test)]
+#[trace]
+#[timeout(Duration::from_secs(4))]
+async fn check_messages_against_the_validators_of_their_broadcast_epoch(#[case] params: TceParams) {
+    let (mut double_echo, _ctx) = create_context(params).await;
+
+    let rotated_out = MessageSigner::new(&[1u8; 32]).unwrap();
+    let rotated_out_id = ValidatorId::from(rotated_out.public_address);
+    let validators = double_echo
+        .validators
+        .iter()
+        .filter(|validator_id| **validator_id != rotated_out_id)
+        .cloned()
+        .collect::<HashSet<_>>();
+    double_echo
+        .validator_store
+        .insert_epoch_validators(1, &validators)
+        .unwrap();
+
+    let previous_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[])
+            .expect("Dummy certificate");
+    double_echo.broadcast(previous_cert.clone(), true).await;
+
+    double_echo.change_epoch(1).await;
+
+    let new_cert =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_2, &[])
+            .expect("Dummy certificate");
+    double_echo.broadcast(new_cert.clone(), true).await;
+
+    // The validator rotated out keeps taking part in the broadcasts of its epoch only
+    assert!(double_echo.accept_message(
+        SignedMessageKind::Echo,
+        previous_cert.id,
+        rotated_out_id,
+        sign_message_for(SignedMessageKind::Echo, &rotated_out, &previous_cert)
+    ));
+    assert!(!double_echo.accept_message(
+        SignedMessageKind::Echo,
+        new_cert.id,
+        rotated_out_id,
+        sign_message_for(SignedMessageKind::Echo, &rotated_out, &new_cert)
+    ));
+}
@@ -0,0 +1,608 @@
+//! Consistency checks of the storage of a validator.
+//!
+//! The checks walk the delivered certificates, the source and target streams,
+//! the indexes and the pending pools, looking for entries which disagree with
+//! each other. Some inconsistencies can be repaired from the remaining data,
+//! the others require the node to synchronize the affected streams again.
+
+use std::{collections::HashMap, fmt};
+
+use topos_core::{
+    types::stream::{CertificateSourceStreamPosition, CertificateTargetStreamPosition, Position},
+    uci::{CertificateId, SubnetId, INITIAL_CERTIFICATE_ID},
+};
+use tracing::info;
+
+use crate::{
+    errors::StorageError,
+    rocks::{map::Map, TargetSourceListKey},
+    types::PoolLocation,
+    validator::ValidatorStore,
+    PendingCertificateId,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Inconsistency {
+    /// No certificate is delivered at a position below the head of the source stream
+    SourceStreamGap {
+        subnet_id: SubnetId,
+        position: Position,
+    },
+    /// The certificate at a position doesn't follow the one at the previous position
+    BrokenPrecedence {
+        subnet_id: SubnetId,
+        position: Position,
+        certificate_id: CertificateId,
+        expected_prev_id: CertificateId,
+    },
+    /// A source stream entry points to a certificate which isn't stored
+    MissingSourceStreamCertificate {
+        position: CertificateSourceStreamPosition,
+        certificate_id: CertificateId,
+    },
+    /// A stored certificate isn't at its delivery position in the source stream
+    MisplacedCertificate {
+        certificate_id: CertificateId,
+        position: CertificateSourceStreamPosition,
+    },
+    /// The recorded head of a source stream isn't its last certificate
+    WrongSourceHead {
+        subnet_id: SubnetId,
+        recorded: Option<(CertificateId, Position)>,
+        expected: Option<(CertificateId, Position)>,
+    },
+    /// No certificate is delivered at a position below the head of a target stream
+    TargetStreamGap {
+        target_subnet_id: SubnetId,
+        source_subnet_id: SubnetId,
+        position: Position,
+    },
+    /// A target stream entry points to a certificate which isn't stored
+    MissingTargetStreamCertificate {
+        target_subnet_id: SubnetId,
+        source_subnet_id: SubnetId,
+        position: Position,
+        certificate_id: CertificateId,
+    },
+    /// The recorded head of a target stream isn't its last position
+    WrongTargetHead {
+        target_subnet_id: SubnetId,
+        source_subnet_id: SubnetId,
+        recorded: Option<Position>,
+        expected: Option<Position>,
+    },
+    /// A certificate is in the pending pool while already delivered
+    PendingAndDelivered {
+        certificate_id: CertificateId,
+        pending_id: PendingCertificateId,
+    },
+    /// A certificate awaits its previous certificate while already delivered
+    AwaitingPrecedenceAndDelivered { certificate_id: CertificateId },
+    /// An entry of the pending pool index doesn't match the pending pool
+    DanglingPendingIndex {
+        certificate_id: CertificateId,
+        pending_id: PendingCertificateId,
+    },
+    /// A pool entry tracks a certificate which isn't in the pools anymore
+    DanglingPoolEntry { certificate_id: CertificateId },
+}
+
+impl Inconsistency {
+    /// Whether the inconsistency can be fixed from the data of the store
+    pub fn is_repairable(&self) -> bool {
+        !matches!(
+            self,
+            Self::SourceStreamGap { .. }
+                | Self::BrokenPrecedence { .. }
+                | Self::MisplacedCertificate { .. }
+                | Self::TargetStreamGap { .. }
+        )
+    }
+}
+
+impl fmt::Display for Inconsistency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SourceStreamGap {
+                subnet_id,
+                position,
+            } => write!(
+                f,
+                "Missing certificate at position {position} of the source stream of {subnet_id}"
+            ),
+            Self::BrokenPrecedence {
+                subnet_id,
+                position,
+                certificate_id,
+                expected_prev_id,
+            } => write!(
+                f,
+                "Certificate {certificate_id} at position {position} of the source stream of \
+                 {subnet_id} doesn't follow {expected_prev_id}"
+            ),
+            Self::MissingSourceStreamCertificate {
+                position,
+                certificate_id,
+            } => write!(
+                f,
+                "Source stream position {position} points to the missing certificate \
+                 {certificate_id}"
+            ),
+            Self::MisplacedCertificate {
+                certificate_id,
+                position,
+            } => write!(
+                f,
+                "Certificate {certificate_id} isn't at its delivery position {position}"
+            ),
+            Self::WrongSourceHead {
+                subnet_id,
+                recorded,
+                expected,
+            } => write!(
+                f,
+                "Source head of {subnet_id} is {recorded:?} instead of {expected:?}"
+            ),
+            Self::TargetStreamGap {
+                target_subnet_id,
+                source_subnet_id,
+                position,
+            } => write!(
+                f,
+                "Missing certificate at position {position} of the target stream of \
+                 {target_subnet_id} for {source_subnet_id}"
+            ),
+            Self::MissingTargetStreamCertificate {
+                target_subnet_id,
+                source_subnet_id,
+                position,
+                certificate_id,
+            } => write!(
+                f,
+                "Position {position} of the target stream of {target_subnet_id} for \
+                 {source_subnet_id} points to the missing certificate {certificate_id}"
+            ),
+            Self::WrongTargetHead {
+                target_subnet_id,
+                source_subnet_id,
+                recorded,
+                expected,
+            } => write!(
+                f,
+                "Target head of {target_subnet_id} for {source_subnet_id} is {recorded:?} instead \
+                 of {expected:?}"
+            ),
+            Self::PendingAndDelivered {
+                certificate_id,
+                pending_id,
+            } => write!(
+                f,
+                "Certificate {certificate_id} is delivered but still pending as {pending_id}"
+            ),
+            Self::AwaitingPrecedenceAndDelivered { certificate_id } => write!(
+                f,
+                "Certificate {certificate_id} is delivered but still awaits its precedence"
+            ),
+            Self::DanglingPendingIndex {
+                certificate_id,
+                pending_id,
+            } => write!(
+                f,
+                "Pending index of {certificate_id} points to {pending_id} which isn't pending"
+            ),
+            Self::DanglingPoolEntry { certificate_id } => write!(
+                f,
+                "Certificate {certificate_id} is tracked while not in the pools"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    /// Number of delivered certificates checked
+    pub certificates: usize,
+    pub inconsistencies: Vec<Inconsistency>,
+}
+
+impl CheckReport {
+    pub fn is_consistent(&self) -> bool {
+        self.inconsistencies.is_empty()
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct RepairReport {
+    pub repaired: Vec<Inconsistency>,
+    /// Inconsistencies found once the repairs are done
+    pub remaining: Vec<Inconsistency>,
+}
+
+/// Looks for inconsistencies between the tables of the store
+pub fn check(store: &ValidatorStore) -> Result<CheckReport, StorageError> {
+    let mut report = CheckReport::default();
+
+    check_source_streams(store, &mut report)?;
+    check_target_streams(store, &mut report)?;
+    check_pending_pools(store, &mut report)?;
+
+    info!(
+        "Storage check of {} certificates found {} inconsistencies",
+        report.certificates,
+        report.inconsistencies.len()
+    );
+
+    Ok(report)
+}
+
+/// Fixes the repairable inconsistencies of the store. Heads of the streams are
+/// recomputed from the remaining stream entries.
+pub fn repair(store: &ValidatorStore) -> Result<RepairReport, StorageError> {
+    let report = check(store)?;
+    let fullnode = &store.fullnode_store;
+    let pending = &store.pending_tables;
+
+    let mut batch = fullnode.perpetual_tables.streams.batch();
+    let mut index_batch = fullnode.index_tables.target_streams.batch();
+    let mut pending_batch = pending.pending_pool.batch();
+
+    let mut repaired = Vec::new();
+    for inconsistency in report.inconsistencies {
+        match &inconsistency {
+            Inconsistency::MissingSourceStreamCertificate { position, .. } => {
+                batch = batch.delete(&fullnode.perpetual_tables.streams, position)?;
+            }
+            Inconsistency::MissingTargetStreamCertificate {
+                target_subnet_id,
+                source_subnet_id,
+                position,
+                ..
+            } => {
+                index_batch = index_batch.delete(
+                    &fullnode.index_tables.target_streams,
+                    CertificateTargetStreamPosition::new(
+                        *target_subnet_id,
+                        *source_subnet_id,
+                        *position,
+                    ),
+                )?;
+            }
+            Inconsistency::PendingAndDelivered {
+                certificate_id,
+                pending_id,
+            } => {
+                pending_batch = pending_batch
+                    .delete(&pending.pending_pool, pending_id)?
+                    .delete(&pending.pending_pool_index, certificate_id)?
+                    .delete(&pending.pool_entries, certificate_id)?
+                    .delete(&pending.receptions, certificate_id)?;
+            }
+            Inconsistency::AwaitingPrecedenceAndDelivered { certificate_id } => {
+                if let Some(entry) = pending.pool_entries.get(certificate_id)? {
+                    if let PoolLocation::Precedence(prev_id) = entry.location {
+                        pending_batch = pending_batch.delete(&pending.precedence_pool, prev_id)?;
+                    }
+                }
+                pending_batch = pending_batch
+                    .delete(&pending.pool_entries, certificate_id)?
+                    .delete(&pending.receptions, certificate_id)?;
+            }
+            Inconsistency::DanglingPendingIndex { certificate_id, .. } => {
+                pending_batch =
+                    pending_batch.delete(&pending.pending_pool_index, certificate_id)?;
+            }
+            Inconsistency::DanglingPoolEntry { certificate_id } => {
+                pending_batch = pending_batch
+                    .delete(&pending.pool_entries, certificate_id)?
+                    .delete(&pending.receptions, certificate_id)?;
+            }
+            // Heads are recomputed below
+            Inconsistency::WrongSourceHead { .. } | Inconsistency::WrongTargetHead { .. } => {}
+            _ => continue,
+        }
+
+        repaired.push(inconsistency);
+    }
+
+    batch.write()?;
+    index_batch.write()?;
+    pending_batch.write()?;
+
+    rebuild_heads(store)?;
+
+    Ok(RepairReport {
+        repaired,
+        remaining: check(store)?.inconsistencies,
+    })
+}
+
+fn check_source_streams(
+    store: &ValidatorStore,
+    report: &mut CheckReport,
+) -> Result<(), StorageError> {
+    let tables = &store.fullnode_store.perpetual_tables;
+    let index = &store.fullnode_store.index_tables;
+
+    let mut heads: HashMap<SubnetId, (CertificateId, Position)> = HashMap::new();
+    let mut previous: Option<(SubnetId, u64, CertificateId)> = None;
+    // The history before the start of the streams synchronized from a trusted
+    // checkpoint can be missing
+    let stream_starts: HashMap<SubnetId, Position> = tables.stream_starts.iter()?.collect();
+
+    for (position, certificate_id) in tables.streams.iter()? {
+        let subnet_id = position.subnet_id;
+
+        // Position and precedence expected after the previous entry of the same stream
+        let (mut expected, previous_id) = match previous {
+            Some((previous_subnet, previous_position, previous_id))
+                if previous_subnet == subnet_id =>
+            {
+                (previous_position + 1, Some(previous_id))
+            }
+            _ => (0, None),
+        };
+
+        // Only consecutive positions are linked together
+        let expected_prev_id = match previous_id {
+            Some(previous_id) if *position.position == expected => Some(previous_id),
+            None if *position.position == 0 => Some(INITIAL_CERTIFICATE_ID),
+            _ => None,
+        };
+
+        if let Some(start) = stream_starts.get(&subnet_id) {
+            expected = expected.max(**start);
+        }
+
+        while expected < *position.position {
+            report.inconsistencies.push(Inconsistency::SourceStreamGap {
+                subnet_id,
+                position: expected.into(),
+            });
+            expected += 1;
+        }
+
+        match tables.certificates.get(&certificate_id)? {
+            None => report
+                .inconsistencies
+                .push(Inconsistency::MissingSourceStreamCertificate {
+                    position: position.clone(),
+                    certificate_id,
+                }),
+            Some(certificate) => {
+                if let Some(expected_prev_id) = expected_prev_id {
+                    if certificate.certificate.prev_id != expected_prev_id {
+                        report
+                            .inconsistencies
+                            .push(Inconsistency::BrokenPrecedence {
+                                subnet_id,
+                                position: position.position,
+                                certificate_id,
+                                expected_prev_id,
+                            });
+                    }
+                }
+
+                heads.insert(subnet_id, (certificate_id, position.position));
+            }
+        }
+
+        previous = Some((subnet_id, *position.position, certificate_id));
+    }
+
+    for (certificate_id, certificate) in tables.certificates.iter()? {
+        report.certificates += 1;
+
+        let position = certificate.proof_of_delivery.delivery_position;
+        if tables.streams.get(&position)? != Some(certificate_id) {
+            report
+                .inconsistencies
+                .push(Inconsistency::MisplacedCertificate {
+                    certificate_id,
+                    position,
+                });
+        }
+    }
+
+    let mut recorded: HashMap<SubnetId, (CertificateId, Position)> =
+        index.source_list.iter()?.collect();
+
+    for (subnet_id, expected) in heads {
+        let recorded = recorded.remove(&subnet_id);
+        if recorded != Some(expected) {
+            report.inconsistencies.push(Inconsistency::WrongSourceHead {
+                subnet_id,
+                recorded,
+                expected: Some(expected),
+            });
+        }
+    }
+
+    for (subnet_id, recorded) in recorded {
+        report.inconsistencies.push(Inconsistency::WrongSourceHead {
+            subnet_id,
+            recorded: Some(recorded),
+            expected: None,
+        });
+    }
+
+    Ok(())
+}
+
+fn check_target_streams(
+    store: &ValidatorStore,
+    report: &mut CheckReport,
+) -> Result<(), StorageError> {
+    let tables = &store.fullnode_store.perpetual_tables;
+    let index = &store.fullnode_store.index_tables;
+
+    let mut heads: HashMap<(SubnetId, SubnetId), Position> = HashMap::new();
+    let mut last_positions: HashMap<(SubnetId, SubnetId), u64> = HashMap::new();
+
+    for (position, certificate_id) in index.target_streams.iter()? {
+        let stream = (position.target_subnet_id, position.source_subnet_id);
+        let mut expected = last_positions
+            .insert(stream, *position.position)
+            .map_or(0, |last| last + 1);
+
+        while expected < *position.position {
+            report.inconsistencies.push(Inconsistency::TargetStreamGap {
+                target_subnet_id: stream.0,
+                source_subnet_id: stream.1,
+                position: expected.into(),
+            });
+            expected += 1;
+        }
+
+        if tables.certificates.get(&certificate_id)?.is_none() {
+            report
+                .inconsistencies
+                .push(Inconsistency::MissingTargetStreamCertificate {
+                    target_subnet_id: stream.0,
+                    source_subnet_id: stream.1,
+                    position: position.position,
+                    certificate_id,
+                });
+        } else {
+            heads.insert(stream, position.position);
+        }
+    }
+
+    let mut recorded: HashMap<(SubnetId, SubnetId), Position> = index
+        .target_source_list
+        .iter()?
+        .map(|(TargetSourceListKey(target, source), position)| ((target, source), position))
+        .collect();
+
+    for (stream, expected) in heads {
+        let recorded = recorded.remove(&stream);
+        if recorded != Some(expected) {
+            report.inconsistencies.push(Inconsistency::WrongTargetHead {
+                target_subnet_id: stream.0,
+                source_subnet_id: stream.1,
+                recorded,
+                expected: Some(expected),
+            });
+        }
+    }
+
+    for ((target_subnet_id, source_subnet_id), recorded) in recorded {
+        report.inconsistencies.push(Inconsistency::WrongTargetHead {
+            target_subnet_id,
+            source_subnet_id,
+            recorded: Some(recorded),
+            expected: None,
+        });
+    }
+
+    Ok(())
+}
+
+fn check_pending_pools(
+    store: &ValidatorStore,
+    report: &mut CheckReport,
+) -> Result<(), StorageError> {
+    let certificates = &store.fullnode_store.perpetual_tables.certificates;
+    let pending = &store.pending_tables;
+
+    for (pending_id, certificate) in pending.pending_pool.iter()? {
+        if certificates.get(&certificate.id)?.is_some() {
+            report
+                .inconsistencies
+                .push(Inconsistency::PendingAndDelivered {
+                    certificate_id: certificate.id,
+                    pending_id,
+                });
+        }
+    }
+
+    for (_, certificate) in pending.precedence_pool.iter()? {
+        if certificates.get(&certificate.id)?.is_some() {
+            report
+                .inconsistencies
+                .push(Inconsistency::AwaitingPrecedenceAndDelivered {
+                    certificate_id: certificate.id,
+                });
+        }
+    }
+
+    for (certificate_id, pending_id) in pending.pending_pool_index.iter()? {
+        let indexed = pending.pending_pool.get(&pending_id)?;
+        if !matches!(indexed, Some(certificate) if certificate.id == certificate_id) {
+            report
+                .inconsistencies
+                .push(Inconsistency::DanglingPendingIndex {
+                    certificate_id,
+                    pending_id,
+                });
+        }
+    }
+
+    for (certificate_id, entry) in pending.pool_entries.iter()? {
+        let tracked = match entry.location {
+            PoolLocation::Pending(pending_id) => pending.pending_pool.get(&pending_id)?,
+            PoolLocation::Precedence(prev_id) => pending.precedence_pool.get(&prev_id)?,
+        };
+
+        if !matches!(tracked, Some(certificate) if certificate.id == certificate_id) {
+            report
+                .inconsistencies
+                .push(Inconsistency::DanglingPoolEntry { certificate_id });
+        }
+    }
+
+    Ok(())
+}
+
+/// Sets the head of every source and target stream to its last entry
+fn rebuild_heads(store: &ValidatorStore) -> Result<(), StorageError> {
+    let tables = &store.fullnode_store.perpetual_tables;
+    let index = &store.fullnode_store.index_tables;
+
+    let source_heads: HashMap<SubnetId, (CertificateId, Position)> = tables
+        .streams
+        .iter()?
+        .map(|(position, certificate_id)| (position.subnet_id, (certificate_id, position.position)))
+        .collect();
+
+    let target_heads: HashMap<(SubnetId, SubnetId), Position> = index
+        .target_streams
+        .iter()?
+        .map(|(position, _)| {
+            (
+                (position.target_subnet_id, position.source_subnet_id),
+                position.position,
+            )
+        })
+        .collect();
+
+    let mut batch = index.source_list.batch();
+
+    for (subnet_id, _) in index.source_list.iter()? {
+        if !source_heads.contains_key(&subnet_id) {
+            batch = batch.delete(&index.source_list, subnet_id)?;
+        }
+    }
+
+    for (TargetSourceListKey(target, source), _) in index.target_source_list.iter()? {
+        if !target_heads.contains_key(&(target, source)) {
+            batch = batch.delete(
+                &index.target_source_list,
+                TargetSourceListKey(target, source),
+            )?;
+        }
+    }
+
+    batch
+        .insert_batch(&index.source_list, source_heads)?
+        .insert_batch(
+            &index.target_source_list,
+            target_heads
+                .into_iter()
+                .map(|((target, source), position)| {
+                    (TargetSourceListKey(target, source), position)
+                }),
+        )?
+        .write()?;
+
+    Ok(())
+}
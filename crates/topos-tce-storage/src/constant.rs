@@ -1,21 +1,83 @@
+use std::time::Duration;
+
+use lazy_static::lazy_static;
+
+lazy_static! {
+    /// Maximum number of certificates of a source subnet kept in the pending and precedence pools
+    pub static ref PENDING_POOL_MAX_CERTIFICATES_PER_SUBNET: usize =
+        std::env::var("TOPOS_PENDING_POOL_MAX_CERTIFICATES_PER_SUBNET")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(1_000);
+    /// Age after which a certificate is evicted from the pending and precedence pools
+    pub static ref PENDING_POOL_CERTIFICATE_TTL: Duration = Duration::from_secs(
+        std::env::var("TOPOS_PENDING_POOL_CERTIFICATE_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(3_600)
+    );
+    /// Interval between two evictions of the expired pending certificates
+    pub static ref PENDING_POOL_CLEANUP_INTERVAL: Duration = Duration::from_secs(
+        std::env::var("TOPOS_PENDING_POOL_CLEANUP_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(60)
+    );
+    /// Duration of the slots in which the validators agree on the next checkpoint
+    pub static ref CHECKPOINT_INTERVAL: Duration = Duration::from_secs(
+        std::env::var("TOPOS_CHECKPOINT_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(60)
+    );
+    /// Interval between two exports of the statistics of the storage
+    pub static ref STORAGE_STATISTICS_INTERVAL: Duration = Duration::from_secs(
+        std::env::var("TOPOS_STORAGE_STATISTICS_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(30)
+    );
+}
+
 pub(crate) mod cfs {
+    /// Version of the schema of each database
+    pub(crate) const SCHEMA: &str = "schema";
+
     pub(crate) const CERTIFICATES: &str = "certificates";
     pub(crate) const STREAMS: &str = "streams";
     pub(crate) const EPOCH_CHAIN: &str = "epoch_chain";
+    pub(crate) const CHECKPOINTS: &str = "checkpoints";
     pub(crate) const UNVERIFIED: &str = "unverified";
+    pub(crate) const EQUIVOCATIONS: &str = "equivocations";
+    pub(crate) const MISBEHAVIOURS: &str = "misbehaviours";
+    pub(crate) const CERTIFICATE_TIMESTAMPS: &str = "certificate_timestamps";
+    pub(crate) const STREAM_STARTS: &str = "stream_starts";
 
     pub(crate) const PENDING_POOL: &str = "pending_pool";
     pub(crate) const PENDING_POOL_INDEX: &str = "pending_pool_index";
     pub(crate) const PRECEDENCE_POOL: &str = "precedence_pool";
+    pub(crate) const POOL_ENTRIES: &str = "pool_entries";
+    pub(crate) const POOL_SUCCESSORS: &str = "pool_successors";
+    pub(crate) const POOL_SUBNET_ENTRIES: &str = "pool_subnet_entries";
+    pub(crate) const POOL_INSERTIONS: &str = "pool_insertions";
+    pub(crate) const EVICTIONS: &str = "evictions";
+    pub(crate) const EVICTION_TIMES: &str = "eviction_times";
+    pub(crate) const RECEPTIONS: &str = "receptions";
 
     pub(crate) const TARGET_STREAMS: &str = "target_streams";
     pub(crate) const TARGET_SOURCE_LIST: &str = "target_source_list";
     pub(crate) const SOURCE_LIST: &str = "source_list";
     pub(crate) const DELIVERED_CERTIFICATES_PER_SOURCE_FOR_TARGET: &str =
         "delivered_certificates_per_source_for_target";
+    pub(crate) const CERTIFICATE_ORDER: &str = "certificate_order";
+    pub(crate) const CERTIFICATE_SEQUENCES: &str = "certificate_sequences";
+    pub(crate) const SOURCE_DELIVERY_TIMES: &str = "source_delivery_times";
+    pub(crate) const TARGET_DELIVERY_TIMES: &str = "target_delivery_times";
 
     pub(crate) const VALIDATORS: &str = "validators";
 
     pub(crate) const EPOCH_SUMMARY: &str = "epoch_summary";
     pub(crate) const BROADCAST_STATES: &str = "broadcast_states";
+    pub(crate) const BROADCAST_HEADERS: &str = "broadcast_headers";
+    pub(crate) const BROADCAST_MESSAGES: &str = "broadcast_messages";
 }
@@ -5,50 +5,152 @@ use std::{collections::HashMap, sync::RwLock};
 use arc_swap::ArcSwap;
 
 use crate::errors::StorageError;
-use crate::types::{EpochId, Validators};
+use crate::rocks::map::Map;
+use crate::types::{ColumnFamilyStatistics, EpochId, Validators};
 
 pub(crate) use self::tables::EpochValidatorsTables;
 pub(crate) use self::tables::ValidatorPerEpochTables;
 
-mod tables;
+pub(crate) mod tables;
 
 /// Epoch contextualized data - can be purged at some point
+///
+/// A node keeps the store of the epoch it was started in until it restarts, the
+/// broadcasts started in the following epochs being persisted in the same store.
 pub struct ValidatorPerEpochStore {
-    #[allow(unused)]
     epoch_id: EpochId,
     #[allow(unused)]
     validators: RwLock<Validators>,
-    #[allow(unused)]
-    tables: ValidatorPerEpochTables,
+    pub(crate) tables: ValidatorPerEpochTables,
 }
 
 impl ValidatorPerEpochStore {
+    /// Opens the store of the epoch, moving to it the broadcasts still in progress
+    /// in the store of the previous epoch the node ran in
     pub fn new(epoch_id: EpochId, path: PathBuf) -> Result<ArcSwap<Self>, StorageError> {
-        let tables: ValidatorPerEpochTables = ValidatorPerEpochTables::open(epoch_id, path);
-        let store = ArcSwap::from(Arc::new(Self {
+        let previous = epoch_id
+            .checked_sub(1)
+            .and_then(|up_to| ValidatorPerEpochTables::latest_epoch(up_to, path.clone()));
+        let tables: ValidatorPerEpochTables = ValidatorPerEpochTables::open(epoch_id, path.clone());
+
+        if let Some(previous) = previous {
+            tables.take_broadcasts(&ValidatorPerEpochTables::open(previous, path))?;
+        }
+
+        Ok(Self::with_tables(epoch_id, tables))
+    }
+
+    /// Returns the last epoch up to `up_to` whose store was created at `path`
+    pub fn latest_epoch(up_to: EpochId, path: PathBuf) -> Option<EpochId> {
+        ValidatorPerEpochTables::latest_epoch(up_to, path)
+    }
+
+    pub fn epoch_id(&self) -> EpochId {
+        self.epoch_id
+    }
+
+    /// Opens the store of the node running at `path` as a read-only secondary instance
+    pub fn new_secondary(
+        epoch_id: EpochId,
+        path: PathBuf,
+        secondary_path: PathBuf,
+    ) -> Result<ArcSwap<Self>, StorageError> {
+        let tables = ValidatorPerEpochTables::open_secondary(epoch_id, path, secondary_path);
+
+        Ok(Self::with_tables(epoch_id, tables))
+    }
+
+    /// Creates the store without persisting it on disk
+    pub fn new_in_memory(epoch_id: EpochId) -> Result<ArcSwap<Self>, StorageError> {
+        let tables = ValidatorPerEpochTables::open_in_memory();
+
+        Ok(Self::with_tables(epoch_id, tables))
+    }
+
+    fn with_tables(epoch_id: EpochId, tables: ValidatorPerEpochTables) -> ArcSwap<Self> {
+        ArcSwap::from(Arc::new(Self {
             epoch_id,
             validators: RwLock::new(Vec::new()),
             tables,
-        }));
-
-        Ok(store)
+        }))
     }
 }
 pub struct EpochValidatorsStore {
-    #[allow(unused)]
     tables: EpochValidatorsTables,
-    #[allow(unused)]
     caches: RwLock<HashMap<EpochId, Validators>>,
 }
 
 impl EpochValidatorsStore {
     pub fn new(path: PathBuf) -> Result<Arc<Self>, StorageError> {
         let tables = EpochValidatorsTables::open(path);
-        let store = Arc::new(Self {
+
+        Ok(Self::with_tables(tables))
+    }
+
+    /// Opens the store of the node running at `path` as a read-only secondary instance
+    pub fn new_secondary(
+        path: PathBuf,
+        secondary_path: PathBuf,
+    ) -> Result<Arc<Self>, StorageError> {
+        let tables = EpochValidatorsTables::open_secondary(path, secondary_path);
+
+        Ok(Self::with_tables(tables))
+    }
+
+    /// Creates the store without persisting it on disk
+    pub fn new_in_memory() -> Result<Arc<Self>, StorageError> {
+        Ok(Self::with_tables(EpochValidatorsTables::open_in_memory()))
+    }
+
+    fn with_tables(tables: EpochValidatorsTables) -> Arc<Self> {
+        Arc::new(Self {
             tables,
             caches: RwLock::new(HashMap::new()),
-        });
+        })
+    }
+
+    /// Applies the latest changes of the primary instance when opened as a secondary one
+    pub(crate) fn catch_up(&self) -> Result<(), StorageError> {
+        Ok(self.tables.catch_up()?)
+    }
+
+    pub(crate) fn statistics(&self) -> Result<Vec<ColumnFamilyStatistics>, StorageError> {
+        Ok(self.tables.statistics()?)
+    }
+
+    /// Registers the validator set of an epoch
+    pub fn insert_validators(
+        &self,
+        epoch_id: EpochId,
+        validators: Validators,
+    ) -> Result<(), StorageError> {
+        self.tables.validators_map.insert(&epoch_id, &validators)?;
+
+        if let Ok(mut caches) = self.caches.write() {
+            caches.insert(epoch_id, validators);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the validator set in effect during the given epoch, which is the one
+    /// registered for this epoch or else for the closest previous one
+    pub fn get_validators(&self, epoch_id: EpochId) -> Result<Option<Validators>, StorageError> {
+        if let Some(validators) = self
+            .caches
+            .read()
+            .ok()
+            .and_then(|caches| caches.get(&epoch_id).cloned())
+        {
+            return Ok(Some(validators));
+        }
 
-        Ok(store)
+        Ok(self
+            .tables
+            .validators_map
+            .iter()?
+            .filter(|(registered_epoch, _)| *registered_epoch <= epoch_id)
+            .max_by_key(|(registered_epoch, _)| *registered_epoch)
+            .map(|(_, validators)| validators))
     }
 }
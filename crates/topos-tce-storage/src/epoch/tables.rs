@@ -1,42 +1,105 @@
-use std::{fs::create_dir_all, path::PathBuf};
+use std::{
+    fs::{create_dir_all, read_dir},
+    path::PathBuf,
+};
 
 use rocksdb::ColumnFamilyDescriptor;
-use topos_core::uci::CertificateId;
+use topos_core::{types::Signature, uci::CertificateId};
 use tracing::warn;
 
 use crate::{
     constant::cfs,
+    errors::InternalStorageError,
     rocks::{
-        db::{default_options, init_db, init_with_cfs},
-        db_column::DBColumn,
+        db::{default_options, init_secondary_with_cfs, init_with_cfs},
+        db_column::{Backend, DBBatch, DBColumn},
+        map::Map,
+        memory::MemoryDB,
+        schema,
+    },
+    types::{
+        BroadcastHeader, BroadcastMessageKey, ColumnFamilyStatistics, EpochId, Validators,
+        VerifiedCheckpointSummary,
     },
-    types::{BroadcastState, EpochId, Validators, VerifiedCheckpointSummary},
 };
 
 pub struct EpochValidatorsTables {
-    #[allow(unused)]
-    validators_map: DBColumn<EpochId, Validators>,
+    /// Validator set registered for each epoch
+    pub(crate) validators_map: DBColumn<EpochId, Validators>,
 }
 
 impl EpochValidatorsTables {
     pub(crate) fn open(mut path: PathBuf) -> Self {
         path.push("validators");
-        let mut options = rocksdb::Options::default();
-        options.create_if_missing(true);
-        let db = init_db(&path, options).unwrap_or_else(|_| panic!("Cannot open DB at {:?}", path));
 
+        let db = init_with_cfs(&path, default_options(), Self::column_families())
+            .unwrap_or_else(|_| panic!("Cannot open DB at {:?}", path));
+
+        Self::with_backend(db.into())
+    }
+
+    pub(crate) fn open_secondary(mut path: PathBuf, mut secondary_path: PathBuf) -> Self {
+        path.push("validators");
+        secondary_path.push("validators");
+        create_dir_all(&secondary_path).expect("Cannot create EpochValidatorsTables directory");
+
+        let db = init_secondary_with_cfs(
+            &path,
+            &secondary_path,
+            default_options(),
+            Self::column_families(),
+        )
+        .unwrap_or_else(|e| panic!("Cannot open DB at {:?} => error {:?}", path, e))
+        .into();
+
+        schema::EPOCH_VALIDATORS.open_secondary(&db);
+
+        Self::from_backend(&db)
+    }
+
+    fn column_families() -> Vec<ColumnFamilyDescriptor> {
+        vec![
+            ColumnFamilyDescriptor::new(cfs::SCHEMA, default_options()),
+            ColumnFamilyDescriptor::new(cfs::VALIDATORS, default_options()),
+        ]
+    }
+
+    pub(crate) fn open_in_memory() -> Self {
+        Self::with_backend(MemoryDB::new(&[cfs::SCHEMA, cfs::VALIDATORS]).into())
+    }
+
+    fn with_backend(db: Backend) -> Self {
+        schema::EPOCH_VALIDATORS.open(&db);
+
+        Self::from_backend(&db)
+    }
+
+    fn from_backend(db: &Backend) -> Self {
         Self {
-            validators_map: DBColumn::reopen(&db, cfs::VALIDATORS),
+            validators_map: DBColumn::from_backend(db, cfs::VALIDATORS),
         }
     }
+
+    /// Applies the latest changes of the primary instance when opened as a secondary one
+    pub(crate) fn catch_up(&self) -> Result<(), InternalStorageError> {
+        self.validators_map.backend.try_catch_up_with_primary()
+    }
+
+    pub(crate) fn statistics(&self) -> Result<Vec<ColumnFamilyStatistics>, InternalStorageError> {
+        self.validators_map
+            .backend
+            .statistics(schema::EPOCH_VALIDATORS.database)
+    }
 }
 
 /// Epoch contextualized data - can be purged at some point
 pub struct ValidatorPerEpochTables {
     #[allow(unused)]
     epoch_summary: DBColumn<EpochSummaryKey, EpochSummaryValue>,
-    #[allow(unused)]
-    broadcast_states: DBColumn<CertificateId, BroadcastState>,
+    /// Certificates currently broadcast
+    pub(crate) broadcast_headers: DBColumn<CertificateId, BroadcastHeader>,
+    /// Messages received or sent for the certificates currently broadcast
+    pub(crate) broadcast_messages: DBColumn<BroadcastMessageKey, Signature>,
     #[allow(unused)]
     validators: Vec<Validators>,
 }
@@ -49,20 +112,150 @@ impl ValidatorPerEpochTables {
             warn!("Path {:?} does not exist, creating it", path);
             create_dir_all(&path).expect("Cannot create ValidatorPerEpochTables directory");
         }
-        let cfs = vec![
+
+        let db = init_with_cfs(&path, default_options(), Self::column_families())
+            .unwrap_or_else(|_| panic!("Cannot open DB at {:?}", path));
+
+        Self::with_backend(db.into())
+    }
+
+    pub(crate) fn open_secondary(
+        epoch_id: EpochId,
+        mut path: PathBuf,
+        mut secondary_path: PathBuf,
+    ) -> Self {
+        for path in [&mut path, &mut secondary_path] {
+            path.push("epochs");
+            path.push(epoch_id.to_string());
+        }
+        create_dir_all(&secondary_path).expect("Cannot create ValidatorPerEpochTables directory");
+
+        let db = init_secondary_with_cfs(
+            &path,
+            &secondary_path,
+            default_options(),
+            Self::column_families(),
+        )
+        .unwrap_or_else(|e| panic!("Cannot open DB at {:?} => error {:?}", path, e))
+        .into();
+
+        schema::EPOCH.open_secondary(&db);
+
+        Self::from_backend(&db)
+    }
+
+    fn column_families() -> Vec<ColumnFamilyDescriptor> {
+        vec![
+            ColumnFamilyDescriptor::new(cfs::SCHEMA, default_options()),
             ColumnFamilyDescriptor::new(cfs::EPOCH_SUMMARY, default_options()),
             ColumnFamilyDescriptor::new(cfs::BROADCAST_STATES, default_options()),
-        ];
+            ColumnFamilyDescriptor::new(cfs::BROADCAST_HEADERS, default_options()),
+            ColumnFamilyDescriptor::new(cfs::BROADCAST_MESSAGES, default_options()),
+        ]
+    }
 
-        let db = init_with_cfs(&path, default_options(), cfs)
-            .unwrap_or_else(|_| panic!("Cannot open DB at {:?}", path));
+    pub(crate) fn open_in_memory() -> Self {
+        Self::with_backend(
+            MemoryDB::new(&[
+                cfs::SCHEMA,
+                cfs::EPOCH_SUMMARY,
+                cfs::BROADCAST_STATES,
+                cfs::BROADCAST_HEADERS,
+                cfs::BROADCAST_MESSAGES,
+            ])
+            .into(),
+        )
+    }
+
+    fn with_backend(db: Backend) -> Self {
+        schema::EPOCH.open(&db);
+
+        Self::from_backend(&db)
+    }
 
+    fn from_backend(db: &Backend) -> Self {
         Self {
-            epoch_summary: DBColumn::reopen(&db, cfs::EPOCH_SUMMARY),
-            broadcast_states: DBColumn::reopen(&db, cfs::BROADCAST_STATES),
+            epoch_summary: DBColumn::from_backend(db, cfs::EPOCH_SUMMARY),
+            broadcast_headers: DBColumn::from_backend(db, cfs::BROADCAST_HEADERS),
+            broadcast_messages: DBColumn::from_backend(db, cfs::BROADCAST_MESSAGES),
             validators: Vec::new(),
         }
     }
+
+    /// Applies the latest changes of the primary instance when opened as a secondary one
+    pub(crate) fn catch_up(&self) -> Result<(), InternalStorageError> {
+        self.broadcast_headers.backend.try_catch_up_with_primary()
+    }
+
+    /// Returns the last epoch up to `up_to` whose tables were created at `path`
+    pub(crate) fn latest_epoch(up_to: EpochId, mut path: PathBuf) -> Option<EpochId> {
+        path.push("epochs");
+
+        read_dir(path)
+            .ok()?
+            .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<EpochId>().ok())
+            .filter(|epoch| *epoch <= up_to)
+            .max()
+    }
+
+    /// Moves the broadcasts still in progress in the tables of a previous epoch,
+    /// unless broadcasts were already started or moved in these tables
+    pub(crate) fn take_broadcasts(&self, previous: &Self) -> Result<(), InternalStorageError> {
+        if self.broadcast_headers.iter()?.next().is_some() {
+            return Ok(());
+        }
+
+        let headers: Vec<_> = previous.broadcast_headers.iter()?.collect();
+        if headers.is_empty() {
+            return Ok(());
+        }
+        let messages: Vec<_> = previous.broadcast_messages.iter()?.collect();
+
+        self.broadcast_headers
+            .batch()
+            .insert_batch(&self.broadcast_headers, headers.iter().cloned())?
+            .insert_batch(&self.broadcast_messages, messages.iter().cloned())?
+            .write()?;
+
+        // Removed once moved, the broadcasts are never resumed twice
+        let mut batch = previous.broadcast_headers.batch();
+        for (certificate_id, _) in &headers {
+            batch = batch.delete(&previous.broadcast_headers, certificate_id)?;
+        }
+        for (key, _) in &messages {
+            batch = batch.delete(&previous.broadcast_messages, key)?;
+        }
+        batch.write()?;
+
+        Ok(())
+    }
+
+    pub(crate) fn statistics(&self) -> Result<Vec<ColumnFamilyStatistics>, InternalStorageError> {
+        self.broadcast_headers
+            .backend
+            .statistics(schema::EPOCH.database)
+    }
+}
+
+/// Migration of the epoch database discarding the progress of the broadcasts whose
+/// messages were signed without their kind, which the other validators now refuse
+pub(crate) fn discard_untagged_broadcasts(
+    db: &Backend,
+    mut batch: DBBatch,
+) -> Result<DBBatch, InternalStorageError> {
+    let broadcast_headers: DBColumn<CertificateId, BroadcastHeader> =
+        DBColumn::from_backend(db, cfs::BROADCAST_HEADERS);
+    let broadcast_messages: DBColumn<BroadcastMessageKey, Signature> =
+        DBColumn::from_backend(db, cfs::BROADCAST_MESSAGES);
+
+    for (certificate_id, _) in broadcast_headers.iter()? {
+        batch = batch.delete(&broadcast_headers, certificate_id)?;
+    }
+    for (key, _) in broadcast_messages.iter()? {
+        batch = batch.delete(&broadcast_messages, key)?;
+    }
+
+    Ok(batch)
 }
 
 #[allow(unused)]
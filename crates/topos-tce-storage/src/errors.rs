@@ -1,10 +1,12 @@
 use thiserror::Error;
 use tokio::sync::{mpsc, oneshot};
 use topos_core::{
-    types::stream::PositionError,
+    types::{stream::PositionError, ValidatorId},
     uci::{CertificateId, SubnetId, SUBNET_ID_LENGTH},
 };
 
+use crate::types::{CheckpointSequenceNumber, EpochId};
+
 #[derive(Error, Debug)]
 pub enum InternalStorageError {
     #[error("The certificate already exists")]
@@ -47,6 +49,43 @@ pub enum InternalStorageError {
 
     #[error("Certificate already exists at position {0} for subnet {1}")]
     CertificateAlreadyExistsAtPosition(u64, SubnetId),
+
+    #[error(
+        "The delivery position of the certificate {0} doesn't follow its previous certificate"
+    )]
+    InvalidDeliveryPosition(CertificateId),
+
+    #[error(
+        "The {database} database uses schema version {found} while only versions up to \
+         {supported} are supported"
+    )]
+    UnsupportedSchemaVersion {
+        database: &'static str,
+        found: u32,
+        supported: u32,
+    },
+
+    #[error(
+        "The {database} database uses schema version {found}, the node owning it has to migrate \
+         it to version {supported} first"
+    )]
+    SchemaMigrationRequired {
+        database: &'static str,
+        found: u32,
+        supported: u32,
+    },
+
+    #[error("The {database} database can only be migrated along with the perpetual database")]
+    PerpetualDatabaseRequired { database: &'static str },
+
+    #[error("The tuning of the storage can only be configured once")]
+    TuningAlreadyConfigured,
+
+    #[error("Pending pools are full for source subnet id {0}")]
+    PendingPoolLimitReached(SubnetId),
+
+    #[error("The certificate {0} of the pending pools already follows the same certificate")]
+    ConflictingPendingCertificate(CertificateId),
 }
 
 #[derive(Debug, Error)]
@@ -63,3 +102,86 @@ pub enum StorageError {
     #[error("Unable to execute shutdown on the storage service: {0}")]
     ShutdownCommunication(mpsc::error::SendError<oneshot::Sender<()>>),
 }
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+
+    #[error(transparent)]
+    InternalStorage(#[from] InternalStorageError),
+
+    #[error("Unable to access the snapshot archive: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Unable to decode the snapshot archive: {0}")]
+    Bincode(#[from] Box<bincode::ErrorKind>),
+
+    #[error("Not a snapshot archive")]
+    InvalidArchive,
+
+    #[error("Unsupported snapshot format version {0}")]
+    UnsupportedVersion(u32),
+
+    #[error("Snapshot checksum mismatch, the archive is corrupted")]
+    ChecksumMismatch,
+
+    #[error("Unable to import a snapshot in a store which already contains certificates")]
+    StoreNotEmpty,
+
+    #[error("Restored stream of subnet {0} doesn't match the snapshot")]
+    StreamMismatch(SubnetId),
+
+    #[error("Delivered certificate {0} has no sequence number in the delivery order")]
+    MissingDeliveryOrder(CertificateId),
+
+    #[error("No storage found at {0:?}")]
+    StoreNotFound(std::path::PathBuf),
+}
+
+#[derive(Debug, Error)]
+pub enum CheckpointError {
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+
+    #[error(transparent)]
+    InternalStorage(#[from] InternalStorageError),
+
+    #[error("Checkpoint signed by the unknown validator {0}")]
+    UnknownValidator(ValidatorId),
+
+    #[error("Duplicated checkpoint signature from {0}")]
+    DuplicatedSignature(ValidatorId),
+
+    #[error("Unable to parse the checkpoint signature from {0}")]
+    MalformedSignature(ValidatorId),
+
+    #[error("Invalid checkpoint signature from {0}")]
+    InvalidSignature(ValidatorId),
+
+    #[error("Checkpoint signatures are for the epoch {got} instead of {expected}")]
+    EpochMismatch { expected: EpochId, got: EpochId },
+
+    #[error(
+        "Not enough checkpoint signatures to reach the threshold: expected {expected}, got {got}"
+    )]
+    ThresholdNotReached { expected: usize, got: usize },
+
+    #[error("Checkpoint {got} doesn't follow the last checkpoint, expected {expected}")]
+    UnexpectedSequenceNumber {
+        expected: CheckpointSequenceNumber,
+        got: CheckpointSequenceNumber,
+    },
+
+    #[error("Checkpoint {0} doesn't chain to the digest of the previous checkpoint")]
+    PreviousDigestMismatch(CheckpointSequenceNumber),
+
+    #[error(
+        "Checkpoint {sequence_number} belongs to the epoch {got} instead of the epoch {expected}"
+    )]
+    UnexpectedEpoch {
+        sequence_number: CheckpointSequenceNumber,
+        expected: EpochId,
+        got: EpochId,
+    },
+}
@@ -1,7 +1,13 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
 use arc_swap::ArcSwap;
 use async_trait::async_trait;
+use rocksdb::IteratorMode;
+use tokio::sync::Mutex;
 
 use topos_core::{
     types::{
@@ -14,10 +20,14 @@ use tracing::{error, info};
 
 use crate::{
     epoch::{EpochValidatorsStore, ValidatorPerEpochStore},
-    errors::{InternalStorageError, StorageError},
+    errors::{CheckpointError, InternalStorageError, StorageError},
     index::IndexTables,
-    rocks::{map::Map, TargetSourceListKey},
+    rocks::{db_column::DBColumn, map::Map, DeliveryTimeKey, TargetSourceListKey},
     store::{ReadStore, WriteStore},
+    types::{
+        CertificateSequenceNumber, CertificateTimestamps, CheckpointSequenceNumber,
+        ColumnFamilyStatistics, EpochId, EpochSummary, Timestamp, VerifiedCheckpointSummary,
+    },
     validator::ValidatorPerpetualTables,
     CertificatePositions, SourceHead,
 };
@@ -29,10 +39,13 @@ mod locking;
 pub struct FullNodeStore {
     certificate_lock_guards: LockGuards<CertificateId>,
     subnet_lock_guards: LockGuards<SubnetId>,
-    #[allow(unused)]
-    epoch_store: ArcSwap<ValidatorPerEpochStore>,
-    #[allow(unused)]
-    validators_store: Arc<EpochValidatorsStore>,
+    /// Sequence number of the next delivered certificate, held while the
+    /// certificate is written so that the delivery order follows the sequence
+    next_sequence_number: Mutex<CertificateSequenceNumber>,
+    /// Held while a checkpoint is appended to the checkpoint chain
+    checkpoint_lock: Mutex<()>,
+    pub(crate) epoch_store: ArcSwap<ValidatorPerEpochStore>,
+    pub(crate) validators_store: Arc<EpochValidatorsStore>,
     pub(crate) perpetual_tables: Arc<ValidatorPerpetualTables>,
     pub(crate) index_tables: Arc<IndexTables>,
 }
@@ -43,23 +56,108 @@ impl FullNodeStore {
         validators_store: Arc<EpochValidatorsStore>,
         perpetual_tables: Arc<ValidatorPerpetualTables>,
         index_tables: Arc<IndexTables>,
+    ) -> Result<Arc<Self>, StorageError> {
+        let next_sequence_number = Self::next_sequence_number(&index_tables)?;
+
+        Ok(Arc::new(Self {
+            certificate_lock_guards: LockGuards::new(),
+            subnet_lock_guards: LockGuards::new(),
+            next_sequence_number: Mutex::new(next_sequence_number),
+            checkpoint_lock: Mutex::new(()),
+            epoch_store,
+            validators_store,
+            perpetual_tables,
+            index_tables,
+        }))
+    }
+
+    /// Opens the store over tables opened as read-only secondary instances.
+    ///
+    /// Nothing is written to the tables, the delivery order is expected to be
+    /// indexed by the node owning them. The store only sees the changes of this
+    /// node once [`FullNodeStore::catch_up`] is called.
+    pub fn open_secondary(
+        epoch_store: ArcSwap<ValidatorPerEpochStore>,
+        validators_store: Arc<EpochValidatorsStore>,
+        perpetual_tables: Arc<ValidatorPerpetualTables>,
+        index_tables: Arc<IndexTables>,
     ) -> Result<Arc<Self>, StorageError> {
         Ok(Arc::new(Self {
             certificate_lock_guards: LockGuards::new(),
             subnet_lock_guards: LockGuards::new(),
+            next_sequence_number: Mutex::new(0),
+            checkpoint_lock: Mutex::new(()),
             epoch_store,
             validators_store,
             perpetual_tables,
             index_tables,
         }))
     }
+
+    /// Applies the latest changes of the node owning the tables when they are
+    /// opened as secondary instances, does nothing otherwise
+    pub fn catch_up(&self) -> Result<(), StorageError> {
+        self.perpetual_tables.catch_up()?;
+        self.index_tables.catch_up()?;
+        self.validators_store.catch_up()?;
+        self.epoch_store.load().tables.catch_up()?;
+
+        Ok(())
+    }
+
+    /// Returns the statistics of the column families of the databases of the store
+    pub fn get_storage_statistics(&self) -> Result<Vec<ColumnFamilyStatistics>, StorageError> {
+        let mut statistics = self.perpetual_tables.statistics()?;
+        statistics.extend(self.index_tables.statistics()?);
+        statistics.extend(self.validators_store.statistics()?);
+        statistics.extend(self.epoch_store.load().tables.statistics()?);
+
+        Ok(statistics)
+    }
+
+    /// Returns the next sequence number of the delivery order, the certificates
+    /// delivered before it was indexed being given one by the index migration
+    fn next_sequence_number(
+        index_tables: &IndexTables,
+    ) -> Result<CertificateSequenceNumber, StorageError> {
+        Ok(index_tables
+            .certificate_order
+            .iter_with_mode(IteratorMode::End)?
+            .next()
+            .map_or(0, |(last, _)| last + 1))
+    }
+
+    /// Resumes the delivery order at `next_sequence_number` once certificates
+    /// were written along with their sequence number, e.g. by a snapshot import
+    pub(crate) async fn resume_delivery_order(
+        &self,
+        next_sequence_number: CertificateSequenceNumber,
+    ) {
+        let mut next = self.next_sequence_number.lock().await;
+        *next = (*next).max(next_sequence_number);
+    }
+
+    /// Opens a store whose tables are kept in memory, nothing is persisted on disk
+    pub fn open_in_memory() -> Result<Arc<Self>, StorageError> {
+        let perpetual_tables = Arc::new(ValidatorPerpetualTables::open_in_memory());
+        let index_tables = Arc::new(IndexTables::open_in_memory(&perpetual_tables));
+
+        Self::open(
+            ValidatorPerEpochStore::new_in_memory(0)?,
+            EpochValidatorsStore::new_in_memory()?,
+            perpetual_tables,
+            index_tables,
+        )
+    }
 }
 
-#[async_trait]
-impl WriteStore for FullNodeStore {
-    async fn insert_certificate_delivered(
+impl FullNodeStore {
+    /// Inserts a delivered certificate first received by the node at `received_at`,
+    /// the time of the delivery is used when the reception is unknown
+    pub(crate) async fn insert_certificate_delivered_received_at(
         &self,
         certificate: &CertificateDelivered,
+        received_at: Option<Timestamp>,
     ) -> Result<CertificatePositions, StorageError> {
         // Lock resources for concurrency issues
         let _cert_guard = self
@@ -189,12 +287,61 @@ impl WriteStore for FullNodeStore {
             &self.index_tables.source_list_per_target,
             source_list_per_target,
         )?;
+
+        let mut next_sequence_number = self.next_sequence_number.lock().await;
+        let sequence_number = *next_sequence_number;
+        let delivered_at = unix_timestamp_millis();
+
+        batch = batch.insert_batch(
+            &self.perpetual_tables.timestamps,
+            [(
+                certificate_id,
+                CertificateTimestamps {
+                    received_at: received_at.unwrap_or(delivered_at),
+                    delivered_at,
+                },
+            )],
+        )?;
+
+        index_batch = index_batch
+            .insert_batch(
+                &self.index_tables.certificate_order,
+                [(sequence_number, certificate_id)],
+            )?
+            .insert_batch(
+                &self.index_tables.certificate_sequences,
+                [(certificate_id, sequence_number)],
+            )?
+            .insert_batch(
+                &self.index_tables.source_delivery_times,
+                [(
+                    DeliveryTimeKey(subnet_id, delivered_at, sequence_number),
+                    certificate_id,
+                )],
+            )?
+            .insert_batch(
+                &self.index_tables.target_delivery_times,
+                certificate
+                    .certificate
+                    .target_subnets
+                    .iter()
+                    .map(|target_subnet_id| {
+                        (
+                            DeliveryTimeKey(*target_subnet_id, delivered_at, sequence_number),
+                            certificate_id,
+                        )
+                    }),
+            )?;
+
         batch.write()?;
         index_batch.write()?;
 
+        *next_sequence_number += 1;
+        drop(next_sequence_number);
+
         info!(
-            "Certificate {} inserted at position {}",
-            certificate.certificate.id, expected_position
+            "Certificate {} inserted at position {} with sequence number {}",
+            certificate.certificate.id, expected_position, sequence_number
         );
 
         Ok(CertificatePositions {
@@ -203,6 +350,163 @@ impl WriteStore for FullNodeStore {
         })
     }
 
+    fn certificates_delivered_between(
+        &self,
+        delivery_times: &DBColumn<DeliveryTimeKey, CertificateId>,
+        subnet_id: &SubnetId,
+        from: Timestamp,
+        to: Timestamp,
+        limit: usize,
+    ) -> Result<Vec<(CertificateDelivered, CertificateTimestamps)>, StorageError> {
+        let certificate_ids: Vec<CertificateId> = delivery_times
+            .prefix_iter_at(subnet_id, &DeliveryTimeKey(*subnet_id, from, 0))?
+            .take_while(|(DeliveryTimeKey(_, delivered_at, _), _)| *delivered_at <= to)
+            .take(limit)
+            .map(|(_, certificate_id)| certificate_id)
+            .collect();
+
+        let certificates = self
+            .perpetual_tables
+            .certificates
+            .multi_get(&certificate_ids[..])?;
+        let timestamps = self
+            .perpetual_tables
+            .timestamps
+            .multi_get(&certificate_ids[..])?;
+
+        Ok(certificate_ids
+            .into_iter()
+            .zip(certificates.into_iter().zip(timestamps))
+            .filter_map(|(certificate_id, (certificate, timestamps))| {
+                certificate
+                    .filter(|c| c.certificate.id == certificate_id)
+                    .zip(timestamps)
+            })
+            .collect())
+    }
+}
+
+impl FullNodeStore {
+    /// Appends a checkpoint to the checkpoint chain and updates the summary of its epoch
+    ///
+    /// The checkpoint needs to follow the last one, both by its sequence number and
+    /// by the digest of the previous checkpoint. Its signatures are expected to have
+    /// been verified against the validator set of its epoch by the caller.
+    pub async fn insert_verified_checkpoint(
+        &self,
+        checkpoint: &VerifiedCheckpointSummary,
+    ) -> Result<(), CheckpointError> {
+        let _guard = self.checkpoint_lock.lock().await;
+        let summary = checkpoint.summary();
+
+        let last = self.get_last_verified_checkpoint()?;
+        let expected = last
+            .as_ref()
+            .map_or(0, |last| last.summary().sequence_number + 1);
+
+        if summary.sequence_number != expected {
+            return Err(CheckpointError::UnexpectedSequenceNumber {
+                expected,
+                got: summary.sequence_number,
+            });
+        }
+
+        if summary.previous_digest != last.as_ref().map(|last| last.summary().digest()) {
+            return Err(CheckpointError::PreviousDigestMismatch(
+                summary.sequence_number,
+            ));
+        }
+
+        // The epoch only changes after a checkpoint agreeing on the next validator set
+        let expected_epoch = last.as_ref().map_or(0, |last| last.summary().next_epoch());
+        if summary.epoch != expected_epoch {
+            return Err(CheckpointError::UnexpectedEpoch {
+                sequence_number: summary.sequence_number,
+                expected: expected_epoch,
+                got: summary.epoch,
+            });
+        }
+
+        let epoch_summary = match self.perpetual_tables.epoch_chain.get(&summary.epoch)? {
+            Some(epoch_summary) => EpochSummary {
+                end_checkpoint: Some(checkpoint.clone()),
+                ..epoch_summary
+            },
+            None => EpochSummary {
+                epoch_id: summary.epoch,
+                start_checkpoint: checkpoint.clone(),
+                end_checkpoint: None,
+            },
+        };
+
+        // Registered first, the next epoch only starts once the checkpoint is written
+        if let Some(next_validators) = &summary.next_validators {
+            self.validators_store.insert_validators(
+                summary.epoch + 1,
+                next_validators
+                    .iter()
+                    .map(|validator_id| validator_id.to_string())
+                    .collect(),
+            )?;
+        }
+
+        self.perpetual_tables
+            .checkpoints
+            .batch()
+            .insert_batch(
+                &self.perpetual_tables.checkpoints,
+                [(summary.sequence_number, checkpoint)],
+            )?
+            .insert_batch(
+                &self.perpetual_tables.epoch_chain,
+                [(summary.epoch, epoch_summary)],
+            )?
+            .write()?;
+
+        Ok(())
+    }
+
+    /// Returns the last checkpoint of the checkpoint chain
+    pub fn get_last_verified_checkpoint(
+        &self,
+    ) -> Result<Option<VerifiedCheckpointSummary>, StorageError> {
+        Ok(self
+            .perpetual_tables
+            .checkpoints
+            .iter_with_mode(IteratorMode::End)?
+            .next()
+            .map(|(_, checkpoint)| checkpoint))
+    }
+
+    /// Returns the current epoch, which only changes once a checkpoint agreeing
+    /// on the validator set of the next epoch is appended to the checkpoint chain
+    pub fn get_current_epoch(&self) -> Result<EpochId, StorageError> {
+        Ok(self.perpetual_tables.current_epoch()?)
+    }
+
+    pub fn get_verified_checkpoint(
+        &self,
+        sequence_number: CheckpointSequenceNumber,
+    ) -> Result<Option<VerifiedCheckpointSummary>, StorageError> {
+        Ok(self.perpetual_tables.checkpoints.get(&sequence_number)?)
+    }
+
+    /// Returns the first and last checkpoints agreed on during the given epoch
+    pub fn get_epoch_summary(&self, epoch: EpochId) -> Result<Option<EpochSummary>, StorageError> {
+        Ok(self.perpetual_tables.epoch_chain.get(&epoch)?)
+    }
+}
+
+#[async_trait]
+impl WriteStore for FullNodeStore {
+    async fn insert_certificate_delivered(
+        &self,
+        certificate: &CertificateDelivered,
+    ) -> Result<CertificatePositions, StorageError> {
+        self.insert_certificate_delivered_received_at(certificate, None)
+            .await
+    }
+
     async fn insert_certificates_delivered(
         &self,
         certificates: &[CertificateDelivered],
@@ -364,4 +668,90 @@ impl ReadStore for FullNodeStore {
             .map(|((_, source_subnet_id), _)| source_subnet_id)
             .collect())
     }
+
+    fn get_certificate_sequence_number(
+        &self,
+        certificate_id: &CertificateId,
+    ) -> Result<Option<CertificateSequenceNumber>, StorageError> {
+        Ok(self
+            .index_tables
+            .certificate_sequences
+            .get(certificate_id)?)
+    }
+
+    fn get_certificates_from_sequence_number(
+        &self,
+        from: CertificateSequenceNumber,
+        limit: usize,
+    ) -> Result<Vec<(CertificateDelivered, CertificateSequenceNumber)>, StorageError> {
+        let sequenced: Vec<(CertificateSequenceNumber, CertificateId)> = self
+            .index_tables
+            .certificate_order
+            .prefix_iter_at(&(), &from)?
+            .take(limit)
+            .collect();
+
+        let certificate_ids: Vec<_> = sequenced.iter().map(|(_, id)| *id).collect();
+
+        let certificates = self
+            .perpetual_tables
+            .certificates
+            .multi_get(&certificate_ids[..])?;
+
+        Ok(sequenced
+            .into_iter()
+            .zip(certificates)
+            .filter_map(|((sequence_number, certificate_id), certificate)| {
+                certificate
+                    .filter(|c| c.certificate.id == certificate_id)
+                    .map(|cert| (cert, sequence_number))
+            })
+            .collect())
+    }
+
+    fn get_certificate_timestamps(
+        &self,
+        certificate_id: &CertificateId,
+    ) -> Result<Option<CertificateTimestamps>, StorageError> {
+        Ok(self.perpetual_tables.timestamps.get(certificate_id)?)
+    }
+
+    fn get_source_certificates_delivered_between(
+        &self,
+        source_subnet_id: &SubnetId,
+        from: Timestamp,
+        to: Timestamp,
+        limit: usize,
+    ) -> Result<Vec<(CertificateDelivered, CertificateTimestamps)>, StorageError> {
+        self.certificates_delivered_between(
+            &self.index_tables.source_delivery_times,
+            source_subnet_id,
+            from,
+            to,
+            limit,
+        )
+    }
+
+    fn get_target_certificates_delivered_between(
+        &self,
+        target_subnet_id: &SubnetId,
+        from: Timestamp,
+        to: Timestamp,
+        limit: usize,
+    ) -> Result<Vec<(CertificateDelivered, CertificateTimestamps)>, StorageError> {
+        self.certificates_delivered_between(
+            &self.index_tables.target_delivery_times,
+            target_subnet_id,
+            from,
+            to,
+            limit,
+        )
+    }
+}
+
+pub(crate) fn unix_timestamp_millis() -> Timestamp {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as Timestamp)
+        .unwrap_or_default()
 }
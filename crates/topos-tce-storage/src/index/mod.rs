@@ -1,48 +1,93 @@
 use std::{fs::create_dir_all, path::PathBuf};
 
-use rocksdb::ColumnFamilyDescriptor;
+use rocksdb::{ColumnFamilyDescriptor, IteratorMode};
 use topos_core::{
-    types::stream::{CertificateTargetStreamPosition, Position},
+    types::stream::{CertificateSourceStreamPosition, CertificateTargetStreamPosition, Position},
     uci::{CertificateId, SubnetId},
 };
-use tracing::warn;
+use tracing::{info, warn};
 
 use crate::{
     constant::cfs,
+    errors::InternalStorageError,
     rocks::{
         constants,
-        db::{default_options, init_with_cfs},
-        db_column::DBColumn,
-        TargetSourceListKey,
+        db::{default_options, init_secondary_with_cfs, init_with_cfs},
+        db_column::{Backend, DBBatch, DBColumn},
+        map::Map,
+        memory::MemoryDB,
+        schema, DeliveryTimeKey, TargetSourceListKey,
     },
-    types::CertificateSequenceNumber,
+    types::{CertificateSequenceNumber, ColumnFamilyStatistics},
+    validator::ValidatorPerpetualTables,
 };
 
-#[allow(unused)]
-pub(crate) struct IndexStore {
-    certificate_order: DBColumn<CertificateSequenceNumber, CertificateId>,
-}
+/// Number of certificates given a sequence number per batch when indexing the delivery order
+const DELIVERY_ORDER_BATCH_SIZE: usize = 10_000;
 
 pub struct IndexTables {
     pub(crate) target_streams: DBColumn<CertificateTargetStreamPosition, CertificateId>,
     pub(crate) target_source_list: DBColumn<TargetSourceListKey, Position>,
     pub(crate) source_list: DBColumn<SubnetId, (CertificateId, Position)>,
     pub(crate) source_list_per_target: DBColumn<(SubnetId, SubnetId), bool>,
+    /// Delivered certificates in the order they were delivered by this node
+    pub(crate) certificate_order: DBColumn<CertificateSequenceNumber, CertificateId>,
+    pub(crate) certificate_sequences: DBColumn<CertificateId, CertificateSequenceNumber>,
+    /// Delivered certificates ordered by delivery time for each source subnet
+    pub(crate) source_delivery_times: DBColumn<DeliveryTimeKey, CertificateId>,
+    /// Delivered certificates ordered by delivery time for each target subnet
+    pub(crate) target_delivery_times: DBColumn<DeliveryTimeKey, CertificateId>,
 }
 
 impl IndexTables {
-    pub fn open(mut path: PathBuf) -> Self {
+    /// Opens the tables indexing the `perpetual_tables`, from which they are migrated
+    pub fn open(mut path: PathBuf, perpetual_tables: &ValidatorPerpetualTables) -> Self {
         path.push("index");
         if !path.exists() {
             warn!("Path {:?} does not exist, creating it", path);
             create_dir_all(&path).expect("Cannot create IndexTables directory");
         }
+
+        let db = init_with_cfs(&path, default_options(), Self::column_families())
+            .unwrap_or_else(|_| panic!("Cannot open DB at {:?}", path));
+
+        Self::with_backend(db.into(), perpetual_tables)
+    }
+
+    /// Opens the tables of the node running at `path` as a read-only secondary
+    /// instance, keeping its own logs at `secondary_path`
+    pub fn open_secondary(mut path: PathBuf, mut secondary_path: PathBuf) -> Self {
+        path.push("index");
+        secondary_path.push("index");
+        create_dir_all(&secondary_path).expect("Cannot create IndexTables directory");
+
+        let db = init_secondary_with_cfs(
+            &path,
+            &secondary_path,
+            default_options(),
+            Self::column_families(),
+        )
+        .unwrap_or_else(|e| panic!("Cannot open DB at {:?} => error {:?}", path, e))
+        .into();
+
+        schema::INDEX.open_secondary(&db);
+
+        Self::from_backend(&db)
+    }
+
+    fn column_families() -> Vec<ColumnFamilyDescriptor> {
         let mut options_stream = default_options();
         options_stream.set_prefix_extractor(rocksdb::SliceTransform::create_fixed_prefix(
             constants::TARGET_STREAMS_PREFIX_SIZE,
         ));
 
-        let cfs = vec![
+        let mut options_delivery_times = default_options();
+        options_delivery_times.set_prefix_extractor(rocksdb::SliceTransform::create_fixed_prefix(
+            constants::SOURCE_STREAMS_PREFIX_SIZE,
+        ));
+
+        vec![
+            ColumnFamilyDescriptor::new(cfs::SCHEMA, default_options()),
             ColumnFamilyDescriptor::new(cfs::TARGET_STREAMS, options_stream),
             ColumnFamilyDescriptor::new(cfs::TARGET_SOURCE_LIST, default_options()),
             ColumnFamilyDescriptor::new(cfs::SOURCE_LIST, default_options()),
@@ -50,19 +95,125 @@ impl IndexTables {
                 cfs::DELIVERED_CERTIFICATES_PER_SOURCE_FOR_TARGET,
                 default_options(),
             ),
-        ];
+            ColumnFamilyDescriptor::new(cfs::CERTIFICATE_ORDER, default_options()),
+            ColumnFamilyDescriptor::new(cfs::CERTIFICATE_SEQUENCES, default_options()),
+            ColumnFamilyDescriptor::new(cfs::SOURCE_DELIVERY_TIMES, options_delivery_times.clone()),
+            ColumnFamilyDescriptor::new(cfs::TARGET_DELIVERY_TIMES, options_delivery_times),
+        ]
+    }
 
-        let db = init_with_cfs(&path, default_options(), cfs)
-            .unwrap_or_else(|_| panic!("Cannot open DB at {:?}", path));
+    /// Opens the tables without persisting them on disk
+    pub fn open_in_memory(perpetual_tables: &ValidatorPerpetualTables) -> Self {
+        let db = MemoryDB::new(&[
+            cfs::SCHEMA,
+            cfs::TARGET_STREAMS,
+            cfs::TARGET_SOURCE_LIST,
+            cfs::SOURCE_LIST,
+            cfs::DELIVERED_CERTIFICATES_PER_SOURCE_FOR_TARGET,
+            cfs::CERTIFICATE_ORDER,
+            cfs::CERTIFICATE_SEQUENCES,
+            cfs::SOURCE_DELIVERY_TIMES,
+            cfs::TARGET_DELIVERY_TIMES,
+        ]);
+
+        Self::with_backend(db.into(), perpetual_tables)
+    }
+
+    fn with_backend(db: Backend, perpetual_tables: &ValidatorPerpetualTables) -> Self {
+        schema::INDEX.open_from_perpetual(&db, &perpetual_tables.streams.backend);
 
+        Self::from_backend(&db)
+    }
+
+    fn from_backend(db: &Backend) -> Self {
         Self {
-            target_streams: DBColumn::reopen(&db, cfs::TARGET_STREAMS),
-            target_source_list: DBColumn::reopen(&db, cfs::TARGET_SOURCE_LIST),
-            source_list: DBColumn::reopen(&db, cfs::SOURCE_LIST),
-            source_list_per_target: DBColumn::reopen(
-                &db,
+            target_streams: DBColumn::from_backend(db, cfs::TARGET_STREAMS),
+            target_source_list: DBColumn::from_backend(db, cfs::TARGET_SOURCE_LIST),
+            source_list: DBColumn::from_backend(db, cfs::SOURCE_LIST),
+            source_list_per_target: DBColumn::from_backend(
+                db,
                 cfs::DELIVERED_CERTIFICATES_PER_SOURCE_FOR_TARGET,
             ),
+            certificate_order: DBColumn::from_backend(db, cfs::CERTIFICATE_ORDER),
+            certificate_sequences: DBColumn::from_backend(db, cfs::CERTIFICATE_SEQUENCES),
+            source_delivery_times: DBColumn::from_backend(db, cfs::SOURCE_DELIVERY_TIMES),
+            target_delivery_times: DBColumn::from_backend(db, cfs::TARGET_DELIVERY_TIMES),
         }
     }
+
+    /// Applies the latest changes of the primary instance when opened as a secondary one
+    pub(crate) fn catch_up(&self) -> Result<(), InternalStorageError> {
+        self.target_streams.backend.try_catch_up_with_primary()
+    }
+
+    pub(crate) fn statistics(&self) -> Result<Vec<ColumnFamilyStatistics>, InternalStorageError> {
+        self.target_streams
+            .backend
+            .statistics(schema::INDEX.database)
+    }
+}
+
+/// Migration of the index database giving a sequence number to the certificates
+/// delivered before the delivery order was indexed, following their source
+/// streams one subnet after the other.
+///
+/// The sequence numbers are written by batches and the certificates which already
+/// have one are skipped, so an interrupted migration resumes where it stopped.
+pub(crate) fn index_delivery_order(
+    db: &Backend,
+    perpetual: &Backend,
+    batch: DBBatch,
+) -> Result<DBBatch, InternalStorageError> {
+    let streams: DBColumn<CertificateSourceStreamPosition, CertificateId> =
+        DBColumn::from_backend(perpetual, cfs::STREAMS);
+    let certificate_order: DBColumn<CertificateSequenceNumber, CertificateId> =
+        DBColumn::from_backend(db, cfs::CERTIFICATE_ORDER);
+    let certificate_sequences: DBColumn<CertificateId, CertificateSequenceNumber> =
+        DBColumn::from_backend(db, cfs::CERTIFICATE_SEQUENCES);
+
+    let mut next_sequence_number = certificate_order
+        .iter_with_mode(IteratorMode::End)?
+        .next()
+        .map_or(0, |(last, _)| last + 1);
+
+    let mut sequenced = Vec::new();
+    for (_, certificate_id) in streams.iter()? {
+        if certificate_sequences.get(&certificate_id)?.is_some() {
+            continue;
+        }
+
+        sequenced.push((next_sequence_number, certificate_id));
+        next_sequence_number += 1;
+
+        if sequenced.len() >= DELIVERY_ORDER_BATCH_SIZE {
+            insert_delivery_order(certificate_order.batch(), db, &sequenced)?.write()?;
+            info!(
+                "Indexed the delivery order up to sequence number {}",
+                next_sequence_number - 1
+            );
+            sequenced.clear();
+        }
+    }
+
+    insert_delivery_order(batch, db, &sequenced)
+}
+
+fn insert_delivery_order(
+    batch: DBBatch,
+    db: &Backend,
+    sequenced: &[(CertificateSequenceNumber, CertificateId)],
+) -> Result<DBBatch, InternalStorageError> {
+    let certificate_order: DBColumn<CertificateSequenceNumber, CertificateId> =
+        DBColumn::from_backend(db, cfs::CERTIFICATE_ORDER);
+    let certificate_sequences: DBColumn<CertificateId, CertificateSequenceNumber> =
+        DBColumn::from_backend(db, cfs::CERTIFICATE_SEQUENCES);
+
+    batch
+        .insert_batch(&certificate_order, sequenced.iter().copied())?
+        .insert_batch(
+            &certificate_sequences,
+            sequenced
+                .iter()
+                .map(|(sequence_number, certificate_id)| (*certificate_id, *sequence_number)),
+        )
 }
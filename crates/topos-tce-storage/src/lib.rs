@@ -9,12 +9,18 @@ use topos_core::{
 };
 
 // v2
+/// Consistency checks of the storage
+pub mod check;
 pub mod constant;
 /// Epoch related store
 pub mod epoch;
 /// Fullnode store
 pub mod fullnode;
 pub mod index;
+/// Offline export and import of the delivered certificates
+pub mod snapshot;
+/// Tuning of the RocksDB databases
+pub mod tuning;
 pub mod types;
 /// Everything that is needed to participate to the protocol
 pub mod validator;
@@ -26,6 +32,9 @@ pub mod errors;
 #[cfg(feature = "rocksdb")]
 pub(crate) mod rocks;
 
+#[cfg(test)]
+use rstest_reuse;
+
 #[cfg(test)]
 mod tests;
 
@@ -63,7 +72,7 @@ pub struct CertificatePositions {
 /// Uniquely identify the source certificate stream head of one subnet.
 /// The head represent the internal state of the TCE regarding a source subnet stream for
 /// certificates that it receives from local sequencer
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SourceHead {
     /// Certificate id of the head
     pub certificate_id: CertificateId,
@@ -21,6 +21,8 @@ pub(crate) mod db;
 pub(crate) mod db_column;
 pub(crate) mod iterator;
 pub(crate) mod map;
+pub(crate) mod memory;
+pub(crate) mod schema;
 pub(crate) mod types;
 
 pub(crate) use types::*;
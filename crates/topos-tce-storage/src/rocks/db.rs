@@ -3,7 +3,7 @@ use std::{path::PathBuf, sync::Arc};
 
 use rocksdb::{ColumnFamilyDescriptor, Options};
 
-use crate::errors::InternalStorageError;
+use crate::{errors::InternalStorageError, tuning};
 
 use super::constants;
 
@@ -20,9 +20,33 @@ pub(crate) fn init_with_cfs(
         rocksdb::DBWithThreadMode::<MultiThreaded>::open_cf_descriptors(&options, path, cfs)?,
     ))
 }
+
+/// Opens the database at `path` as a read-only secondary instance, which keeps
+/// its own logs at `secondary_path` and follows the primary instance when caught up
+pub(crate) fn init_secondary_with_cfs(
+    path: &PathBuf,
+    secondary_path: &PathBuf,
+    mut options: rocksdb::Options,
+    cfs: Vec<ColumnFamilyDescriptor>,
+) -> Result<RocksDB, InternalStorageError> {
+    // The files of the primary instance can be deleted by its compactions,
+    // a secondary instance needs to keep all of them open
+    options.set_max_open_files(-1);
+
+    Ok(Arc::new(
+        rocksdb::DBWithThreadMode::<MultiThreaded>::open_cf_descriptors_as_secondary(
+            &options,
+            path,
+            secondary_path,
+            cfs,
+        )?,
+    ))
+}
+
 pub(crate) fn default_options() -> rocksdb::Options {
     let mut options = Options::default();
     options.create_if_missing(true);
+    tuning::apply(&mut options);
 
     options
 }
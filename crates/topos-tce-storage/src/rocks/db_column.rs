@@ -1,4 +1,10 @@
-use std::{borrow::Borrow, marker::PhantomData, sync::Arc};
+use std::{
+    borrow::Borrow,
+    collections::HashMap,
+    marker::PhantomData,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+};
 
 #[cfg(test)]
 use std::path::Path;
@@ -6,21 +12,133 @@ use std::path::Path;
 #[cfg(test)]
 use rocksdb::ColumnFamilyDescriptor;
 use rocksdb::{
-    BoundColumnFamily, DBRawIteratorWithThreadMode, DBWithThreadMode, Direction, IteratorMode,
-    MultiThreaded, ReadOptions, WriteBatch,
+    properties, BoundColumnFamily, DBRawIteratorWithThreadMode, Direction, IteratorMode,
+    ReadOptions, WriteBatch,
 };
 
 use bincode::Options;
+use once_cell::sync::Lazy;
 use serde::{de::DeserializeOwned, Serialize};
 
-use crate::errors::InternalStorageError;
+use crate::{errors::InternalStorageError, types::ColumnFamilyStatistics};
+
+use super::{
+    iterator::ColumnIterator,
+    map::Map,
+    memory::{MemoryDB, MemoryOperation},
+    RocksDB,
+};
+
+/// Column families of the RocksDB databases, listed once per database as listing
+/// them reads the MANIFEST from disk
+static COLUMN_FAMILIES: Lazy<Mutex<HashMap<PathBuf, Arc<Vec<String>>>>> =
+    Lazy::new(Default::default);
+
+/// Returns the column families of the database, apart from the default one
+fn column_families(db: &RocksDB) -> Result<Arc<Vec<String>>, InternalStorageError> {
+    let mut cache = COLUMN_FAMILIES
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    if let Some(column_families) = cache.get(db.path()) {
+        return Ok(column_families.clone());
+    }
+
+    let column_families: Arc<Vec<String>> = Arc::new(
+        rocksdb::DBWithThreadMode::<rocksdb::MultiThreaded>::list_cf(
+            &rocksdb::Options::default(),
+            db.path(),
+        )?
+        .into_iter()
+        .filter(|column_family| column_family != rocksdb::DEFAULT_COLUMN_FAMILY_NAME)
+        .collect(),
+    );
+    cache.insert(db.path().to_path_buf(), column_families.clone());
+
+    Ok(column_families)
+}
+
+/// Database holding the columns, either RocksDB or in memory
+#[derive(Clone, Debug)]
+pub(crate) enum Backend {
+    RocksDB(RocksDB),
+    Memory(MemoryDB),
+}
+
+impl From<RocksDB> for Backend {
+    fn from(db: RocksDB) -> Self {
+        Self::RocksDB(db)
+    }
+}
+
+impl From<MemoryDB> for Backend {
+    fn from(db: MemoryDB) -> Self {
+        Self::Memory(db)
+    }
+}
+
+impl Backend {
+    /// Applies the latest changes of the primary instance to a secondary instance,
+    /// nothing to do for a database in memory
+    pub(crate) fn try_catch_up_with_primary(&self) -> Result<(), InternalStorageError> {
+        match self {
+            Self::RocksDB(db) => Ok(db.try_catch_up_with_primary()?),
+            Self::Memory(_) => Ok(()),
+        }
+    }
 
-use super::{iterator::ColumnIterator, map::Map, RocksDB};
+    /// Returns the statistics of every column family of the database
+    pub(crate) fn statistics(
+        &self,
+        database: &str,
+    ) -> Result<Vec<ColumnFamilyStatistics>, InternalStorageError> {
+        match self {
+            Self::RocksDB(db) => {
+                let mut statistics = Vec::new();
+
+                for column_family in column_families(db)?.iter() {
+                    let Some(cf) = db.cf_handle(column_family) else {
+                        continue;
+                    };
+
+                    let property = |name| {
+                        db.property_int_value_cf(&cf, name)
+                            .map(Option::unwrap_or_default)
+                    };
+
+                    statistics.push(ColumnFamilyStatistics {
+                        database: database.to_string(),
+                        estimated_keys: property(properties::ESTIMATE_NUM_KEYS)?,
+                        sst_files_size: property(properties::LIVE_SST_FILES_SIZE)?,
+                        memtable_size: property(properties::CUR_SIZE_ALL_MEM_TABLES)?,
+                        pending_compaction_bytes: property(
+                            properties::ESTIMATE_PENDING_COMPACTION_BYTES,
+                        )?,
+                        column_family: column_family.clone(),
+                    });
+                }
+
+                Ok(statistics)
+            }
+            Self::Memory(db) => Ok(db
+                .column_sizes()
+                .into_iter()
+                .map(|(column_family, keys, size)| ColumnFamilyStatistics {
+                    database: database.to_string(),
+                    column_family: column_family.to_string(),
+                    estimated_keys: keys,
+                    memtable_size: size,
+                    ..Default::default()
+                })
+                .collect()),
+        }
+    }
+}
 
 /// A DBColumn represents a CF structure
 #[derive(Clone, Debug)]
 pub struct DBColumn<K, V> {
-    pub(crate) rocksdb: RocksDB,
+    pub(crate) backend: Backend,
     _phantom: PhantomData<fn(K) -> V>,
     cf: &'static str,
 }
@@ -50,24 +168,27 @@ impl<K, V> DBColumn<K, V> {
             )
         };
 
-        Ok(Self {
-            rocksdb,
-            _phantom: PhantomData,
-            cf: column,
-        })
+        Ok(Self::from_backend(&Backend::RocksDB(rocksdb), column))
     }
 
     pub fn reopen(db: &RocksDB, column: &'static str) -> Self {
+        Self::from_backend(&Backend::RocksDB(db.clone()), column)
+    }
+
+    pub(crate) fn from_backend(backend: &Backend, column: &'static str) -> Self {
         Self {
-            rocksdb: db.clone(),
+            backend: backend.clone(),
             _phantom: PhantomData,
             cf: column,
         }
     }
 
     /// Returns the CF of the DBColumn, used to build queries.
-    fn cf(&self) -> Result<Arc<BoundColumnFamily<'_>>, InternalStorageError> {
-        self.rocksdb
+    fn cf<'db>(
+        &self,
+        rocksdb: &'db RocksDB,
+    ) -> Result<Arc<BoundColumnFamily<'db>>, InternalStorageError> {
+        rocksdb
             .cf_handle(self.cf)
             .ok_or(InternalStorageError::InvalidColumnFamily(self.cf))
     }
@@ -82,13 +203,14 @@ where
     ///
     /// Key are fixed length bincode serialized.
     pub(crate) fn insert(&self, key: &K, value: &V) -> Result<(), InternalStorageError> {
-        let cf = self.cf()?;
-
         let key_buf = be_fix_int_ser(key)?;
 
         let value_buf = bincode::serialize(value)?;
 
-        self.rocksdb.put_cf(&cf, key_buf, value_buf)?;
+        match &self.backend {
+            Backend::RocksDB(rocksdb) => rocksdb.put_cf(&self.cf(rocksdb)?, key_buf, value_buf)?,
+            Backend::Memory(db) => db.put(self.cf, key_buf, value_buf)?,
+        }
 
         Ok(())
     }
@@ -99,7 +221,10 @@ where
     pub(crate) fn delete(&self, key: &K) -> Result<(), InternalStorageError> {
         let key_buf = be_fix_int_ser(key)?;
 
-        self.rocksdb.delete_cf(&self.cf()?, key_buf)?;
+        match &self.backend {
+            Backend::RocksDB(rocksdb) => rocksdb.delete_cf(&self.cf(rocksdb)?, key_buf)?,
+            Backend::Memory(db) => db.delete(self.cf, key_buf)?,
+        }
 
         Ok(())
     }
@@ -110,13 +235,20 @@ where
     pub(crate) fn get(&self, key: &K) -> Result<Option<V>, InternalStorageError> {
         let key_buf = be_fix_int_ser(key)?;
 
-        self.rocksdb
-            .get_pinned_cf(&self.cf()?, key_buf)?
-            .map_or(Ok(None), |v| {
-                bincode::deserialize::<V>(&v)
-                    .map(|r| Some(r))
-                    .map_err(|_| InternalStorageError::UnableToDeserializeValue)
-            })
+        let deserialize = |v: &[u8]| {
+            bincode::deserialize::<V>(v)
+                .map(|r| Some(r))
+                .map_err(|_| InternalStorageError::UnableToDeserializeValue)
+        };
+
+        match &self.backend {
+            Backend::RocksDB(rocksdb) => rocksdb
+                .get_pinned_cf(&self.cf(rocksdb)?, key_buf)?
+                .map_or(Ok(None), |v| deserialize(&v)),
+            Backend::Memory(db) => db
+                .get(self.cf, &key_buf)?
+                .map_or(Ok(None), |v| deserialize(&v)),
+        }
     }
 
     pub(crate) fn multi_insert(
@@ -132,14 +264,22 @@ where
         let keys: Result<Vec<_>, InternalStorageError> =
             keys.iter().map(|k| be_fix_int_ser(k)).collect();
 
-        let results: Result<Vec<_>, InternalStorageError> = self
-            .rocksdb
-            .batched_multi_get_cf_opt(&self.cf()?, keys?, false, &ReadOptions::default())
-            .into_iter()
-            .map(|r| r.map_err(InternalStorageError::RocksDBError))
-            .collect();
+        let results: Vec<Option<Vec<u8>>> = match &self.backend {
+            Backend::RocksDB(rocksdb) => rocksdb
+                .batched_multi_get_cf_opt(&self.cf(rocksdb)?, keys?, false, &ReadOptions::default())
+                .into_iter()
+                .map(|r| {
+                    r.map(|v| v.map(|v| v.to_vec()))
+                        .map_err(InternalStorageError::RocksDBError)
+                })
+                .collect::<Result<_, _>>()?,
+            Backend::Memory(db) => keys?
+                .iter()
+                .map(|key| db.get(self.cf, key))
+                .collect::<Result<_, _>>()?,
+        };
 
-        results?
+        results
             .into_iter()
             .map(|e| match e {
                 Some(v) => bincode::deserialize(&v)
@@ -155,24 +295,43 @@ where
         let key_buf = be_fix_int_ser(key)?;
         let value_buf = bincode::serialize(&value)?;
 
-        Ok(self.rocksdb.merge_cf(&self.cf()?, key_buf, value_buf)?)
+        match &self.backend {
+            Backend::RocksDB(rocksdb) => {
+                Ok(rocksdb.merge_cf(&self.cf(rocksdb)?, key_buf, value_buf)?)
+            }
+            Backend::Memory(_) => Err(InternalStorageError::InvalidQueryArgument(
+                "Merge is not supported by the in-memory storage",
+            )),
+        }
     }
 
     pub(crate) fn batch(&self) -> DBBatch {
-        DBBatch::new(&self.rocksdb)
+        DBBatch::new(&self.backend)
     }
 }
 
-pub(crate) struct DBBatch {
-    rocksdb: Arc<DBWithThreadMode<MultiThreaded>>,
-    batch: WriteBatch,
+pub(crate) enum DBBatch {
+    RocksDB {
+        rocksdb: RocksDB,
+        batch: WriteBatch,
+    },
+    Memory {
+        db: MemoryDB,
+        operations: Vec<MemoryOperation>,
+    },
 }
 
 impl DBBatch {
-    fn new(rocksdb: &Arc<DBWithThreadMode<MultiThreaded>>) -> Self {
-        Self {
-            rocksdb: rocksdb.clone(),
-            batch: WriteBatch::default(),
+    fn new(backend: &Backend) -> Self {
+        match backend {
+            Backend::RocksDB(rocksdb) => Self::RocksDB {
+                rocksdb: rocksdb.clone(),
+                batch: WriteBatch::default(),
+            },
+            Backend::Memory(db) => Self::Memory {
+                db: db.clone(),
+                operations: Vec::new(),
+            },
         }
     }
 
@@ -186,10 +345,25 @@ impl DBBatch {
         V: Serialize,
         Key: Borrow<K>,
     {
-        check_cross_batch(&self.rocksdb, &db.rocksdb)?;
-
         let key_buffer = be_fix_int_ser(key.borrow())?;
-        self.batch.delete_cf(&db.cf()?, key_buffer);
+
+        match (&mut self, &db.backend) {
+            (Self::RocksDB { rocksdb, batch }, Backend::RocksDB(current))
+                if Arc::ptr_eq(rocksdb, current) =>
+            {
+                batch.delete_cf(&db.cf(current)?, key_buffer);
+            }
+            (
+                Self::Memory {
+                    db: memory,
+                    operations,
+                },
+                Backend::Memory(current),
+            ) if memory.ptr_eq(current) => {
+                operations.push(MemoryOperation::Delete(db.cf, key_buffer));
+            }
+            _ => return Err(InternalStorageError::ConcurrentDBBatchDetected),
+        }
 
         Ok(self)
     }
@@ -205,22 +379,46 @@ impl DBBatch {
         Key: Borrow<K>,
         Value: Borrow<V>,
     {
-        check_cross_batch(&self.rocksdb, &db.rocksdb)?;
-
-        values
-            .into_iter()
-            .try_for_each::<_, Result<(), InternalStorageError>>(|(k, v)| {
-                let key_buffer = be_fix_int_ser(k.borrow())?;
-                let value_buffer = bincode::serialize(v.borrow())?;
-                self.batch.put_cf(&db.cf()?, key_buffer, value_buffer);
-                Ok(())
-            })?;
+        match (&mut self, &db.backend) {
+            (Self::RocksDB { rocksdb, batch }, Backend::RocksDB(current))
+                if Arc::ptr_eq(rocksdb, current) =>
+            {
+                values
+                    .into_iter()
+                    .try_for_each::<_, Result<(), InternalStorageError>>(|(k, v)| {
+                        let key_buffer = be_fix_int_ser(k.borrow())?;
+                        let value_buffer = bincode::serialize(v.borrow())?;
+                        batch.put_cf(&db.cf(current)?, key_buffer, value_buffer);
+                        Ok(())
+                    })?;
+            }
+            (
+                Self::Memory {
+                    db: memory,
+                    operations,
+                },
+                Backend::Memory(current),
+            ) if memory.ptr_eq(current) => {
+                values
+                    .into_iter()
+                    .try_for_each::<_, Result<(), InternalStorageError>>(|(k, v)| {
+                        let key_buffer = be_fix_int_ser(k.borrow())?;
+                        let value_buffer = bincode::serialize(v.borrow())?;
+                        operations.push(MemoryOperation::Put(db.cf, key_buffer, value_buffer));
+                        Ok(())
+                    })?;
+            }
+            _ => return Err(InternalStorageError::ConcurrentDBBatchDetected),
+        }
 
         Ok(self)
     }
 
     pub(crate) fn write(self) -> Result<(), InternalStorageError> {
-        self.rocksdb.write(self.batch)?;
+        match self {
+            Self::RocksDB { rocksdb, batch } => rocksdb.write(batch)?,
+            Self::Memory { db, operations } => db.write(operations)?,
+        }
 
         Ok(())
     }
@@ -234,43 +432,80 @@ where
     type Iterator = ColumnIterator<'a, K, V>;
 
     fn iter(&'a self) -> Result<Self::Iterator, InternalStorageError> {
-        let mut raw_iterator = self.rocksdb.raw_iterator_cf(&self.cf()?);
-        raw_iterator.seek_to_first();
+        match &self.backend {
+            Backend::RocksDB(rocksdb) => {
+                let mut raw_iterator = rocksdb.raw_iterator_cf(&self.cf(rocksdb)?);
+                raw_iterator.seek_to_first();
 
-        Ok(ColumnIterator::new(raw_iterator))
+                Ok(ColumnIterator::new(raw_iterator))
+            }
+            Backend::Memory(db) => Ok(ColumnIterator::from_cursor(db.cursor(self.cf, &[], &[])?)),
+        }
     }
 
     fn iter_with_mode(
         &'a self,
         mode: IteratorMode<'_>,
     ) -> Result<Self::Iterator, InternalStorageError> {
-        let mut raw_iterator = self.rocksdb.raw_iterator_cf(&self.cf()?);
-
-        let direction = match mode {
-            IteratorMode::Start => {
-                raw_iterator.seek_to_first();
-                Direction::Forward
+        match &self.backend {
+            Backend::RocksDB(rocksdb) => {
+                let mut raw_iterator = rocksdb.raw_iterator_cf(&self.cf(rocksdb)?);
+
+                let direction = match mode {
+                    IteratorMode::Start => {
+                        raw_iterator.seek_to_first();
+                        Direction::Forward
+                    }
+                    IteratorMode::End => {
+                        raw_iterator.seek_to_last();
+                        Direction::Forward
+                    }
+                    IteratorMode::From(key, Direction::Forward) => {
+                        raw_iterator.seek(key);
+                        Direction::Forward
+                    }
+                    IteratorMode::From(key, Direction::Reverse) => {
+                        raw_iterator.seek_for_prev(key);
+                        Direction::Reverse
+                    }
+                };
+
+                Ok(ColumnIterator::new_with_direction(raw_iterator, direction))
             }
-            IteratorMode::End => {
-                raw_iterator.seek_to_last();
-                Direction::Forward
+            Backend::Memory(db) => {
+                let cursor = match mode {
+                    IteratorMode::Start => db.cursor(self.cf, &[], &[])?,
+                    // Starts from the last entry, as the RocksDB iterator does
+                    IteratorMode::End => match db.last_key(self.cf)? {
+                        Some(last_key) => db.cursor_at(self.cf, &last_key, Direction::Forward)?,
+                        None => db.cursor(self.cf, &[], &[])?,
+                    },
+                    IteratorMode::From(key, direction) => db.cursor_at(self.cf, key, direction)?,
+                };
+
+                Ok(ColumnIterator::from_cursor(cursor))
             }
-            _ => unimplemented!(),
-        };
-
-        Ok(ColumnIterator::new_with_direction(raw_iterator, direction))
+        }
     }
 
     fn prefix_iter<P: Serialize>(
         &'a self,
         prefix: &P,
     ) -> Result<Self::Iterator, InternalStorageError> {
-        let iterator = self
-            .rocksdb
-            .prefix_iterator_cf(&self.cf()?, be_fix_int_ser(prefix)?)
-            .into();
+        let prefix = be_fix_int_ser(prefix)?;
+
+        match &self.backend {
+            Backend::RocksDB(rocksdb) => {
+                let iterator = rocksdb
+                    .prefix_iterator_cf(&self.cf(rocksdb)?, prefix)
+                    .into();
 
-        Ok(ColumnIterator::new(iterator))
+                Ok(ColumnIterator::new(iterator))
+            }
+            Backend::Memory(db) => Ok(ColumnIterator::from_cursor(
+                db.cursor(self.cf, &prefix, &prefix)?,
+            )),
+        }
     }
 
     fn prefix_iter_at<P: Serialize, I: Serialize>(
@@ -278,13 +513,22 @@ where
         prefix: &P,
         index: &I,
     ) -> Result<Self::Iterator, InternalStorageError> {
-        let mut iterator: DBRawIteratorWithThreadMode<_> = self
-            .rocksdb
-            .prefix_iterator_cf(&self.cf()?, be_fix_int_ser(prefix)?)
-            .into();
+        let prefix = be_fix_int_ser(prefix)?;
+        let index = be_fix_int_ser(index)?;
+
+        match &self.backend {
+            Backend::RocksDB(rocksdb) => {
+                let mut iterator: DBRawIteratorWithThreadMode<_> = rocksdb
+                    .prefix_iterator_cf(&self.cf(rocksdb)?, prefix)
+                    .into();
 
-        iterator.seek(be_fix_int_ser(index)?);
-        Ok(ColumnIterator::new(iterator))
+                iterator.seek(index);
+                Ok(ColumnIterator::new(iterator))
+            }
+            Backend::Memory(db) => Ok(ColumnIterator::from_cursor(
+                db.cursor(self.cf, &index, &prefix)?,
+            )),
+        }
     }
 }
 
@@ -298,11 +542,3 @@ where
         .with_fixint_encoding()
         .serialize(t)?)
 }
-
-fn check_cross_batch(base: &RocksDB, current: &RocksDB) -> Result<(), InternalStorageError> {
-    if !Arc::ptr_eq(base, current) {
-        return Err(InternalStorageError::ConcurrentDBBatchDetected);
-    }
-
-    Ok(())
-}
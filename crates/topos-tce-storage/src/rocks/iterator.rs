@@ -4,12 +4,22 @@ use bincode::Options;
 use rocksdb::{DBRawIteratorWithThreadMode, DBWithThreadMode, Direction, MultiThreaded};
 use serde::de::DeserializeOwned;
 
+use super::memory::MemoryCursor;
+
 pub struct ColumnIterator<'a, K, V> {
-    iterator: DBRawIteratorWithThreadMode<'a, DBWithThreadMode<MultiThreaded>>,
-    direction: Direction,
+    iterator: RawIterator<'a>,
     _phantom: PhantomData<(K, V)>,
 }
 
+enum RawIterator<'a> {
+    RocksDB {
+        iterator: DBRawIteratorWithThreadMode<'a, DBWithThreadMode<MultiThreaded>>,
+        direction: Direction,
+    },
+    /// Cursor over an in-memory column
+    Memory(MemoryCursor),
+}
+
 impl<'a, K, V> ColumnIterator<'a, K, V> {
     /// Creates a new ColumnIterator base on a DBRawIteratorWithThreadMode
     pub fn new(iterator: DBRawIteratorWithThreadMode<'a, DBWithThreadMode<MultiThreaded>>) -> Self {
@@ -21,8 +31,18 @@ impl<'a, K, V> ColumnIterator<'a, K, V> {
         direction: Direction,
     ) -> Self {
         Self {
-            iterator,
-            direction,
+            iterator: RawIterator::RocksDB {
+                iterator,
+                direction,
+            },
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Creates a new ColumnIterator over entries of an in-memory column
+    pub(crate) fn from_cursor(cursor: MemoryCursor) -> Self {
+        Self {
+            iterator: RawIterator::Memory(cursor),
             _phantom: PhantomData,
         }
     }
@@ -36,25 +56,37 @@ where
     type Item = (K, V);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.iterator.valid() {
-            let config = bincode::DefaultOptions::new()
-                .with_big_endian()
-                .with_fixint_encoding();
-
-            let key = self.iterator.key().and_then(|k| config.deserialize(k).ok());
-            let value = self
-                .iterator
-                .value()
-                .and_then(|v| bincode::deserialize(v).ok());
-
-            match self.direction {
-                Direction::Forward => self.iterator.next(),
-                Direction::Reverse => self.iterator.prev(),
+        let config = bincode::DefaultOptions::new()
+            .with_big_endian()
+            .with_fixint_encoding();
+
+        match &mut self.iterator {
+            RawIterator::RocksDB {
+                iterator,
+                direction,
+            } => {
+                if !iterator.valid() {
+                    return None;
+                }
+
+                let key = iterator.key().and_then(|k| config.deserialize(k).ok());
+                let value = iterator.value().and_then(|v| bincode::deserialize(v).ok());
+
+                match direction {
+                    Direction::Forward => iterator.next(),
+                    Direction::Reverse => iterator.prev(),
+                }
+
+                key.and_then(|k| value.map(|v| (k, v)))
             }
+            RawIterator::Memory(cursor) => {
+                let (key, value) = cursor.next()?;
 
-            key.and_then(|k| value.map(|v| (k, v)))
-        } else {
-            None
+                let key = config.deserialize(&key).ok();
+                let value = bincode::deserialize(&value).ok();
+
+                key.and_then(|k| value.map(|v| (k, v)))
+            }
         }
     }
 }
@@ -0,0 +1,279 @@
+//! In-memory database backing the [`DBColumn`](super::db_column::DBColumn)s
+//! when no disk storage is wanted.
+//!
+//! Keys are stored using the same encoding as in RocksDB and ordered bytewise,
+//! so iterations over positions and prefixes behave the same way.
+
+use std::{
+    collections::{BTreeMap, HashMap, VecDeque},
+    ops::Bound,
+    sync::{Arc, Mutex, RwLock},
+};
+
+use rocksdb::Direction;
+
+use crate::errors::InternalStorageError;
+
+type Column = BTreeMap<Vec<u8>, Vec<u8>>;
+
+/// Number of entries read at once by a [`MemoryCursor`]
+const CURSOR_CHUNK_SIZE: usize = 128;
+
+/// Write operation applied as part of a batch
+#[derive(Debug)]
+pub(crate) enum MemoryOperation {
+    Put(&'static str, Vec<u8>, Vec<u8>),
+    Delete(&'static str, Vec<u8>),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct MemoryDB {
+    columns: Arc<RwLock<HashMap<&'static str, Column>>>,
+    /// Size of the keys and values of each column in bytes, kept up to date
+    /// by the writes so that the statistics don't go through the entries
+    sizes: Arc<Mutex<HashMap<&'static str, u64>>>,
+}
+
+impl MemoryDB {
+    /// Creates an empty database with the given columns
+    pub(crate) fn new(columns: &[&'static str]) -> Self {
+        Self {
+            columns: Arc::new(RwLock::new(
+                columns
+                    .iter()
+                    .map(|column| (*column, Column::new()))
+                    .collect(),
+            )),
+            sizes: Arc::new(Mutex::new(
+                columns.iter().map(|column| (*column, 0)).collect(),
+            )),
+        }
+    }
+
+    pub(crate) fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.columns, &other.columns)
+    }
+
+    pub(crate) fn put(
+        &self,
+        column: &'static str,
+        key: Vec<u8>,
+        value: Vec<u8>,
+    ) -> Result<(), InternalStorageError> {
+        self.write(vec![MemoryOperation::Put(column, key, value)])
+    }
+
+    pub(crate) fn delete(
+        &self,
+        column: &'static str,
+        key: Vec<u8>,
+    ) -> Result<(), InternalStorageError> {
+        self.write(vec![MemoryOperation::Delete(column, key)])
+    }
+
+    pub(crate) fn get(
+        &self,
+        column: &'static str,
+        key: &[u8],
+    ) -> Result<Option<Vec<u8>>, InternalStorageError> {
+        self.read(column, |entries| entries.get(key).cloned())
+    }
+
+    /// Applies all the operations at once
+    pub(crate) fn write(
+        &self,
+        operations: Vec<MemoryOperation>,
+    ) -> Result<(), InternalStorageError> {
+        let mut columns = self
+            .columns
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        // Checking every column first to not partially apply the batch
+        for operation in &operations {
+            let (MemoryOperation::Put(column, ..) | MemoryOperation::Delete(column, _)) = operation;
+            if !columns.contains_key(column) {
+                return Err(InternalStorageError::InvalidColumnFamily(*column));
+            }
+        }
+
+        let mut sizes = self
+            .sizes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        for operation in operations {
+            match operation {
+                MemoryOperation::Put(column, key, value) => {
+                    let size = sizes.entry(column).or_default();
+                    *size += (key.len() + value.len()) as u64;
+
+                    let key_len = key.len();
+                    if let Some(previous) = columns.entry(column).or_default().insert(key, value) {
+                        *size -= (key_len + previous.len()) as u64;
+                    }
+                }
+                MemoryOperation::Delete(column, key) => {
+                    if let Some(previous) = columns.entry(column).or_default().remove(&key) {
+                        *sizes.entry(column).or_default() -= (key.len() + previous.len()) as u64;
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the name of each column along with its number of entries
+    /// and the size of its keys and values in bytes, ordered by name
+    pub(crate) fn column_sizes(&self) -> Vec<(&'static str, u64, u64)> {
+        let columns = self
+            .columns
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let sizes = self
+            .sizes
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let mut column_sizes: Vec<_> = columns
+            .iter()
+            .map(|(column, entries)| {
+                let size = sizes.get(column).copied().unwrap_or_default();
+
+                (*column, entries.len() as u64, size)
+            })
+            .collect();
+        column_sizes.sort_by_key(|(column, ..)| *column);
+
+        column_sizes
+    }
+
+    /// Returns a cursor over the entries starting with `prefix`, from the first
+    /// key greater or equal to `from`
+    pub(crate) fn cursor(
+        &self,
+        column: &'static str,
+        from: &[u8],
+        prefix: &[u8],
+    ) -> Result<MemoryCursor, InternalStorageError> {
+        self.read(column, |_| ())?;
+
+        Ok(MemoryCursor {
+            db: self.clone(),
+            column,
+            next: Bound::Included(from.max(prefix).to_vec()),
+            prefix: prefix.to_vec(),
+            direction: Direction::Forward,
+            chunk: VecDeque::new(),
+            exhausted: false,
+        })
+    }
+
+    /// Returns a cursor over the entries from `key` in the given direction,
+    /// starting with `key` if it exists
+    pub(crate) fn cursor_at(
+        &self,
+        column: &'static str,
+        key: &[u8],
+        direction: Direction,
+    ) -> Result<MemoryCursor, InternalStorageError> {
+        self.read(column, |_| ())?;
+
+        Ok(MemoryCursor {
+            db: self.clone(),
+            column,
+            next: Bound::Included(key.to_vec()),
+            prefix: Vec::new(),
+            direction,
+            chunk: VecDeque::new(),
+            exhausted: false,
+        })
+    }
+
+    /// Returns the greatest key of the column
+    pub(crate) fn last_key(
+        &self,
+        column: &'static str,
+    ) -> Result<Option<Vec<u8>>, InternalStorageError> {
+        self.read(column, |entries| entries.keys().next_back().cloned())
+    }
+
+    fn read<R>(
+        &self,
+        column: &'static str,
+        f: impl FnOnce(&Column) -> R,
+    ) -> Result<R, InternalStorageError> {
+        let columns = self
+            .columns
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        columns
+            .get(column)
+            .map(f)
+            .ok_or(InternalStorageError::InvalidColumnFamily(column))
+    }
+}
+
+/// Iterates lazily over a column, reading its entries by chunks so that the column
+/// is neither copied nor locked for the whole iteration. Like a RocksDB iterator
+/// without snapshot, the changes made during the iteration may be visible.
+pub(crate) struct MemoryCursor {
+    db: MemoryDB,
+    column: &'static str,
+    /// Bound from which the next chunk is read, excluding the last returned key
+    next: Bound<Vec<u8>>,
+    prefix: Vec<u8>,
+    direction: Direction,
+    chunk: VecDeque<(Vec<u8>, Vec<u8>)>,
+    exhausted: bool,
+}
+
+impl MemoryCursor {
+    fn read_chunk(&mut self) {
+        let (next, prefix, direction) = (&self.next, &self.prefix, self.direction);
+
+        let chunk: VecDeque<_> = self
+            .db
+            .read(self.column, |entries| {
+                let matching = |(key, _): &(&Vec<u8>, &Vec<u8>)| key.starts_with(prefix);
+                let owned = |(key, value): (&Vec<u8>, &Vec<u8>)| (key.clone(), value.clone());
+
+                match direction {
+                    Direction::Forward => entries
+                        .range((next.clone(), Bound::Unbounded))
+                        .take_while(matching)
+                        .take(CURSOR_CHUNK_SIZE)
+                        .map(owned)
+                        .collect(),
+                    Direction::Reverse => entries
+                        .range((Bound::Unbounded, next.clone()))
+                        .rev()
+                        .take_while(matching)
+                        .take(CURSOR_CHUNK_SIZE)
+                        .map(owned)
+                        .collect(),
+                }
+            })
+            .unwrap_or_default();
+
+        self.exhausted = chunk.len() < CURSOR_CHUNK_SIZE;
+        if let Some((key, _)) = chunk.back() {
+            self.next = Bound::Excluded(key.clone());
+        }
+        self.chunk = chunk;
+    }
+}
+
+impl Iterator for MemoryCursor {
+    type Item = (Vec<u8>, Vec<u8>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.chunk.is_empty() && !self.exhausted {
+            self.read_chunk();
+        }
+
+        self.chunk.pop_front()
+    }
+}
@@ -0,0 +1,214 @@
+//! Versioning of the layout of the databases.
+//!
+//! Every database stores the version of the schema it was written with. When a
+//! database is opened, the migrations between its version and the one supported
+//! by the node are applied in order. A database written by a newer node is refused.
+//! A database opened as a read-only secondary instance is never migrated, it has to
+//! be at the supported version already.
+//!
+//! The index database is derived from the perpetual one, its migrations can read
+//! the perpetual database which is opened and migrated first.
+
+use std::cmp::Ordering;
+
+use tracing::info;
+
+use crate::{constant::cfs, errors::InternalStorageError};
+
+use super::db_column::{Backend, DBBatch, DBColumn};
+
+pub type SchemaVersion = u32;
+
+/// Version of the layout used before the databases were versioned
+pub(crate) const INITIAL_SCHEMA_VERSION: SchemaVersion = 1;
+
+const SCHEMA_VERSION_KEY: u8 = 0;
+
+/// Change of the layout of a database
+pub(crate) struct Migration {
+    /// Version of the schema once the migration is applied
+    pub(crate) version: SchemaVersion,
+    pub(crate) description: &'static str,
+    pub(crate) migrate: Migrate,
+}
+
+/// Adds the changes of a migration to the batch, which is written
+/// along with the new version of the schema
+#[derive(Clone, Copy)]
+pub(crate) enum Migrate {
+    /// Reads the migrated database only
+    Database(fn(&Backend, DBBatch) -> Result<DBBatch, InternalStorageError>),
+    /// Also reads the perpetual database, given as second argument
+    FromPerpetual(fn(&Backend, &Backend, DBBatch) -> Result<DBBatch, InternalStorageError>),
+}
+
+pub(crate) struct Schema {
+    /// Name of the database, used in logs and errors
+    pub(crate) database: &'static str,
+    /// Migrations ordered by version
+    pub(crate) migrations: &'static [Migration],
+}
+
+pub(crate) const PERPETUAL: Schema = Schema {
+    database: "perpetual",
+    migrations: &[],
+};
+
+pub(crate) const INDEX: Schema = Schema {
+    database: "index",
+    migrations: &[Migration {
+        version: 2,
+        description: "index the delivery order of the certificates",
+        migrate: Migrate::FromPerpetual(crate::index::index_delivery_order),
+    }],
+};
+
+pub(crate) const PENDING: Schema = Schema {
+    database: "pending",
+    migrations: &[Migration {
+        version: 2,
+        description: "index the certificates of the pools by source subnet, age and previous \
+                      certificate",
+        migrate: Migrate::Database(crate::validator::tables::index_pools),
+    }],
+};
+
+pub(crate) const EPOCH_VALIDATORS: Schema = Schema {
+    database: "validators",
+    migrations: &[],
+};
+
+pub(crate) const EPOCH: Schema = Schema {
+    database: "epoch",
+    migrations: &[Migration {
+        version: 2,
+        description: "discard the broadcasts signed without the kind of their messages",
+        migrate: Migrate::Database(crate::epoch::tables::discard_untagged_broadcasts),
+    }],
+};
+
+impl Schema {
+    /// Version of the schema supported by the node
+    pub(crate) fn version(&self) -> SchemaVersion {
+        self.migrations
+            .last()
+            .map_or(INITIAL_SCHEMA_VERSION, |migration| migration.version)
+    }
+
+    /// Applies the schema before the tables of a database are used,
+    /// the node can't run on a database it doesn't support
+    pub(crate) fn open(&self, db: &Backend) {
+        self.apply(db)
+            .unwrap_or_else(|error| panic!("Cannot open the {} database: {error}", self.database));
+    }
+
+    /// Applies the schema of a database derived from the perpetual one
+    pub(crate) fn open_from_perpetual(&self, db: &Backend, perpetual: &Backend) {
+        self.apply_from_perpetual(db, Some(perpetual))
+            .unwrap_or_else(|error| panic!("Cannot open the {} database: {error}", self.database));
+    }
+
+    /// Checks the schema of a database opened as a read-only secondary instance,
+    /// which can't migrate it
+    pub(crate) fn open_secondary(&self, db: &Backend) {
+        self.check(db)
+            .unwrap_or_else(|error| panic!("Cannot open the {} database: {error}", self.database));
+    }
+
+    /// Checks that the database is at the supported version of the schema
+    pub(crate) fn check(&self, db: &Backend) -> Result<(), InternalStorageError> {
+        let column: DBColumn<u8, SchemaVersion> = DBColumn::from_backend(db, cfs::SCHEMA);
+
+        let found = column
+            .get(&SCHEMA_VERSION_KEY)?
+            .unwrap_or(INITIAL_SCHEMA_VERSION);
+        let supported = self.version();
+
+        match found.cmp(&supported) {
+            Ordering::Greater => Err(InternalStorageError::UnsupportedSchemaVersion {
+                database: self.database,
+                found,
+                supported,
+            }),
+            Ordering::Less => Err(InternalStorageError::SchemaMigrationRequired {
+                database: self.database,
+                found,
+                supported,
+            }),
+            Ordering::Equal => Ok(()),
+        }
+    }
+
+    /// Brings the database to the supported version of the schema
+    pub(crate) fn apply(&self, db: &Backend) -> Result<(), InternalStorageError> {
+        self.apply_from_perpetual(db, None)
+    }
+
+    /// Brings the database to the supported version of the schema, the migrations
+    /// reading the perpetual database fail when it isn't given
+    pub(crate) fn apply_from_perpetual(
+        &self,
+        db: &Backend,
+        perpetual: Option<&Backend>,
+    ) -> Result<(), InternalStorageError> {
+        let column: DBColumn<u8, SchemaVersion> = DBColumn::from_backend(db, cfs::SCHEMA);
+
+        let stored = column.get(&SCHEMA_VERSION_KEY)?;
+        let found = stored.unwrap_or(INITIAL_SCHEMA_VERSION);
+        let supported = self.version();
+
+        if found > supported {
+            return Err(InternalStorageError::UnsupportedSchemaVersion {
+                database: self.database,
+                found,
+                supported,
+            });
+        }
+
+        let migrations: Vec<_> = self
+            .migrations
+            .iter()
+            .filter(|migration| migration.version > found)
+            .collect();
+
+        if !migrations.is_empty() {
+            info!(
+                "Migrating the {} database from schema version {} to {}",
+                self.database, found, supported
+            );
+        }
+
+        for (index, migration) in migrations.iter().enumerate() {
+            info!(
+                "[{}/{}] Migrating the {} database to schema version {}: {}",
+                index + 1,
+                migrations.len(),
+                self.database,
+                migration.version,
+                migration.description
+            );
+
+            let batch = match (migration.migrate, perpetual) {
+                (Migrate::Database(migrate), _) => migrate(db, column.batch())?,
+                (Migrate::FromPerpetual(migrate), Some(perpetual)) => {
+                    migrate(db, perpetual, column.batch())?
+                }
+                (Migrate::FromPerpetual(_), None) => {
+                    return Err(InternalStorageError::PerpetualDatabaseRequired {
+                        database: self.database,
+                    })
+                }
+            };
+
+            batch
+                .insert_batch(&column, [(SCHEMA_VERSION_KEY, migration.version)])?
+                .write()?;
+        }
+
+        if stored.is_none() && migrations.is_empty() {
+            column.insert(&SCHEMA_VERSION_KEY, &supported)?;
+        }
+
+        Ok(())
+    }
+}
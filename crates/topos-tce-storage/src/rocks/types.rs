@@ -4,7 +4,10 @@ use topos_core::{
     uci::{Certificate, CertificateId},
 };
 
-use crate::SubnetId;
+use crate::{
+    types::{CertificateSequenceNumber, Timestamp},
+    SubnetId,
+};
 
 use super::db_column::DBColumn;
 
@@ -16,6 +19,16 @@ pub(crate) struct TargetSourceListKey(
     pub(crate) SubnetId,
 );
 
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct DeliveryTimeKey(
+    // Source or target subnet id
+    pub(crate) SubnetId,
+    // Delivery time
+    pub(crate) Timestamp,
+    // Sequence number, unique for every delivered certificate
+    pub(crate) CertificateSequenceNumber,
+);
+
 /// Column that keeps certificates that are not yet delivered
 pub(crate) type PendingCertificatesColumn = DBColumn<u64, Certificate>;
 /// Column that keeps list of all certificates retrievable by their id
@@ -0,0 +1,552 @@
+//! Offline snapshots of the delivered certificates.
+//!
+//! A snapshot contains every delivered certificate up to the checkpoint taken
+//! at the time of the export, along with the target streams they belong to, their
+//! sequence number in the delivery order and their timestamps. Importing a snapshot
+//! writes them back as they were exported, which rebuilds the streams and the index
+//! tables exactly as they were on the exporting node.
+//!
+//! The archive is a sequence of records, each one prefixed by its length (u32),
+//! so that neither the export nor the import holds the whole history in memory:
+//!
+//! | magic (8 bytes) | version (u32) | records... | end record |
+//!
+//! Every source stream is written as its head followed by its certificates, then
+//! come the target streams. The end record holds the keccak256 of all the previous
+//! records. An import reads the whole archive and checks it before writing anything,
+//! the heads of the source streams being written last so that a failed import can
+//! be retried.
+//!
+//! Both nodes must be stopped while exporting or importing a snapshot.
+
+use std::{
+    collections::{HashMap, HashSet},
+    io::{Read, Seek, Write},
+};
+
+use serde::{Deserialize, Serialize};
+use tiny_keccak::Keccak;
+use topos_core::{
+    types::{
+        stream::{CertificateTargetStreamPosition, Position},
+        CertificateDelivered,
+    },
+    uci::{CertificateId, SubnetId},
+};
+use tracing::info;
+
+use crate::{
+    errors::SnapshotError,
+    fullnode::FullNodeStore,
+    rocks::{map::Map, DeliveryTimeKey, TargetSourceListKey},
+    store::ReadStore,
+    types::{CertificateSequenceNumber, CertificateTimestamps},
+    SourceHead,
+};
+
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 2;
+
+const SNAPSHOT_MAGIC: &[u8; 8] = b"TOPOSSNP";
+
+/// Maximum size of a record, a larger one means the archive is corrupted
+const MAX_RECORD_SIZE: usize = 64 * 1024 * 1024;
+
+/// Number of records written to the store per batch during an import
+const IMPORT_BATCH_SIZE: usize = 1_000;
+
+#[derive(Serialize, Deserialize)]
+pub(crate) enum SnapshotRecord {
+    /// Head of a source stream, followed by the certificates of the stream up to it
+    Head(SourceHead),
+    Certificate(SnapshotCertificate),
+    /// Position of an exported certificate in a target stream
+    TargetStream(CertificateTargetStreamPosition, CertificateId),
+    /// Last record, keccak256 of all the previous ones
+    End([u8; 32]),
+}
+
+#[derive(Serialize, Deserialize)]
+pub(crate) struct SnapshotCertificate {
+    certificate: CertificateDelivered,
+    /// Sequence number of the certificate in the delivery order of the exporting node
+    sequence_number: CertificateSequenceNumber,
+    /// Unknown for the certificates delivered before the timestamps were recorded
+    timestamps: Option<CertificateTimestamps>,
+}
+
+/// Summary of an exported or imported snapshot
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnapshotSummary {
+    pub subnets: usize,
+    pub certificates: usize,
+}
+
+/// Writes every delivered certificate up to the current checkpoint into `writer`
+pub fn export(store: &FullNodeStore, writer: impl Write) -> Result<SnapshotSummary, SnapshotError> {
+    let mut checkpoint: Vec<SourceHead> = store.get_checkpoint()?.into_values().collect();
+    checkpoint.sort_by_key(|head| head.subnet_id);
+
+    let mut writer = RecordWriter::new(writer)?;
+    let mut summary = SnapshotSummary {
+        subnets: checkpoint.len(),
+        certificates: 0,
+    };
+
+    for head in &checkpoint {
+        writer.write(&SnapshotRecord::Head(head.clone()))?;
+
+        for (position, certificate_id) in store
+            .perpetual_tables
+            .streams
+            .prefix_iter(&head.subnet_id)?
+            .take_while(|(position, _)| {
+                position.subnet_id == head.subnet_id && position.position <= head.position
+            })
+        {
+            let certificate = match store.perpetual_tables.certificates.get(&certificate_id)? {
+                Some(certificate) if certificate.certificate.id == certificate_id => certificate,
+                _ => return Err(SnapshotError::StreamMismatch(position.subnet_id)),
+            };
+
+            let sequence_number = store
+                .index_tables
+                .certificate_sequences
+                .get(&certificate_id)?
+                .ok_or(SnapshotError::MissingDeliveryOrder(certificate_id))?;
+
+            writer.write(&SnapshotRecord::Certificate(SnapshotCertificate {
+                certificate,
+                sequence_number,
+                timestamps: store.perpetual_tables.timestamps.get(&certificate_id)?,
+            }))?;
+            summary.certificates += 1;
+        }
+    }
+
+    for (position, certificate_id) in store.index_tables.target_streams.iter()? {
+        if checkpoint
+            .binary_search_by_key(&position.source_subnet_id, |head| head.subnet_id)
+            .is_ok()
+        {
+            writer.write(&SnapshotRecord::TargetStream(position, certificate_id))?;
+        }
+    }
+
+    writer.finish()?;
+
+    info!(
+        "Snapshot exported with {} certificates of {} subnets",
+        summary.certificates, summary.subnets
+    );
+
+    Ok(summary)
+}
+
+/// Restores the certificates of the snapshot read from `reader` into an empty store.
+///
+/// The archive is read twice: it is entirely checked first, then written to the store.
+pub async fn import<R: Read + Seek>(
+    store: &FullNodeStore,
+    mut reader: R,
+) -> Result<SnapshotSummary, SnapshotError> {
+    if !store.get_checkpoint()?.is_empty() {
+        return Err(SnapshotError::StoreNotEmpty);
+    }
+
+    let summary = check(RecordReader::new(&mut reader)?)?;
+
+    reader.rewind()?;
+    restore(store, RecordReader::new(&mut reader)?).await?;
+
+    info!(
+        "Snapshot imported with {} certificates of {} subnets",
+        summary.certificates, summary.subnets
+    );
+
+    Ok(summary)
+}
+
+/// Checks the checksum of the archive and that every stream it holds is complete
+fn check<R: Read>(mut records: RecordReader<R>) -> Result<SnapshotSummary, SnapshotError> {
+    let mut summary = SnapshotSummary {
+        subnets: 0,
+        certificates: 0,
+    };
+    let mut subnets = HashSet::new();
+    // Source stream being read and the position of its last certificate
+    let mut stream: Option<(SourceHead, Option<Position>)> = None;
+    // Next position of each target stream
+    let mut target_streams: HashMap<(SubnetId, SubnetId), Position> = HashMap::new();
+
+    while let Some(record) = records.next()? {
+        match record {
+            SnapshotRecord::Head(head) => {
+                check_stream_end(stream.take())?;
+
+                if !subnets.insert(head.subnet_id) {
+                    return Err(SnapshotError::StreamMismatch(head.subnet_id));
+                }
+
+                summary.subnets += 1;
+                stream = Some((head, None));
+            }
+            SnapshotRecord::Certificate(SnapshotCertificate { certificate, .. }) => {
+                let Some((head, last)) = stream.as_mut() else {
+                    return Err(SnapshotError::InvalidArchive);
+                };
+
+                let position = &certificate.proof_of_delivery.delivery_position;
+                let follows_last = match last {
+                    Some(last) => last.increment().ok() == Some(position.position),
+                    None => position.position == Position::ZERO,
+                };
+
+                if !follows_last
+                    || position.subnet_id != head.subnet_id
+                    || certificate.certificate.source_subnet_id != head.subnet_id
+                    || position.position > head.position
+                    || (position.position == head.position
+                        && certificate.certificate.id != head.certificate_id)
+                {
+                    return Err(SnapshotError::StreamMismatch(head.subnet_id));
+                }
+
+                *last = Some(position.position);
+                summary.certificates += 1;
+            }
+            SnapshotRecord::TargetStream(position, _) => {
+                check_stream_end(stream.take())?;
+
+                let next = target_streams
+                    .entry((position.target_subnet_id, position.source_subnet_id))
+                    .or_insert(Position::ZERO);
+
+                if !subnets.contains(&position.source_subnet_id) || position.position != *next {
+                    return Err(SnapshotError::StreamMismatch(position.source_subnet_id));
+                }
+
+                *next = next
+                    .increment()
+                    .map_err(|_| SnapshotError::StreamMismatch(position.source_subnet_id))?;
+            }
+            SnapshotRecord::End(_) => return Err(SnapshotError::InvalidArchive),
+        }
+    }
+
+    check_stream_end(stream)?;
+
+    Ok(summary)
+}
+
+/// Checks that the source stream being read reached its head
+fn check_stream_end(stream: Option<(SourceHead, Option<Position>)>) -> Result<(), SnapshotError> {
+    match stream {
+        Some((head, last)) if last != Some(head.position) => {
+            Err(SnapshotError::StreamMismatch(head.subnet_id))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Writes the records of a checked archive to the store
+async fn restore<R: Read>(
+    store: &FullNodeStore,
+    mut records: RecordReader<R>,
+) -> Result<(), SnapshotError> {
+    let mut heads = Vec::new();
+    let mut certificates = Vec::new();
+    let mut target_streams = Vec::new();
+    let mut next_sequence_number = 0;
+
+    while let Some(record) = records.next()? {
+        match record {
+            SnapshotRecord::Head(head) => heads.push(head),
+            SnapshotRecord::Certificate(certificate) => {
+                next_sequence_number = next_sequence_number.max(certificate.sequence_number + 1);
+                certificates.push(certificate);
+
+                if certificates.len() >= IMPORT_BATCH_SIZE {
+                    write_certificates(store, &certificates)?;
+                    certificates.clear();
+                }
+            }
+            SnapshotRecord::TargetStream(position, certificate_id) => {
+                target_streams.push((position, certificate_id));
+
+                if target_streams.len() >= IMPORT_BATCH_SIZE {
+                    write_target_streams(store, &target_streams)?;
+                    target_streams.clear();
+                }
+            }
+            SnapshotRecord::End(_) => return Err(SnapshotError::InvalidArchive),
+        }
+    }
+
+    write_certificates(store, &certificates)?;
+    write_target_streams(store, &target_streams)?;
+
+    // Written last, the store isn't considered as restored before
+    store
+        .index_tables
+        .source_list
+        .batch()
+        .insert_batch(
+            &store.index_tables.source_list,
+            heads
+                .iter()
+                .map(|head| (head.subnet_id, (head.certificate_id, head.position))),
+        )?
+        .write()?;
+
+    store.resume_delivery_order(next_sequence_number).await;
+
+    Ok(())
+}
+
+fn write_certificates(
+    store: &FullNodeStore,
+    certificates: &[SnapshotCertificate],
+) -> Result<(), SnapshotError> {
+    let perpetual_tables = &store.perpetual_tables;
+    let index_tables = &store.index_tables;
+
+    let delivery_times = || {
+        certificates.iter().filter_map(|snapshot| {
+            snapshot
+                .timestamps
+                .map(|timestamps| (snapshot, timestamps.delivered_at))
+        })
+    };
+
+    perpetual_tables
+        .certificates
+        .batch()
+        .insert_batch(
+            &perpetual_tables.certificates,
+            certificates
+                .iter()
+                .map(|snapshot| (snapshot.certificate.certificate.id, &snapshot.certificate)),
+        )?
+        .insert_batch(
+            &perpetual_tables.streams,
+            certificates.iter().map(|snapshot| {
+                (
+                    &snapshot.certificate.proof_of_delivery.delivery_position,
+                    snapshot.certificate.certificate.id,
+                )
+            }),
+        )?
+        .insert_batch(
+            &perpetual_tables.timestamps,
+            certificates.iter().filter_map(|snapshot| {
+                snapshot
+                    .timestamps
+                    .map(|timestamps| (snapshot.certificate.certificate.id, timestamps))
+            }),
+        )?
+        .write()?;
+
+    index_tables
+        .certificate_order
+        .batch()
+        .insert_batch(
+            &index_tables.certificate_order,
+            certificates.iter().map(|snapshot| {
+                (
+                    snapshot.sequence_number,
+                    snapshot.certificate.certificate.id,
+                )
+            }),
+        )?
+        .insert_batch(
+            &index_tables.certificate_sequences,
+            certificates.iter().map(|snapshot| {
+                (
+                    snapshot.certificate.certificate.id,
+                    snapshot.sequence_number,
+                )
+            }),
+        )?
+        .insert_batch(
+            &index_tables.source_list_per_target,
+            certificates.iter().flat_map(|snapshot| {
+                let certificate = &snapshot.certificate.certificate;
+
+                certificate
+                    .target_subnets
+                    .iter()
+                    .map(move |target_subnet_id| {
+                        ((*target_subnet_id, certificate.source_subnet_id), true)
+                    })
+            }),
+        )?
+        .insert_batch(
+            &index_tables.source_delivery_times,
+            delivery_times().map(|(snapshot, delivered_at)| {
+                let certificate = &snapshot.certificate.certificate;
+
+                (
+                    DeliveryTimeKey(
+                        certificate.source_subnet_id,
+                        delivered_at,
+                        snapshot.sequence_number,
+                    ),
+                    certificate.id,
+                )
+            }),
+        )?
+        .insert_batch(
+            &index_tables.target_delivery_times,
+            delivery_times().flat_map(|(snapshot, delivered_at)| {
+                let certificate = &snapshot.certificate.certificate;
+
+                certificate
+                    .target_subnets
+                    .iter()
+                    .map(move |target_subnet_id| {
+                        (
+                            DeliveryTimeKey(
+                                *target_subnet_id,
+                                delivered_at,
+                                snapshot.sequence_number,
+                            ),
+                            certificate.id,
+                        )
+                    })
+            }),
+        )?
+        .write()?;
+
+    Ok(())
+}
+
+fn write_target_streams(
+    store: &FullNodeStore,
+    target_streams: &[(CertificateTargetStreamPosition, CertificateId)],
+) -> Result<(), SnapshotError> {
+    let index_tables = &store.index_tables;
+
+    index_tables
+        .target_streams
+        .batch()
+        .insert_batch(&index_tables.target_streams, target_streams.iter().copied())?
+        // The target streams are ordered, the last position of each one is written last
+        .insert_batch(
+            &index_tables.target_source_list,
+            target_streams.iter().map(|(position, _)| {
+                (
+                    TargetSourceListKey(position.target_subnet_id, position.source_subnet_id),
+                    position.position,
+                )
+            }),
+        )?
+        .write()?;
+
+    Ok(())
+}
+
+/// Writes the records of an archive along with their checksum
+pub(crate) struct RecordWriter<W> {
+    writer: W,
+    hasher: Keccak,
+}
+
+impl<W: Write> RecordWriter<W> {
+    pub(crate) fn new(mut writer: W) -> Result<Self, SnapshotError> {
+        writer.write_all(SNAPSHOT_MAGIC)?;
+        writer.write_all(&SNAPSHOT_FORMAT_VERSION.to_be_bytes())?;
+
+        Ok(Self {
+            writer,
+            hasher: Keccak::new_keccak256(),
+        })
+    }
+
+    pub(crate) fn write(&mut self, record: &SnapshotRecord) -> Result<(), SnapshotError> {
+        let record = bincode::serialize(record)?;
+        let length = (record.len() as u32).to_be_bytes();
+
+        self.hasher.update(&length);
+        self.hasher.update(&record);
+        self.writer.write_all(&length)?;
+        self.writer.write_all(&record)?;
+
+        Ok(())
+    }
+
+    pub(crate) fn finish(self) -> Result<(), SnapshotError> {
+        let Self { mut writer, hasher } = self;
+
+        let mut checksum = [0u8; 32];
+        hasher.finalize(&mut checksum);
+
+        let record = bincode::serialize(&SnapshotRecord::End(checksum))?;
+        writer.write_all(&(record.len() as u32).to_be_bytes())?;
+        writer.write_all(&record)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Reads the records of an archive, checking its checksum once the end is reached
+pub(crate) struct RecordReader<R> {
+    reader: R,
+    hasher: Keccak,
+}
+
+impl<R: Read> RecordReader<R> {
+    pub(crate) fn new(mut reader: R) -> Result<Self, SnapshotError> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != SNAPSHOT_MAGIC {
+            return Err(SnapshotError::InvalidArchive);
+        }
+
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version)?;
+        let version = u32::from_be_bytes(version);
+        if version != SNAPSHOT_FORMAT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion(version));
+        }
+
+        Ok(Self {
+            reader,
+            hasher: Keccak::new_keccak256(),
+        })
+    }
+
+    /// Returns the next record, or `None` once the end of the archive is reached
+    pub(crate) fn next(&mut self) -> Result<Option<SnapshotRecord>, SnapshotError> {
+        let mut length = [0u8; 4];
+        self.reader.read_exact(&mut length)?;
+
+        let size = u32::from_be_bytes(length) as usize;
+        if size > MAX_RECORD_SIZE {
+            return Err(SnapshotError::InvalidArchive);
+        }
+
+        let mut record = vec![0u8; size];
+        self.reader.read_exact(&mut record)?;
+
+        match bincode::deserialize(&record)? {
+            SnapshotRecord::End(checksum) => {
+                let hasher = std::mem::replace(&mut self.hasher, Keccak::new_keccak256());
+                let mut expected = [0u8; 32];
+                hasher.finalize(&mut expected);
+
+                if checksum != expected {
+                    return Err(SnapshotError::ChecksumMismatch);
+                }
+
+                Ok(None)
+            }
+            snapshot_record => {
+                self.hasher.update(&length);
+                self.hasher.update(&record);
+
+                Ok(Some(snapshot_record))
+            }
+        }
+    }
+}
@@ -7,7 +7,9 @@ use topos_core::{
 };
 
 use crate::{
-    errors::StorageError, CertificatePositions, CertificateTargetStreamPosition, SourceHead,
+    errors::StorageError,
+    types::{CertificateSequenceNumber, CertificateTimestamps, Timestamp},
+    CertificatePositions, CertificateTargetStreamPosition, SourceHead,
 };
 
 #[async_trait]
@@ -65,4 +67,44 @@ pub trait ReadStore: Send {
         &self,
         target_subnet_id: &SubnetId,
     ) -> Result<Vec<SubnetId>, StorageError>;
+
+    /// Returns the sequence number given to a certificate when it was delivered
+    fn get_certificate_sequence_number(
+        &self,
+        certificate_id: &CertificateId,
+    ) -> Result<Option<CertificateSequenceNumber>, StorageError>;
+
+    /// Returns the certificates in the order they were delivered by this node,
+    /// starting from a sequence number.
+    fn get_certificates_from_sequence_number(
+        &self,
+        from: CertificateSequenceNumber,
+        limit: usize,
+    ) -> Result<Vec<(CertificateDelivered, CertificateSequenceNumber)>, StorageError>;
+
+    /// Returns the times at which a certificate was received and delivered
+    fn get_certificate_timestamps(
+        &self,
+        certificate_id: &CertificateId,
+    ) -> Result<Option<CertificateTimestamps>, StorageError>;
+
+    /// Returns the certificates of a source subnet delivered between two
+    /// timestamps included, ordered by delivery time.
+    fn get_source_certificates_delivered_between(
+        &self,
+        source_subnet_id: &SubnetId,
+        from: Timestamp,
+        to: Timestamp,
+        limit: usize,
+    ) -> Result<Vec<(CertificateDelivered, CertificateTimestamps)>, StorageError>;
+
+    /// Returns the certificates for a target subnet delivered between two
+    /// timestamps included, ordered by delivery time.
+    fn get_target_certificates_delivered_between(
+        &self,
+        target_subnet_id: &SubnetId,
+        from: Timestamp,
+        to: Timestamp,
+        limit: usize,
+    ) -> Result<Vec<(CertificateDelivered, CertificateTimestamps)>, StorageError>;
 }
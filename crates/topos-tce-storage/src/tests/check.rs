@@ -0,0 +1,157 @@
+use std::sync::Arc;
+
+use rstest::rstest;
+use rstest_reuse::apply;
+use test_log::test;
+use topos_core::types::stream::{CertificateSourceStreamPosition, Position};
+use topos_test_sdk::{
+    certificates::create_certificate_chain,
+    constants::{SOURCE_SUBNET_ID_1, SOURCE_SUBNET_ID_2, TARGET_SUBNET_ID_1},
+};
+
+use super::support::{backends, memory_store};
+use crate::{
+    check::{self, Inconsistency},
+    rocks::map::Map,
+    store::WriteStore,
+    validator::ValidatorStore,
+};
+
+#[apply(backends)]
+#[test(tokio::test)]
+async fn delivered_certificates_are_consistent(store: Arc<ValidatorStore>) {
+    store
+        .insert_certificates_delivered(&create_certificate_chain(
+            SOURCE_SUBNET_ID_1,
+            &[TARGET_SUBNET_ID_1],
+            10,
+        ))
+        .await
+        .unwrap();
+    store
+        .insert_certificates_delivered(&create_certificate_chain(
+            SOURCE_SUBNET_ID_2,
+            &[TARGET_SUBNET_ID_1],
+            3,
+        ))
+        .await
+        .unwrap();
+
+    let report = check::check(&store).unwrap();
+
+    assert_eq!(report.certificates, 13);
+    assert!(report.is_consistent(), "{:?}", report.inconsistencies);
+}
+
+#[apply(backends)]
+#[test(tokio::test)]
+async fn wrong_heads_and_stale_pending_certificates_are_repaired(store: Arc<ValidatorStore>) {
+    let certificates = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 5);
+    store
+        .insert_certificates_delivered(&certificates)
+        .await
+        .unwrap();
+
+    let index = &store.fullnode_store.index_tables;
+    let head = index.source_list.get(&SOURCE_SUBNET_ID_1).unwrap().unwrap();
+    index
+        .source_list
+        .insert(&SOURCE_SUBNET_ID_1, &(head.0, Position::ZERO))
+        .unwrap();
+
+    // Leftover of a crash between the delivery and the cleanup of the pending pool
+    let stale = store
+        .insert_pending_certificates(&[certificates[2].certificate.clone()])
+        .unwrap()[0];
+
+    let report = check::check(&store).unwrap();
+    assert!(report
+        .inconsistencies
+        .contains(&Inconsistency::WrongSourceHead {
+            subnet_id: SOURCE_SUBNET_ID_1,
+            recorded: Some((head.0, Position::ZERO)),
+            expected: Some(head),
+        }));
+    assert!(report
+        .inconsistencies
+        .contains(&Inconsistency::PendingAndDelivered {
+            certificate_id: certificates[2].certificate.id,
+            pending_id: stale,
+        }));
+    assert!(report
+        .inconsistencies
+        .iter()
+        .all(Inconsistency::is_repairable));
+
+    let repair = check::repair(&store).unwrap();
+
+    assert_eq!(repair.repaired.len(), report.inconsistencies.len());
+    assert!(repair.remaining.is_empty(), "{:?}", repair.remaining);
+    assert_eq!(
+        index.source_list.get(&SOURCE_SUBNET_ID_1).unwrap(),
+        Some(head)
+    );
+    assert!(store.get_pending_certificate(&stale).unwrap().is_none());
+}
+
+#[rstest]
+#[test(tokio::test)]
+async fn source_stream_gap_is_reported_but_not_repaired(memory_store: Arc<ValidatorStore>) {
+    let certificates = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 5);
+    memory_store
+        .insert_certificates_delivered(&certificates)
+        .await
+        .unwrap();
+
+    let missing = CertificateSourceStreamPosition::new(SOURCE_SUBNET_ID_1, 3u64);
+    memory_store
+        .fullnode_store
+        .perpetual_tables
+        .streams
+        .delete(&missing)
+        .unwrap();
+
+    let expected = [
+        Inconsistency::SourceStreamGap {
+            subnet_id: SOURCE_SUBNET_ID_1,
+            position: missing.position,
+        },
+        Inconsistency::MisplacedCertificate {
+            certificate_id: certificates[3].certificate.id,
+            position: missing.clone(),
+        },
+    ];
+
+    let report = check::check(&memory_store).unwrap();
+    assert_eq!(report.inconsistencies, expected);
+
+    let repair = check::repair(&memory_store).unwrap();
+    assert!(repair.repaired.is_empty());
+    assert_eq!(repair.remaining, expected);
+}
+
+#[apply(backends)]
+#[test(tokio::test)]
+async fn history_before_a_trusted_stream_start_can_be_missing(store: Arc<ValidatorStore>) {
+    let certificates = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 5);
+    store
+        .insert_certificates_delivered(&certificates[2..])
+        .await
+        .unwrap();
+
+    let report = check::check(&store).unwrap();
+    assert_eq!(
+        report.inconsistencies,
+        [0u64, 1].map(|position| Inconsistency::SourceStreamGap {
+            subnet_id: SOURCE_SUBNET_ID_1,
+            position: position.into(),
+        })
+    );
+
+    store
+        .record_trusted_stream_start(SOURCE_SUBNET_ID_1, 2u64.into())
+        .unwrap();
+
+    let report = check::check(&store).unwrap();
+    assert!(report.is_consistent(), "{:?}", report.inconsistencies);
+}
@@ -0,0 +1,346 @@
+use std::{collections::HashSet, sync::Arc};
+
+use rstest::rstest;
+use rstest_reuse::apply;
+use test_log::test;
+use topos_core::{
+    api::grpc::tce::v1::CheckpointSummary as GrpcCheckpointSummary, types::ValidatorId,
+};
+use topos_crypto::messages::MessageSigner;
+use topos_test_sdk::{
+    certificates::create_certificate_chain,
+    constants::{SOURCE_SUBNET_ID_1, SOURCE_SUBNET_ID_2, TARGET_SUBNET_ID_1},
+};
+
+use super::support::{backends, memory_store};
+use crate::{
+    errors::CheckpointError,
+    store::{ReadStore, WriteStore},
+    types::{
+        CheckpointDiffCursor, CheckpointSummary, EpochId, ValidatorQuorumSignatureInfo,
+        VerifiedCheckpointSummary,
+    },
+    validator::ValidatorStore,
+};
+
+fn create_signers(count: u8) -> Vec<MessageSigner> {
+    (1..=count)
+        .map(|i| MessageSigner::new(&[i; 32]).unwrap())
+        .collect()
+}
+
+fn validators(signers: &[MessageSigner]) -> HashSet<ValidatorId> {
+    signers
+        .iter()
+        .map(|signer| ValidatorId::from(signer.public_address))
+        .collect()
+}
+
+fn sign(summary: CheckpointSummary, signers: &[MessageSigner]) -> VerifiedCheckpointSummary {
+    let signatures = signers
+        .iter()
+        .map(|signer| {
+            let validator_id = ValidatorId::from(signer.public_address);
+            let signature = signer
+                .sign_message(&summary.signing_payload(&validator_id))
+                .unwrap();
+
+            (validator_id, signature.to_string())
+        })
+        .collect();
+
+    let quorum = ValidatorQuorumSignatureInfo {
+        epoch: summary.epoch,
+        signatures,
+    };
+
+    VerifiedCheckpointSummary(summary, quorum)
+}
+
+/// Creates the next checkpoint over the current source heads of the store
+fn next_checkpoint(
+    store: &ValidatorStore,
+    epoch: EpochId,
+    signers: &[MessageSigner],
+) -> VerifiedCheckpointSummary {
+    let last = store.fullnode_store.get_last_verified_checkpoint().unwrap();
+    let heads = store.get_checkpoint().unwrap().into_values().collect();
+
+    sign(
+        CheckpointSummary::new(epoch, last.as_ref().map(|last| last.summary()), heads),
+        signers,
+    )
+}
+
+#[test]
+fn checkpoint_signatures_are_verified() {
+    let signers = create_signers(4);
+    let validators = validators(&signers);
+    let summary = CheckpointSummary::new(0, None, vec![]);
+
+    let checkpoint = sign(summary.clone(), &signers[..3]);
+    assert!(checkpoint.verify(&validators, 3).is_ok());
+
+    assert!(matches!(
+        checkpoint.verify(&validators, 4),
+        Err(CheckpointError::ThresholdNotReached {
+            expected: 4,
+            got: 3
+        })
+    ));
+
+    let outsider = create_signers(5).pop().unwrap();
+    let unknown = sign(summary.clone(), &[outsider]);
+    assert!(matches!(
+        unknown.verify(&validators, 1),
+        Err(CheckpointError::UnknownValidator(_))
+    ));
+
+    let mut duplicated = sign(summary.clone(), &signers[..1]);
+    duplicated
+        .1
+        .signatures
+        .push(duplicated.1.signatures[0].clone());
+    assert!(matches!(
+        duplicated.verify(&validators, 1),
+        Err(CheckpointError::DuplicatedSignature(_))
+    ));
+
+    let mut forged = sign(summary, &signers[..2]);
+    forged.1.signatures[0].1 = forged.1.signatures[1].1.clone();
+    assert!(matches!(
+        forged.verify(&validators, 2),
+        Err(CheckpointError::InvalidSignature(_))
+    ));
+
+    let mut other_epoch = checkpoint;
+    other_epoch.1.epoch = 1;
+    assert!(matches!(
+        other_epoch.verify(&validators, 3),
+        Err(CheckpointError::EpochMismatch {
+            expected: 0,
+            got: 1
+        })
+    ));
+}
+
+#[rstest]
+#[test(tokio::test)]
+async fn checkpoint_summary_round_trips_through_grpc(memory_store: Arc<ValidatorStore>) {
+    memory_store
+        .insert_certificates_delivered(&create_certificate_chain(
+            SOURCE_SUBNET_ID_1,
+            &[TARGET_SUBNET_ID_1],
+            2,
+        ))
+        .await
+        .unwrap();
+
+    let first = CheckpointSummary::new(
+        0,
+        None,
+        memory_store
+            .get_checkpoint()
+            .unwrap()
+            .into_values()
+            .collect(),
+    );
+    let second = CheckpointSummary::new(0, Some(&first), first.checkpoint_data.clone())
+        .with_next_validators(validators(&create_signers(4)));
+
+    for summary in [first, second] {
+        let grpc: GrpcCheckpointSummary = summary.clone().into();
+
+        assert_eq!(CheckpointSummary::try_from(grpc).unwrap(), summary);
+    }
+}
+
+#[apply(backends)]
+#[test(tokio::test)]
+async fn checkpoints_are_chained(store: Arc<ValidatorStore>) {
+    let signers = create_signers(4);
+    let fullnode_store = &store.fullnode_store;
+
+    store
+        .insert_certificates_delivered(&create_certificate_chain(
+            SOURCE_SUBNET_ID_1,
+            &[TARGET_SUBNET_ID_1],
+            2,
+        ))
+        .await
+        .unwrap();
+
+    let first = next_checkpoint(&store, 0, &signers);
+    assert_eq!(first.summary().sequence_number, 0);
+    assert_eq!(first.summary().previous_digest, None);
+    fullnode_store
+        .insert_verified_checkpoint(&first)
+        .await
+        .unwrap();
+
+    store
+        .insert_certificates_delivered(&create_certificate_chain(
+            SOURCE_SUBNET_ID_2,
+            &[TARGET_SUBNET_ID_1],
+            1,
+        ))
+        .await
+        .unwrap();
+
+    let second = next_checkpoint(&store, 0, &signers);
+    assert_eq!(second.summary().sequence_number, 1);
+    assert_eq!(
+        second.summary().previous_digest,
+        Some(first.summary().digest())
+    );
+    assert_eq!(second.summary().checkpoint_data.len(), 2);
+
+    // Replaying the first checkpoint doesn't follow the chain anymore
+    fullnode_store
+        .insert_verified_checkpoint(&second)
+        .await
+        .unwrap();
+    assert!(matches!(
+        fullnode_store.insert_verified_checkpoint(&first).await,
+        Err(CheckpointError::UnexpectedSequenceNumber {
+            expected: 2,
+            got: 0
+        })
+    ));
+
+    assert_eq!(
+        fullnode_store.get_last_verified_checkpoint().unwrap(),
+        Some(second.clone())
+    );
+    assert_eq!(
+        fullnode_store.get_verified_checkpoint(0).unwrap(),
+        Some(first)
+    );
+}
+
+#[apply(backends)]
+#[test(tokio::test)]
+async fn checkpoint_diff_is_paginated(store: Arc<ValidatorStore>) {
+    let subnet_1 = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 3);
+    let subnet_2 = create_certificate_chain(SOURCE_SUBNET_ID_2, &[TARGET_SUBNET_ID_1], 3);
+    store
+        .insert_certificates_delivered(&subnet_1)
+        .await
+        .unwrap();
+    store
+        .insert_certificates_delivered(&subnet_2)
+        .await
+        .unwrap();
+
+    // The requester already knows the first certificate of the first subnet
+    let from = vec![subnet_1[0].proof_of_delivery.clone()];
+    let expected: HashSet<_> = subnet_1[1..]
+        .iter()
+        .chain(subnet_2.iter())
+        .map(|certificate| certificate.certificate.id)
+        .collect();
+
+    let (first_page, cursor) = store
+        .get_checkpoint_diff_page(from.clone(), None, 4)
+        .unwrap();
+    let cursor = cursor.expect("The diff doesn't fit in a single page");
+    assert_eq!(first_page.values().map(Vec::len).sum::<usize>(), 4);
+
+    let cursor = CheckpointDiffCursor::from_bytes(&cursor.to_bytes()).unwrap();
+    let (second_page, cursor) = store
+        .get_checkpoint_diff_page(from, Some(cursor), 4)
+        .unwrap();
+    assert!(cursor.is_none());
+
+    let received: Vec<_> = first_page
+        .into_values()
+        .chain(second_page.into_values())
+        .flatten()
+        .map(|proof| proof.certificate_id)
+        .collect();
+    assert_eq!(received.len(), expected.len());
+    assert_eq!(received.into_iter().collect::<HashSet<_>>(), expected);
+}
+
+#[rstest]
+#[test(tokio::test)]
+async fn checkpoint_not_chained_to_the_previous_one_is_refused(memory_store: Arc<ValidatorStore>) {
+    let signers = create_signers(4);
+    let fullnode_store = &memory_store.fullnode_store;
+
+    let first = next_checkpoint(&memory_store, 0, &signers);
+    fullnode_store
+        .insert_verified_checkpoint(&first)
+        .await
+        .unwrap();
+
+    let mut forked = first.summary().clone();
+    forked.sequence_number = 1;
+    forked.previous_digest = Some([0u8; 32]);
+    assert!(matches!(
+        fullnode_store
+            .insert_verified_checkpoint(&sign(forked, &signers))
+            .await,
+        Err(CheckpointError::PreviousDigestMismatch(1))
+    ));
+
+    // The epoch can't change without agreeing on the next validator set
+    let next_epoch = next_checkpoint(&memory_store, 1, &signers);
+    assert!(matches!(
+        fullnode_store.insert_verified_checkpoint(&next_epoch).await,
+        Err(CheckpointError::UnexpectedEpoch {
+            sequence_number: 1,
+            expected: 0,
+            got: 1
+        })
+    ));
+}
+
+#[rstest]
+#[test(tokio::test)]
+async fn epoch_summary_spans_the_checkpoints_of_the_epoch(memory_store: Arc<ValidatorStore>) {
+    let signers = create_signers(4);
+    let fullnode_store = &memory_store.fullnode_store;
+
+    let next_signers = create_signers(5);
+
+    let mut checkpoints = Vec::new();
+    for epoch in [0, 0, 0, 1] {
+        let checkpoint = match checkpoints.len() {
+            // The third checkpoint ends the first epoch
+            2 => {
+                let last = fullnode_store.get_last_verified_checkpoint().unwrap();
+                let summary =
+                    CheckpointSummary::new(epoch, last.as_ref().map(|last| last.summary()), vec![])
+                        .with_next_validators(validators(&next_signers));
+
+                sign(summary, &signers)
+            }
+            _ => next_checkpoint(&memory_store, epoch, &signers),
+        };
+
+        assert_eq!(fullnode_store.get_current_epoch().unwrap(), epoch);
+        fullnode_store
+            .insert_verified_checkpoint(&checkpoint)
+            .await
+            .unwrap();
+        checkpoints.push(checkpoint);
+    }
+
+    assert_eq!(fullnode_store.get_current_epoch().unwrap(), 1);
+    assert_eq!(
+        memory_store.get_epoch_validators(1).unwrap(),
+        Some(validators(&next_signers))
+    );
+
+    let first_epoch = fullnode_store.get_epoch_summary(0).unwrap().unwrap();
+    assert_eq!(first_epoch.start_checkpoint, checkpoints[0]);
+    assert_eq!(first_epoch.end_checkpoint, Some(checkpoints[2].clone()));
+
+    let second_epoch = fullnode_store.get_epoch_summary(1).unwrap().unwrap();
+    assert_eq!(second_epoch.start_checkpoint, checkpoints[3]);
+    assert_eq!(second_epoch.end_checkpoint, None);
+    assert_eq!(second_epoch.last_checkpoint(), &checkpoints[3]);
+
+    assert!(fullnode_store.get_epoch_summary(2).unwrap().is_none());
+}
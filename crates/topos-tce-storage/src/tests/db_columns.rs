@@ -1,4 +1,8 @@
+use std::sync::Arc;
+
+use rocksdb::{Direction, IteratorMode};
 use rstest::rstest;
+use rstest_reuse::apply;
 use test_log::test;
 use topos_core::types::stream::CertificateSourceStreamPosition;
 use topos_core::uci::Certificate;
@@ -7,9 +11,11 @@ use topos_test_sdk::constants::SOURCE_SUBNET_ID_1;
 use crate::tests::{PREV_CERTIFICATE_ID, SOURCE_STORAGE_SUBNET_ID};
 use crate::{
     rocks::{map::Map, CertificatesColumn, PendingCertificatesColumn, SourceStreamsColumn},
+    validator::ValidatorStore,
     Position,
 };
 
+use super::support::backends;
 use super::support::columns::{certificates_column, pending_column, source_streams_column};
 
 #[rstest]
@@ -119,3 +125,41 @@ async fn position_can_be_fetch_for_multiple_subnets() {}
 #[test(tokio::test)]
 #[ignore = "not yet implemented"]
 async fn position_can_be_fetch_for_all_subnets() {}
+
+#[apply(backends)]
+#[test(tokio::test)]
+async fn iterator_modes_go_over_the_whole_column(store: Arc<ValidatorStore>) {
+    let pending_column = &store.pending_tables.pending_pool;
+    let certificate =
+        Certificate::new_with_default_fields(PREV_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[]).unwrap();
+
+    // More entries than the in-memory iterator reads at once
+    for pending_id in 0..300 {
+        pending_column.insert(&pending_id, &certificate).unwrap();
+    }
+
+    let keys = |mode: IteratorMode<'_>| -> Vec<u64> {
+        pending_column
+            .iter_with_mode(mode)
+            .unwrap()
+            .map(|(pending_id, _)| pending_id)
+            .collect()
+    };
+
+    assert_eq!(keys(IteratorMode::Start), (0..300).collect::<Vec<_>>());
+    assert_eq!(keys(IteratorMode::End), vec![299]);
+    assert_eq!(
+        keys(IteratorMode::From(
+            &100u64.to_be_bytes(),
+            Direction::Forward
+        )),
+        (100..300).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        keys(IteratorMode::From(
+            &100u64.to_be_bytes(),
+            Direction::Reverse
+        )),
+        (0..=100).rev().collect::<Vec<_>>()
+    );
+}
@@ -1,11 +1,12 @@
-use std::sync::Arc;
+use std::{collections::HashSet, str::FromStr, sync::Arc};
 
 use rstest::rstest;
+use rstest_reuse::apply;
 use test_log::test;
 use topos_core::{
     types::{
         stream::{CertificateSourceStreamPosition, CertificateTargetStreamPosition, Position},
-        CertificateDelivered, ProofOfDelivery,
+        CertificateDelivered, ProofOfDelivery, ValidatorId,
     },
     uci::{Certificate, SubnetId},
 };
@@ -13,19 +14,28 @@ use topos_core::{
 use crate::{
     rocks::map::Map,
     store::{ReadStore, WriteStore},
+    types::{BroadcastMessage, BroadcastState, EquivocationEvidence},
     validator::ValidatorStore,
 };
 
-use self::support::store;
+use self::support::{backends, store};
 
 use topos_test_sdk::certificates::create_certificate_chain;
 use topos_test_sdk::constants::*;
 
+mod check;
+mod checkpoints;
 mod db_columns;
 mod pending_certificates;
 mod position;
 mod rocks;
+mod schema;
+mod secondary;
+mod sequence;
+mod snapshot;
+mod statistics;
 pub(crate) mod support;
+mod timestamps;
 
 const SOURCE_STORAGE_SUBNET_ID: SubnetId = SOURCE_SUBNET_ID_1;
 const TARGET_STORAGE_SUBNET_ID_1: SubnetId = TARGET_SUBNET_ID_1;
@@ -40,7 +50,7 @@ fn can_persist_a_pending_certificate(store: Arc<ValidatorStore>) {
     assert!(store.insert_pending_certificate(&certificate).is_ok());
 }
 
-#[rstest]
+#[apply(backends)]
 #[test(tokio::test)]
 async fn can_persist_a_delivered_certificate(store: Arc<ValidatorStore>) {
     let certificate = Certificate::new_with_default_fields(
@@ -95,7 +105,7 @@ async fn can_persist_a_delivered_certificate(store: Arc<ValidatorStore>) {
     assert_eq!(stream_element.1, certificate.certificate.id);
 }
 
-#[rstest]
+#[apply(backends)]
 #[test(tokio::test)]
 async fn delivered_certificate_are_added_to_target_stream(store: Arc<ValidatorStore>) {
     let certificates_column = store.fullnode_store.perpetual_tables.certificates.clone();
@@ -204,7 +214,7 @@ async fn pending_certificate_are_removed_during_persist_action(store: Arc<Valida
     assert!(matches!(pending_column.get(&pending_id), Ok(None)));
 }
 
-#[rstest]
+#[apply(backends)]
 #[test(tokio::test)]
 async fn fetch_certificates_for_subnets(store: Arc<ValidatorStore>) {
     let other_certificate = Certificate::new_with_default_fields(
@@ -333,7 +343,7 @@ async fn fetch_certificates_for_subnets(store: Arc<ValidatorStore>) {
     assert_eq!(expected_certificates, certificates);
 }
 
-#[rstest]
+#[apply(backends)]
 #[test(tokio::test)]
 async fn pending_certificate_can_be_removed(store: Arc<ValidatorStore>) {
     let pending_column = store.pending_tables.pending_pool.clone();
@@ -368,7 +378,7 @@ async fn pending_certificate_can_be_removed(store: Arc<ValidatorStore>) {
     assert!(pending_column.iter().unwrap().next().is_some());
 }
 
-#[rstest]
+#[apply(backends)]
 #[test(tokio::test)]
 async fn get_source_head_for_subnet(store: Arc<ValidatorStore>) {
     let expected_certificates_for_source_subnet_1: Vec<_> =
@@ -474,7 +484,7 @@ async fn get_source_head_for_subnet(store: Arc<ValidatorStore>) {
     assert_eq!(11, *last_certificate_subnet_2.position); //check position
 }
 
-#[rstest]
+#[apply(backends)]
 #[test(tokio::test)]
 async fn get_pending_certificates(store: Arc<ValidatorStore>) {
     let certificates_for_source_subnet_1 =
@@ -551,3 +561,159 @@ async fn get_pending_certificates(store: Arc<ValidatorStore>) {
     );
     assert_eq!(expected_pending_certificates, pending_certificates);
 }
+
+#[rstest]
+#[test(tokio::test)]
+async fn detect_conflicting_delivered_certificate(store: Arc<ValidatorStore>) {
+    let certificates = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 2);
+
+    store
+        .insert_certificates_delivered(&certificates)
+        .await
+        .unwrap();
+
+    let conflicting = Certificate::new_with_default_fields(
+        certificates[0].certificate.id,
+        SOURCE_SUBNET_ID_1,
+        &[TARGET_SUBNET_ID_2],
+    )
+    .unwrap();
+
+    let next = Certificate::new_with_default_fields(
+        certificates[1].certificate.id,
+        SOURCE_SUBNET_ID_1,
+        &[TARGET_SUBNET_ID_1],
+    )
+    .unwrap();
+
+    assert_eq!(
+        store
+            .get_conflicting_delivered_certificate(&conflicting)
+            .unwrap(),
+        Some(certificates[1].certificate.clone())
+    );
+    assert!(store
+        .get_conflicting_delivered_certificate(&certificates[1].certificate)
+        .unwrap()
+        .is_none());
+    assert!(store
+        .get_conflicting_delivered_certificate(&next)
+        .unwrap()
+        .is_none());
+
+    let evidence = EquivocationEvidence {
+        known: certificates[1].certificate.clone(),
+        conflicting: conflicting.clone(),
+    };
+    store.insert_equivocation_evidence(&evidence).unwrap();
+
+    assert_eq!(
+        store.get_equivocation_evidence(&conflicting.id).unwrap(),
+        Some(evidence.clone())
+    );
+    assert_eq!(store.get_equivocation_evidences().unwrap(), vec![evidence]);
+}
+
+#[rstest]
+#[test(tokio::test)]
+async fn broadcast_state_is_removed_upon_delivery(store: Arc<ValidatorStore>) {
+    let certificates = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 1);
+    let certificate = &certificates[0];
+
+    let mut state = BroadcastState {
+        certificate: certificate.certificate.clone(),
+        epoch: 0,
+        echoes: vec![(ValidatorId::default(), "echo".to_string())],
+        readies: vec![],
+        echo_sent: Some("sent".to_string()),
+        ready_sent: None,
+    };
+    store.insert_broadcast_state(&state).unwrap();
+
+    // The following messages are persisted one by one
+    store
+        .insert_broadcast_message(
+            &certificate.certificate.id,
+            BroadcastMessage::Ready(ValidatorId::default()),
+            &"ready".to_string(),
+        )
+        .unwrap();
+    store
+        .insert_broadcast_message(
+            &certificate.certificate.id,
+            BroadcastMessage::ReadySent,
+            &"ready sent".to_string(),
+        )
+        .unwrap();
+    state.readies = vec![(ValidatorId::default(), "ready".to_string())];
+    state.ready_sent = Some("ready sent".to_string());
+
+    assert_eq!(
+        store
+            .get_broadcast_state(&certificate.certificate.id)
+            .unwrap(),
+        Some(state.clone())
+    );
+    assert_eq!(store.get_broadcast_states().unwrap(), vec![state]);
+
+    store
+        .insert_certificate_delivered(certificate)
+        .await
+        .unwrap();
+
+    assert!(store
+        .get_broadcast_state(&certificate.certificate.id)
+        .unwrap()
+        .is_none());
+    assert!(store.get_broadcast_states().unwrap().is_empty());
+    assert_eq!(
+        store
+            .fullnode_store
+            .epoch_store
+            .load()
+            .tables
+            .broadcast_messages
+            .iter()
+            .unwrap()
+            .count(),
+        0
+    );
+}
+
+#[rstest]
+#[test]
+fn epoch_validators_apply_until_next_registration(store: Arc<ValidatorStore>) {
+    let genesis_validators: HashSet<ValidatorId> =
+        [ValidatorId::from_str("0xb4973cdb10894d1d1547673bd758589034c2bba5").unwrap()].into();
+    let rotated_validators: HashSet<ValidatorId> = [
+        ValidatorId::from_str("0xb4973cdb10894d1d1547673bd758589034c2bba5").unwrap(),
+        ValidatorId::from_str("0x2c1f19c2e6b1ff1d9e5c6a1c3d5a0b0d5c8a8c7e").unwrap(),
+    ]
+    .into();
+
+    assert!(store.get_epoch_validators(0).unwrap().is_none());
+
+    store
+        .insert_epoch_validators(0, &genesis_validators)
+        .unwrap();
+    store
+        .insert_epoch_validators(3, &rotated_validators)
+        .unwrap();
+
+    assert_eq!(
+        store.get_epoch_validators(0).unwrap(),
+        Some(genesis_validators.clone())
+    );
+    assert_eq!(
+        store.get_epoch_validators(2).unwrap(),
+        Some(genesis_validators)
+    );
+    assert_eq!(
+        store.get_epoch_validators(3).unwrap(),
+        Some(rotated_validators.clone())
+    );
+    assert_eq!(
+        store.get_epoch_validators(10).unwrap(),
+        Some(rotated_validators)
+    );
+}
@@ -1,14 +1,25 @@
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 
 use rstest::rstest;
+use rstest_reuse::apply;
 use topos_core::uci::{Certificate, INITIAL_CERTIFICATE_ID};
 use topos_test_sdk::{
     certificates::create_certificate_at_position,
-    constants::{SOURCE_SUBNET_ID_1, TARGET_SUBNET_ID_1},
+    constants::{
+        CERTIFICATE_ID_1, CERTIFICATE_ID_2, SOURCE_SUBNET_ID_1, SOURCE_SUBNET_ID_2,
+        TARGET_SUBNET_ID_1,
+    },
+    storage::create_folder,
 };
 
-use super::support::store;
-use crate::{store::WriteStore, validator::ValidatorStore};
+use super::support::{backends, fullnode_store_at, store};
+use crate::{
+    errors::{InternalStorageError, StorageError},
+    rocks::map::Map,
+    store::WriteStore,
+    types::{EvictionReason, PoolRetention},
+    validator::ValidatorStore,
+};
 
 #[rstest]
 fn adding_genesis_pending_certificate(store: Arc<ValidatorStore>) {
@@ -86,3 +97,420 @@ async fn adding_pending_certificate_already_delivered(store: Arc<ValidatorStore>
         .insert_pending_certificate(&initial_certificate_delivered.certificate)
         .is_err());
 }
+
+#[rstest]
+#[tokio::test]
+async fn delivered_certificate_is_removed_from_the_pools(store: Arc<ValidatorStore>) {
+    let certificate_delivered = create_certificate_at_position::default();
+    let certificate_id = certificate_delivered.certificate.id;
+
+    store
+        .insert_pending_certificate(&certificate_delivered.certificate)
+        .unwrap()
+        .unwrap();
+
+    store
+        .insert_certificate_delivered(&certificate_delivered)
+        .await
+        .unwrap();
+
+    assert!(store.get_pending_id(&certificate_id).unwrap().is_none());
+    assert_eq!(store.count_pending_certificates().unwrap(), 0);
+    assert!(store
+        .pending_tables
+        .pool_entries
+        .get(&certificate_id)
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn pending_certificates_survive_reopening_the_store() {
+    let path = create_folder::default();
+    let fullnode_store = fullnode_store_at(path.clone());
+    let first = Certificate::new_with_default_fields(
+        INITIAL_CERTIFICATE_ID,
+        SOURCE_SUBNET_ID_1,
+        &[TARGET_SUBNET_ID_1],
+    )
+    .unwrap();
+    let second = Certificate::new_with_default_fields(
+        INITIAL_CERTIFICATE_ID,
+        SOURCE_SUBNET_ID_2,
+        &[TARGET_SUBNET_ID_1],
+    )
+    .unwrap();
+
+    let first_id = {
+        let store = ValidatorStore::open(path.clone(), fullnode_store.clone()).unwrap();
+
+        store.insert_pending_certificate(&first).unwrap().unwrap()
+    };
+
+    let store = ValidatorStore::open(path, fullnode_store).unwrap();
+    let second_id = store.insert_pending_certificate(&second).unwrap().unwrap();
+
+    assert!(second_id > first_id);
+    assert_eq!(
+        store.get_pending_certificate(&first_id).unwrap(),
+        Some(first)
+    );
+    assert_eq!(
+        store.get_pending_certificate(&second_id).unwrap(),
+        Some(second)
+    );
+}
+
+#[apply(backends)]
+fn pending_certificates_are_chained_with_the_ones_awaiting_them(store: Arc<ValidatorStore>) {
+    let pending = Certificate::new_with_default_fields(
+        INITIAL_CERTIFICATE_ID,
+        SOURCE_SUBNET_ID_1,
+        &[TARGET_SUBNET_ID_1],
+    )
+    .unwrap();
+    let awaiting_precedence =
+        Certificate::new_with_default_fields(pending.id, SOURCE_SUBNET_ID_1, &[]).unwrap();
+
+    store.insert_pending_certificate(&pending).unwrap().unwrap();
+    assert!(store
+        .insert_pending_certificate(&awaiting_precedence)
+        .unwrap()
+        .is_none());
+
+    let chains = store.get_pending_certificate_chains().unwrap();
+    assert_eq!(chains.len(), 1);
+    assert_eq!(
+        chains[&SOURCE_SUBNET_ID_1],
+        vec![pending.clone(), awaiting_precedence.clone()]
+    );
+
+    let (count, last) = store
+        .get_pending_certificates_for_subnets(&[SOURCE_SUBNET_ID_1])
+        .unwrap()
+        .remove(&SOURCE_SUBNET_ID_1)
+        .unwrap();
+    assert_eq!(count, 2);
+    assert_eq!(last, Some(awaiting_precedence.clone()));
+
+    assert_eq!(
+        store
+            .get_pool_certificates(&[awaiting_precedence.id, CERTIFICATE_ID_2, pending.id])
+            .unwrap(),
+        vec![Some(awaiting_precedence), None, Some(pending)]
+    );
+}
+
+#[apply(backends)]
+fn pending_certificate_ids_are_bounded(store: Arc<ValidatorStore>) {
+    let pending = Certificate::new_with_default_fields(
+        INITIAL_CERTIFICATE_ID,
+        SOURCE_SUBNET_ID_1,
+        &[TARGET_SUBNET_ID_1],
+    )
+    .unwrap();
+    let awaiting_precedence =
+        Certificate::new_with_default_fields(pending.id, SOURCE_SUBNET_ID_1, &[]).unwrap();
+
+    store.insert_pending_certificate(&pending).unwrap();
+    store
+        .insert_pending_certificate(&awaiting_precedence)
+        .unwrap();
+
+    assert_eq!(
+        store.get_pending_certificate_ids(10).unwrap(),
+        vec![pending.id, awaiting_precedence.id]
+    );
+    assert_eq!(
+        store.get_pending_certificate_ids(1).unwrap(),
+        vec![pending.id]
+    );
+    assert!(store.get_pending_certificate_ids(0).unwrap().is_empty());
+}
+
+#[apply(backends)]
+fn expired_certificates_are_evicted_from_both_pools(store: Arc<ValidatorStore>) {
+    let pending = Certificate::new_with_default_fields(
+        INITIAL_CERTIFICATE_ID,
+        SOURCE_SUBNET_ID_1,
+        &[TARGET_SUBNET_ID_1],
+    )
+    .unwrap();
+    let awaiting_precedence =
+        Certificate::new_with_default_fields(CERTIFICATE_ID_1, SOURCE_SUBNET_ID_1, &[]).unwrap();
+
+    store.insert_pending_certificate(&pending).unwrap().unwrap();
+    assert!(store
+        .insert_pending_certificate(&awaiting_precedence)
+        .unwrap()
+        .is_none());
+
+    assert!(store
+        .evict_expired_pending_certificates()
+        .unwrap()
+        .is_empty());
+
+    store.set_pool_retention(PoolRetention {
+        max_age: Duration::ZERO,
+        ..store.pool_retention()
+    });
+
+    let evicted = store.evict_expired_pending_certificates().unwrap();
+    assert_eq!(evicted.len(), 2);
+    assert!(evicted
+        .iter()
+        .all(|record| record.reason == EvictionReason::Expired));
+
+    assert_eq!(store.count_pending_certificates().unwrap(), 0);
+    assert!(store.get_pending_id(&pending.id).unwrap().is_none());
+    assert!(store
+        .pending_tables
+        .precedence_pool
+        .get(&CERTIFICATE_ID_1)
+        .unwrap()
+        .is_none());
+    assert_eq!(store.pending_tables.pool_entries.iter().unwrap().count(), 0);
+    assert_eq!(
+        store
+            .pending_tables
+            .pool_subnet_entries
+            .iter()
+            .unwrap()
+            .count(),
+        0
+    );
+    assert_eq!(
+        store.pending_tables.pool_insertions.iter().unwrap().count(),
+        0
+    );
+
+    let record = store
+        .get_evicted_certificate(&awaiting_precedence.id)
+        .unwrap()
+        .unwrap();
+    assert_eq!(record.subnet_id, SOURCE_SUBNET_ID_1);
+    assert_eq!(record.reason, EvictionReason::Expired);
+}
+
+#[apply(backends)]
+fn subnet_limit_evicts_the_oldest_certificate_awaiting_precedence(store: Arc<ValidatorStore>) {
+    store.set_pool_retention(PoolRetention {
+        max_certificates_per_subnet: 2,
+        ..store.pool_retention()
+    });
+
+    let pending = Certificate::new_with_default_fields(
+        INITIAL_CERTIFICATE_ID,
+        SOURCE_SUBNET_ID_1,
+        &[TARGET_SUBNET_ID_1],
+    )
+    .unwrap();
+    let oldest =
+        Certificate::new_with_default_fields(CERTIFICATE_ID_1, SOURCE_SUBNET_ID_1, &[]).unwrap();
+    let newest =
+        Certificate::new_with_default_fields(CERTIFICATE_ID_2, SOURCE_SUBNET_ID_1, &[]).unwrap();
+
+    store.insert_pending_certificate(&pending).unwrap().unwrap();
+    store.insert_pending_certificate(&oldest).unwrap();
+    store.insert_pending_certificate(&newest).unwrap();
+
+    assert!(store.get_pending_id(&pending.id).unwrap().is_some());
+    assert!(store
+        .pending_tables
+        .precedence_pool
+        .get(&CERTIFICATE_ID_1)
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        store
+            .pending_tables
+            .precedence_pool
+            .get(&CERTIFICATE_ID_2)
+            .unwrap()
+            .unwrap(),
+        newest
+    );
+
+    let record = store.get_evicted_certificate(&oldest.id).unwrap().unwrap();
+    assert_eq!(record.reason, EvictionReason::SubnetLimitReached);
+    assert_eq!(store.get_evicted_certificates().unwrap().len(), 1);
+}
+
+#[apply(backends)]
+fn subnet_limit_rejects_certificates_when_nothing_can_be_evicted(store: Arc<ValidatorStore>) {
+    store.set_pool_retention(PoolRetention {
+        max_certificates_per_subnet: 1,
+        ..store.pool_retention()
+    });
+
+    let pending = Certificate::new_with_default_fields(
+        INITIAL_CERTIFICATE_ID,
+        SOURCE_SUBNET_ID_1,
+        &[TARGET_SUBNET_ID_1],
+    )
+    .unwrap();
+    let awaiting_precedence =
+        Certificate::new_with_default_fields(CERTIFICATE_ID_1, SOURCE_SUBNET_ID_1, &[]).unwrap();
+
+    store.insert_pending_certificate(&pending).unwrap().unwrap();
+
+    assert!(matches!(
+        store.insert_pending_certificate(&awaiting_precedence),
+        Err(StorageError::InternalStorage(
+            InternalStorageError::PendingPoolLimitReached(subnet_id)
+        )) if subnet_id == SOURCE_SUBNET_ID_1
+    ));
+    assert!(store
+        .pending_tables
+        .precedence_pool
+        .get(&CERTIFICATE_ID_1)
+        .unwrap()
+        .is_none());
+    assert!(store.get_pending_id(&pending.id).unwrap().is_some());
+}
+
+#[apply(backends)]
+fn subnet_limit_is_set_per_subnet_and_applies_to_batches(store: Arc<ValidatorStore>) {
+    store.set_pool_retention(PoolRetention {
+        max_certificates_per_subnet: 1,
+        subnet_limits: [(SOURCE_SUBNET_ID_2, 2)].into_iter().collect(),
+        ..store.pool_retention()
+    });
+    let mut evictions = store.subscribe_evictions();
+
+    let first_subnet = [
+        Certificate::new_with_default_fields(
+            INITIAL_CERTIFICATE_ID,
+            SOURCE_SUBNET_ID_1,
+            &[TARGET_SUBNET_ID_1],
+        )
+        .unwrap(),
+        Certificate::new_with_default_fields(CERTIFICATE_ID_1, SOURCE_SUBNET_ID_1, &[]).unwrap(),
+    ];
+
+    assert!(matches!(
+        store.insert_pending_certificates(&first_subnet),
+        Err(StorageError::InternalStorage(
+            InternalStorageError::PendingPoolLimitReached(subnet_id)
+        )) if subnet_id == SOURCE_SUBNET_ID_1
+    ));
+    assert_eq!(store.count_pending_certificates().unwrap(), 0);
+
+    let awaiting_precedence =
+        Certificate::new_with_default_fields(CERTIFICATE_ID_1, SOURCE_SUBNET_ID_2, &[]).unwrap();
+    store
+        .insert_pending_certificate(&awaiting_precedence)
+        .unwrap();
+
+    let second_subnet = [
+        Certificate::new_with_default_fields(INITIAL_CERTIFICATE_ID, SOURCE_SUBNET_ID_2, &[])
+            .unwrap(),
+        Certificate::new_with_default_fields(CERTIFICATE_ID_2, SOURCE_SUBNET_ID_2, &[]).unwrap(),
+    ];
+
+    assert_eq!(
+        store
+            .insert_pending_certificates(&second_subnet)
+            .unwrap()
+            .len(),
+        2
+    );
+
+    let record = evictions.try_recv().unwrap();
+    assert_eq!(record.certificate_id, awaiting_precedence.id);
+    assert_eq!(record.reason, EvictionReason::SubnetLimitReached);
+    assert!(evictions.try_recv().is_err());
+
+    assert_eq!(
+        store
+            .pending_tables
+            .pool_subnet_entries
+            .prefix_iter(&SOURCE_SUBNET_ID_2)
+            .unwrap()
+            .count(),
+        2
+    );
+}
+
+#[apply(backends)]
+fn conflicting_certificates_are_rejected_from_both_pools(store: Arc<ValidatorStore>) {
+    let pending = Certificate::new_with_default_fields(
+        INITIAL_CERTIFICATE_ID,
+        SOURCE_SUBNET_ID_1,
+        &[TARGET_SUBNET_ID_1],
+    )
+    .unwrap();
+    let conflicting_pending =
+        Certificate::new_with_default_fields(INITIAL_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[])
+            .unwrap();
+    let awaiting_precedence =
+        Certificate::new_with_default_fields(CERTIFICATE_ID_1, SOURCE_SUBNET_ID_1, &[]).unwrap();
+    let conflicting_awaiting = Certificate::new_with_default_fields(
+        CERTIFICATE_ID_1,
+        SOURCE_SUBNET_ID_1,
+        &[TARGET_SUBNET_ID_1],
+    )
+    .unwrap();
+
+    store.insert_pending_certificate(&pending).unwrap().unwrap();
+    store
+        .insert_pending_certificate(&awaiting_precedence)
+        .unwrap();
+
+    assert!(matches!(
+        store.insert_pending_certificate(&conflicting_pending),
+        Err(StorageError::InternalStorage(
+            InternalStorageError::ConflictingPendingCertificate(known_id)
+        )) if known_id == pending.id
+    ));
+    assert!(matches!(
+        store.insert_pending_certificates(&[conflicting_awaiting.clone()]),
+        Err(StorageError::InternalStorage(
+            InternalStorageError::ConflictingPendingCertificate(known_id)
+        )) if known_id == awaiting_precedence.id
+    ));
+
+    // The genesis certificates of the other subnets follow the same initial certificate id
+    let other_subnet_genesis =
+        Certificate::new_with_default_fields(INITIAL_CERTIFICATE_ID, SOURCE_SUBNET_ID_2, &[])
+            .unwrap();
+    assert!(store
+        .get_conflicting_pool_certificate(&other_subnet_genesis)
+        .unwrap()
+        .is_none());
+    assert!(store
+        .insert_pending_certificate(&other_subnet_genesis)
+        .unwrap()
+        .is_some());
+
+    assert_eq!(
+        store
+            .get_conflicting_pool_certificate(&conflicting_awaiting)
+            .unwrap(),
+        Some(awaiting_precedence.clone())
+    );
+    assert!(store
+        .get_conflicting_pool_certificate(&awaiting_precedence)
+        .unwrap()
+        .is_none());
+    assert_eq!(
+        store
+            .pending_tables
+            .precedence_pool
+            .get(&CERTIFICATE_ID_1)
+            .unwrap(),
+        Some(awaiting_precedence)
+    );
+
+    // Once the known certificate leaves the pools, the conflicting one is accepted
+    store.set_pool_retention(PoolRetention {
+        max_age: Duration::ZERO,
+        ..store.pool_retention()
+    });
+    store.evict_expired_pending_certificates().unwrap();
+
+    assert!(store
+        .insert_pending_certificate(&conflicting_pending)
+        .unwrap()
+        .is_some());
+}
@@ -1,4 +1,19 @@
+use std::sync::Arc;
+
+use rstest::rstest;
+use rstest_reuse::apply;
 use test_log::test;
+use topos_test_sdk::{
+    certificates::create_certificate_chain,
+    constants::{SOURCE_SUBNET_ID_1, SOURCE_SUBNET_ID_2, TARGET_SUBNET_ID_1},
+};
+
+use super::support::backends;
+use crate::{
+    errors::{InternalStorageError, StorageError},
+    store::{ReadStore, WriteStore},
+    validator::ValidatorStore,
+};
 
 #[test(tokio::test)]
 #[ignore = "not yet implemented"]
@@ -7,3 +22,108 @@ async fn position_can_be_fetch_for_multiple_subnets() {}
 #[test(tokio::test)]
 #[ignore = "not yet implemented"]
 async fn position_can_be_fetch_for_all_subnets() {}
+
+#[apply(backends)]
+#[test(tokio::test)]
+async fn contiguous_source_head_stops_at_the_first_gap(store: Arc<ValidatorStore>) {
+    let certificates = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 4);
+
+    assert!(store
+        .get_contiguous_source_head(&SOURCE_SUBNET_ID_1)
+        .unwrap()
+        .is_none());
+
+    // Synchronized from a trusted head without the prior history
+    store
+        .insert_certificates_delivered(&certificates[2..])
+        .await
+        .unwrap();
+    assert!(store
+        .get_contiguous_source_head(&SOURCE_SUBNET_ID_1)
+        .unwrap()
+        .is_none());
+
+    store
+        .insert_certificate_delivered(&certificates[0])
+        .await
+        .unwrap();
+    let contiguous_head = store
+        .get_contiguous_source_head(&SOURCE_SUBNET_ID_1)
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        contiguous_head.certificate_id,
+        certificates[0].certificate.id
+    );
+    assert_eq!(*contiguous_head.position, 0);
+    assert_eq!(
+        *store
+            .get_source_head(&SOURCE_SUBNET_ID_1)
+            .unwrap()
+            .unwrap()
+            .position,
+        3
+    );
+
+    store
+        .insert_certificate_delivered(&certificates[1])
+        .await
+        .unwrap();
+    assert_eq!(
+        store
+            .get_contiguous_source_head(&SOURCE_SUBNET_ID_1)
+            .unwrap()
+            .unwrap()
+            .certificate_id,
+        certificates[3].certificate.id
+    );
+
+    assert!(store
+        .get_contiguous_source_head(&SOURCE_SUBNET_ID_2)
+        .unwrap()
+        .is_none());
+}
+
+#[apply(backends)]
+#[test(tokio::test)]
+async fn synchronized_certificates_follow_their_previous_certificate(store: Arc<ValidatorStore>) {
+    let certificates = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 3);
+    store
+        .insert_certificate_delivered(&certificates[0])
+        .await
+        .unwrap();
+
+    // The position of the Proof of Delivery isn't signed by the validators
+    let mut forged = certificates[1].proof_of_delivery.clone();
+    forged.delivery_position.position = 2u64.into();
+    store.insert_unverified_proofs(vec![forged]).unwrap();
+
+    assert!(matches!(
+        store
+            .synchronize_certificate(certificates[1].certificate.clone())
+            .await,
+        Err(StorageError::InternalStorage(
+            InternalStorageError::InvalidDeliveryPosition(certificate_id)
+        )) if certificate_id == certificates[1].certificate.id
+    ));
+    assert!(store
+        .get_unverified_proof(&certificates[1].certificate.id)
+        .unwrap()
+        .is_none());
+
+    store
+        .insert_unverified_proofs(vec![certificates[1].proof_of_delivery.clone()])
+        .unwrap();
+    store
+        .synchronize_certificate(certificates[1].certificate.clone())
+        .await
+        .unwrap();
+    assert_eq!(
+        *store
+            .get_source_head(&SOURCE_SUBNET_ID_1)
+            .unwrap()
+            .unwrap()
+            .position,
+        1
+    );
+}
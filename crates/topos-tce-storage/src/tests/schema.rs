@@ -0,0 +1,276 @@
+use topos_core::uci::{Certificate, INITIAL_CERTIFICATE_ID};
+use topos_test_sdk::{
+    constants::{CERTIFICATE_ID_1, SOURCE_SUBNET_ID_1},
+    storage::create_folder,
+};
+
+use crate::{
+    constant::cfs,
+    epoch::ValidatorPerEpochTables,
+    errors::InternalStorageError,
+    rocks::{
+        db_column::{Backend, DBBatch, DBColumn},
+        map::Map,
+        memory::MemoryDB,
+        schema::{Migrate, Migration, Schema, SchemaVersion, INITIAL_SCHEMA_VERSION},
+    },
+    types::{BroadcastState, PoolLocation},
+    validator::{ValidatorPendingTables, ValidatorPerpetualTables},
+};
+
+const TEST_SCHEMA: Schema = Schema {
+    database: "test",
+    migrations: &[
+        Migration {
+            version: 2,
+            description: "first migration",
+            migrate: Migrate::Database(to_version_2),
+        },
+        Migration {
+            version: 3,
+            description: "second migration",
+            migrate: Migrate::Database(to_version_3),
+        },
+    ],
+};
+
+fn to_version_2(db: &Backend, batch: DBBatch) -> Result<DBBatch, InternalStorageError> {
+    applied(db, batch, 2)
+}
+
+fn to_version_3(db: &Backend, batch: DBBatch) -> Result<DBBatch, InternalStorageError> {
+    applied(db, batch, 3)
+}
+
+/// Marks the migration as applied in the test column
+fn applied(
+    db: &Backend,
+    batch: DBBatch,
+    version: SchemaVersion,
+) -> Result<DBBatch, InternalStorageError> {
+    batch.insert_batch(&migrations_column(db), [(version, true)])
+}
+
+fn migrations_column(db: &Backend) -> DBColumn<SchemaVersion, bool> {
+    DBColumn::from_backend(db, cfs::VALIDATORS)
+}
+
+fn schema_column(db: &Backend) -> DBColumn<u8, SchemaVersion> {
+    DBColumn::from_backend(db, cfs::SCHEMA)
+}
+
+fn memory_backend() -> Backend {
+    MemoryDB::new(&[cfs::SCHEMA, cfs::VALIDATORS]).into()
+}
+
+#[test]
+fn unversioned_database_is_stamped_with_the_initial_version() {
+    let db = memory_backend();
+    let schema = Schema {
+        database: "test",
+        migrations: &[],
+    };
+
+    schema.apply(&db).unwrap();
+
+    assert_eq!(
+        schema_column(&db).get(&0).unwrap(),
+        Some(INITIAL_SCHEMA_VERSION)
+    );
+}
+
+#[test]
+fn migrations_are_applied_in_order_only_once() {
+    let db = memory_backend();
+
+    TEST_SCHEMA.apply(&db).unwrap();
+
+    assert_eq!(schema_column(&db).get(&0).unwrap(), Some(3));
+    assert_eq!(migrations_column(&db).get(&2).unwrap(), Some(true));
+    assert_eq!(migrations_column(&db).get(&3).unwrap(), Some(true));
+
+    migrations_column(&db).delete(&2).unwrap();
+    migrations_column(&db).delete(&3).unwrap();
+
+    TEST_SCHEMA.apply(&db).unwrap();
+
+    assert_eq!(migrations_column(&db).get(&2).unwrap(), None);
+    assert_eq!(migrations_column(&db).get(&3).unwrap(), None);
+}
+
+#[test]
+fn only_missing_migrations_are_applied() {
+    let db = memory_backend();
+    schema_column(&db).insert(&0, &2).unwrap();
+
+    TEST_SCHEMA.apply(&db).unwrap();
+
+    assert_eq!(schema_column(&db).get(&0).unwrap(), Some(3));
+    assert_eq!(migrations_column(&db).get(&2).unwrap(), None);
+    assert_eq!(migrations_column(&db).get(&3).unwrap(), Some(true));
+}
+
+#[test]
+fn newer_schema_version_is_refused() {
+    let db = memory_backend();
+    schema_column(&db).insert(&0, &4).unwrap();
+
+    assert!(matches!(
+        TEST_SCHEMA.apply(&db),
+        Err(InternalStorageError::UnsupportedSchemaVersion {
+            database: "test",
+            found: 4,
+            supported: 3,
+        })
+    ));
+    assert!(migrations_column(&db).get(&2).unwrap().is_none());
+}
+
+#[test]
+fn schema_is_only_checked_for_secondary_instances() {
+    let db = memory_backend();
+    schema_column(&db).insert(&0, &2).unwrap();
+
+    assert!(matches!(
+        TEST_SCHEMA.check(&db),
+        Err(InternalStorageError::SchemaMigrationRequired {
+            database: "test",
+            found: 2,
+            supported: 3,
+        })
+    ));
+    assert_eq!(schema_column(&db).get(&0).unwrap(), Some(2));
+    assert!(migrations_column(&db).get(&3).unwrap().is_none());
+
+    TEST_SCHEMA.apply(&db).unwrap();
+    assert!(TEST_SCHEMA.check(&db).is_ok());
+}
+
+#[test]
+#[should_panic(expected = "schema version")]
+fn opening_a_database_of_a_newer_node_fails() {
+    let path = create_folder::default();
+
+    {
+        let tables = ValidatorPerpetualTables::open(path.clone());
+        schema_column(&tables.certificates.backend)
+            .insert(&0, &(INITIAL_SCHEMA_VERSION + 1))
+            .unwrap();
+    }
+
+    ValidatorPerpetualTables::open(path);
+}
+
+#[test]
+fn pool_certificates_are_indexed_by_the_pending_migration() {
+    let path = create_folder::default();
+    let pending =
+        Certificate::new_with_default_fields(INITIAL_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[])
+            .unwrap();
+    let awaiting_precedence =
+        Certificate::new_with_default_fields(CERTIFICATE_ID_1, SOURCE_SUBNET_ID_1, &[]).unwrap();
+
+    {
+        // Pools written before they were indexed
+        let tables = ValidatorPendingTables::open(path.clone());
+        tables.pending_pool.insert(&0, &pending).unwrap();
+        tables
+            .precedence_pool
+            .insert(&CERTIFICATE_ID_1, &awaiting_precedence)
+            .unwrap();
+        schema_column(&tables.pending_pool.backend)
+            .insert(&0, &INITIAL_SCHEMA_VERSION)
+            .unwrap();
+    }
+
+    let tables = ValidatorPendingTables::open(path);
+
+    assert_eq!(
+        tables
+            .pool_successors
+            .get(&(SOURCE_SUBNET_ID_1, INITIAL_CERTIFICATE_ID))
+            .unwrap(),
+        Some(pending.id)
+    );
+    assert_eq!(
+        tables
+            .pool_successors
+            .get(&(SOURCE_SUBNET_ID_1, CERTIFICATE_ID_1))
+            .unwrap(),
+        Some(awaiting_precedence.id)
+    );
+    assert_eq!(
+        tables
+            .pool_entries
+            .get(&pending.id)
+            .unwrap()
+            .map(|entry| (entry.subnet_id, entry.location)),
+        Some((SOURCE_SUBNET_ID_1, PoolLocation::Pending(0)))
+    );
+    assert_eq!(
+        tables
+            .pool_entries
+            .get(&awaiting_precedence.id)
+            .unwrap()
+            .map(|entry| (entry.subnet_id, entry.location)),
+        Some((
+            SOURCE_SUBNET_ID_1,
+            PoolLocation::Precedence(CERTIFICATE_ID_1)
+        ))
+    );
+
+    let subnet_entries: Vec<_> = tables
+        .pool_subnet_entries
+        .prefix_iter(&SOURCE_SUBNET_ID_1)
+        .unwrap()
+        .map(|((_, _, certificate_id), _)| certificate_id)
+        .collect();
+    let insertions: Vec<_> = tables
+        .pool_insertions
+        .iter()
+        .unwrap()
+        .map(|((_, certificate_id), _)| certificate_id)
+        .collect();
+    assert_eq!(subnet_entries.len(), 2);
+    assert_eq!(insertions.len(), 2);
+    assert!([pending.id, awaiting_precedence.id]
+        .iter()
+        .all(|id| subnet_entries.contains(id) && insertions.contains(id)));
+}
+
+#[test]
+fn broadcasts_signed_without_their_message_kind_are_discarded() {
+    let path = create_folder::default();
+    let certificate =
+        Certificate::new_with_default_fields(INITIAL_CERTIFICATE_ID, SOURCE_SUBNET_ID_1, &[])
+            .unwrap();
+    let state = BroadcastState {
+        certificate: certificate.clone(),
+        epoch: 0,
+        echoes: vec![],
+        readies: vec![],
+        echo_sent: Some("echo sent".to_string()),
+        ready_sent: None,
+    };
+
+    {
+        // Progress written before the messages were signed along with their kind
+        let tables = ValidatorPerEpochTables::open(0, path.clone());
+        tables
+            .broadcast_headers
+            .insert(&certificate.id, &state.header())
+            .unwrap();
+        tables
+            .broadcast_messages
+            .multi_insert(state.messages())
+            .unwrap();
+        schema_column(&tables.broadcast_headers.backend)
+            .insert(&0, &INITIAL_SCHEMA_VERSION)
+            .unwrap();
+    }
+
+    let tables = ValidatorPerEpochTables::open(0, path);
+
+    assert!(tables.broadcast_headers.iter().unwrap().next().is_none());
+    assert!(tables.broadcast_messages.iter().unwrap().next().is_none());
+}
@@ -0,0 +1,148 @@
+use std::{path::Path, sync::Arc};
+
+use test_log::test;
+use topos_test_sdk::{
+    certificates::create_certificate_chain,
+    constants::{SOURCE_SUBNET_ID_1, TARGET_SUBNET_ID_1},
+    storage::create_folder,
+};
+
+use crate::{
+    epoch::{EpochValidatorsStore, ValidatorPerEpochStore},
+    fullnode::FullNodeStore,
+    index::IndexTables,
+    rocks::map::Map,
+    store::{ReadStore, WriteStore},
+    types::{BroadcastState, EpochId},
+    validator::{ValidatorPerpetualTables, ValidatorStore},
+};
+
+fn open_primary(path: &Path) -> Arc<ValidatorStore> {
+    open_primary_at(path, 0)
+}
+
+/// Opens the primary store as a node started in the given epoch
+fn open_primary_at(path: &Path, epoch: EpochId) -> Arc<ValidatorStore> {
+    let perpetual_tables = Arc::new(ValidatorPerpetualTables::open(path.to_path_buf()));
+    let index_tables = Arc::new(IndexTables::open(path.to_path_buf(), &perpetual_tables));
+
+    let fullnode_store = FullNodeStore::open(
+        ValidatorPerEpochStore::new(epoch, path.to_path_buf()).unwrap(),
+        EpochValidatorsStore::new(path.to_path_buf()).unwrap(),
+        perpetual_tables,
+        index_tables,
+    )
+    .unwrap();
+
+    ValidatorStore::open(path.to_path_buf(), fullnode_store).unwrap()
+}
+
+fn open_secondary(path: &Path, secondary_path: &Path) -> Arc<ValidatorStore> {
+    let (path, secondary_path) = (path.to_path_buf(), secondary_path.to_path_buf());
+
+    let fullnode_store = FullNodeStore::open_secondary(
+        ValidatorPerEpochStore::new_secondary(0, path.clone(), secondary_path.clone()).unwrap(),
+        EpochValidatorsStore::new_secondary(path.clone(), secondary_path.clone()).unwrap(),
+        Arc::new(ValidatorPerpetualTables::open_secondary(
+            path.clone(),
+            secondary_path.clone(),
+        )),
+        Arc::new(IndexTables::open_secondary(
+            path.clone(),
+            secondary_path.clone(),
+        )),
+    )
+    .unwrap();
+
+    ValidatorStore::open_secondary(path, secondary_path, fullnode_store).unwrap()
+}
+
+#[test(tokio::test)]
+async fn secondary_store_follows_the_primary_once_caught_up() {
+    let path = create_folder::default();
+    let certificates = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 3);
+
+    let primary = open_primary(&path);
+    primary
+        .insert_certificates_delivered(&certificates[..2])
+        .await
+        .unwrap();
+
+    let secondary = open_secondary(&path, &create_folder::default());
+    assert!(secondary
+        .get_certificate(&certificates[1].certificate.id)
+        .unwrap()
+        .is_some());
+
+    primary
+        .insert_certificate_delivered(&certificates[2])
+        .await
+        .unwrap();
+    assert!(secondary
+        .get_certificate(&certificates[2].certificate.id)
+        .unwrap()
+        .is_none());
+
+    secondary.catch_up().unwrap();
+
+    assert!(secondary
+        .get_certificate(&certificates[2].certificate.id)
+        .unwrap()
+        .is_some());
+    assert_eq!(
+        secondary
+            .get_certificate_sequence_number(&certificates[2].certificate.id)
+            .unwrap(),
+        Some(2)
+    );
+}
+
+#[test(tokio::test)]
+async fn secondary_store_is_read_only() {
+    let path = create_folder::default();
+    let certificates = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 1);
+
+    let _primary = open_primary(&path);
+    let secondary = open_secondary(&path, &create_folder::default());
+
+    assert!(secondary
+        .insert_certificates_delivered(&certificates)
+        .await
+        .is_err());
+    assert!(secondary
+        .insert_pending_certificate(&certificates[0].certificate)
+        .is_err());
+}
+
+#[test(tokio::test)]
+async fn broadcasts_are_moved_to_the_epoch_store_of_a_restart() {
+    let path = create_folder::default();
+    let certificates = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 1);
+    let state = BroadcastState {
+        certificate: certificates[0].certificate.clone(),
+        epoch: 0,
+        echoes: vec![],
+        readies: vec![],
+        echo_sent: Some("sent".to_string()),
+        ready_sent: None,
+    };
+
+    open_primary_at(&path, 0)
+        .insert_broadcast_state(&state)
+        .unwrap();
+
+    // Restarted during the epoch 2, after running during the epoch 0 only
+    let primary = open_primary_at(&path, 2);
+    assert_eq!(primary.get_broadcast_states().unwrap(), vec![state]);
+    drop(primary);
+
+    let previous = ValidatorPerEpochStore::new(0, path.to_path_buf()).unwrap();
+    assert!(previous
+        .load()
+        .tables
+        .broadcast_headers
+        .iter()
+        .unwrap()
+        .next()
+        .is_none());
+}
@@ -0,0 +1,202 @@
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+use rstest::rstest;
+use rstest_reuse::apply;
+use test_log::test;
+use topos_core::{types::CertificateDelivered, uci::CertificateId};
+use topos_test_sdk::{
+    certificates::create_certificate_chain,
+    constants::{SOURCE_SUBNET_ID_1, SOURCE_SUBNET_ID_2, TARGET_SUBNET_ID_1},
+};
+
+use super::support::{backends, memory_store};
+use crate::{
+    constant::cfs,
+    fullnode::FullNodeStore,
+    rocks::{
+        db_column::DBColumn,
+        map::Map,
+        schema::{self, SchemaVersion, INITIAL_SCHEMA_VERSION},
+    },
+    store::{ReadStore, WriteStore},
+    validator::ValidatorStore,
+};
+
+/// Delivers the certificates of two subnets one after the other
+async fn deliver_interleaved(store: &ValidatorStore) -> Vec<CertificateId> {
+    let first = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 3);
+    let second = create_certificate_chain(SOURCE_SUBNET_ID_2, &[TARGET_SUBNET_ID_1], 3);
+
+    let mut delivered = Vec::new();
+    for (a, b) in first.iter().zip(second.iter()) {
+        for certificate in [a, b] {
+            store
+                .insert_certificate_delivered(certificate)
+                .await
+                .unwrap();
+            delivered.push(certificate.certificate.id);
+        }
+    }
+
+    delivered
+}
+
+fn reopen(store: &FullNodeStore) -> Arc<FullNodeStore> {
+    FullNodeStore::open(
+        ArcSwap::new(store.epoch_store.load_full()),
+        store.validators_store.clone(),
+        store.perpetual_tables.clone(),
+        store.index_tables.clone(),
+    )
+    .unwrap()
+}
+
+fn ids(certificates: Vec<(CertificateDelivered, u64)>) -> Vec<(CertificateId, u64)> {
+    certificates
+        .into_iter()
+        .map(|(certificate, sequence_number)| (certificate.certificate.id, sequence_number))
+        .collect()
+}
+
+#[apply(backends)]
+#[test(tokio::test)]
+async fn certificates_are_sequenced_in_delivery_order(store: Arc<ValidatorStore>) {
+    let delivered = deliver_interleaved(&store).await;
+
+    let expected: Vec<_> = delivered.iter().copied().zip(0..).collect();
+
+    assert_eq!(
+        ids(store.get_certificates_from_sequence_number(0, 100).unwrap()),
+        expected
+    );
+
+    for (certificate_id, sequence_number) in &expected {
+        assert_eq!(
+            store
+                .get_certificate_sequence_number(certificate_id)
+                .unwrap(),
+            Some(*sequence_number)
+        );
+    }
+}
+
+#[apply(backends)]
+#[test(tokio::test)]
+async fn can_query_certificates_since_a_sequence_number(store: Arc<ValidatorStore>) {
+    let delivered = deliver_interleaved(&store).await;
+
+    assert_eq!(
+        ids(store.get_certificates_from_sequence_number(2, 3).unwrap()),
+        vec![(delivered[2], 2), (delivered[3], 3), (delivered[4], 4)]
+    );
+
+    assert_eq!(
+        ids(store.get_certificates_from_sequence_number(5, 10).unwrap()),
+        vec![(delivered[5], 5)]
+    );
+
+    assert!(store
+        .get_certificates_from_sequence_number(6, 10)
+        .unwrap()
+        .is_empty());
+}
+
+#[rstest]
+#[test(tokio::test)]
+async fn undelivered_certificate_has_no_sequence_number(memory_store: Arc<ValidatorStore>) {
+    let certificate = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 1);
+
+    assert_eq!(
+        memory_store
+            .get_certificate_sequence_number(&certificate[0].certificate.id)
+            .unwrap(),
+        None
+    );
+}
+
+#[rstest]
+#[test(tokio::test)]
+async fn sequence_continues_after_reopening(memory_store: Arc<ValidatorStore>) {
+    let certificates = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 4);
+
+    memory_store
+        .insert_certificates_delivered(&certificates[..2])
+        .await
+        .unwrap();
+
+    let reopened = reopen(&memory_store.fullnode_store);
+    reopened
+        .insert_certificates_delivered(&certificates[2..])
+        .await
+        .unwrap();
+
+    assert_eq!(
+        reopened
+            .get_certificate_sequence_number(&certificates[3].certificate.id)
+            .unwrap(),
+        Some(3)
+    );
+}
+
+#[rstest]
+#[test(tokio::test)]
+async fn delivery_order_is_indexed_by_the_index_migration(memory_store: Arc<ValidatorStore>) {
+    let certificates = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 3);
+    memory_store
+        .insert_certificates_delivered(&certificates)
+        .await
+        .unwrap();
+
+    // Drop the order index of all but the first certificate, as for a migration
+    // interrupted after its first batch
+    let fullnode_store = &memory_store.fullnode_store;
+    let index_tables = &fullnode_store.index_tables;
+    for (sequence_number, certificate_id) in index_tables.certificate_order.iter().unwrap().skip(1)
+    {
+        index_tables
+            .certificate_order
+            .delete(&sequence_number)
+            .unwrap();
+        index_tables
+            .certificate_sequences
+            .delete(&certificate_id)
+            .unwrap();
+    }
+    let index = &index_tables.certificate_order.backend;
+    DBColumn::<u8, SchemaVersion>::from_backend(index, cfs::SCHEMA)
+        .insert(&0, &INITIAL_SCHEMA_VERSION)
+        .unwrap();
+
+    schema::INDEX
+        .apply_from_perpetual(
+            index,
+            Some(&fullnode_store.perpetual_tables.streams.backend),
+        )
+        .unwrap();
+
+    let reopened = reopen(fullnode_store);
+
+    let expected: Vec<_> = certificates
+        .iter()
+        .map(|certificate| certificate.certificate.id)
+        .zip(0..)
+        .collect();
+
+    assert_eq!(
+        ids(reopened
+            .get_certificates_from_sequence_number(0, 10)
+            .unwrap()),
+        expected
+    );
+
+    let next = create_certificate_chain(SOURCE_SUBNET_ID_2, &[TARGET_SUBNET_ID_1], 1);
+    reopened.insert_certificates_delivered(&next).await.unwrap();
+
+    assert_eq!(
+        reopened
+            .get_certificate_sequence_number(&next[0].certificate.id)
+            .unwrap(),
+        Some(3)
+    );
+}
@@ -0,0 +1,197 @@
+use std::{io::Cursor, sync::Arc};
+
+use rstest::rstest;
+use rstest_reuse::apply;
+use test_log::test;
+use topos_core::types::stream::{CertificateTargetStreamPosition, Position};
+use topos_test_sdk::{
+    certificates::create_certificate_chain,
+    constants::{SOURCE_SUBNET_ID_1, SOURCE_SUBNET_ID_2, TARGET_SUBNET_ID_1, TARGET_SUBNET_ID_2},
+};
+
+use super::support::{backends, memory_store};
+use crate::{
+    errors::SnapshotError,
+    fullnode::FullNodeStore,
+    rocks::map::Map,
+    snapshot::{self, RecordReader, RecordWriter, SnapshotRecord, SnapshotSummary},
+    store::{ReadStore, WriteStore},
+    validator::ValidatorStore,
+};
+
+async fn exported_snapshot(store: &ValidatorStore) -> Vec<u8> {
+    store
+        .insert_certificates_delivered(&create_certificate_chain(
+            SOURCE_SUBNET_ID_1,
+            &[TARGET_SUBNET_ID_1, TARGET_SUBNET_ID_2],
+            10,
+        ))
+        .await
+        .unwrap();
+    store
+        .insert_certificates_delivered(&create_certificate_chain(
+            SOURCE_SUBNET_ID_2,
+            &[TARGET_SUBNET_ID_1],
+            5,
+        ))
+        .await
+        .unwrap();
+
+    let mut archive = Vec::new();
+    assert_eq!(
+        snapshot::export(&store.fullnode_store, &mut archive).unwrap(),
+        SnapshotSummary {
+            subnets: 2,
+            certificates: 15
+        }
+    );
+
+    archive
+}
+
+#[apply(backends)]
+#[test(tokio::test)]
+async fn snapshot_restores_streams_and_indexes(store: Arc<ValidatorStore>) {
+    let archive = exported_snapshot(&store).await;
+
+    let restored = FullNodeStore::open_in_memory().unwrap();
+    assert_eq!(
+        snapshot::import(&restored, Cursor::new(&archive))
+            .await
+            .unwrap(),
+        SnapshotSummary {
+            subnets: 2,
+            certificates: 15
+        }
+    );
+
+    for subnet_id in [SOURCE_SUBNET_ID_1, SOURCE_SUBNET_ID_2] {
+        let expected = store.get_source_head(&subnet_id).unwrap().unwrap();
+        let head = restored.get_source_head(&subnet_id).unwrap().unwrap();
+
+        assert_eq!(head.certificate_id, expected.certificate_id);
+        assert_eq!(head.position, expected.position);
+    }
+
+    let from = CertificateTargetStreamPosition::new(
+        TARGET_SUBNET_ID_1,
+        SOURCE_SUBNET_ID_2,
+        Position::ZERO,
+    );
+    assert_eq!(
+        restored
+            .get_target_stream_certificates_from_position(from, 100)
+            .unwrap()
+            .into_iter()
+            .map(|(certificate, _)| certificate.certificate.id)
+            .collect::<Vec<_>>(),
+        store
+            .get_target_stream_certificates_from_position(from, 100)
+            .unwrap()
+            .into_iter()
+            .map(|(certificate, _)| certificate.certificate.id)
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(
+        restored
+            .get_target_source_subnet_list(&TARGET_SUBNET_ID_1)
+            .unwrap()
+            .len(),
+        2
+    );
+
+    // The delivery order and the timestamps are the ones of the exporting node
+    let fullnode_store = &store.fullnode_store;
+    for (sequence_number, certificate_id) in fullnode_store
+        .index_tables
+        .certificate_order
+        .iter()
+        .unwrap()
+    {
+        assert_eq!(
+            restored
+                .index_tables
+                .certificate_sequences
+                .get(&certificate_id)
+                .unwrap(),
+            Some(sequence_number)
+        );
+        assert_eq!(
+            restored
+                .get_certificate_timestamps(&certificate_id)
+                .unwrap(),
+            fullnode_store
+                .get_certificate_timestamps(&certificate_id)
+                .unwrap()
+        );
+    }
+}
+
+#[rstest]
+#[test(tokio::test)]
+async fn snapshot_import_checks_the_streams_before_writing(memory_store: Arc<ValidatorStore>) {
+    let archive = exported_snapshot(&memory_store).await;
+
+    // An archive whose last source stream stops before its head
+    let mut records = RecordReader::new(Cursor::new(&archive)).unwrap();
+    let mut truncated = Vec::new();
+    let mut writer = RecordWriter::new(&mut truncated).unwrap();
+    let mut previous = None;
+    while let Some(record) = records.next().unwrap() {
+        if matches!(record, SnapshotRecord::TargetStream(..)) {
+            break;
+        }
+        if let Some(previous) = previous.replace(record) {
+            writer.write(&previous).unwrap();
+        }
+    }
+    writer.finish().unwrap();
+
+    let restored = FullNodeStore::open_in_memory().unwrap();
+    assert!(matches!(
+        snapshot::import(&restored, Cursor::new(&truncated)).await,
+        Err(SnapshotError::StreamMismatch(_))
+    ));
+    assert!(restored
+        .perpetual_tables
+        .certificates
+        .iter()
+        .unwrap()
+        .next()
+        .is_none());
+
+    // Nothing was written, the valid archive can still be imported
+    assert_eq!(
+        snapshot::import(&restored, Cursor::new(&archive))
+            .await
+            .unwrap()
+            .certificates,
+        15
+    );
+}
+
+#[rstest]
+#[test(tokio::test)]
+async fn snapshot_import_rejects_corrupted_archive(memory_store: Arc<ValidatorStore>) {
+    let mut archive = exported_snapshot(&memory_store).await;
+    let last = archive.len() - 1;
+    archive[last] ^= 0xff;
+
+    let restored = FullNodeStore::open_in_memory().unwrap();
+    assert!(matches!(
+        snapshot::import(&restored, Cursor::new(&archive)).await,
+        Err(SnapshotError::ChecksumMismatch)
+    ));
+    assert!(restored.get_checkpoint().unwrap().is_empty());
+}
+
+#[rstest]
+#[test(tokio::test)]
+async fn snapshot_import_requires_an_empty_store(memory_store: Arc<ValidatorStore>) {
+    let archive = exported_snapshot(&memory_store).await;
+
+    assert!(matches!(
+        snapshot::import(&memory_store.fullnode_store, Cursor::new(&archive)).await,
+        Err(SnapshotError::StoreNotEmpty)
+    ));
+}
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+
+use rstest::rstest;
+use rstest_reuse::apply;
+use test_log::test;
+use topos_test_sdk::{
+    certificates::create_certificate_chain,
+    constants::{SOURCE_SUBNET_ID_1, TARGET_SUBNET_ID_1},
+};
+
+use super::support::backends;
+use crate::{constant::cfs, rocks::memory::MemoryDB, store::WriteStore, validator::ValidatorStore};
+
+#[apply(backends)]
+#[test(tokio::test)]
+async fn statistics_cover_the_written_column_families(store: Arc<ValidatorStore>) {
+    store
+        .insert_certificates_delivered(&create_certificate_chain(
+            SOURCE_SUBNET_ID_1,
+            &[TARGET_SUBNET_ID_1],
+            3,
+        ))
+        .await
+        .unwrap();
+
+    let statistics = store.get_storage_statistics().unwrap();
+
+    let certificates = statistics
+        .iter()
+        .find(|statistics| {
+            statistics.database == "perpetual" && statistics.column_family == cfs::CERTIFICATES
+        })
+        .expect("Missing statistics of the certificates");
+
+    assert!(certificates.estimated_keys > 0);
+    assert!(certificates.memtable_size > 0);
+
+    assert!(statistics
+        .iter()
+        .any(|statistics| statistics.database == "pending"));
+}
+
+#[test]
+fn memory_sizes_follow_the_writes() {
+    let db = MemoryDB::new(&["column"]);
+
+    db.put("column", b"key".to_vec(), b"value".to_vec())
+        .unwrap();
+    db.put("column", b"key".to_vec(), b"longer value".to_vec())
+        .unwrap();
+    db.put("column", b"other".to_vec(), b"value".to_vec())
+        .unwrap();
+    assert_eq!(db.column_sizes(), vec![("column", 2, 15 + 10)]);
+
+    db.delete("column", b"key".to_vec()).unwrap();
+    db.delete("column", b"missing".to_vec()).unwrap();
+    assert_eq!(db.column_sizes(), vec![("column", 1, 10)]);
+}
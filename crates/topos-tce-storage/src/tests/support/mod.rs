@@ -8,7 +8,8 @@ use std::{
 
 use once_cell::sync::Lazy;
 use rocksdb::Options;
-use rstest::fixture;
+use rstest::{fixture, rstest};
+use rstest_reuse::template;
 use topos_test_sdk::storage::create_folder;
 
 use crate::{
@@ -40,26 +41,46 @@ pub(crate) fn database_name() -> &'static str {
 #[fixture]
 pub(crate) fn store() -> Arc<ValidatorStore> {
     let temp_dir = create_folder::default();
-    let perpetual_tables = Arc::new(ValidatorPerpetualTables::open(temp_dir.clone()));
-    let index_tables = Arc::new(IndexTables::open(temp_dir.clone()));
+    let store = fullnode_store_at(temp_dir.clone());
+
+    ValidatorStore::open(temp_dir, store).unwrap()
+}
+
+/// Opens the full node store persisted at `path`
+pub(crate) fn fullnode_store_at(path: PathBuf) -> Arc<FullNodeStore> {
+    let perpetual_tables = Arc::new(ValidatorPerpetualTables::open(path.clone()));
+    let index_tables = Arc::new(IndexTables::open(path.clone(), &perpetual_tables));
 
     let participants_store =
-        EpochValidatorsStore::new(temp_dir.clone()).expect("Unable to create Participant store");
+        EpochValidatorsStore::new(path.clone()).expect("Unable to create Participant store");
 
     let epoch_store =
-        ValidatorPerEpochStore::new(0, temp_dir.clone()).expect("Unable to create Per epoch store");
+        ValidatorPerEpochStore::new(0, path).expect("Unable to create Per epoch store");
 
-    let store = FullNodeStore::open(
+    FullNodeStore::open(
         epoch_store,
         participants_store,
         perpetual_tables,
         index_tables,
     )
-    .expect("Unable to create full node store");
+    .expect("Unable to create full node store")
+}
 
-    ValidatorStore::open(temp_dir, store).unwrap()
+#[fixture]
+pub(crate) fn memory_store() -> Arc<ValidatorStore> {
+    let store = FullNodeStore::open_in_memory().expect("Unable to create full node store");
+
+    ValidatorStore::open_in_memory(store).unwrap()
 }
 
+/// Runs the test against both the RocksDB and the in-memory storage,
+/// the store being given as the `store` argument
+#[template]
+#[rstest]
+#[case::rocksdb(crate::tests::support::store())]
+#[case::memory(crate::tests::support::memory_store())]
+pub(crate) fn backends(#[case] store: Arc<ValidatorStore>) {}
+
 #[fixture]
 pub(crate) fn rocks_db(database_name: &'static str) -> Arc<RocksDB> {
     let mut dbs = DB.lock().unwrap();
@@ -0,0 +1,141 @@
+use std::sync::Arc;
+
+use rstest::rstest;
+use rstest_reuse::apply;
+use test_log::test;
+use topos_core::{types::CertificateDelivered, uci::CertificateId};
+use topos_test_sdk::{
+    certificates::create_certificate_chain,
+    constants::{SOURCE_SUBNET_ID_1, SOURCE_SUBNET_ID_2, TARGET_SUBNET_ID_1, TARGET_SUBNET_ID_2},
+};
+
+use super::support::{backends, memory_store};
+use crate::{
+    fullnode::unix_timestamp_millis,
+    store::{ReadStore, WriteStore},
+    types::CertificateTimestamps,
+    validator::ValidatorStore,
+};
+
+fn ids(certificates: Vec<(CertificateDelivered, CertificateTimestamps)>) -> Vec<CertificateId> {
+    certificates
+        .into_iter()
+        .map(|(certificate, _)| certificate.certificate.id)
+        .collect()
+}
+
+#[rstest]
+#[test(tokio::test)]
+async fn delivered_certificate_is_timestamped(memory_store: Arc<ValidatorStore>) {
+    let certificates = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 1);
+
+    let before = unix_timestamp_millis();
+    memory_store
+        .insert_certificates_delivered(&certificates)
+        .await
+        .unwrap();
+    let after = unix_timestamp_millis();
+
+    let timestamps = memory_store
+        .get_certificate_timestamps(&certificates[0].certificate.id)
+        .unwrap()
+        .unwrap();
+
+    assert!(before <= timestamps.delivered_at && timestamps.delivered_at <= after);
+    assert_eq!(timestamps.received_at, timestamps.delivered_at);
+}
+
+#[rstest]
+#[test(tokio::test)]
+async fn reception_of_pending_certificate_is_kept_until_delivery(
+    memory_store: Arc<ValidatorStore>,
+) {
+    let certificates = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 1);
+    let certificate_id = certificates[0].certificate.id;
+
+    let before = unix_timestamp_millis();
+    memory_store
+        .insert_pending_certificate(&certificates[0].certificate)
+        .unwrap();
+
+    memory_store
+        .insert_certificate_delivered(&certificates[0])
+        .await
+        .unwrap();
+
+    let timestamps = memory_store
+        .get_certificate_timestamps(&certificate_id)
+        .unwrap()
+        .unwrap();
+
+    assert!(before <= timestamps.received_at);
+    assert!(timestamps.received_at <= timestamps.delivered_at);
+    assert!(memory_store
+        .pending_tables
+        .receptions
+        .get(&certificate_id)
+        .unwrap()
+        .is_none());
+}
+
+#[apply(backends)]
+#[test(tokio::test)]
+async fn can_query_certificates_delivered_between(store: Arc<ValidatorStore>) {
+    let first = create_certificate_chain(SOURCE_SUBNET_ID_1, &[TARGET_SUBNET_ID_1], 3);
+    let second = create_certificate_chain(
+        SOURCE_SUBNET_ID_2,
+        &[TARGET_SUBNET_ID_1, TARGET_SUBNET_ID_2],
+        2,
+    );
+
+    store.insert_certificates_delivered(&first).await.unwrap();
+    store.insert_certificates_delivered(&second).await.unwrap();
+
+    let delivered_at = |certificate_id: &CertificateId| {
+        store
+            .get_certificate_timestamps(certificate_id)
+            .unwrap()
+            .unwrap()
+            .delivered_at
+    };
+
+    let start = delivered_at(&first[0].certificate.id);
+    let end = delivered_at(&second[1].certificate.id);
+
+    let first_ids: Vec<_> = first.iter().map(|c| c.certificate.id).collect();
+    let second_ids: Vec<_> = second.iter().map(|c| c.certificate.id).collect();
+
+    assert_eq!(
+        ids(store
+            .get_source_certificates_delivered_between(&SOURCE_SUBNET_ID_1, start, end, 10)
+            .unwrap()),
+        first_ids
+    );
+    assert_eq!(
+        ids(store
+            .get_source_certificates_delivered_between(&SOURCE_SUBNET_ID_1, start, end, 2)
+            .unwrap()),
+        first_ids[..2]
+    );
+    assert_eq!(
+        ids(store
+            .get_target_certificates_delivered_between(&TARGET_SUBNET_ID_1, start, end, 10)
+            .unwrap()),
+        [first_ids.clone(), second_ids.clone()].concat()
+    );
+    assert_eq!(
+        ids(store
+            .get_target_certificates_delivered_between(&TARGET_SUBNET_ID_2, start, end, 10)
+            .unwrap()),
+        second_ids
+    );
+
+    assert!(store
+        .get_source_certificates_delivered_between(&SOURCE_SUBNET_ID_1, end + 1, u64::MAX, 10)
+        .unwrap()
+        .is_empty());
+    assert!(store
+        .get_source_certificates_delivered_between(&SOURCE_SUBNET_ID_2, 0, start - 1, 10)
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,115 @@
+//! Tuning of the RocksDB databases of the node.
+//!
+//! The tuning is configured once when the node starts, before the databases
+//! are opened, and is applied to every database and column family opened afterwards.
+
+use std::str::FromStr;
+
+use once_cell::sync::OnceCell;
+use rocksdb::{BlockBasedOptions, Cache, DBCompressionType};
+use serde::{Deserialize, Serialize};
+
+use crate::errors::InternalStorageError;
+
+/// Compression of the blocks written on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Compression {
+    None,
+    Snappy,
+    Lz4,
+    Zstd,
+}
+
+impl From<Compression> for DBCompressionType {
+    fn from(value: Compression) -> Self {
+        match value {
+            Compression::None => Self::None,
+            Compression::Snappy => Self::Snappy,
+            Compression::Lz4 => Self::Lz4,
+            Compression::Zstd => Self::Zstd,
+        }
+    }
+}
+
+impl FromStr for Compression {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::None),
+            "snappy" => Ok(Self::Snappy),
+            "lz4" => Ok(Self::Lz4),
+            "zstd" => Ok(Self::Zstd),
+            _ => Err(format!(
+                "unknown compression '{s}', expected one of none, snappy, lz4 or zstd"
+            )),
+        }
+    }
+}
+
+/// Settings of the databases, RocksDB defaults are kept for the ones not set
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct StorageTuning {
+    /// Size in bytes of the block cache shared by all the databases,
+    /// each column family has its own small cache if not set
+    pub block_cache_size: Option<usize>,
+    /// Compression of the blocks written on disk
+    pub compression: Option<Compression>,
+    /// Size in bytes a memtable reaches before being flushed on disk
+    pub write_buffer_size: Option<usize>,
+    /// Maximum number of memtables kept in memory for each column family
+    pub max_write_buffer_number: Option<i32>,
+}
+
+struct Tuning {
+    settings: StorageTuning,
+    block_cache: Option<Cache>,
+}
+
+static TUNING: OnceCell<Tuning> = OnceCell::new();
+
+/// Configures the tuning of the databases opened afterwards, it can only be configured once
+pub fn configure(settings: StorageTuning) -> Result<(), InternalStorageError> {
+    let block_cache = settings
+        .block_cache_size
+        .map(Cache::new_lru_cache)
+        .transpose()?;
+
+    TUNING
+        .set(Tuning {
+            settings,
+            block_cache,
+        })
+        .map_err(|_| InternalStorageError::TuningAlreadyConfigured)
+}
+
+/// Applies the configured tuning to the options of a database or of a column family
+pub(crate) fn apply(options: &mut rocksdb::Options) {
+    let Some(Tuning {
+        settings,
+        block_cache,
+    }) = TUNING.get()
+    else {
+        return;
+    };
+
+    if let Some(compression) = settings.compression {
+        options.set_compression_type(compression.into());
+    }
+
+    if let Some(size) = settings.write_buffer_size {
+        options.set_write_buffer_size(size);
+    }
+
+    if let Some(number) = settings.max_write_buffer_number {
+        options.set_max_write_buffer_number(number);
+    }
+
+    if let Some(block_cache) = block_cache {
+        let mut block_options = BlockBasedOptions::default();
+        block_options.set_block_cache(block_cache);
+        options.set_block_based_table_factory(&block_options);
+    }
+}
@@ -1,16 +1,52 @@
-use topos_core::{
-    api::grpc::checkpoints::SourceStreamPosition,
-    types::{CertificateDelivered, Ready, Signature},
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+    time::Duration,
 };
 
-use crate::{CertificatePositions, PendingCertificateId};
+use serde::{Deserialize, Serialize};
+use topos_core::{
+    api::grpc::{
+        checkpoints::SourceStreamPosition,
+        tce::v1::{
+            CheckpointSummary as GrpcCheckpointSummary,
+            ColumnFamilyStatistics as GrpcColumnFamilyStatistics,
+        },
+    },
+    errors::GrpcParsingError,
+    types::{stream::Position, CertificateDelivered, Signature, ValidatorId},
+    uci::{Certificate, CertificateId, SubnetId},
+};
 
-pub type Echo = String;
+use crate::{
+    constant::{PENDING_POOL_CERTIFICATE_TTL, PENDING_POOL_MAX_CERTIFICATES_PER_SUBNET},
+    errors::{CheckpointError, InternalStorageError},
+    CertificatePositions, PendingCertificateId, SourceHead,
+};
 
 pub type CertificateSequenceNumber = u64;
+/// Unix timestamp in milliseconds
+pub type Timestamp = u64;
 pub type EpochId = u64;
+pub type CheckpointSequenceNumber = u64;
+/// Keccak256 digest of a checkpoint summary
+pub type CheckpointDigest = [u8; 32];
 pub type Validators = Vec<String>;
 
+/// Position where a paginated checkpoint diff resumes for each subnet not fully returned yet
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckpointDiffCursor(pub Vec<(SubnetId, Position)>);
+
+impl CheckpointDiffCursor {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        bincode::serialize(self).expect("Checkpoint diff cursor serialization can't fail")
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, InternalStorageError> {
+        Ok(bincode::deserialize(bytes)?)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum PendingResult {
     AlreadyDelivered,
@@ -21,30 +57,460 @@ pub enum PendingResult {
 #[derive(Debug, Clone)]
 pub struct CertificateDeliveredWithPositions(pub CertificateDelivered, pub CertificatePositions);
 
-#[allow(unused)]
+/// Evidence that a source subnet produced two different certificates
+/// for the same position of its source stream.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EquivocationEvidence {
+    /// The certificate known first at this position (delivered or in broadcast)
+    pub known: Certificate,
+    /// The certificate conflicting with the known one
+    pub conflicting: Certificate,
+}
+
+/// Limits applied to the pending and precedence pools
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolRetention {
+    /// Maximum number of certificates kept per source subnet without a limit of its own
+    pub max_certificates_per_subnet: usize,
+    /// Maximum number of certificates kept for specific source subnets
+    pub subnet_limits: HashMap<SubnetId, usize>,
+    /// Age after which a certificate is evicted
+    pub max_age: Duration,
+}
+
+impl PoolRetention {
+    /// Maximum number of certificates kept for the source subnet
+    pub fn max_certificates(&self, subnet_id: &SubnetId) -> usize {
+        self.subnet_limits
+            .get(subnet_id)
+            .copied()
+            .unwrap_or(self.max_certificates_per_subnet)
+    }
+}
+
+impl Default for PoolRetention {
+    fn default() -> Self {
+        Self {
+            max_certificates_per_subnet: *PENDING_POOL_MAX_CERTIFICATES_PER_SUBNET,
+            subnet_limits: HashMap::new(),
+            max_age: *PENDING_POOL_CERTIFICATE_TTL,
+        }
+    }
+}
+
+/// Why a certificate was removed from the pools without being delivered
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EvictionReason {
+    /// The certificate stayed in the pools longer than allowed
+    Expired,
+    /// The source subnet had too many certificates in the pools
+    SubnetLimitReached,
+}
+
+/// Record of a certificate evicted from the pools
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EvictedCertificate {
+    pub certificate_id: CertificateId,
+    pub subnet_id: SubnetId,
+    pub reason: EvictionReason,
+    /// Unix timestamp of the eviction, in seconds
+    pub evicted_at: u64,
+}
+
+/// Times at which a delivered certificate was seen by the node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CertificateTimestamps {
+    /// First time the certificate was received, equal to the delivery time
+    /// when the certificate was delivered without being broadcast by the node
+    pub received_at: Timestamp,
+    pub delivered_at: Timestamp,
+}
+
+/// Key of a misbehaviour in the ledger of a validator: the validator, the
+/// detection time in milliseconds and a counter ordering the ones detected together
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct MisbehaviourKey(pub(crate) ValidatorId, pub(crate) Timestamp, pub(crate) u64);
+
+/// Tracking of a certificate held in one of the pools
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct PoolEntry {
+    pub(crate) subnet_id: SubnetId,
+    /// Unix timestamp of the insertion, in seconds
+    pub(crate) inserted_at: u64,
+    pub(crate) location: PoolLocation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) enum PoolLocation {
+    Pending(PendingCertificateId),
+    /// Awaiting the delivery of the certificate with the given id
+    Precedence(CertificateId),
+}
+
+/// First and last checkpoints agreed on by the validators during an epoch
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct EpochSummary {
-    epoch_id: EpochId,
-    start_checkpoint: VerifiedCheckpointSummary,
-    end_checkpoint: Option<VerifiedCheckpointSummary>,
+    pub epoch_id: EpochId,
+    pub start_checkpoint: VerifiedCheckpointSummary,
+    /// Latest checkpoint of the epoch, if more than one checkpoint was agreed on
+    pub end_checkpoint: Option<VerifiedCheckpointSummary>,
+}
+
+impl EpochSummary {
+    /// Latest checkpoint agreed on during the epoch
+    pub fn last_checkpoint(&self) -> &VerifiedCheckpointSummary {
+        self.end_checkpoint
+            .as_ref()
+            .unwrap_or(&self.start_checkpoint)
+    }
 }
 
-#[allow(unused)]
+/// Heads of the source streams at some point of an epoch, chained to the
+/// previous checkpoint by its digest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct CheckpointSummary {
-    epoch: EpochId,
-    sequence_number: usize,
-    checkpoint_data: Vec<SourceStreamPosition>,
+    pub epoch: EpochId,
+    pub sequence_number: CheckpointSequenceNumber,
+    /// Digest of the previous checkpoint, `None` for the first checkpoint of the chain
+    pub previous_digest: Option<CheckpointDigest>,
+    /// Head of every source stream, ordered by subnet id
+    pub checkpoint_data: Vec<SourceHead>,
+    /// Validator set of the next epoch, ordered by id, set when the checkpoint
+    /// ends its epoch
+    pub next_validators: Option<Vec<ValidatorId>>,
+}
+
+impl CheckpointSummary {
+    pub fn new(
+        epoch: EpochId,
+        previous: Option<&CheckpointSummary>,
+        mut checkpoint_data: Vec<SourceHead>,
+    ) -> Self {
+        checkpoint_data.sort_by_key(|head| head.subnet_id);
+
+        Self {
+            epoch,
+            sequence_number: previous.map_or(0, |previous| previous.sequence_number + 1),
+            previous_digest: previous.map(CheckpointSummary::digest),
+            checkpoint_data,
+            next_validators: None,
+        }
+    }
+
+    /// Ends the epoch of the checkpoint, the next one starting with the given validators
+    pub fn with_next_validators(
+        mut self,
+        validators: impl IntoIterator<Item = ValidatorId>,
+    ) -> Self {
+        let mut validators: Vec<_> = validators.into_iter().collect();
+        validators.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+        self.next_validators = Some(validators);
+
+        self
+    }
+
+    /// Epoch of the checkpoint following this one
+    pub fn next_epoch(&self) -> EpochId {
+        match self.next_validators {
+            Some(_) => self.epoch + 1,
+            None => self.epoch,
+        }
+    }
+
+    /// Keccak256 digest of the summary, which is what the validators sign
+    pub fn digest(&self) -> CheckpointDigest {
+        tiny_keccak::keccak256(
+            &bincode::serialize(self).expect("Checkpoint summary serialization can't fail"),
+        )
+    }
+
+    /// Payload signed by a validator to agree on the checkpoint
+    pub fn signing_payload(&self, validator_id: &ValidatorId) -> Vec<u8> {
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&self.digest());
+        payload.extend_from_slice(validator_id.as_bytes());
+
+        payload
+    }
 }
-pub struct VerifiedCheckpointSummary(CheckpointSummary, ValidatorQuorumSignatureInfo);
 
-#[allow(unused)]
+impl From<CheckpointSummary> for GrpcCheckpointSummary {
+    fn from(value: CheckpointSummary) -> Self {
+        Self {
+            epoch: value.epoch,
+            sequence_number: value.sequence_number,
+            previous_digest: value
+                .previous_digest
+                .map(|digest| digest.to_vec())
+                .unwrap_or_default(),
+            heads: value
+                .checkpoint_data
+                .into_iter()
+                .map(|head| {
+                    SourceStreamPosition {
+                        source_subnet_id: head.subnet_id,
+                        position: *head.position,
+                        certificate_id: Some(head.certificate_id),
+                    }
+                    .into()
+                })
+                .collect(),
+            next_validators: value
+                .next_validators
+                .unwrap_or_default()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<GrpcCheckpointSummary> for CheckpointSummary {
+    type Error = GrpcParsingError;
+
+    fn try_from(value: GrpcCheckpointSummary) -> Result<Self, Self::Error> {
+        let previous_digest = match value.previous_digest.len() {
+            0 => None,
+            _ => Some(value.previous_digest.try_into().map_err(|_| {
+                GrpcParsingError::GrpcMalformedType("checkpoint_summary.previous_digest")
+            })?),
+        };
+
+        let checkpoint_data = value
+            .heads
+            .into_iter()
+            .map(|head| {
+                let head = SourceStreamPosition::try_from(head)?;
+
+                Ok(SourceHead {
+                    certificate_id: head.certificate_id.ok_or(
+                        GrpcParsingError::GrpcMalformedType("checkpoint_summary.heads"),
+                    )?,
+                    subnet_id: head.source_subnet_id,
+                    position: head.position.into(),
+                })
+            })
+            .collect::<Result<_, GrpcParsingError>>()?;
+
+        // A validator set is never empty, no validators meaning the epoch goes on
+        let next_validators = match value.next_validators.len() {
+            0 => None,
+            _ => Some(
+                value
+                    .next_validators
+                    .into_iter()
+                    .map(ValidatorId::try_from)
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| {
+                        GrpcParsingError::GrpcMalformedType("checkpoint_summary.next_validators")
+                    })?,
+            ),
+        };
+
+        Ok(Self {
+            epoch: value.epoch,
+            sequence_number: value.sequence_number,
+            previous_digest,
+            checkpoint_data,
+            next_validators,
+        })
+    }
+}
+
+/// Checkpoint summary along with the signatures of the validators who agreed on it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VerifiedCheckpointSummary(pub CheckpointSummary, pub ValidatorQuorumSignatureInfo);
+
+impl VerifiedCheckpointSummary {
+    pub fn summary(&self) -> &CheckpointSummary {
+        &self.0
+    }
+
+    /// Verifies that the checkpoint has been agreed on by the given validator set
+    ///
+    /// Every signature needs to come from a distinct and known validator and to be
+    /// valid over the digest of the summary. The number of signatures needs to reach
+    /// the `threshold` expected by the caller.
+    pub fn verify(
+        &self,
+        validators: &HashSet<ValidatorId>,
+        threshold: usize,
+    ) -> Result<(), CheckpointError> {
+        let (summary, quorum) = (&self.0, &self.1);
+
+        if quorum.epoch != summary.epoch {
+            return Err(CheckpointError::EpochMismatch {
+                expected: summary.epoch,
+                got: quorum.epoch,
+            });
+        }
+
+        let mut signers = HashSet::with_capacity(quorum.signatures.len());
+
+        for (validator_id, signature) in &quorum.signatures {
+            if !validators.contains(validator_id) {
+                return Err(CheckpointError::UnknownValidator(*validator_id));
+            }
+
+            if !signers.insert(*validator_id) {
+                return Err(CheckpointError::DuplicatedSignature(*validator_id));
+            }
+
+            let signature = topos_crypto::messages::Signature::from_str(signature)
+                .map_err(|_| CheckpointError::MalformedSignature(*validator_id))?;
+
+            signature
+                .verify(
+                    summary.signing_payload(validator_id),
+                    validator_id.address(),
+                )
+                .map_err(|_| CheckpointError::InvalidSignature(*validator_id))?;
+        }
+
+        if signers.len() < threshold {
+            return Err(CheckpointError::ThresholdNotReached {
+                expected: threshold,
+                got: signers.len(),
+            });
+        }
+
+        Ok(())
+    }
+}
+
+/// Signatures of the validators who agreed on a checkpoint
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ValidatorQuorumSignatureInfo {
-    epoch: EpochId,
-    signature: [u8; 32],
+    pub epoch: EpochId,
+    /// Hex encoded ECDSA signature of each validator over the checkpoint
+    pub signatures: Vec<(ValidatorId, Signature)>,
 }
 
-#[allow(unused)]
+/// Progress of the broadcast of a certificate, persisted in order to
+/// resume the broadcast after a restart of the validator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct BroadcastState {
-    echoes: Vec<(Echo, Signature)>,
-    readies: Vec<(Ready, Signature)>,
-    delivered: bool,
+    pub certificate: Certificate,
+    /// Epoch in which the broadcast started
+    pub epoch: EpochId,
+    /// Echo messages received from the other validators
+    pub echoes: Vec<(ValidatorId, Signature)>,
+    /// Ready messages received from the other validators
+    pub readies: Vec<(ValidatorId, Signature)>,
+    /// Signature of the Echo message sent by the local validator, if any
+    pub echo_sent: Option<Signature>,
+    /// Signature of the Ready message sent by the local validator, if any
+    pub ready_sent: Option<Signature>,
+}
+
+/// Message of a broadcast, persisted as soon as it is received or before being sent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BroadcastMessage {
+    /// Echo received from a validator
+    Echo(ValidatorId),
+    /// Ready received from a validator
+    Ready(ValidatorId),
+    /// Echo sent by the local validator
+    EchoSent,
+    /// Ready sent by the local validator
+    ReadySent,
+}
+
+/// Certificate and epoch of a broadcast in progress, its messages being
+/// persisted one by one along with it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct BroadcastHeader {
+    pub(crate) certificate: Certificate,
+    pub(crate) epoch: EpochId,
+}
+
+/// Key of a message of the broadcast of a certificate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct BroadcastMessageKey(pub(crate) CertificateId, pub(crate) BroadcastMessage);
+
+impl BroadcastState {
+    pub(crate) fn header(&self) -> BroadcastHeader {
+        BroadcastHeader {
+            certificate: self.certificate.clone(),
+            epoch: self.epoch,
+        }
+    }
+
+    /// Messages of the broadcast as they are stored
+    pub(crate) fn messages(&self) -> Vec<(BroadcastMessageKey, Signature)> {
+        let key = |message| BroadcastMessageKey(self.certificate.id, message);
+
+        self.echoes
+            .iter()
+            .map(|(sender, signature)| (key(BroadcastMessage::Echo(*sender)), signature.clone()))
+            .chain(self.readies.iter().map(|(sender, signature)| {
+                (key(BroadcastMessage::Ready(*sender)), signature.clone())
+            }))
+            .chain(
+                self.echo_sent
+                    .iter()
+                    .map(|signature| (key(BroadcastMessage::EchoSent), signature.clone())),
+            )
+            .chain(
+                self.ready_sent
+                    .iter()
+                    .map(|signature| (key(BroadcastMessage::ReadySent), signature.clone())),
+            )
+            .collect()
+    }
+
+    /// Rebuilds the progress of a broadcast from its header and messages
+    pub(crate) fn from_messages(
+        header: BroadcastHeader,
+        messages: impl IntoIterator<Item = (BroadcastMessage, Signature)>,
+    ) -> Self {
+        let mut state = Self {
+            certificate: header.certificate,
+            epoch: header.epoch,
+            echoes: Vec::new(),
+            readies: Vec::new(),
+            echo_sent: None,
+            ready_sent: None,
+        };
+
+        for (message, signature) in messages {
+            match message {
+                BroadcastMessage::Echo(sender) => state.echoes.push((sender, signature)),
+                BroadcastMessage::Ready(sender) => state.readies.push((sender, signature)),
+                BroadcastMessage::EchoSent => state.echo_sent = Some(signature),
+                BroadcastMessage::ReadySent => state.ready_sent = Some(signature),
+            }
+        }
+
+        state
+    }
+}
+
+/// Statistics of one column family of the storage
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnFamilyStatistics {
+    /// Name of the database holding the column family
+    pub database: String,
+    pub column_family: String,
+    /// Estimated number of keys
+    pub estimated_keys: u64,
+    /// Total size of the live SST files, in bytes
+    pub sst_files_size: u64,
+    /// Size of the memtables, in bytes
+    pub memtable_size: u64,
+    /// Estimated number of bytes the compactions have to rewrite
+    pub pending_compaction_bytes: u64,
+}
+
+impl From<ColumnFamilyStatistics> for GrpcColumnFamilyStatistics {
+    fn from(value: ColumnFamilyStatistics) -> Self {
+        Self {
+            database: value.database,
+            column_family: value.column_family,
+            estimated_keys: value.estimated_keys,
+            sst_files_size: value.sst_files_size,
+            memtable_size: value.memtable_size,
+            pending_compaction_bytes: value.pending_compaction_bytes,
+        }
+    }
 }
@@ -1,51 +1,144 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     path::PathBuf,
-    sync::{atomic::Ordering, Arc},
+    str::FromStr,
+    sync::{atomic::Ordering, Arc, MutexGuard},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use async_trait::async_trait;
 
+use tokio::sync::broadcast;
 use topos_core::{
     types::{
         stream::{CertificateSourceStreamPosition, Position},
-        CertificateDelivered, ProofOfDelivery,
+        CertificateDelivered, Misbehaviour, ProofOfDelivery, Signature, ValidatorId,
     },
     uci::{Certificate, CertificateId, SubnetId, INITIAL_CERTIFICATE_ID},
 };
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, instrument, warn};
 
 use crate::{
+    epoch::ValidatorPerEpochStore,
     errors::{InternalStorageError, StorageError},
-    fullnode::FullNodeStore,
-    rocks::map::Map,
+    fullnode::{unix_timestamp_millis, FullNodeStore},
+    rocks::{db_column::DBBatch, map::Map},
     store::{ReadStore, WriteStore},
+    types::{
+        BroadcastMessage, BroadcastMessageKey, BroadcastState, CertificateSequenceNumber,
+        CertificateTimestamps, CheckpointDiffCursor, ColumnFamilyStatistics, EpochId,
+        EquivocationEvidence, EvictedCertificate, EvictionReason, MisbehaviourKey, PoolEntry,
+        PoolLocation, PoolRetention, Timestamp,
+    },
     CertificatePositions, CertificateTargetStreamPosition, PendingCertificateId, SourceHead,
 };
 
 pub(crate) use self::tables::ValidatorPendingTables;
 pub use self::tables::ValidatorPerpetualTables;
 
-mod tables;
+pub(crate) mod tables;
 
 /// Contains all persistent data about the validator
 pub struct ValidatorStore {
     pub(crate) pending_tables: ValidatorPendingTables,
     pub(crate) fullnode_store: Arc<FullNodeStore>,
+    /// Notifies the certificates evicted from the pools
+    evictions_sender: broadcast::Sender<EvictedCertificate>,
+    /// Paths of the storage of the node owning it and of the secondary instance,
+    /// when opened as a secondary instance
+    secondary_paths: Option<(PathBuf, PathBuf)>,
 }
 
 impl ValidatorStore {
+    pub const EVICTIONS_CHANNEL_SIZE: usize = 2048;
+
     pub fn open(
         path: PathBuf,
         fullnode_store: Arc<FullNodeStore>,
     ) -> Result<Arc<Self>, StorageError> {
         let pending_tables: ValidatorPendingTables = ValidatorPendingTables::open(path);
-        let store = Arc::new(Self {
+
+        Ok(Self::with_tables(pending_tables, fullnode_store, None))
+    }
+
+    fn with_tables(
+        pending_tables: ValidatorPendingTables,
+        fullnode_store: Arc<FullNodeStore>,
+        secondary_paths: Option<(PathBuf, PathBuf)>,
+    ) -> Arc<Self> {
+        let (evictions_sender, _) = broadcast::channel(Self::EVICTIONS_CHANNEL_SIZE);
+
+        Arc::new(Self {
             pending_tables,
             fullnode_store,
-        });
+            evictions_sender,
+            secondary_paths,
+        })
+    }
+
+    /// Opens the store of the node running at `path` as a read-only secondary
+    /// instance, the full node store is expected to be opened as secondary too
+    pub fn open_secondary(
+        path: PathBuf,
+        secondary_path: PathBuf,
+        fullnode_store: Arc<FullNodeStore>,
+    ) -> Result<Arc<Self>, StorageError> {
+        Ok(Self::with_tables(
+            ValidatorPendingTables::open_secondary(path.clone(), secondary_path.clone()),
+            fullnode_store,
+            Some((path, secondary_path)),
+        ))
+    }
+
+    /// Applies the latest changes of the node owning the store when opened
+    /// as a secondary instance, does nothing otherwise
+    pub fn catch_up(&self) -> Result<(), StorageError> {
+        self.pending_tables.catch_up()?;
+        self.fullnode_store.catch_up()?;
+        self.follow_epoch_store()
+    }
+
+    /// Switches to the epoch store of the current epoch once the node owning the
+    /// store created it, which happens when the node restarts in a new epoch
+    fn follow_epoch_store(&self) -> Result<(), StorageError> {
+        let Some((path, secondary_path)) = &self.secondary_paths else {
+            return Ok(());
+        };
+
+        let current_epoch = self.fullnode_store.get_current_epoch()?;
+        let Some(epoch) = ValidatorPerEpochStore::latest_epoch(current_epoch, path.clone()) else {
+            return Ok(());
+        };
+        if epoch <= self.fullnode_store.epoch_store.load().epoch_id() {
+            return Ok(());
+        }
+
+        info!("Following the storage of the epoch {epoch}");
+        let epoch_store =
+            ValidatorPerEpochStore::new_secondary(epoch, path.clone(), secondary_path.clone())?;
+        self.fullnode_store
+            .epoch_store
+            .store(epoch_store.load_full());
+
+        Ok(())
+    }
+
+    /// Returns the statistics of the column families of the databases of the store
+    pub fn get_storage_statistics(&self) -> Result<Vec<ColumnFamilyStatistics>, StorageError> {
+        let mut statistics = self.pending_tables.statistics()?;
+        statistics.extend(self.fullnode_store.get_storage_statistics()?);
+
+        Ok(statistics)
+    }
 
-        Ok(store)
+    /// Opens the store without persisting anything on disk, the full node store
+    /// is expected to be in memory too
+    pub fn open_in_memory(fullnode_store: Arc<FullNodeStore>) -> Result<Arc<Self>, StorageError> {
+        Ok(Self::with_tables(
+            ValidatorPendingTables::open_in_memory(),
+            fullnode_store,
+            None,
+        ))
     }
 
     pub fn get_fullnode_store(&self) -> Arc<FullNodeStore> {
@@ -88,41 +181,168 @@ impl ValidatorStore {
             .map(|(_, s)| (*s, (0, None)))
             .collect();
 
-        for (_, certificate) in self.pending_tables.pending_pool.iter()? {
-            if !subnets.contains(&certificate.source_subnet_id) {
+        for (subnet_id, mut chain) in
+            self.pending_chains(|subnet_id| subnets.contains(subnet_id))?
+        {
+            result.insert(subnet_id, (chain.len() as u64, chain.pop()));
+        }
+
+        Ok(result)
+    }
+
+    /// Returns the pending certificates of every subnet, each one followed by the
+    /// certificates awaiting it in the precedence pool
+    pub fn get_pending_certificate_chains(
+        &self,
+    ) -> Result<HashMap<SubnetId, Vec<Certificate>>, StorageError> {
+        self.pending_chains(|_| true)
+    }
+
+    /// Returns at most `limit` ids of pending certificates, each one followed by the ids
+    /// of the certificates awaiting it in the precedence pool
+    ///
+    /// Only the id indexes of the pools are read, and the reading stops once the limit is reached
+    pub fn get_pending_certificate_ids(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<CertificateId>, StorageError> {
+        let mut certificate_ids = Vec::new();
+
+        for (certificate_id, _) in self.pending_tables.pending_pool_index.iter()? {
+            if certificate_ids.len() >= limit {
+                break;
+            }
+
+            let Some(entry) = self.pending_tables.pool_entries.get(&certificate_id)? else {
                 continue;
+            };
+
+            let mut latest_id = certificate_id;
+            certificate_ids.push(certificate_id);
+
+            while certificate_ids.len() < limit {
+                match self
+                    .pending_tables
+                    .pool_successors
+                    .get(&(entry.subnet_id, latest_id))?
+                {
+                    Some(successor) => {
+                        latest_id = successor;
+                        certificate_ids.push(successor);
+                    }
+                    None => break,
+                }
             }
+        }
 
-            let mut latest_cert = certificate;
-            let entry = result
-                .entry(latest_cert.source_subnet_id)
-                .or_insert((0, None));
+        Ok(certificate_ids)
+    }
 
-            entry.0 += 1;
-            while let Some(certificate) =
-                self.pending_tables.precedence_pool.get(&latest_cert.id)?
-            {
-                latest_cert = certificate;
-                entry.0 += 1;
+    /// Returns the certificates of the pending and precedence pools with the given ids
+    pub fn get_pool_certificates(
+        &self,
+        certificate_ids: &[CertificateId],
+    ) -> Result<Vec<Option<Certificate>>, StorageError> {
+        certificate_ids
+            .iter()
+            .map(|certificate_id| {
+                let entry = self.pending_tables.pool_entries.get(certificate_id)?;
+
+                Ok(match entry.map(|entry| entry.location) {
+                    Some(PoolLocation::Pending(pending_id)) => {
+                        self.pending_tables.pending_pool.get(&pending_id)?
+                    }
+                    Some(PoolLocation::Precedence(prev_id)) => self
+                        .pending_tables
+                        .precedence_pool
+                        .get(&prev_id)?
+                        .filter(|certificate| certificate.id == *certificate_id),
+                    None => None,
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the certificate of the pools following the same previous certificate
+    /// of the same source subnet as the given one, if it is a different certificate
+    pub fn get_conflicting_pool_certificate(
+        &self,
+        certificate: &Certificate,
+    ) -> Result<Option<Certificate>, StorageError> {
+        match self
+            .pending_tables
+            .pool_successors
+            .get(&(certificate.source_subnet_id, certificate.prev_id))?
+        {
+            Some(known_id) if known_id != certificate.id => {
+                Ok(self.get_pool_certificates(&[known_id])?.pop().flatten())
             }
+            _ => Ok(None),
+        }
+    }
 
-            entry.1 = Some(latest_cert);
+    fn pending_chains(
+        &self,
+        include: impl Fn(&SubnetId) -> bool,
+    ) -> Result<HashMap<SubnetId, Vec<Certificate>>, StorageError> {
+        let mut chains: HashMap<SubnetId, Vec<Certificate>> = HashMap::new();
+
+        for (_, certificate) in self.pending_tables.pending_pool.iter()? {
+            if !include(&certificate.source_subnet_id) {
+                continue;
+            }
+
+            let chain = chains.entry(certificate.source_subnet_id).or_default();
+            let mut latest_id = certificate.id;
+            chain.push(certificate);
+
+            while let Some(certificate) = self.pending_tables.precedence_pool.get(&latest_id)? {
+                latest_id = certificate.id;
+                chain.push(certificate);
+            }
         }
 
-        Ok(result)
+        Ok(chains)
     }
 
     pub fn insert_pending_certificates(
         &self,
         certificates: &[Certificate],
     ) -> Result<Vec<PendingCertificateId>, StorageError> {
+        let _pools = self.lock_pools();
+
+        for certificate in certificates {
+            if let Some(known) = self.get_conflicting_pool_certificate(certificate)? {
+                return Err(StorageError::InternalStorage(
+                    InternalStorageError::ConflictingPendingCertificate(known.id),
+                ));
+            }
+        }
+
+        let mut batch = self.pending_tables.pending_pool.batch();
+        let inserted_at = unix_timestamp();
+        let mut evicted = Vec::new();
+
+        let mut incoming: HashMap<SubnetId, Vec<CertificateId>> = HashMap::new();
+        for certificate in certificates {
+            incoming
+                .entry(certificate.source_subnet_id)
+                .or_default()
+                .push(certificate.id);
+        }
+
+        for (subnet_id, certificate_ids) in &incoming {
+            let records;
+            (batch, records) =
+                self.make_room_in_pools(batch, subnet_id, certificate_ids, inserted_at)?;
+            evicted.extend(records);
+        }
+
         let id = self
             .pending_tables
             .next_pending_id
             .fetch_add(certificates.len() as u64, Ordering::Relaxed);
 
-        let mut batch = self.pending_tables.pending_pool.batch();
-
         let (values, index, ids) = certificates.iter().enumerate().fold(
             (Vec::new(), Vec::new(), Vec::new()),
             |(mut values, mut index, mut ids), (idx, certificate)| {
@@ -136,10 +356,30 @@ impl ValidatorStore {
             },
         );
 
+        for (certificate, id) in certificates.iter().zip(&ids) {
+            batch = self.insert_pool_entry(
+                batch,
+                certificate.id,
+                PoolEntry {
+                    subnet_id: certificate.source_subnet_id,
+                    inserted_at,
+                    location: PoolLocation::Pending(*id),
+                },
+            )?;
+        }
+
         batch = batch.insert_batch(&self.pending_tables.pending_pool, values)?;
         batch = batch.insert_batch(&self.pending_tables.pending_pool_index, index)?;
+        batch = batch.insert_batch(
+            &self.pending_tables.pool_successors,
+            certificates
+                .iter()
+                .map(|c| ((c.source_subnet_id, c.prev_id), c.id)),
+        )?;
+        batch = self.record_receptions(batch, certificates.iter().map(|c| c.id))?;
 
         batch.write()?;
+        self.notify_evictions(evicted);
 
         Ok(ids)
     }
@@ -160,27 +400,396 @@ impl ValidatorStore {
                 .get_certificate(&certificate.prev_id)?
                 .is_some();
 
+        let _pools = self.lock_pools();
+
+        // Only one certificate of the pools can follow a given previous certificate
+        if let Some(known) = self.get_conflicting_pool_certificate(certificate)? {
+            return Err(StorageError::InternalStorage(
+                InternalStorageError::ConflictingPendingCertificate(known.id),
+            ));
+        }
+
+        let subnet_id = certificate.source_subnet_id;
+        let inserted_at = unix_timestamp();
+        let (mut batch, evicted) = self.make_room_in_pools(
+            self.pending_tables.pending_pool.batch(),
+            &subnet_id,
+            &[certificate.id],
+            inserted_at,
+        )?;
+        batch = self
+            .record_receptions(batch, [certificate.id])?
+            .insert_batch(
+                &self.pending_tables.pool_successors,
+                [(
+                    (certificate.source_subnet_id, certificate.prev_id),
+                    certificate.id,
+                )],
+            )?;
+
+        let awaiting = self
+            .pending_tables
+            .precedence_pool
+            .get(&certificate.prev_id)?;
+
         if prev_delivered {
             let id = self
                 .pending_tables
                 .next_pending_id
                 .fetch_add(1, Ordering::Relaxed);
 
-            self.pending_tables.pending_pool.insert(&id, certificate)?;
-            self.pending_tables
-                .pending_pool_index
-                .insert(&certificate.id, &id)?;
+            // The certificate leaves the precedence pool once its previous one is delivered
+            if matches!(&awaiting, Some(awaiting) if awaiting.id == certificate.id) {
+                batch = batch.delete(&self.pending_tables.precedence_pool, certificate.prev_id)?;
+            }
+
+            batch = batch
+                .insert_batch(&self.pending_tables.pending_pool, [(id, certificate)])?
+                .insert_batch(
+                    &self.pending_tables.pending_pool_index,
+                    [(certificate.id, id)],
+                )?;
+            batch = self.insert_pool_entry(
+                batch,
+                certificate.id,
+                PoolEntry {
+                    subnet_id,
+                    inserted_at,
+                    location: PoolLocation::Pending(id),
+                },
+            )?;
+
+            batch.write()?;
+            self.notify_evictions(evicted);
 
             Ok(Some(id))
         } else {
-            self.pending_tables
-                .precedence_pool
-                .insert(&certificate.prev_id, certificate)?;
+            batch = batch.insert_batch(
+                &self.pending_tables.precedence_pool,
+                [(certificate.prev_id, certificate)],
+            )?;
+            batch = self.insert_pool_entry(
+                batch,
+                certificate.id,
+                PoolEntry {
+                    subnet_id,
+                    inserted_at,
+                    location: PoolLocation::Precedence(certificate.prev_id),
+                },
+            )?;
+
+            batch.write()?;
+            self.notify_evictions(evicted);
 
             Ok(None)
         }
     }
 
+    pub fn pool_retention(&self) -> PoolRetention {
+        self.pending_tables
+            .retention
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    pub fn set_pool_retention(&self, retention: PoolRetention) {
+        *self
+            .pending_tables
+            .retention
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = retention;
+    }
+
+    /// Returns a receiver notified of the certificates evicted from the pools from now on
+    pub fn subscribe_evictions(&self) -> broadcast::Receiver<EvictedCertificate> {
+        self.evictions_sender.subscribe()
+    }
+
+    /// Evicts the certificates which stayed in the pools longer than the
+    /// retention allows, along with the eviction records older than that.
+    /// Everything is removed in a single batch.
+    pub fn evict_expired_pending_certificates(
+        &self,
+    ) -> Result<Vec<EvictedCertificate>, StorageError> {
+        let _pools = self.lock_pools();
+
+        let max_age = self.pool_retention().max_age.as_secs();
+        let now = unix_timestamp();
+        let is_expired = |timestamp: u64| now.saturating_sub(timestamp) >= max_age;
+
+        let mut batch = self.pending_tables.pending_pool.batch();
+        let mut evicted = Vec::new();
+
+        // Both indexes are ordered by time, the scans stop at the first entry to keep
+        for ((inserted_at, certificate_id), _) in self.pending_tables.pool_insertions.iter()? {
+            if !is_expired(inserted_at) {
+                break;
+            }
+
+            if let Some(entry) = self.pending_tables.pool_entries.get(&certificate_id)? {
+                let record;
+                (batch, record) = self.evict_from_pools(
+                    batch,
+                    certificate_id,
+                    entry,
+                    EvictionReason::Expired,
+                    now,
+                )?;
+                evicted.push(record);
+            }
+        }
+
+        for ((evicted_at, certificate_id), _) in self.pending_tables.eviction_times.iter()? {
+            if !is_expired(evicted_at) {
+                break;
+            }
+
+            batch = batch.delete(
+                &self.pending_tables.eviction_times,
+                (evicted_at, certificate_id),
+            )?;
+
+            // The certificate may have been evicted again since then
+            if matches!(
+                self.pending_tables.evictions.get(&certificate_id)?,
+                Some(record) if record.evicted_at == evicted_at
+            ) {
+                batch = batch.delete(&self.pending_tables.evictions, certificate_id)?;
+            }
+        }
+
+        batch.write()?;
+        self.notify_evictions(evicted.clone());
+
+        if !evicted.is_empty() {
+            info!(
+                "Evicted {} expired certificates from the pending pools",
+                evicted.len()
+            );
+        }
+
+        Ok(evicted)
+    }
+
+    pub fn get_evicted_certificate(
+        &self,
+        certificate_id: &CertificateId,
+    ) -> Result<Option<EvictedCertificate>, StorageError> {
+        Ok(self.pending_tables.evictions.get(certificate_id)?)
+    }
+
+    pub fn get_evicted_certificates(&self) -> Result<Vec<EvictedCertificate>, StorageError> {
+        Ok(self
+            .pending_tables
+            .evictions
+            .iter()?
+            .map(|(_, record)| record)
+            .collect())
+    }
+
+    /// Evicts the oldest certificates of the subnet awaiting precedence so that
+    /// the incoming certificates fit in its limit. Certificates already in the pending
+    /// pool are never evicted to make room.
+    fn make_room_in_pools(
+        &self,
+        mut batch: DBBatch,
+        subnet_id: &SubnetId,
+        incoming: &[CertificateId],
+        now: u64,
+    ) -> Result<(DBBatch, Vec<EvictedCertificate>), StorageError> {
+        let max_certificates = self.pool_retention().max_certificates(subnet_id);
+
+        let entries: Vec<_> = self
+            .pending_tables
+            .pool_subnet_entries
+            .prefix_iter(subnet_id)?
+            .take_while(|((entry_subnet_id, _, _), _)| entry_subnet_id == subnet_id)
+            .filter(|((_, _, certificate_id), _)| !incoming.contains(certificate_id))
+            .collect();
+
+        let count = entries.len() + incoming.len();
+        if count <= max_certificates {
+            return Ok((batch, Vec::new()));
+        }
+
+        let excess = count - max_certificates;
+
+        // The entries of the subnet are ordered from the oldest one
+        let evictable: Vec<_> = entries
+            .into_iter()
+            .filter(|(_, location)| matches!(location, PoolLocation::Precedence(_)))
+            .take(excess)
+            .collect();
+
+        if evictable.len() < excess {
+            return Err(StorageError::InternalStorage(
+                InternalStorageError::PendingPoolLimitReached(*subnet_id),
+            ));
+        }
+
+        let mut evicted = Vec::new();
+        for ((subnet_id, inserted_at, certificate_id), location) in evictable {
+            let entry = PoolEntry {
+                subnet_id,
+                inserted_at,
+                location,
+            };
+            let record;
+            (batch, record) = self.evict_from_pools(
+                batch,
+                certificate_id,
+                entry,
+                EvictionReason::SubnetLimitReached,
+                now,
+            )?;
+
+            warn!(
+                "Certificate {} of subnet {} evicted from the precedence pool: limit of {} \
+                 certificates reached",
+                record.certificate_id, record.subnet_id, max_certificates
+            );
+            evicted.push(record);
+        }
+
+        Ok((batch, evicted))
+    }
+
+    /// Tracks a certificate entering the pools, replacing its previous
+    /// entry when it moves from the precedence pool to the pending one
+    fn insert_pool_entry(
+        &self,
+        mut batch: DBBatch,
+        certificate_id: CertificateId,
+        entry: PoolEntry,
+    ) -> Result<DBBatch, StorageError> {
+        if let Some(previous) = self.pending_tables.pool_entries.get(&certificate_id)? {
+            batch = self.delete_pool_entry(batch, certificate_id, &previous)?;
+        }
+
+        Ok(batch
+            .insert_batch(
+                &self.pending_tables.pool_subnet_entries,
+                [(
+                    (entry.subnet_id, entry.inserted_at, certificate_id),
+                    entry.location,
+                )],
+            )?
+            .insert_batch(
+                &self.pending_tables.pool_insertions,
+                [((entry.inserted_at, certificate_id), entry.subnet_id)],
+            )?
+            .insert_batch(&self.pending_tables.pool_entries, [(certificate_id, entry)])?)
+    }
+
+    fn delete_pool_entry(
+        &self,
+        batch: DBBatch,
+        certificate_id: CertificateId,
+        entry: &PoolEntry,
+    ) -> Result<DBBatch, StorageError> {
+        Ok(batch
+            .delete(&self.pending_tables.pool_entries, certificate_id)?
+            .delete(
+                &self.pending_tables.pool_subnet_entries,
+                (entry.subnet_id, entry.inserted_at, certificate_id),
+            )?
+            .delete(
+                &self.pending_tables.pool_insertions,
+                (entry.inserted_at, certificate_id),
+            )?)
+    }
+
+    fn lock_pools(&self) -> MutexGuard<'_, ()> {
+        self.pending_tables
+            .pools_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn notify_evictions(&self, evicted: Vec<EvictedCertificate>) {
+        for record in evicted {
+            // Nobody listening to the evictions isn't an error
+            _ = self.evictions_sender.send(record);
+        }
+    }
+
+    /// Records the first reception of the certificates entering the pools
+    fn record_receptions(
+        &self,
+        batch: DBBatch,
+        certificate_ids: impl IntoIterator<Item = CertificateId>,
+    ) -> Result<DBBatch, StorageError> {
+        let received_at = unix_timestamp_millis();
+        let mut receptions = Vec::new();
+
+        for certificate_id in certificate_ids {
+            if self
+                .pending_tables
+                .receptions
+                .get(&certificate_id)?
+                .is_none()
+            {
+                receptions.push((certificate_id, received_at));
+            }
+        }
+
+        Ok(batch.insert_batch(&self.pending_tables.receptions, receptions)?)
+    }
+
+    fn evict_from_pools(
+        &self,
+        mut batch: DBBatch,
+        certificate_id: CertificateId,
+        entry: PoolEntry,
+        reason: EvictionReason,
+        evicted_at: u64,
+    ) -> Result<(DBBatch, EvictedCertificate), StorageError> {
+        let prev_id = match entry.location {
+            PoolLocation::Pending(pending_id) => {
+                let prev_id = self
+                    .pending_tables
+                    .pending_pool
+                    .get(&pending_id)?
+                    .map(|certificate| certificate.prev_id);
+
+                batch = batch
+                    .delete(&self.pending_tables.pending_pool, pending_id)?
+                    .delete(&self.pending_tables.pending_pool_index, certificate_id)?;
+
+                prev_id
+            }
+            PoolLocation::Precedence(prev_id) => {
+                batch = batch.delete(&self.pending_tables.precedence_pool, prev_id)?;
+
+                Some(prev_id)
+            }
+        };
+
+        if let Some(prev_id) = prev_id {
+            batch =
+                self.remove_pool_successor(batch, (entry.subnet_id, prev_id), &certificate_id)?;
+        }
+
+        let record = EvictedCertificate {
+            certificate_id,
+            subnet_id: entry.subnet_id,
+            reason,
+            evicted_at,
+        };
+
+        batch = self
+            .delete_pool_entry(batch, certificate_id, &entry)?
+            .delete(&self.pending_tables.receptions, certificate_id)?
+            .insert_batch(&self.pending_tables.evictions, [(certificate_id, &record)])?
+            .insert_batch(
+                &self.pending_tables.eviction_times,
+                [((evicted_at, certificate_id), entry.subnet_id)],
+            )?;
+
+        Ok((batch, record))
+    }
+
     #[instrument(skip(self, proofs))]
     pub fn insert_unverified_proofs(
         &self,
@@ -214,6 +823,18 @@ impl ValidatorStore {
     ) -> Result<(), StorageError> {
         if let Ok(Some(proof_of_delivery)) = self.get_unverified_proof(&certificate.id) {
             let certificate_id = certificate.id;
+            if let Err(error) =
+                self.check_delivery_position(&certificate, &proof_of_delivery.delivery_position)
+            {
+                // Dropped to be fetched again along with the certificate
+                self.fullnode_store
+                    .perpetual_tables
+                    .unverified
+                    .delete(&certificate_id)?;
+
+                return Err(error);
+            }
+
             debug!(
                 "Certificate Sync: certificate {} is now defined as delivered",
                 certificate_id
@@ -242,6 +863,41 @@ impl ValidatorStore {
         }
     }
 
+    /// Checks that the delivery position given by the Proof of Delivery of a synchronized
+    /// certificate, which the validators don't sign, follows the one of its previous
+    /// certificate. Without the previous certificate, the certificate has to start the
+    /// source stream or the part of it synchronized from a trusted checkpoint.
+    fn check_delivery_position(
+        &self,
+        certificate: &Certificate,
+        position: &CertificateSourceStreamPosition,
+    ) -> Result<(), StorageError> {
+        let perpetual_tables = &self.fullnode_store.perpetual_tables;
+
+        let is_valid = position.subnet_id == certificate.source_subnet_id
+            && match perpetual_tables.certificates.get(&certificate.prev_id)? {
+                Some(previous) => {
+                    let previous_position = previous.proof_of_delivery.delivery_position;
+
+                    previous_position.subnet_id == position.subnet_id
+                        && previous_position.position.increment().ok() == Some(position.position)
+                }
+                None => {
+                    position.position == Position::ZERO
+                        || perpetual_tables.stream_starts.get(&position.subnet_id)?
+                            == Some(position.position)
+                }
+            };
+
+        if !is_valid {
+            return Err(StorageError::InternalStorage(
+                InternalStorageError::InvalidDeliveryPosition(certificate.id),
+            ));
+        }
+
+        Ok(())
+    }
+
     pub fn get_unverified_proof(
         &self,
         certificate_id: &CertificateId,
@@ -253,67 +909,474 @@ impl ValidatorStore {
             .get(certificate_id)?)
     }
 
-    pub fn get_checkpoint_diff(
+    /// Returns the highest position of the source stream of `subnet_id` up to which
+    /// every certificate is delivered, `None` if the first one isn't delivered.
+    ///
+    /// It differs from the source head when certificates were synchronized
+    /// from a trusted checkpoint without the prior history.
+    pub fn get_contiguous_source_head(
         &self,
-        from: Vec<ProofOfDelivery>,
-    ) -> Result<HashMap<SubnetId, Vec<ProofOfDelivery>>, StorageError> {
-        // Parse the from in order to extract the different position per subnets
-        let mut from_positions: HashMap<SubnetId, Vec<ProofOfDelivery>> = from
-            .into_iter()
-            .map(|v| (v.delivery_position.subnet_id, vec![v]))
-            .collect();
+        subnet_id: &SubnetId,
+    ) -> Result<Option<SourceHead>, StorageError> {
+        let mut contiguous_head = None;
 
-        // Request the local head checkpoint
-        let subnets: HashMap<SubnetId, Position> = self
+        for (expected, (position, certificate_id)) in self
             .fullnode_store
-            .index_tables
-            .source_list
+            .perpetual_tables
+            .streams
+            .prefix_iter(subnet_id)?
+            .enumerate()
+        {
+            if *position.position != expected as u64 {
+                break;
+            }
+
+            contiguous_head = Some(SourceHead {
+                certificate_id,
+                subnet_id: *subnet_id,
+                position: position.position,
+            });
+        }
+
+        Ok(contiguous_head)
+    }
+
+    /// Records that the source stream of `subnet_id` is synchronized from the trusted
+    /// `position`, so that the history missing before it isn't seen as a corruption.
+    /// The lowest recorded position is kept.
+    pub fn record_trusted_stream_start(
+        &self,
+        subnet_id: SubnetId,
+        position: Position,
+    ) -> Result<(), StorageError> {
+        let stream_starts = &self.fullnode_store.perpetual_tables.stream_starts;
+
+        if stream_starts
+            .get(&subnet_id)?
+            .map_or(true, |start| *position < *start)
+        {
+            stream_starts.insert(&subnet_id, &position)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the number of Proofs of Delivery received through synchronization
+    /// whose certificate isn't delivered yet
+    pub fn count_unverified_proofs(&self) -> Result<u64, StorageError> {
+        Ok(self
+            .fullnode_store
+            .perpetual_tables
+            .unverified
             .iter()?
-            .map(|(subnet_id, (_, position))| (subnet_id, position))
-            .collect();
+            .count() as u64)
+    }
 
-        // For every local known subnets we want to iterate and check if there
-        // is a delta between the from_position and our head position.
-        for (subnet, local_position) in subnets {
-            let entry = from_positions.entry(subnet).or_default();
+    /// Returns the delivered certificate occupying the source stream position that the
+    /// given certificate is expected to take, if it is a different certificate.
+    ///
+    /// The expected position can only be computed when the previous certificate of the
+    /// source stream is already delivered, otherwise `None` is returned.
+    pub fn get_conflicting_delivered_certificate(
+        &self,
+        certificate: &Certificate,
+    ) -> Result<Option<Certificate>, StorageError> {
+        let subnet_id = certificate.source_subnet_id;
 
-            let certs: Vec<_> = if let Some(position) = entry.pop() {
-                if local_position <= position.delivery_position.position {
-                    continue;
-                }
-                self.fullnode_store
-                    .perpetual_tables
-                    .streams
-                    .prefix_iter_at(&subnet, &position)?
-                    .take(100)
-                    .map(|(_, v)| v)
+        let position = if certificate.prev_id == INITIAL_CERTIFICATE_ID {
+            Position::ZERO
+        } else {
+            match self.fullnode_store.get_certificate(&certificate.prev_id)? {
+                Some(previous) => previous
+                    .proof_of_delivery
+                    .delivery_position
+                    .position
+                    .increment()
+                    .map_err(|e| InternalStorageError::PositionError(e, subnet_id.into()))?,
+                None => return Ok(None),
+            }
+        };
+
+        let position = CertificateSourceStreamPosition::new(subnet_id, position);
+
+        match self
+            .fullnode_store
+            .perpetual_tables
+            .streams
+            .get(&position)?
+        {
+            Some(delivered_id) if delivered_id != certificate.id => Ok(self
+                .fullnode_store
+                .get_certificate(&delivered_id)?
+                .map(|delivered| delivered.certificate)),
+            _ => Ok(None),
+        }
+    }
+
+    /// Persists an equivocation evidence, indexed by the id of the conflicting certificate
+    pub fn insert_equivocation_evidence(
+        &self,
+        evidence: &EquivocationEvidence,
+    ) -> Result<(), StorageError> {
+        self.fullnode_store
+            .perpetual_tables
+            .equivocations
+            .insert(&evidence.conflicting.id, evidence)?;
+
+        Ok(())
+    }
+
+    pub fn get_equivocation_evidence(
+        &self,
+        certificate_id: &CertificateId,
+    ) -> Result<Option<EquivocationEvidence>, StorageError> {
+        Ok(self
+            .fullnode_store
+            .perpetual_tables
+            .equivocations
+            .get(certificate_id)?)
+    }
+
+    pub fn get_equivocation_evidences(&self) -> Result<Vec<EquivocationEvidence>, StorageError> {
+        Ok(self
+            .fullnode_store
+            .perpetual_tables
+            .equivocations
+            .iter()?
+            .map(|(_, evidence)| evidence)
+            .collect())
+    }
+
+    /// Appends a misbehaviour to the ledger of the given validator
+    pub fn insert_misbehaviour(
+        &self,
+        validator_id: &ValidatorId,
+        misbehaviour: Misbehaviour,
+    ) -> Result<(), StorageError> {
+        let tables = &self.fullnode_store.perpetual_tables;
+        let key = MisbehaviourKey(
+            *validator_id,
+            unix_timestamp_millis(),
+            tables.next_misbehaviour.fetch_add(1, Ordering::Relaxed),
+        );
+
+        tables.misbehaviours.insert(&key, &misbehaviour)?;
+
+        Ok(())
+    }
+
+    pub fn get_misbehaviours(
+        &self,
+        validator_id: &ValidatorId,
+    ) -> Result<Vec<Misbehaviour>, StorageError> {
+        Ok(self
+            .fullnode_store
+            .perpetual_tables
+            .misbehaviours
+            .prefix_iter(validator_id)?
+            .take_while(|(MisbehaviourKey(owner, ..), _)| owner == validator_id)
+            .map(|(_, misbehaviour)| misbehaviour)
+            .collect())
+    }
+
+    pub fn get_all_misbehaviours(
+        &self,
+    ) -> Result<Vec<(ValidatorId, Vec<Misbehaviour>)>, StorageError> {
+        let mut ledgers: Vec<(ValidatorId, Vec<Misbehaviour>)> = Vec::new();
+
+        for (MisbehaviourKey(validator_id, ..), misbehaviour) in
+            self.fullnode_store.perpetual_tables.misbehaviours.iter()?
+        {
+            match ledgers.last_mut() {
+                Some((owner, ledger)) if *owner == validator_id => ledger.push(misbehaviour),
+                _ => ledgers.push((validator_id, vec![misbehaviour])),
+            }
+        }
+
+        Ok(ledgers)
+    }
+
+    /// Persists the progress of the broadcast of a certificate
+    pub fn insert_broadcast_state(&self, state: &BroadcastState) -> Result<(), StorageError> {
+        let epoch_store = self.fullnode_store.epoch_store.load();
+        let tables = &epoch_store.tables;
+
+        tables
+            .broadcast_headers
+            .batch()
+            .insert_batch(
+                &tables.broadcast_headers,
+                [(state.certificate.id, state.header())],
+            )?
+            .insert_batch(&tables.broadcast_messages, state.messages())?
+            .write()?;
+
+        Ok(())
+    }
+
+    /// Persists one message of the broadcast of a certificate
+    pub fn insert_broadcast_message(
+        &self,
+        certificate_id: &CertificateId,
+        message: BroadcastMessage,
+        signature: &Signature,
+    ) -> Result<(), StorageError> {
+        self.fullnode_store
+            .epoch_store
+            .load()
+            .tables
+            .broadcast_messages
+            .insert(&BroadcastMessageKey(*certificate_id, message), signature)?;
+
+        Ok(())
+    }
+
+    pub fn get_broadcast_state(
+        &self,
+        certificate_id: &CertificateId,
+    ) -> Result<Option<BroadcastState>, StorageError> {
+        let epoch_store = self.fullnode_store.epoch_store.load();
+        let tables = &epoch_store.tables;
+
+        let Some(header) = tables.broadcast_headers.get(certificate_id)? else {
+            return Ok(None);
+        };
+
+        let messages = tables
+            .broadcast_messages
+            .prefix_iter(certificate_id)?
+            .take_while(|(BroadcastMessageKey(id, _), _)| id == certificate_id)
+            .map(|(BroadcastMessageKey(_, message), signature)| (message, signature));
+
+        Ok(Some(BroadcastState::from_messages(header, messages)))
+    }
+
+    /// Returns the progress of every certificate still in broadcast
+    pub fn get_broadcast_states(&self) -> Result<Vec<BroadcastState>, StorageError> {
+        let epoch_store = self.fullnode_store.epoch_store.load();
+        let tables = &epoch_store.tables;
+
+        let mut messages: HashMap<CertificateId, Vec<_>> = HashMap::new();
+        for (BroadcastMessageKey(certificate_id, message), signature) in
+            tables.broadcast_messages.iter()?
+        {
+            messages
+                .entry(certificate_id)
+                .or_default()
+                .push((message, signature));
+        }
+
+        Ok(tables
+            .broadcast_headers
+            .iter()?
+            .map(|(certificate_id, header)| {
+                BroadcastState::from_messages(
+                    header,
+                    messages.remove(&certificate_id).unwrap_or_default(),
+                )
+            })
+            .collect())
+    }
+
+    pub fn delete_broadcast_state(
+        &self,
+        certificate_id: &CertificateId,
+    ) -> Result<(), StorageError> {
+        let epoch_store = self.fullnode_store.epoch_store.load();
+        let tables = &epoch_store.tables;
+
+        let mut batch = tables
+            .broadcast_headers
+            .batch()
+            .delete(&tables.broadcast_headers, *certificate_id)?;
+        for (key, _) in tables
+            .broadcast_messages
+            .prefix_iter(certificate_id)?
+            .take_while(|(BroadcastMessageKey(id, _), _)| id == certificate_id)
+        {
+            batch = batch.delete(&tables.broadcast_messages, key)?;
+        }
+        batch.write()?;
+
+        Ok(())
+    }
+
+    /// Registers the validator set of an epoch
+    pub fn insert_epoch_validators(
+        &self,
+        epoch_id: EpochId,
+        validators: &HashSet<ValidatorId>,
+    ) -> Result<(), StorageError> {
+        self.fullnode_store.validators_store.insert_validators(
+            epoch_id,
+            validators
+                .iter()
+                .map(|validator_id| validator_id.to_string())
+                .collect(),
+        )
+    }
+
+    /// Returns the validator set in effect during the given epoch
+    pub fn get_epoch_validators(
+        &self,
+        epoch_id: EpochId,
+    ) -> Result<Option<HashSet<ValidatorId>>, StorageError> {
+        Ok(self
+            .fullnode_store
+            .validators_store
+            .get_validators(epoch_id)?
+            .map(|validators| {
+                validators
+                    .iter()
+                    .filter_map(|validator| match ValidatorId::from_str(validator) {
+                        Ok(validator_id) => Some(validator_id),
+                        Err(_) => {
+                            warn!(
+                                "Invalid validator id {validator} registered for epoch {epoch_id}"
+                            );
+                            None
+                        }
+                    })
                     .collect()
-            } else {
+            }))
+    }
+
+    /// Returns at most `limit` Proofs of Delivery of the certificates delivered after the
+    /// given source heads, the subnets without head being returned from their start.
+    ///
+    /// The returned cursor, if any, resumes the diff where this page stopped, in which
+    /// case `from` is ignored and only the subnets of the cursor are returned.
+    pub fn get_checkpoint_diff_page(
+        &self,
+        from: Vec<ProofOfDelivery>,
+        cursor: Option<CheckpointDiffCursor>,
+        limit: usize,
+    ) -> Result<
+        (
+            HashMap<SubnetId, Vec<ProofOfDelivery>>,
+            Option<CheckpointDiffCursor>,
+        ),
+        StorageError,
+    > {
+        let mut starts: Vec<(SubnetId, Position)> = match cursor {
+            Some(CheckpointDiffCursor(starts)) => starts,
+            None => {
+                let from: HashMap<SubnetId, Position> = from
+                    .into_iter()
+                    .map(|proof| {
+                        (
+                            proof.delivery_position.subnet_id,
+                            proof.delivery_position.position,
+                        )
+                    })
+                    .collect();
+
                 self.fullnode_store
-                    .perpetual_tables
-                    .streams
-                    .prefix_iter(&subnet)?
-                    .take(100)
-                    .map(|(_, v)| v)
+                    .index_tables
+                    .source_list
+                    .iter()?
+                    .filter_map(|(subnet_id, (_, head))| match from.get(&subnet_id) {
+                        Some(position) if *position >= head => None,
+                        Some(position) => Some((subnet_id, Position::from(**position + 1))),
+                        None => Some((subnet_id, Position::ZERO)),
+                    })
                     .collect()
-            };
+            }
+        };
+        starts.sort_by_key(|(subnet_id, _)| *subnet_id);
 
+        let mut diff = HashMap::new();
+        let mut next = Vec::new();
+        let mut budget = limit;
+
+        for (subnet_id, start) in starts {
+            if budget == 0 {
+                next.push((subnet_id, start));
+                continue;
+            }
+
+            let mut certificate_ids: Vec<(Position, CertificateId)> = self
+                .fullnode_store
+                .perpetual_tables
+                .streams
+                .prefix_iter_at(
+                    &subnet_id,
+                    &CertificateSourceStreamPosition {
+                        subnet_id,
+                        position: start,
+                    },
+                )?
+                .take(budget + 1)
+                .map(|(position, certificate_id)| (position.position, certificate_id))
+                .collect();
+
+            if certificate_ids.len() > budget {
+                next.push((subnet_id, certificate_ids[budget].0));
+                certificate_ids.truncate(budget);
+            }
+            budget -= certificate_ids.len();
+
+            let ids: Vec<_> = certificate_ids.into_iter().map(|(_, id)| id).collect();
             let proofs: Vec<_> = self
                 .fullnode_store
-                .get_certificates(&certs)?
+                .get_certificates(&ids)?
                 .into_iter()
                 .filter_map(|v| v.map(|c| c.proof_of_delivery))
                 .collect();
 
-            info!(
-                "Certificate Sync: distance between from and head for {} subnet is {}",
-                subnet,
-                proofs.len()
-            );
-            entry.extend_from_slice(&proofs[..]);
+            if !proofs.is_empty() {
+                diff.insert(subnet_id, proofs);
+            }
         }
 
-        Ok(from_positions)
+        Ok((
+            diff,
+            (!next.is_empty()).then_some(CheckpointDiffCursor(next)),
+        ))
+    }
+
+    /// Removes the index entry of a certificate leaving the pools, unless another
+    /// certificate is indexed after the same previous certificate
+    fn remove_pool_successor(
+        &self,
+        batch: DBBatch,
+        key: (SubnetId, CertificateId),
+        certificate_id: &CertificateId,
+    ) -> Result<DBBatch, StorageError> {
+        match self.pending_tables.pool_successors.get(&key)? {
+            Some(successor) if successor == *certificate_id => {
+                Ok(batch.delete(&self.pending_tables.pool_successors, key)?)
+            }
+            _ => Ok(batch),
+        }
+    }
+
+    fn remove_delivered_from_pools(&self, certificate: &Certificate) -> Result<(), StorageError> {
+        let _pools = self.lock_pools();
+
+        let certificate_id = &certificate.id;
+        let mut batch = self.pending_tables.pending_pool.batch();
+        batch = self.remove_pool_successor(
+            batch,
+            (certificate.source_subnet_id, certificate.prev_id),
+            certificate_id,
+        )?;
+
+        if let Some(pending_id) = self.pending_tables.pending_pool_index.get(certificate_id)? {
+            batch = batch
+                .delete(&self.pending_tables.pending_pool, pending_id)?
+                .delete(&self.pending_tables.pending_pool_index, certificate_id)?;
+        }
+
+        if let Some(entry) = self.pending_tables.pool_entries.get(certificate_id)? {
+            batch = self.delete_pool_entry(batch, *certificate_id, &entry)?;
+        }
+
+        batch
+            .delete(&self.pending_tables.receptions, certificate_id)?
+            .write()?;
+
+        Ok(())
     }
 
     #[cfg(test)]
@@ -326,6 +1389,18 @@ impl ValidatorStore {
             self.pending_tables
                 .pending_pool_index
                 .delete(&certificate.id)?;
+            if let Some(entry) = self.pending_tables.pool_entries.get(&certificate.id)? {
+                self.delete_pool_entry(
+                    self.pending_tables.pool_entries.batch(),
+                    certificate.id,
+                    &entry,
+                )?
+                .write()?;
+            }
+            self.pending_tables.receptions.delete(&certificate.id)?;
+            self.pending_tables
+                .pool_successors
+                .delete(&(certificate.source_subnet_id, certificate.prev_id))?;
 
             Ok(certificate)
         } else {
@@ -400,6 +1475,53 @@ impl ReadStore for ValidatorStore {
         self.fullnode_store
             .get_target_source_subnet_list(target_subnet_id)
     }
+
+    fn get_certificate_sequence_number(
+        &self,
+        certificate_id: &CertificateId,
+    ) -> Result<Option<CertificateSequenceNumber>, StorageError> {
+        self.fullnode_store
+            .get_certificate_sequence_number(certificate_id)
+    }
+
+    fn get_certificates_from_sequence_number(
+        &self,
+        from: CertificateSequenceNumber,
+        limit: usize,
+    ) -> Result<Vec<(CertificateDelivered, CertificateSequenceNumber)>, StorageError> {
+        self.fullnode_store
+            .get_certificates_from_sequence_number(from, limit)
+    }
+
+    fn get_certificate_timestamps(
+        &self,
+        certificate_id: &CertificateId,
+    ) -> Result<Option<CertificateTimestamps>, StorageError> {
+        self.fullnode_store
+            .get_certificate_timestamps(certificate_id)
+    }
+
+    fn get_source_certificates_delivered_between(
+        &self,
+        source_subnet_id: &SubnetId,
+        from: Timestamp,
+        to: Timestamp,
+        limit: usize,
+    ) -> Result<Vec<(CertificateDelivered, CertificateTimestamps)>, StorageError> {
+        self.fullnode_store
+            .get_source_certificates_delivered_between(source_subnet_id, from, to, limit)
+    }
+
+    fn get_target_certificates_delivered_between(
+        &self,
+        target_subnet_id: &SubnetId,
+        from: Timestamp,
+        to: Timestamp,
+        limit: usize,
+    ) -> Result<Vec<(CertificateDelivered, CertificateTimestamps)>, StorageError> {
+        self.fullnode_store
+            .get_target_certificates_delivered_between(target_subnet_id, from, to, limit)
+    }
 }
 
 #[async_trait]
@@ -408,19 +1530,25 @@ impl WriteStore for ValidatorStore {
         &self,
         certificate: &CertificateDelivered,
     ) -> Result<CertificatePositions, StorageError> {
+        let received_at = self
+            .pending_tables
+            .receptions
+            .get(&certificate.certificate.id)?;
+
         let position = self
             .fullnode_store
-            .insert_certificate_delivered(certificate)
+            .insert_certificate_delivered_received_at(certificate, received_at)
             .await?;
 
-        if let Ok(Some(pending_id)) = self
-            .pending_tables
-            .pending_pool_index
-            .get(&certificate.certificate.id)
-        {
-            _ = self.pending_tables.pending_pool.delete(&pending_id);
+        if let Err(error) = self.remove_delivered_from_pools(&certificate.certificate) {
+            warn!(
+                "Unable to remove the delivered certificate {} from the pending pools: {error:?}",
+                certificate.certificate.id
+            );
         }
 
+        _ = self.delete_broadcast_state(&certificate.certificate.id);
+
         if let Ok(Some(certificate)) = self
             .pending_tables
             .precedence_pool
@@ -441,3 +1569,10 @@ impl WriteStore for ValidatorStore {
             .await
     }
 }
+
+pub(crate) fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
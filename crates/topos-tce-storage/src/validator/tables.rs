@@ -1,20 +1,36 @@
-use std::{collections::BTreeSet, fs::create_dir_all, path::PathBuf, sync::atomic::AtomicU64};
+use std::{
+    collections::BTreeSet,
+    fs::create_dir_all,
+    path::PathBuf,
+    sync::{atomic::AtomicU64, Mutex, RwLock},
+};
 
-use rocksdb::ColumnFamilyDescriptor;
+use rocksdb::{ColumnFamilyDescriptor, IteratorMode};
 use topos_core::{
-    types::{stream::CertificateSourceStreamPosition, CertificateDelivered, ProofOfDelivery},
-    uci::{Certificate, CertificateId},
+    types::{
+        stream::{CertificateSourceStreamPosition, Position},
+        CertificateDelivered, Misbehaviour, ProofOfDelivery,
+    },
+    uci::{Certificate, CertificateId, SubnetId},
 };
 use tracing::warn;
 
 use crate::{
     constant::cfs,
+    errors::InternalStorageError,
     rocks::{
         constants,
-        db::{default_options, init_with_cfs},
-        db_column::DBColumn,
+        db::{default_options, init_secondary_with_cfs, init_with_cfs},
+        db_column::{Backend, DBBatch, DBColumn},
+        map::Map,
+        memory::MemoryDB,
+        schema,
+    },
+    types::{
+        CertificateTimestamps, CheckpointSequenceNumber, ColumnFamilyStatistics, EpochId,
+        EpochSummary, EquivocationEvidence, EvictedCertificate, MisbehaviourKey, PoolEntry,
+        PoolLocation, PoolRetention, Timestamp, VerifiedCheckpointSummary,
     },
-    types::{EpochId, EpochSummary},
     PendingCertificateId,
 };
 
@@ -26,8 +42,23 @@ pub struct ValidatorPendingTables {
     pub(crate) pending_pool: DBColumn<PendingCertificateId, Certificate>,
     pub(crate) pending_pool_index: DBColumn<CertificateId, PendingCertificateId>,
     pub(crate) precedence_pool: DBColumn<CertificateId, Certificate>,
-    #[allow(unused)]
-    expiration_tracker: (), // Unknown
+    /// Subnet, age and location of every certificate held in the pools
+    pub(crate) pool_entries: DBColumn<CertificateId, PoolEntry>,
+    /// Certificate of the pools following each previous certificate id of each source subnet
+    pub(crate) pool_successors: DBColumn<(SubnetId, CertificateId), CertificateId>,
+    /// Certificates of the pools of each source subnet, oldest first
+    pub(crate) pool_subnet_entries: DBColumn<(SubnetId, u64, CertificateId), PoolLocation>,
+    /// Certificates of the pools ordered by insertion time
+    pub(crate) pool_insertions: DBColumn<(u64, CertificateId), SubnetId>,
+    /// Certificates evicted from the pools before being delivered
+    pub(crate) evictions: DBColumn<CertificateId, EvictedCertificate>,
+    /// Eviction records ordered by eviction time
+    pub(crate) eviction_times: DBColumn<(u64, CertificateId), SubnetId>,
+    /// First time each certificate held in the pools was received
+    pub(crate) receptions: DBColumn<CertificateId, Timestamp>,
+    pub(crate) retention: RwLock<PoolRetention>,
+    /// Serializes the changes of the pools, the limits being checked before writing
+    pub(crate) pools_lock: Mutex<()>,
 }
 
 impl ValidatorPendingTables {
@@ -37,34 +68,206 @@ impl ValidatorPendingTables {
             warn!("Path {:?} does not exist, creating it", path);
             create_dir_all(&path).expect("Cannot create ValidatorPendingTables directory");
         }
-        let cfs = vec![
+
+        let db = init_with_cfs(&path, default_options(), Self::column_families())
+            .unwrap_or_else(|_| panic!("Cannot open DB at {:?}", path));
+
+        Self::with_backend(db.into())
+    }
+
+    /// Opens the tables of the node running at `path` as a read-only secondary
+    /// instance, keeping its own logs at `secondary_path`
+    pub fn open_secondary(mut path: PathBuf, mut secondary_path: PathBuf) -> Self {
+        path.push("pending");
+        secondary_path.push("pending");
+        create_dir_all(&secondary_path).expect("Cannot create ValidatorPendingTables directory");
+
+        let db = init_secondary_with_cfs(
+            &path,
+            &secondary_path,
+            default_options(),
+            Self::column_families(),
+        )
+        .unwrap_or_else(|e| panic!("Cannot open DB at {:?} => error {:?}", path, e))
+        .into();
+
+        schema::PENDING.open_secondary(&db);
+
+        Self::from_backend(&db)
+    }
+
+    fn column_families() -> Vec<ColumnFamilyDescriptor> {
+        vec![
+            ColumnFamilyDescriptor::new(cfs::SCHEMA, default_options()),
             ColumnFamilyDescriptor::new(cfs::PENDING_POOL, default_options()),
             ColumnFamilyDescriptor::new(cfs::PENDING_POOL_INDEX, default_options()),
             ColumnFamilyDescriptor::new(cfs::PRECEDENCE_POOL, default_options()),
-        ];
+            ColumnFamilyDescriptor::new(cfs::POOL_ENTRIES, default_options()),
+            ColumnFamilyDescriptor::new(cfs::POOL_SUCCESSORS, default_options()),
+            ColumnFamilyDescriptor::new(cfs::POOL_SUBNET_ENTRIES, default_options()),
+            ColumnFamilyDescriptor::new(cfs::POOL_INSERTIONS, default_options()),
+            ColumnFamilyDescriptor::new(cfs::EVICTIONS, default_options()),
+            ColumnFamilyDescriptor::new(cfs::EVICTION_TIMES, default_options()),
+            ColumnFamilyDescriptor::new(cfs::RECEPTIONS, default_options()),
+        ]
+    }
 
-        let db = init_with_cfs(&path, default_options(), cfs)
-            .unwrap_or_else(|_| panic!("Cannot open DB at {:?}", path));
+    /// Opens the tables without persisting them on disk
+    pub fn open_in_memory() -> Self {
+        let db = MemoryDB::new(&[
+            cfs::SCHEMA,
+            cfs::PENDING_POOL,
+            cfs::PENDING_POOL_INDEX,
+            cfs::PRECEDENCE_POOL,
+            cfs::POOL_ENTRIES,
+            cfs::POOL_SUCCESSORS,
+            cfs::POOL_SUBNET_ENTRIES,
+            cfs::POOL_INSERTIONS,
+            cfs::EVICTIONS,
+            cfs::EVICTION_TIMES,
+            cfs::RECEPTIONS,
+        ]);
+
+        Self::with_backend(db.into())
+    }
+
+    fn with_backend(db: Backend) -> Self {
+        schema::PENDING.open(&db);
+
+        Self::from_backend(&db)
+    }
+
+    fn from_backend(db: &Backend) -> Self {
+        let pending_pool: DBColumn<PendingCertificateId, Certificate> =
+            DBColumn::from_backend(db, cfs::PENDING_POOL);
+
+        // The pending ids resume after the last one of the pool, to never
+        // overwrite the pending certificates persisted before a restart
+        let next_pending_id = pending_pool
+            .iter_with_mode(IteratorMode::End)
+            .ok()
+            .and_then(|mut pending| pending.next())
+            .map_or(0, |(last, _)| last + 1);
 
         Self {
-            // TODO: Fetch it from the storage
-            next_pending_id: AtomicU64::new(0),
+            next_pending_id: AtomicU64::new(next_pending_id),
             fetching_pool: BTreeSet::new(),
-            pending_pool: DBColumn::reopen(&db, cfs::PENDING_POOL),
-            pending_pool_index: DBColumn::reopen(&db, cfs::PENDING_POOL_INDEX),
-            precedence_pool: DBColumn::reopen(&db, cfs::PRECEDENCE_POOL),
-            expiration_tracker: (),
+            pending_pool,
+            pending_pool_index: DBColumn::from_backend(db, cfs::PENDING_POOL_INDEX),
+            precedence_pool: DBColumn::from_backend(db, cfs::PRECEDENCE_POOL),
+            pool_entries: DBColumn::from_backend(db, cfs::POOL_ENTRIES),
+            pool_successors: DBColumn::from_backend(db, cfs::POOL_SUCCESSORS),
+            pool_subnet_entries: DBColumn::from_backend(db, cfs::POOL_SUBNET_ENTRIES),
+            pool_insertions: DBColumn::from_backend(db, cfs::POOL_INSERTIONS),
+            evictions: DBColumn::from_backend(db, cfs::EVICTIONS),
+            eviction_times: DBColumn::from_backend(db, cfs::EVICTION_TIMES),
+            receptions: DBColumn::from_backend(db, cfs::RECEPTIONS),
+            retention: RwLock::new(PoolRetention::default()),
+            pools_lock: Mutex::new(()),
         }
     }
+
+    /// Applies the latest changes of the primary instance when opened as a secondary one
+    pub(crate) fn catch_up(&self) -> Result<(), InternalStorageError> {
+        self.pending_pool.backend.try_catch_up_with_primary()
+    }
+
+    pub(crate) fn statistics(&self) -> Result<Vec<ColumnFamilyStatistics>, InternalStorageError> {
+        self.pending_pool
+            .backend
+            .statistics(schema::PENDING.database)
+    }
+}
+
+/// Migration of the pending database indexing the certificates of both pools by
+/// their source subnet, their age and the id of their previous certificate. The
+/// certificates held before the pools were indexed are aged from the migration.
+pub(crate) fn index_pools(db: &Backend, batch: DBBatch) -> Result<DBBatch, InternalStorageError> {
+    let pending_pool: DBColumn<PendingCertificateId, Certificate> =
+        DBColumn::from_backend(db, cfs::PENDING_POOL);
+    let precedence_pool: DBColumn<CertificateId, Certificate> =
+        DBColumn::from_backend(db, cfs::PRECEDENCE_POOL);
+    let pool_entries: DBColumn<CertificateId, PoolEntry> =
+        DBColumn::from_backend(db, cfs::POOL_ENTRIES);
+    let pool_successors: DBColumn<(SubnetId, CertificateId), CertificateId> =
+        DBColumn::from_backend(db, cfs::POOL_SUCCESSORS);
+    let pool_subnet_entries: DBColumn<(SubnetId, u64, CertificateId), PoolLocation> =
+        DBColumn::from_backend(db, cfs::POOL_SUBNET_ENTRIES);
+    let pool_insertions: DBColumn<(u64, CertificateId), SubnetId> =
+        DBColumn::from_backend(db, cfs::POOL_INSERTIONS);
+
+    let inserted_at = super::unix_timestamp();
+
+    let certificates: Vec<_> = pending_pool
+        .iter()?
+        .map(|(pending_id, certificate)| (certificate, PoolLocation::Pending(pending_id)))
+        .chain(
+            precedence_pool
+                .iter()?
+                .map(|(prev_id, certificate)| (certificate, PoolLocation::Precedence(prev_id))),
+        )
+        .collect();
+
+    batch
+        .insert_batch(
+            &pool_entries,
+            certificates.iter().map(|(certificate, location)| {
+                (
+                    certificate.id,
+                    PoolEntry {
+                        subnet_id: certificate.source_subnet_id,
+                        inserted_at,
+                        location: *location,
+                    },
+                )
+            }),
+        )?
+        .insert_batch(
+            &pool_successors,
+            certificates.iter().map(|(certificate, _)| {
+                (
+                    (certificate.source_subnet_id, certificate.prev_id),
+                    certificate.id,
+                )
+            }),
+        )?
+        .insert_batch(
+            &pool_subnet_entries,
+            certificates.iter().map(|(certificate, location)| {
+                (
+                    (certificate.source_subnet_id, inserted_at, certificate.id),
+                    *location,
+                )
+            }),
+        )?
+        .insert_batch(
+            &pool_insertions,
+            certificates.iter().map(|(certificate, _)| {
+                ((inserted_at, certificate.id), certificate.source_subnet_id)
+            }),
+        )
 }
 
 /// Data that shouldn't be purged at all.
 pub struct ValidatorPerpetualTables {
     pub(crate) certificates: DBColumn<CertificateId, CertificateDelivered>,
     pub(crate) streams: DBColumn<CertificateSourceStreamPosition, CertificateId>,
-    #[allow(unused)]
-    epoch_chain: DBColumn<EpochId, EpochSummary>,
+    /// First and last checkpoints of every epoch
+    pub(crate) epoch_chain: DBColumn<EpochId, EpochSummary>,
+    /// Chain of the checkpoints agreed on by the validators
+    pub(crate) checkpoints: DBColumn<CheckpointSequenceNumber, VerifiedCheckpointSummary>,
     pub(crate) unverified: DBColumn<CertificateId, ProofOfDelivery>,
+    /// Equivocation evidences indexed by the id of the conflicting certificate
+    pub(crate) equivocations: DBColumn<CertificateId, EquivocationEvidence>,
+    /// Misbehaviours ledger of each validator, one entry per misbehaviour
+    pub(crate) misbehaviours: DBColumn<MisbehaviourKey, Misbehaviour>,
+    /// Counter of the misbehaviours inserted since the tables were opened
+    pub(crate) next_misbehaviour: AtomicU64,
+    /// Reception and delivery times of every delivered certificate
+    pub(crate) timestamps: DBColumn<CertificateId, CertificateTimestamps>,
+    /// Position from which the source streams synchronized from a trusted checkpoint
+    /// are complete, the certificates delivered before it being optional
+    pub(crate) stream_starts: DBColumn<SubnetId, Position>,
 }
 
 impl ValidatorPerpetualTables {
@@ -74,27 +277,113 @@ impl ValidatorPerpetualTables {
             warn!("Path {:?} does not exist, creating it", path);
             create_dir_all(&path).expect("Cannot create ValidatorPerpetualTables directory");
         }
+
+        let db =
+            init_with_cfs(&path, default_options(), Self::column_families()).unwrap_or_else(|e| {
+                panic!("Cannot open DB at {:?} => error {:?}", path, e);
+            });
+
+        Self::with_backend(db.into())
+    }
+
+    /// Opens the tables of the node running at `path` as a read-only secondary
+    /// instance, keeping its own logs at `secondary_path`
+    pub fn open_secondary(mut path: PathBuf, mut secondary_path: PathBuf) -> Self {
+        path.push("perpetual");
+        secondary_path.push("perpetual");
+        create_dir_all(&secondary_path).expect("Cannot create ValidatorPerpetualTables directory");
+
+        let db = init_secondary_with_cfs(
+            &path,
+            &secondary_path,
+            default_options(),
+            Self::column_families(),
+        )
+        .unwrap_or_else(|e| panic!("Cannot open DB at {:?} => error {:?}", path, e))
+        .into();
+
+        schema::PERPETUAL.open_secondary(&db);
+
+        Self::from_backend(&db)
+    }
+
+    fn column_families() -> Vec<ColumnFamilyDescriptor> {
         let mut options_stream = default_options();
         options_stream.set_prefix_extractor(rocksdb::SliceTransform::create_fixed_prefix(
             constants::SOURCE_STREAMS_PREFIX_SIZE,
         ));
 
-        let cfs = vec![
+        vec![
+            ColumnFamilyDescriptor::new(cfs::SCHEMA, default_options()),
             ColumnFamilyDescriptor::new(cfs::CERTIFICATES, default_options()),
             ColumnFamilyDescriptor::new(cfs::STREAMS, options_stream),
             ColumnFamilyDescriptor::new(cfs::EPOCH_CHAIN, default_options()),
+            ColumnFamilyDescriptor::new(cfs::CHECKPOINTS, default_options()),
             ColumnFamilyDescriptor::new(cfs::UNVERIFIED, default_options()),
-        ];
+            ColumnFamilyDescriptor::new(cfs::EQUIVOCATIONS, default_options()),
+            ColumnFamilyDescriptor::new(cfs::MISBEHAVIOURS, default_options()),
+            ColumnFamilyDescriptor::new(cfs::CERTIFICATE_TIMESTAMPS, default_options()),
+            ColumnFamilyDescriptor::new(cfs::STREAM_STARTS, default_options()),
+        ]
+    }
+
+    /// Opens the tables without persisting them on disk
+    pub fn open_in_memory() -> Self {
+        let db = MemoryDB::new(&[
+            cfs::SCHEMA,
+            cfs::CERTIFICATES,
+            cfs::STREAMS,
+            cfs::EPOCH_CHAIN,
+            cfs::CHECKPOINTS,
+            cfs::UNVERIFIED,
+            cfs::EQUIVOCATIONS,
+            cfs::MISBEHAVIOURS,
+            cfs::CERTIFICATE_TIMESTAMPS,
+            cfs::STREAM_STARTS,
+        ]);
+
+        Self::with_backend(db.into())
+    }
+
+    fn with_backend(db: Backend) -> Self {
+        schema::PERPETUAL.open(&db);
 
-        let db = init_with_cfs(&path, default_options(), cfs).unwrap_or_else(|e| {
-            panic!("Cannot open DB at {:?} => error {:?}", path, e);
-        });
+        Self::from_backend(&db)
+    }
 
+    fn from_backend(db: &Backend) -> Self {
         Self {
-            certificates: DBColumn::reopen(&db, cfs::CERTIFICATES),
-            streams: DBColumn::reopen(&db, cfs::STREAMS),
-            epoch_chain: DBColumn::reopen(&db, cfs::EPOCH_CHAIN),
-            unverified: DBColumn::reopen(&db, cfs::UNVERIFIED),
+            certificates: DBColumn::from_backend(db, cfs::CERTIFICATES),
+            streams: DBColumn::from_backend(db, cfs::STREAMS),
+            epoch_chain: DBColumn::from_backend(db, cfs::EPOCH_CHAIN),
+            checkpoints: DBColumn::from_backend(db, cfs::CHECKPOINTS),
+            unverified: DBColumn::from_backend(db, cfs::UNVERIFIED),
+            equivocations: DBColumn::from_backend(db, cfs::EQUIVOCATIONS),
+            misbehaviours: DBColumn::from_backend(db, cfs::MISBEHAVIOURS),
+            next_misbehaviour: AtomicU64::new(0),
+            timestamps: DBColumn::from_backend(db, cfs::CERTIFICATE_TIMESTAMPS),
+            stream_starts: DBColumn::from_backend(db, cfs::STREAM_STARTS),
         }
     }
+
+    /// Applies the latest changes of the primary instance when opened as a secondary one
+    pub(crate) fn catch_up(&self) -> Result<(), InternalStorageError> {
+        self.certificates.backend.try_catch_up_with_primary()
+    }
+
+    /// Returns the current epoch, which only changes once a checkpoint agreeing
+    /// on the validator set of the next epoch is appended to the checkpoint chain
+    pub fn current_epoch(&self) -> Result<EpochId, InternalStorageError> {
+        Ok(self
+            .checkpoints
+            .iter_with_mode(IteratorMode::End)?
+            .next()
+            .map_or(0, |(_, last)| last.summary().next_epoch()))
+    }
+
+    pub(crate) fn statistics(&self) -> Result<Vec<ColumnFamilyStatistics>, InternalStorageError> {
+        self.certificates
+            .backend
+            .statistics(schema::PERPETUAL.database)
+    }
 }
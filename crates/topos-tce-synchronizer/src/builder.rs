@@ -1,15 +1,17 @@
-use std::{future::IntoFuture, sync::Arc};
+use std::{collections::HashSet, future::IntoFuture, sync::Arc};
 
 use tokio::{spawn, sync::mpsc};
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::sync::CancellationToken;
+use topos_core::types::{ProofOfDelivery, ValidatorId};
 use topos_p2p::NetworkClient;
 use topos_tce_gatekeeper::GatekeeperClient;
-use topos_tce_storage::validator::ValidatorStore;
+use topos_tce_storage::{store::ReadStore, validator::ValidatorStore};
 
 use crate::{
     checkpoints_collector::{
-        CheckpointSynchronizer, CheckpointsCollectorConfig, CheckpointsCollectorError,
+        verify_trusted_checkpoint, CheckpointSynchronizer, CheckpointsCollectorConfig,
+        CheckpointsCollectorError,
     },
     Synchronizer, SynchronizerError, SynchronizerEvent,
 };
@@ -19,6 +21,19 @@ pub struct SynchronizerBuilder {
     network_client: Option<NetworkClient>,
     store: Option<Arc<ValidatorStore>>,
     sync_interval_seconds: u64,
+    /// Validators allowed to sign the Ready messages of the synchronized proofs (required)
+    validators: HashSet<ValidatorId>,
+    /// Number of Ready messages required for a synchronized proof to be valid,
+    /// between 1 and the number of validators (required)
+    delivery_threshold: u64,
+    /// Proofs of Delivery of the source heads to start the synchronization from
+    trusted_checkpoint: Vec<ProofOfDelivery>,
+    /// Validator set of the epoch of the trusted checkpoint
+    trusted_validators: HashSet<ValidatorId>,
+    /// Number of Ready messages required for a proof of the trusted checkpoint to be valid
+    trusted_delivery_threshold: u64,
+    /// Whether the history prior to the trusted checkpoint is synchronized (default: false)
+    backfill: bool,
     /// Size of the channel producing events (default: 100)
     event_channel_size: usize,
     /// CancellationToken used to trigger shutdown of the Synchronizer
@@ -32,6 +47,12 @@ impl Default for SynchronizerBuilder {
             network_client: None,
             store: None,
             sync_interval_seconds: 1,
+            validators: HashSet::new(),
+            delivery_threshold: 0,
+            trusted_checkpoint: Vec::new(),
+            trusted_validators: HashSet::new(),
+            trusted_delivery_threshold: 0,
+            backfill: false,
             event_channel_size: 100,
             shutdown: None,
         }
@@ -49,6 +70,48 @@ impl SynchronizerBuilder {
                 CheckpointsCollectorError::NoStore,
             ))?;
         };
+
+        // Proofs of Delivery are only trusted if signed by enough known validators
+        if self.validators.is_empty() {
+            return Err(SynchronizerError::CheckpointsCollectorError(
+                CheckpointsCollectorError::NoValidators,
+            ));
+        }
+        if self.delivery_threshold == 0 || self.delivery_threshold > self.validators.len() as u64 {
+            return Err(SynchronizerError::CheckpointsCollectorError(
+                CheckpointsCollectorError::InvalidDeliveryThreshold {
+                    threshold: self.delivery_threshold,
+                    validators: self.validators.len(),
+                },
+            ));
+        }
+
+        let trusted_heads = verify_trusted_checkpoint(
+            std::mem::take(&mut self.trusted_checkpoint),
+            &self.trusted_validators,
+            self.trusted_delivery_threshold,
+        )?;
+
+        // The streams behind the trusted checkpoint restart from it, the history
+        // before it is only synchronized on backfill
+        if let Some(store) = &self.store {
+            let local_heads = store
+                .get_checkpoint()
+                .map_err(CheckpointsCollectorError::from)?;
+
+            for (subnet_id, trusted) in &trusted_heads {
+                let position = trusted.delivery_position.position;
+                if local_heads
+                    .get(subnet_id)
+                    .map_or(true, |head| *head.position < *position)
+                {
+                    store
+                        .record_trusted_stream_start(*subnet_id, position)
+                        .map_err(CheckpointsCollectorError::from)?;
+                }
+            }
+        }
+
         let (events, events_recv) = mpsc::channel(self.event_channel_size);
         let (sync_events, checkpoints_collector_stream) = mpsc::channel(self.event_channel_size);
 
@@ -56,7 +119,10 @@ impl SynchronizerBuilder {
 
         spawn(
             CheckpointSynchronizer {
-                config: CheckpointsCollectorConfig::default(),
+                config: CheckpointsCollectorConfig {
+                    backfill: self.backfill,
+                    ..Default::default()
+                },
                 network: if let Some(network) = self.network_client {
                     network
                 } else {
@@ -79,6 +145,12 @@ impl SynchronizerBuilder {
                     ))?;
                 },
                 current_request_id: None,
+                validators: self.validators,
+                delivery_threshold: self.delivery_threshold,
+                peer_scores: Default::default(),
+                certificates_fetched: 0,
+                last_pool_sync: None,
+                trusted_heads,
                 shutdown: shutdown.child_token(),
                 events: sync_events,
             }
@@ -121,6 +193,39 @@ impl SynchronizerBuilder {
         self
     }
 
+    pub fn with_validators(mut self, validators: HashSet<ValidatorId>) -> Self {
+        self.validators = validators;
+
+        self
+    }
+
+    pub fn with_delivery_threshold(mut self, delivery_threshold: u64) -> Self {
+        self.delivery_threshold = delivery_threshold;
+
+        self
+    }
+
+    /// Sets the Proofs of Delivery of the source heads to start the synchronization from,
+    /// verified against the validator set of their epoch and its delivery threshold
+    pub fn with_trusted_checkpoint(
+        mut self,
+        trusted_checkpoint: Vec<ProofOfDelivery>,
+        validators: HashSet<ValidatorId>,
+        delivery_threshold: u64,
+    ) -> Self {
+        self.trusted_checkpoint = trusted_checkpoint;
+        self.trusted_validators = validators;
+        self.trusted_delivery_threshold = delivery_threshold;
+
+        self
+    }
+
+    pub fn with_backfill(mut self, backfill: bool) -> Self {
+        self.backfill = backfill;
+
+        self
+    }
+
     pub fn with_shutdown(mut self, shutdown: CancellationToken) -> Self {
         self.shutdown = Some(shutdown);
 
@@ -1,15 +1,56 @@
+use std::time::Duration;
+
 pub struct CheckpointsCollectorConfig {
     pub(crate) sync_interval_seconds: u64,
+    /// Minimum delay between two synchronizations of the pending pools of the peers,
+    /// which are more expensive to serve than the source heads
+    pub(crate) pool_sync_interval: Duration,
+    /// Number of peers asked concurrently for their checkpoint and certificates
+    pub(crate) max_concurrent_peers: usize,
+    /// Maximum duration of a single request to a peer
+    pub(crate) request_timeout: Duration,
+    /// Number of other peers asked for a chunk of certificates when a peer fails to provide it
+    pub(crate) max_retries: usize,
+    /// Number of certificates asked per request
+    pub(crate) certificates_chunk_size: usize,
+    /// Delay before asking another peer once a peer refused a request because of its rate limits
+    pub(crate) throttle_backoff: Duration,
+    /// Number of Proofs of Delivery asked per checkpoint page
+    pub(crate) checkpoint_page_size: u32,
+    /// Maximum number of checkpoint pages fetched from a peer during a single round
+    pub(crate) max_checkpoint_pages: usize,
+    /// Whether the history prior to the trusted checkpoint is synchronized
+    /// once the node is up to date
+    pub(crate) backfill: bool,
 }
 
+// The defaults keep the requests of a catching-up node under the default
+// `SynchronizerServiceLimits` of the peers it fetches from
 impl CheckpointsCollectorConfig {
     const SYNC_INTERVAL_SECONDS: u64 = 10;
+    const POOL_SYNC_INTERVAL: Duration = Duration::from_secs(30);
+    const MAX_CONCURRENT_PEERS: usize = 3;
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+    const MAX_RETRIES: usize = 2;
+    const CERTIFICATES_CHUNK_SIZE: usize = 100;
+    const THROTTLE_BACKOFF: Duration = Duration::from_secs(1);
+    const CHECKPOINT_PAGE_SIZE: u32 = 500;
+    const MAX_CHECKPOINT_PAGES: usize = 10;
 }
 
 impl Default for CheckpointsCollectorConfig {
     fn default() -> Self {
         Self {
             sync_interval_seconds: Self::SYNC_INTERVAL_SECONDS,
+            pool_sync_interval: Self::POOL_SYNC_INTERVAL,
+            max_concurrent_peers: Self::MAX_CONCURRENT_PEERS,
+            request_timeout: Self::REQUEST_TIMEOUT,
+            max_retries: Self::MAX_RETRIES,
+            certificates_chunk_size: Self::CERTIFICATES_CHUNK_SIZE,
+            throttle_backoff: Self::THROTTLE_BACKOFF,
+            checkpoint_page_size: Self::CHECKPOINT_PAGE_SIZE,
+            max_checkpoint_pages: Self::MAX_CHECKPOINT_PAGES,
+            backfill: false,
         }
     }
 }
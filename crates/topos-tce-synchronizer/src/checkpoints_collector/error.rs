@@ -1,5 +1,7 @@
 use thiserror::Error;
 use tokio::sync::oneshot::error::RecvError;
+use topos_core::{errors::ProofOfDeliveryError, uci::CertificateId};
+use topos_tce_storage::errors::StorageError;
 
 #[derive(Error, Debug)]
 pub enum CheckpointsCollectorError {
@@ -23,4 +25,22 @@ pub enum CheckpointsCollectorError {
 
     #[error("Unable to start the CheckpointsCollector: No store provided")]
     NoStore,
+
+    #[error("Unable to start the CheckpointsCollector: No validators provided")]
+    NoValidators,
+
+    #[error(
+        "Unable to start the CheckpointsCollector: invalid delivery threshold {threshold} for \
+         {validators} validators"
+    )]
+    InvalidDeliveryThreshold { threshold: u64, validators: usize },
+
+    #[error("Unable to start the CheckpointsCollector: {0}")]
+    Storage(#[from] StorageError),
+
+    #[error("Invalid Proof of Delivery of {certificate_id} in the trusted checkpoint: {error}")]
+    InvalidTrustedCheckpoint {
+        certificate_id: CertificateId,
+        error: ProofOfDeliveryError,
+    },
 }
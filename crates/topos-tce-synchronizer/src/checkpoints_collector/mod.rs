@@ -3,12 +3,19 @@ use std::{
     future::IntoFuture,
     str::FromStr,
     sync::Arc,
+    time::Duration,
 };
 
-use futures::{future::BoxFuture, FutureExt};
-use tokio::sync::mpsc;
+use futures::{
+    future::{join_all, BoxFuture},
+    stream, FutureExt, StreamExt,
+};
+use tokio::{
+    sync::mpsc,
+    time::{timeout, Instant},
+};
 use tokio_util::sync::CancellationToken;
-use tonic::Status;
+use tonic::{Code, Status};
 use topos_core::{
     api::grpc::{
         self,
@@ -16,11 +23,12 @@ use topos_core::{
         tce::v1::{
             synchronizer_service_client::SynchronizerServiceClient,
             synchronizer_service_server::SynchronizerServiceServer, CheckpointRequest,
-            CheckpointResponse, FetchCertificatesRequest,
+            CheckpointResponse, FetchCertificatesRequest, FetchCertificatesResponse,
+            PendingCertificateIdsRequest,
         },
     },
-    errors::GrpcParsingError,
-    types::ProofOfDelivery,
+    errors::{GrpcParsingError, ProofOfDeliveryError},
+    types::{ProofOfDelivery, ValidatorId},
     uci::{Certificate, CertificateId, SubnetId},
 };
 
@@ -32,13 +40,15 @@ use uuid::Uuid;
 
 mod config;
 mod error;
+mod peers;
 #[cfg(test)]
 mod tests;
 
 pub use config::CheckpointsCollectorConfig;
 pub use error::CheckpointsCollectorError;
 
-use crate::SynchronizerService;
+use self::peers::PeerScores;
+use crate::{SyncProgress, SynchronizerService};
 
 pub struct CheckpointSynchronizer {
     pub(crate) config: CheckpointsCollectorConfig,
@@ -50,9 +60,21 @@ pub struct CheckpointSynchronizer {
 
     pub(crate) current_request_id: Option<APIUuid>,
 
+    /// Validators allowed to sign the Ready messages of a Proof of Delivery
+    pub(crate) validators: HashSet<ValidatorId>,
+    /// Number of Ready messages required for a Proof of Delivery to be valid
+    pub(crate) delivery_threshold: u64,
+    /// Scores of the peers asked for checkpoints and certificates
+    pub(crate) peer_scores: PeerScores,
+    /// Number of certificates fetched since the start
+    pub(crate) certificates_fetched: u64,
+    /// Last synchronization of the pending pools of the peers
+    pub(crate) last_pool_sync: Option<Instant>,
+    /// Verified Proofs of Delivery of the trusted checkpoint, per subnet
+    pub(crate) trusted_heads: HashMap<SubnetId, ProofOfDelivery>,
+
     pub(crate) shutdown: CancellationToken,
 
-    #[allow(dead_code)]
     pub(crate) events: mpsc::Sender<CheckpointsCollectorEvent>,
 }
 
@@ -73,11 +95,13 @@ impl IntoFuture for CheckpointSynchronizer {
                         // On every tick, checking if there is a pending synchronization
                         // If there is, skip
                         // If there is not,
-                        //  1. Ask a random peer for the diff between local and its latest checkpoint
-                        //  2. Validate the PoD diff, if fail, go back to 1
-                        //  3. Based on the diff, check if we already have some of the certs
-                        //      - Fetch every missing certs from one peer
+                        //  1. Ask the best peers for the diff between local and their latest checkpoint
+                        //  2. Validate the PoD diffs, invalid proofs penalize the peer
+                        //  3. Based on the diffs, check if we already have some of the certs
+                        //      - Fetch every missing certs, spreading the chunks across the best peers
                         //      - Each certs triggers a precedence check
+                        //  4. Fetch the certificates pending in the pools of the peers
+                        //     which are unknown locally, to broadcast them again
                         if self.current_request_id.is_none() {
                             if let Err(error) = self.initiate_request().await {
                                 warn!("Unsuccessful sync due to: {}", error);
@@ -106,9 +130,18 @@ enum SyncError {
     #[allow(unused)]
     UnableToParseSubnetId,
 
-    #[error("Gatekeeper returned no peer")]
+    #[error("Gatekeeper returned no trusted peer")]
     NoPeerAvailable,
 
+    #[error("No peer answered the checkpoint request")]
+    NoCheckpointReceived,
+
+    #[error("Peer {0} didn't answer in time")]
+    Timeout(PeerId),
+
+    #[error("Unable to fetch {0} certificates from any peer")]
+    MissingCertificates(usize),
+
     #[error(transparent)]
     GrpcParsingError(#[from] GrpcParsingError),
 
@@ -128,66 +161,245 @@ enum SyncError {
     Grpc(#[from] Status),
 }
 
+impl SyncError {
+    /// Whether the peer refused the request because of its rate limits
+    fn is_throttled(&self) -> bool {
+        matches!(self, Self::Grpc(status) if status.code() == Code::ResourceExhausted)
+    }
+}
+
+/// Kind of synchronization round
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SyncRound {
+    /// Catching up with the source heads of the peers
+    Heads,
+    /// Synchronizing the history missing before the source heads
+    Backfill,
+}
+
+/// Outcome of a request to a peer, used to update its score
+#[derive(Debug)]
+enum PeerOutcome {
+    Answered(Duration),
+    Failed,
+    /// The peer answered with the given number of unexpected items
+    Invalid(Duration, usize),
+    /// The peer refused the request because of its rate limits, which isn't held against it
+    Throttled,
+}
+
 impl CheckpointSynchronizer {
-    async fn ask_for_checkpoint(
+    /// Selects the best trusted peers to ask, at most `max_concurrent_peers` of them
+    async fn select_peers(&self) -> Result<Vec<PeerId>, SyncError> {
+        let peers = self
+            .gatekeeper
+            .get_random_peers(self.config.max_concurrent_peers * 2)
+            .await
+            .map_err(|_| SyncError::UnableToFetchTargetPeer)?;
+
+        let mut peers = self.peer_scores.rank(peers);
+        peers.truncate(self.config.max_concurrent_peers);
+
+        if peers.is_empty() {
+            return Err(SyncError::NoPeerAvailable);
+        }
+
+        Ok(peers)
+    }
+
+    /// Returns the Proofs of Delivery of the given delivered certificates
+    fn proofs_of(
         &self,
-        peer: PeerId,
-    ) -> Result<HashMap<SubnetId, Vec<ProofOfDelivery>>, SyncError> {
-        let request_id: APIUuid = Uuid::new_v4().into();
-
-        let checkpoint: Vec<grpc::tce::v1::ProofOfDelivery> = {
-            let certificate_ids = self
-                .store
-                .get_checkpoint()?
-                .values()
-                .map(|head| head.certificate_id)
-                .collect::<Vec<_>>();
-
-            self.store
-                .get_certificates(&certificate_ids[..])?
+        certificate_ids: &[CertificateId],
+    ) -> Result<Vec<ProofOfDelivery>, SyncError> {
+        Ok(self
+            .store
+            .get_certificates(certificate_ids)?
+            .into_iter()
+            .filter_map(|value| {
+                value.map(|delivered_certificate| delivered_certificate.proof_of_delivery)
+            })
+            .collect())
+    }
+
+    /// Returns the Proofs of Delivery of the local source heads, replaced by the
+    /// ones of the trusted checkpoint for the subnets which are behind it
+    fn local_checkpoint(&self) -> Result<Vec<grpc::tce::v1::ProofOfDelivery>, SyncError> {
+        let certificate_ids = self
+            .store
+            .get_checkpoint()?
+            .values()
+            .map(|head| head.certificate_id)
+            .collect::<Vec<_>>();
+
+        Ok(
+            with_trusted_heads(self.proofs_of(&certificate_ids)?, &self.trusted_heads)
                 .into_iter()
-                .filter_map(|value| {
-                    value.map(|delivered_certificate| delivered_certificate.proof_of_delivery)
-                })
                 .map(Into::into)
-                .collect()
-        };
+                .collect(),
+        )
+    }
 
-        let req = CheckpointRequest {
-            request_id: Some(request_id),
-            checkpoint,
-        };
+    /// Returns the Proofs of Delivery to ask the peers for the history missing before
+    /// the local source heads, `None` if there is no gap in the local history.
+    /// The subnets whose first certificate is missing are left out so that the peers
+    /// answer from the start of their stream.
+    fn backfill_checkpoint(
+        &self,
+    ) -> Result<Option<Vec<grpc::tce::v1::ProofOfDelivery>>, SyncError> {
+        let mut has_gap = false;
+        let mut certificate_ids = Vec::new();
+
+        for (subnet_id, head) in self.store.get_checkpoint()? {
+            match self.store.get_contiguous_source_head(&subnet_id)? {
+                Some(contiguous_head) => {
+                    has_gap |= contiguous_head.position != head.position;
+                    certificate_ids.push(contiguous_head.certificate_id);
+                }
+                None => has_gap = true,
+            }
+        }
+
+        if !has_gap {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            self.proofs_of(&certificate_ids)?
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+        ))
+    }
+
+    /// Returns the Proofs of Delivery of the trusted checkpoint whose certificate
+    /// isn't delivered yet
+    fn missing_trusted_heads(&self) -> Result<CheckpointDiff, SyncError> {
+        let mut diff = CheckpointDiff::new();
+
+        for (subnet_id, proof) in &self.trusted_heads {
+            if self.store.get_certificate(&proof.certificate_id)?.is_none() {
+                diff.insert(*subnet_id, vec![proof.clone()]);
+            }
+        }
+
+        Ok(diff)
+    }
+
+    /// Removes the Proofs of Delivery of the certificates already delivered
+    fn without_delivered(&self, diff: CheckpointDiff) -> Result<CheckpointDiff, SyncError> {
+        diff.into_iter()
+            .map(|(subnet_id, proofs)| {
+                let certificate_ids: Vec<_> =
+                    proofs.iter().map(|proof| proof.certificate_id).collect();
+                let delivered = self.store.get_certificates(&certificate_ids)?;
+
+                let proofs: Vec<_> = proofs
+                    .into_iter()
+                    .zip(delivered)
+                    .filter_map(|(proof, delivered)| delivered.is_none().then_some(proof))
+                    .collect();
+
+                Ok::<_, SyncError>((subnet_id, proofs))
+            })
+            .collect()
+    }
+
+    async fn ask_for_checkpoint(
+        &self,
+        peer: PeerId,
+        checkpoint: Vec<grpc::tce::v1::ProofOfDelivery>,
+    ) -> Result<(CheckpointDiff, Duration), SyncError> {
+        let mut diff = CheckpointDiff::new();
+        let mut latency = None;
+        let mut continuation_token = Vec::new();
 
         debug!("Asking {} for latest checkpoint", peer);
-        let mut client: SynchronizerServiceClient<_> = self
-            .network
-            .new_grpc_client::<SynchronizerServiceClient<_>, SynchronizerServiceServer<SynchronizerService>>(peer)
-            .await
-            .unwrap();
+        for _ in 0..self.config.max_checkpoint_pages {
+            let request_id: APIUuid = Uuid::new_v4().into();
+            let req = CheckpointRequest {
+                request_id: Some(request_id),
+                checkpoint: checkpoint.clone(),
+                limit: self.config.checkpoint_page_size,
+                continuation_token,
+            };
 
-        let response: CheckpointResponse = client.fetch_checkpoint(req).await.unwrap().into_inner();
+            let started = Instant::now();
+            let response = timeout(self.config.request_timeout, async {
+                let mut client: SynchronizerServiceClient<_> = self
+                    .network
+                    .new_grpc_client::<SynchronizerServiceClient<_>, SynchronizerServiceServer<SynchronizerService>>(peer)
+                    .await?;
 
-        let diff = response
-            .checkpoint_diff
-            .into_iter()
-            .map(|v| {
-                let subnet =
-                    SubnetId::from_str(&v.key[..]).map_err(|_| SyncError::UnableToParseSubnetId)?;
-                let proofs = v
+                Ok::<_, SyncError>(client.fetch_checkpoint(req).await?.into_inner())
+            })
+            .await
+            .map_err(|_| SyncError::Timeout(peer))?;
+            let response: CheckpointResponse = match response {
+                Ok(response) => response,
+                // The pages already received are kept, the next ones are asked next round
+                Err(error) if error.is_throttled() && latency.is_some() => {
+                    debug!("Peer {} is rate limiting the checkpoint pages", peer);
+                    break;
+                }
+                Err(error) => return Err(error),
+            };
+            latency.get_or_insert(started.elapsed());
+
+            for entry in response.checkpoint_diff {
+                let subnet = SubnetId::from_str(&entry.key[..])
+                    .map_err(|_| SyncError::UnableToParseSubnetId)?;
+                let proofs = entry
                     .value
                     .into_iter()
                     .map(TryInto::try_into)
                     .collect::<Result<Vec<_>, _>>()?;
-                Ok::<_, SyncError>((subnet, proofs))
-            })
-            .collect::<Result<HashMap<_, _>, _>>()?;
 
-        Ok(diff)
+                diff.entry(subnet).or_default().extend(proofs);
+            }
+
+            // Peers without pagination support never return a continuation token
+            if response.continuation_token.is_empty() {
+                break;
+            }
+            continuation_token = response.continuation_token;
+        }
+
+        Ok((diff, latency.unwrap_or_default()))
+    }
+
+    /// Verify every Proof of Delivery received from `peer` and only return the valid ones.
+    /// Every rejected proof is reported as an event and accounted to the peer.
+    async fn verify_proofs(&mut self, peer: PeerId, diff: CheckpointDiff) -> CheckpointDiff {
+        let (verified, rejected) =
+            verify_checkpoint_diff(diff, &self.validators, self.delivery_threshold);
+
+        if !rejected.is_empty() {
+            warn!(
+                "Peer {} sent {} invalid proofs of delivery",
+                peer,
+                rejected.len()
+            );
+            self.peer_scores.record_invalid(peer, rejected.len());
+        }
+
+        for (certificate_id, error) in rejected {
+            _ = self
+                .events
+                .send(CheckpointsCollectorEvent::ProofOfDeliveryRejected {
+                    peer,
+                    certificate_id,
+                    error,
+                })
+                .await;
+        }
+
+        verified
     }
 
     fn insert_unverified_proofs(
         &self,
-        diff: HashMap<SubnetId, Vec<ProofOfDelivery>>,
+        diff: CheckpointDiff,
     ) -> Result<Vec<Vec<CertificateId>>, SyncError> {
         let mut certs: HashSet<CertificateId> = HashSet::new();
         for (_subnet, proofs) in diff {
@@ -203,7 +415,7 @@ impl CheckpointSynchronizer {
 
         let certs = certs.into_iter().collect::<Vec<_>>();
 
-        for certs in certs.chunks(10) {
+        for certs in certs.chunks(self.config.certificates_chunk_size.max(1)) {
             chunked_certs.push(certs.to_vec());
         }
 
@@ -212,76 +424,682 @@ impl CheckpointSynchronizer {
 
     async fn fetch_certificates(
         &self,
-        certificate_ids: Vec<CertificateId>,
+        peer: PeerId,
+        certificate_ids: &[CertificateId],
     ) -> Result<Vec<Certificate>, SyncError> {
-        let target_peer = self
-            .gatekeeper
-            .get_random_peers(1)
-            .await
-            .map_err(|_| SyncError::UnableToFetchTargetPeer)
-            .map(|peers| peers.last().cloned().ok_or(SyncError::NoPeerAvailable))??;
-
-        let request_id: Option<APIUuid> = Some(Uuid::new_v4().into());
-        let req = FetchCertificatesRequest {
-            request_id,
-            certificates: certificate_ids
-                .iter()
-                .map(|cert| (*cert.as_array()).into())
-                .collect(),
-        };
+        let req = certificates_request(certificate_ids);
 
         debug!(
             "Ask {} for certificates payload: {:?}",
-            target_peer, certificate_ids
+            peer, certificate_ids
         );
-        let mut client: SynchronizerServiceClient<_> = self
-            .network
-            .new_grpc_client::<SynchronizerServiceClient<_>, SynchronizerServiceServer<SynchronizerService>>(target_peer)
-            .await?;
+        let response = timeout(self.config.request_timeout, async {
+            let mut client: SynchronizerServiceClient<_> = self
+                .network
+                .new_grpc_client::<SynchronizerServiceClient<_>, SynchronizerServiceServer<SynchronizerService>>(peer)
+                .await?;
+
+            Ok::<_, SyncError>(client.fetch_certificates(req).await?.into_inner())
+        })
+        .await
+        .map_err(|_| SyncError::Timeout(peer))??;
+
+        parse_certificates(response)
+    }
+
+    async fn fetch_pending_certificates(
+        &self,
+        peer: PeerId,
+        certificate_ids: &[CertificateId],
+    ) -> Result<Vec<Certificate>, SyncError> {
+        let req = certificates_request(certificate_ids);
+
+        debug!(
+            "Ask {} for pending certificates payload: {:?}",
+            peer, certificate_ids
+        );
+        let response = timeout(self.config.request_timeout, async {
+            let mut client: SynchronizerServiceClient<_> = self
+                .network
+                .new_grpc_client::<SynchronizerServiceClient<_>, SynchronizerServiceServer<SynchronizerService>>(peer)
+                .await?;
+
+            Ok::<_, SyncError>(client.fetch_pending_certificates(req).await?.into_inner())
+        })
+        .await
+        .map_err(|_| SyncError::Timeout(peer))??;
+
+        parse_certificates(response)
+    }
+
+    async fn ask_for_pending_certificate_ids(
+        &self,
+        peer: PeerId,
+    ) -> Result<(Vec<CertificateId>, Duration), SyncError> {
+        let request_id: Option<APIUuid> = Some(Uuid::new_v4().into());
+        let req = PendingCertificateIdsRequest { request_id };
+
+        debug!("Asking {} for its pending certificates", peer);
+        let started = Instant::now();
+        let response = timeout(self.config.request_timeout, async {
+            let mut client: SynchronizerServiceClient<_> = self
+                .network
+                .new_grpc_client::<SynchronizerServiceClient<_>, SynchronizerServiceServer<SynchronizerService>>(peer)
+                .await?;
 
-        let response = client.fetch_certificates(req).await?.into_inner();
+            Ok::<_, SyncError>(
+                client
+                    .fetch_pending_certificate_ids(req)
+                    .await?
+                    .into_inner(),
+            )
+        })
+        .await
+        .map_err(|_| SyncError::Timeout(peer))??;
+        let latency = started.elapsed();
 
-        let certificates: Result<Vec<Certificate>, _> = response
-            .certificates
+        let certificate_ids = response
+            .certificate_ids
             .into_iter()
             .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((certificate_ids, latency))
+    }
+
+    /// Returns the certificates which are neither in the local pools nor delivered
+    fn unknown_certificates(
+        &self,
+        certificate_ids: Vec<CertificateId>,
+    ) -> Result<Vec<CertificateId>, SyncError> {
+        let pooled = self.store.get_pool_certificates(&certificate_ids)?;
+        let delivered = self.store.get_certificates(&certificate_ids)?;
+
+        Ok(certificate_ids
+            .into_iter()
+            .zip(pooled.into_iter().zip(delivered))
+            .filter_map(|(certificate_id, (pooled, delivered))| {
+                (pooled.is_none() && delivered.is_none()).then_some(certificate_id)
+            })
+            .collect())
+    }
+
+    /// Fetches the given pending certificates from `peer`, chunk by chunk and in order
+    async fn fetch_pending_chunks(
+        &self,
+        peer: PeerId,
+        certificate_ids: Vec<CertificateId>,
+    ) -> (PeerId, Vec<Certificate>, PeerOutcome) {
+        let chunks: Vec<_> = certificate_ids
+            .chunks(self.config.certificates_chunk_size.max(1))
             .collect();
+        let mut fetched = Vec::new();
+        let mut unexpected = 0;
+        let started = Instant::now();
+
+        for chunk in &chunks {
+            match self.fetch_pending_certificates(peer, chunk).await {
+                Ok(certificates) => {
+                    for certificate in certificates {
+                        if chunk.contains(&certificate.id) && certificate.verify_id().is_ok() {
+                            fetched.push(certificate);
+                        } else {
+                            unexpected += 1;
+                        }
+                    }
+                }
+                Err(error) if error.is_throttled() => {
+                    debug!("Peer {} is rate limiting the pending certificates", peer);
+                    return (peer, fetched, PeerOutcome::Throttled);
+                }
+                Err(error) => {
+                    warn!(
+                        "Unable to fetch pending certificates from {}: {}",
+                        peer, error
+                    );
+                    return (peer, fetched, PeerOutcome::Failed);
+                }
+            }
+        }
 
-        Ok(certificates?)
+        let latency = started.elapsed() / chunks.len().max(1) as u32;
+        if unexpected > 0 {
+            warn!(
+                "Peer {} sent {} unrequested or forged pending certificates",
+                peer, unexpected
+            );
+            (peer, fetched, PeerOutcome::Invalid(latency, unexpected))
+        } else {
+            (peer, fetched, PeerOutcome::Answered(latency))
+        }
+    }
+
+    /// Fetches a chunk of certificates, starting with the `first` peer of `peers` and
+    /// moving on to the next ones for the certificates which weren't provided,
+    /// up to `max_retries` times.
+    /// Returns the fetched certificates along with the peer which sent them, the number
+    /// of certificates which couldn't be fetched and the outcome of every request.
+    async fn fetch_chunk(
+        &self,
+        certificate_ids: Vec<CertificateId>,
+        peers: &[PeerId],
+        first: usize,
+    ) -> (
+        Vec<(PeerId, Certificate)>,
+        usize,
+        Vec<(PeerId, PeerOutcome)>,
+    ) {
+        let mut remaining: HashSet<CertificateId> = certificate_ids.into_iter().collect();
+        let mut fetched = Vec::new();
+        let mut outcomes = Vec::new();
+
+        for peer in peers
+            .iter()
+            .cycle()
+            .skip(first % peers.len())
+            .take(peers.len().min(self.config.max_retries + 1))
+        {
+            let requested: Vec<_> = remaining.iter().copied().collect();
+            let started = Instant::now();
+
+            match self.fetch_certificates(*peer, &requested).await {
+                Ok(certificates) => {
+                    let latency = started.elapsed();
+                    let mut unexpected = 0;
+
+                    for certificate in certificates {
+                        // The body must hash to the id whose Proof of Delivery was verified
+                        if certificate.verify_id().is_ok() && remaining.remove(&certificate.id) {
+                            fetched.push((*peer, certificate));
+                        } else {
+                            unexpected += 1;
+                        }
+                    }
+
+                    if unexpected > 0 {
+                        warn!(
+                            "Peer {} sent {} unrequested or forged certificates",
+                            peer, unexpected
+                        );
+                        outcomes.push((*peer, PeerOutcome::Invalid(latency, unexpected)));
+                    } else {
+                        outcomes.push((*peer, PeerOutcome::Answered(latency)));
+                    }
+                }
+                Err(error) if error.is_throttled() => {
+                    debug!("Peer {} is rate limiting the certificate requests", peer);
+                    outcomes.push((*peer, PeerOutcome::Throttled));
+                    tokio::time::sleep(self.config.throttle_backoff).await;
+                }
+                Err(error) => {
+                    warn!("Unable to fetch certificates from {}: {}", peer, error);
+                    outcomes.push((*peer, PeerOutcome::Failed));
+                }
+            }
+
+            if remaining.is_empty() {
+                break;
+            }
+        }
+
+        (fetched, remaining.len(), outcomes)
+    }
+
+    fn record_outcome(&mut self, peer: PeerId, outcome: PeerOutcome) {
+        match outcome {
+            PeerOutcome::Answered(latency) => self.peer_scores.record_success(peer, latency),
+            PeerOutcome::Failed => self.peer_scores.record_failure(peer),
+            PeerOutcome::Invalid(latency, count) => {
+                self.peer_scores.record_success(peer, latency);
+                self.peer_scores.record_invalid(peer, count);
+            }
+            PeerOutcome::Throttled => {}
+        }
     }
 
     async fn initiate_request(&mut self) -> Result<(), SyncError> {
-        //  1. Ask a random peer for the diff between local and its latest checkpoint
-        let target_peer = self
-            .gatekeeper
-            .get_random_peers(1)
-            .await
-            .map_err(|_| SyncError::UnableToFetchTargetPeer)
-            .map(|peers| peers.last().cloned().ok_or(SyncError::NoPeerAvailable))??;
+        let heads = self.sync_heads().await;
+
+        // The pools are synchronized whether or not the heads are, lagging nodes
+        // being the ones needing the pending certificates the most
+        if self.pool_sync_due(Instant::now()) {
+            self.last_pool_sync = Some(Instant::now());
+
+            if let Err(error) = self.sync_pending_pool().await {
+                warn!("Unsuccessful pending pool sync due to: {}", error);
+            }
+        }
+
+        heads
+    }
+
+    fn pool_sync_due(&self, now: Instant) -> bool {
+        self.last_pool_sync.map_or(true, |last| {
+            now.duration_since(last) >= self.config.pool_sync_interval
+        })
+    }
+
+    async fn sync_heads(&mut self) -> Result<(), SyncError> {
+        let checkpoint = self.local_checkpoint()?;
+        let trusted_heads = self.missing_trusted_heads()?;
+
+        let fetched = self
+            .sync_from(checkpoint, trusted_heads, SyncRound::Heads)
+            .await?;
+
+        // The older history is only synchronized once the node is up to date
+        if fetched == 0 && self.config.backfill {
+            if let Some(checkpoint) = self.backfill_checkpoint()? {
+                debug!("Backfilling the history prior to the trusted checkpoint");
+                self.sync_from(checkpoint, CheckpointDiff::new(), SyncRound::Backfill)
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fetches the certificates still in broadcast in the pools of the peers which are
+    /// unknown locally, so that they can be echoed by the double echo
+    async fn sync_pending_pool(&mut self) -> Result<(), SyncError> {
+        let peers = self.select_peers().await?;
+
+        let responses = join_all(
+            peers
+                .iter()
+                .map(|peer| self.ask_for_pending_certificate_ids(*peer)),
+        )
+        .await;
+
+        // Every missing certificate is fetched from the first peer announcing it,
+        // in the order of the peer so that certificates follow the one they depend on
+        let mut announced = HashSet::new();
+        let mut to_fetch = Vec::new();
+        for (peer, response) in peers.into_iter().zip(responses) {
+            match response {
+                Ok((certificate_ids, latency)) => {
+                    self.peer_scores.record_success(peer, latency);
+                    let certificate_ids = certificate_ids
+                        .into_iter()
+                        .filter(|certificate_id| announced.insert(*certificate_id))
+                        .collect();
+
+                    let missing = self.unknown_certificates(certificate_ids)?;
+                    if !missing.is_empty() {
+                        to_fetch.push((peer, missing));
+                    }
+                }
+                Err(SyncError::Grpc(status)) if status.code() == Code::Unimplemented => {
+                    debug!("Peer {} doesn't share its pending certificates", peer);
+                }
+                Err(error) if error.is_throttled() => {
+                    debug!("Peer {} is rate limiting the pending certificate ids", peer);
+                }
+                Err(error) => {
+                    warn!(
+                        "Unable to get the pending certificates of {}: {}",
+                        peer, error
+                    );
+                    self.peer_scores.record_failure(peer);
+                }
+            }
+        }
+
+        let results = join_all(
+            to_fetch
+                .into_iter()
+                .map(|(peer, certificate_ids)| self.fetch_pending_chunks(peer, certificate_ids)),
+        )
+        .await;
+
+        for (peer, certificates, outcome) in results {
+            self.record_outcome(peer, outcome);
+
+            if !certificates.is_empty() {
+                debug!(
+                    "Fetched {} pending certificates from {}",
+                    certificates.len(),
+                    peer
+                );
+                _ = self
+                    .events
+                    .send(CheckpointsCollectorEvent::PendingCertificatesFetched {
+                        peer,
+                        certificates,
+                    })
+                    .await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Synchronizes the certificates delivered after the given `checkpoint` along with
+    /// the ones of the `known` proofs, returns the number of certificates to fetch
+    async fn sync_from(
+        &mut self,
+        checkpoint: Vec<grpc::tce::v1::ProofOfDelivery>,
+        known: CheckpointDiff,
+        round: SyncRound,
+    ) -> Result<usize, SyncError> {
+        //  1. Ask the best peers for the diff between the checkpoint and their latest one
+        let peers = self.select_peers().await?;
+
+        let responses = join_all(
+            peers
+                .iter()
+                .map(|peer| self.ask_for_checkpoint(*peer, checkpoint.clone())),
+        )
+        .await;
+
+        //  2. Validate the PoD diffs, invalid proofs are never persisted
+        let mut diff = known;
+        let mut answering_peers = Vec::new();
+        for (peer, response) in peers.into_iter().zip(responses) {
+            match response {
+                Ok((peer_diff, latency)) => {
+                    self.peer_scores.record_success(peer, latency);
+                    let verified = self.verify_proofs(peer, peer_diff).await;
+                    merge_checkpoint_diff(&mut diff, verified);
+                    answering_peers.push(peer);
+                }
+                Err(error) if error.is_throttled() => {
+                    debug!("Peer {} is rate limiting the checkpoint requests", peer);
+                }
+                Err(error) => {
+                    warn!("Unable to get the checkpoint of {}: {}", peer, error);
+                    self.peer_scores.record_failure(peer);
+                }
+            }
+        }
+
+        if answering_peers.is_empty() {
+            return Err(SyncError::NoCheckpointReceived);
+        }
 
-        let diff = self.ask_for_checkpoint(target_peer).await?;
+        let diff = self.without_delivered(diff)?;
+        let to_fetch = diff.values().map(Vec::len).sum();
 
+        let mut progress = SyncProgress {
+            peers: answering_peers.clone(),
+            subnet_lags: diff
+                .iter()
+                .map(|(subnet, proofs)| (*subnet, proofs.len() as u64))
+                .collect(),
+            pending_proofs: 0,
+            certificates_fetched: self.certificates_fetched,
+        };
+
+        //  3. Fetch the missing certificates, spreading the chunks across the best peers
+        let stream_order: Vec<(SubnetId, Vec<CertificateId>)> = diff
+            .iter()
+            .map(|(subnet_id, proofs)| {
+                (
+                    *subnet_id,
+                    proofs.iter().map(|proof| proof.certificate_id).collect(),
+                )
+            })
+            .collect();
         let certificates_to_catchup = self.insert_unverified_proofs(diff)?;
+        progress.pending_proofs = self.store.count_unverified_proofs()?;
+        if round == SyncRound::Heads {
+            self.report_progress(&progress).await;
+        }
+        let peers = self.peer_scores.rank(answering_peers);
+        if peers.is_empty() {
+            return Err(SyncError::NoPeerAvailable);
+        }
+
+        let results: Vec<_> = {
+            let peers = &peers;
+            let this = &*self;
 
-        for certificates in certificates_to_catchup {
-            let certificates = self.fetch_certificates(certificates).await?;
+            stream::iter(certificates_to_catchup.into_iter().enumerate())
+                .map(|(index, certificates)| this.fetch_chunk(certificates, peers, index))
+                .buffer_unordered(self.config.max_concurrent_peers.max(1))
+                .collect()
+                .await
+        };
+
+        let mut missing = 0;
+        let mut fetched = HashMap::new();
+        let mut fetched_per_peer: HashMap<PeerId, usize> = HashMap::new();
+        for (certificates, chunk_missing, outcomes) in results {
+            missing += chunk_missing;
+            for (peer, outcome) in outcomes {
+                self.record_outcome(peer, outcome);
+            }
+
+            for (peer, certificate) in certificates {
+                *fetched_per_peer.entry(peer).or_default() += 1;
+                fetched.insert(certificate.id, certificate);
+            }
+        }
+
+        //  4. Persist the fetched certificates of every subnet in position order
+        for (subnet_id, synchronized) in self.synchronize_in_order(stream_order, fetched).await {
+            if let Some(lag) = progress.subnet_lags.get_mut(&subnet_id) {
+                *lag = lag.saturating_sub(synchronized as u64);
+            }
+        }
+
+        for (peer, count) in fetched_per_peer {
+            self.certificates_fetched += count as u64;
+            _ = self
+                .events
+                .send(CheckpointsCollectorEvent::CertificatesFetched { peer, count })
+                .await;
+        }
+
+        progress.certificates_fetched = self.certificates_fetched;
+        progress.pending_proofs = self.store.count_unverified_proofs()?;
+        if round == SyncRound::Heads {
+            self.report_progress(&progress).await;
+        }
+
+        if missing > 0 {
+            return Err(SyncError::MissingCertificates(missing));
+        }
+
+        Ok(to_fetch)
+    }
+
+    /// Persists the fetched certificates of each subnet in the order of `stream_order`,
+    /// stopping at the first one which is missing or fails to be persisted so that the
+    /// source head never moves past a hole in the history. The certificates after it
+    /// keep their unverified proof and are fetched again during the next round.
+    /// Returns the number of certificates persisted per subnet.
+    async fn synchronize_in_order(
+        &self,
+        stream_order: Vec<(SubnetId, Vec<CertificateId>)>,
+        mut fetched: HashMap<CertificateId, Certificate>,
+    ) -> Vec<(SubnetId, usize)> {
+        let streams: Vec<_> = stream_order
+            .into_iter()
+            .map(|(subnet_id, certificate_ids)| {
+                let certificates: Vec<_> = certificate_ids
+                    .iter()
+                    .map_while(|certificate_id| fetched.remove(certificate_id))
+                    .collect();
+                if certificates.len() < certificate_ids.len() {
+                    debug!(
+                        "Certificate {} of subnet {} is missing, the following ones are \
+                         synchronized during the next round",
+                        certificate_ids[certificates.len()],
+                        subnet_id
+                    );
+                }
 
-            // TODO: verify every certificates
-            for certificate in certificates {
                 let store = self.store.clone();
-                tokio::spawn(async move {
-                    // Validate
-                    // Check precedence
-                    let certificate_id = certificate.id;
-                    match store.synchronize_certificate(certificate).await {
-                        Ok(_) => debug!("Certificate {} synchronized", certificate_id),
-                        Err(e) => error!("Failed to sync because of: {:?}", e),
+                async move {
+                    let mut synchronized = 0;
+                    for certificate in certificates {
+                        let certificate_id = certificate.id;
+                        if let Err(error) = store.synchronize_certificate(certificate).await {
+                            error!(
+                                "Unable to synchronize the certificate {}: {:?}",
+                                certificate_id, error
+                            );
+                            break;
+                        }
+
+                        debug!("Certificate {} synchronized", certificate_id);
+                        synchronized += 1;
                     }
-                });
+
+                    (subnet_id, synchronized)
+                }
+            })
+            .collect();
+
+        join_all(streams).await
+    }
+
+    async fn report_progress(&self, progress: &SyncProgress) {
+        debug!("Synchronization progress: {:?}", progress);
+        _ = self
+            .events
+            .send(CheckpointsCollectorEvent::Progress(progress.clone()))
+            .await;
+    }
+}
+
+type CheckpointDiff = HashMap<SubnetId, Vec<ProofOfDelivery>>;
+
+fn certificates_request(certificate_ids: &[CertificateId]) -> FetchCertificatesRequest {
+    FetchCertificatesRequest {
+        request_id: Some(Uuid::new_v4().into()),
+        certificates: certificate_ids
+            .iter()
+            .map(|cert| (*cert.as_array()).into())
+            .collect(),
+    }
+}
+
+fn parse_certificates(response: FetchCertificatesResponse) -> Result<Vec<Certificate>, SyncError> {
+    let certificates: Result<Vec<Certificate>, _> = response
+        .certificates
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect();
+
+    Ok(certificates?)
+}
+
+/// Verify the Proofs of Delivery of a trusted checkpoint, keeping the highest one per subnet
+pub(crate) fn verify_trusted_checkpoint(
+    proofs: Vec<ProofOfDelivery>,
+    validators: &HashSet<ValidatorId>,
+    threshold: u64,
+) -> Result<HashMap<SubnetId, ProofOfDelivery>, CheckpointsCollectorError> {
+    let mut trusted_heads: HashMap<SubnetId, ProofOfDelivery> = HashMap::new();
+
+    for proof in proofs {
+        proof.verify(validators, threshold).map_err(|error| {
+            CheckpointsCollectorError::InvalidTrustedCheckpoint {
+                certificate_id: proof.certificate_id,
+                error,
             }
+        })?;
+
+        let subnet_id = proof.delivery_position.subnet_id;
+        let is_higher = trusted_heads.get(&subnet_id).map_or(true, |head| {
+            head.delivery_position.position < proof.delivery_position.position
+        });
+
+        if is_higher {
+            trusted_heads.insert(subnet_id, proof);
         }
-        Ok(())
     }
+
+    Ok(trusted_heads)
+}
+
+/// Replace the local heads by the trusted ones for the subnets which are behind them
+pub(crate) fn with_trusted_heads(
+    heads: Vec<ProofOfDelivery>,
+    trusted_heads: &HashMap<SubnetId, ProofOfDelivery>,
+) -> Vec<ProofOfDelivery> {
+    let mut checkpoint: HashMap<SubnetId, ProofOfDelivery> = heads
+        .into_iter()
+        .map(|proof| (proof.delivery_position.subnet_id, proof))
+        .collect();
+
+    for (subnet_id, trusted) in trusted_heads {
+        let is_behind = checkpoint.get(subnet_id).map_or(true, |head| {
+            head.delivery_position.position < trusted.delivery_position.position
+        });
+
+        if is_behind {
+            checkpoint.insert(*subnet_id, trusted.clone());
+        }
+    }
+
+    checkpoint.into_values().collect()
+}
+
+/// Merge the Proofs of Delivery of `other` into `diff`, ignoring the ones already present.
+/// The proofs of each subnet are kept in position order.
+pub(crate) fn merge_checkpoint_diff(diff: &mut CheckpointDiff, other: CheckpointDiff) {
+    for (subnet, proofs) in other {
+        let known = diff.entry(subnet).or_default();
+        let known_ids: HashSet<CertificateId> =
+            known.iter().map(|proof| proof.certificate_id).collect();
+
+        known.extend(
+            proofs
+                .into_iter()
+                .filter(|proof| !known_ids.contains(&proof.certificate_id)),
+        );
+        known.sort_by_key(|proof| proof.delivery_position.position);
+    }
+}
+
+/// Split a checkpoint diff between the Proofs of Delivery that are valid for the
+/// given validator set and the rejected ones along with the reason of the rejection.
+pub(crate) fn verify_checkpoint_diff(
+    diff: CheckpointDiff,
+    validators: &HashSet<ValidatorId>,
+    threshold: u64,
+) -> (CheckpointDiff, Vec<(CertificateId, ProofOfDeliveryError)>) {
+    let mut rejected = Vec::new();
+
+    let verified = diff
+        .into_iter()
+        .map(|(subnet, proofs)| {
+            let proofs = proofs
+                .into_iter()
+                .filter(|proof| match proof.verify(validators, threshold) {
+                    Ok(()) => true,
+                    Err(error) => {
+                        rejected.push((proof.certificate_id, error));
+                        false
+                    }
+                })
+                .collect();
+
+            (subnet, proofs)
+        })
+        .collect();
+
+    (verified, rejected)
 }
 
-pub enum CheckpointsCollectorEvent {}
+#[derive(Debug)]
+pub enum CheckpointsCollectorEvent {
+    /// A Proof of Delivery sent by a peer failed its verification
+    ProofOfDeliveryRejected {
+        peer: PeerId,
+        certificate_id: CertificateId,
+        error: ProofOfDeliveryError,
+    },
+    /// Certificates were fetched from a peer
+    CertificatesFetched { peer: PeerId, count: usize },
+    /// Progress of the current synchronization round
+    Progress(SyncProgress),
+    /// Certificates still in broadcast were fetched from the pending pools of a peer
+    PendingCertificatesFetched {
+        peer: PeerId,
+        certificates: Vec<Certificate>,
+    },
+}
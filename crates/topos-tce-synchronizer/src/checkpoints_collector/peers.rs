@@ -0,0 +1,142 @@
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use topos_p2p::PeerId;
+
+/// Latency assumed for a peer which never answered, so that new peers get a chance
+/// to be selected against known ones
+const DEFAULT_LATENCY: Duration = Duration::from_millis(200);
+
+/// Weight of the latest latency in the moving average of the latency of a peer
+const LATENCY_SMOOTHING: f64 = 0.3;
+
+/// Multiplier of the cost of a peer failing every request
+const FAILURE_PENALTY: f64 = 10.0;
+
+/// Duration a peer is banned for the first time it sends invalid data,
+/// doubled on each new offense
+const BAN_DURATION: Duration = Duration::from_secs(60);
+
+/// Longest duration a peer can be banned for
+const MAX_BAN_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// Track record of a peer the synchronizer fetched from
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct PeerScore {
+    /// Moving average of the latency of the successful requests
+    pub(crate) latency: Option<Duration>,
+    /// Number of requests answered
+    pub(crate) successes: u64,
+    /// Number of requests which failed or timed out
+    pub(crate) failures: u64,
+    /// Number of invalid items sent, such as proofs of delivery failing
+    /// their verification or certificates which weren't requested
+    pub(crate) invalid: u64,
+    /// Number of times the peer was banned for sending invalid data
+    pub(crate) bans: u32,
+    /// End of the current ban of the peer
+    pub(crate) banned_until: Option<Instant>,
+}
+
+impl PeerScore {
+    /// Cost of fetching from this peer, the lower the better
+    fn cost(&self) -> f64 {
+        let latency = self.latency.unwrap_or(DEFAULT_LATENCY).as_secs_f64();
+        let requests = self.successes + self.failures;
+        let failure_rate = if requests == 0 {
+            0.0
+        } else {
+            self.failures as f64 / requests as f64
+        };
+
+        latency * (1.0 + FAILURE_PENALTY * failure_rate)
+    }
+}
+
+/// Scores of the peers based on their latency and on the correctness of their answers.
+/// A peer which sent invalid data isn't selected until its ban ends, the ban being
+/// longer each time so that a malicious peer is kept away without losing an honest
+/// peer for good over a single bad answer.
+#[derive(Debug)]
+pub(crate) struct PeerScores {
+    scores: HashMap<PeerId, PeerScore>,
+    ban_duration: Duration,
+}
+
+impl Default for PeerScores {
+    fn default() -> Self {
+        Self {
+            scores: HashMap::new(),
+            ban_duration: BAN_DURATION,
+        }
+    }
+}
+
+impl PeerScores {
+    #[cfg(test)]
+    pub(crate) fn with_ban_duration(ban_duration: Duration) -> Self {
+        Self {
+            ban_duration,
+            ..Default::default()
+        }
+    }
+
+    pub(crate) fn record_success(&mut self, peer: PeerId, latency: Duration) {
+        let score = self.scores.entry(peer).or_default();
+
+        score.successes += 1;
+        score.latency = Some(match score.latency {
+            Some(average) => {
+                average.mul_f64(1.0 - LATENCY_SMOOTHING) + latency.mul_f64(LATENCY_SMOOTHING)
+            }
+            None => latency,
+        });
+    }
+
+    pub(crate) fn record_failure(&mut self, peer: PeerId) {
+        self.scores.entry(peer).or_default().failures += 1;
+    }
+
+    pub(crate) fn record_invalid(&mut self, peer: PeerId, count: usize) {
+        let score = self.scores.entry(peer).or_default();
+
+        score.invalid += count as u64;
+        let ban = self
+            .ban_duration
+            .saturating_mul(2u32.saturating_pow(score.bans))
+            .min(MAX_BAN_DURATION);
+        score.bans = score.bans.saturating_add(1);
+        score.banned_until = Some(Instant::now() + ban);
+    }
+
+    pub(crate) fn is_trusted(&self, peer: &PeerId) -> bool {
+        self.scores
+            .get(peer)
+            .and_then(|score| score.banned_until)
+            .map_or(true, |banned_until| banned_until <= Instant::now())
+    }
+
+    /// Returns the trusted peers among `peers`, the best ones first.
+    /// Peers with the same cost keep their relative order.
+    pub(crate) fn rank(&self, peers: impl IntoIterator<Item = PeerId>) -> Vec<PeerId> {
+        let mut seen = HashSet::new();
+        let mut peers: Vec<(PeerId, f64)> = peers
+            .into_iter()
+            .filter(|peer| self.is_trusted(peer) && seen.insert(*peer))
+            .map(|peer| {
+                let cost = self
+                    .scores
+                    .get(&peer)
+                    .map_or_else(|| PeerScore::default().cost(), |score| score.cost());
+
+                (peer, cost)
+            })
+            .collect();
+
+        peers.sort_by(|(_, a), (_, b)| a.total_cmp(b));
+
+        peers.into_iter().map(|(peer, _)| peer).collect()
+    }
+}
@@ -1,14 +1,24 @@
-use std::time::Duration;
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
 
 use rstest::rstest;
+use tokio_util::sync::CancellationToken;
 use topos_core::{
     api::grpc::tce::v1::{
         synchronizer_service_client::SynchronizerServiceClient,
-        synchronizer_service_server::SynchronizerServiceServer, CheckpointMapFieldEntry,
-        CheckpointRequest, CheckpointResponse, FetchCertificatesRequest,
+        synchronizer_service_server::{
+            SynchronizerService as GrpcSynchronizerService, SynchronizerServiceServer,
+        },
+        CheckpointMapFieldEntry, CheckpointRequest, CheckpointResponse, FetchCertificatesRequest,
+        PendingCertificateIdsRequest, SyncStatus,
     },
-    types::CertificateDelivered,
+    errors::ProofOfDeliveryError,
+    types::{CertificateDelivered, ProofOfDelivery, SignedMessageKind, ValidatorId},
 };
+use topos_crypto::messages::MessageSigner;
+use topos_p2p::PeerId;
 
 use topos_p2p::GrpcRouter;
 use topos_test_sdk::{
@@ -19,10 +29,34 @@ use topos_test_sdk::{
 
 use uuid::Uuid;
 
-use crate::SynchronizerService;
+use crate::{
+    limits::{PeerRateLimiter, SynchronizerServiceLimits},
+    SyncProgress, Synchronizer, SynchronizerError, SynchronizerService,
+};
+
+use super::{
+    merge_checkpoint_diff, peers::PeerScores, verify_checkpoint_diff, verify_trusted_checkpoint,
+    with_trusted_heads, CheckpointsCollectorConfig, CheckpointsCollectorError,
+};
 
 mod integration;
 
+fn sign_proof(proof: &mut ProofOfDelivery, signers: &[MessageSigner]) {
+    proof.readies = signers
+        .iter()
+        .map(|signer| {
+            let validator_id = ValidatorId::from(signer.public_address);
+
+            let payload =
+                SignedMessageKind::Ready.signing_payload(&proof.certificate_id, &validator_id);
+
+            let signature = signer.sign_message(&payload).unwrap();
+
+            (validator_id.to_string(), signature.to_string())
+        })
+        .collect();
+}
+
 #[test]
 fn encode() {
     use topos_core::api::grpc::shared::v1::Uuid as APIUuid;
@@ -30,6 +64,8 @@ fn encode() {
     let req = CheckpointRequest {
         request_id: Some(request_id),
         checkpoint: vec![],
+        limit: 10,
+        continuation_token: vec![1, 2, 3],
     };
 
     let x: Vec<u8> = req.clone().into();
@@ -48,6 +84,7 @@ fn encode() {
             key: subnet.to_string(),
             value: vec![cert.proof_of_delivery.into()],
         }],
+        continuation_token: vec![],
     };
 
     let x: Vec<u8> = req.clone().into();
@@ -82,9 +119,7 @@ async fn check_fetch_certificates() {
         create_validator_store(vec![], futures::future::ready(fullnode_store.clone())).await;
 
     let router = GrpcRouter::new(tonic::transport::Server::builder()).add_service(
-        SynchronizerServiceServer::new(SynchronizerService {
-            validator_store: validator_store.clone(),
-        }),
+        SynchronizerServiceServer::new(SynchronizerService::new(validator_store.clone())),
     );
 
     let (client, _, _) = cfg
@@ -121,6 +156,296 @@ async fn check_fetch_certificates() {
     assert_eq!(res.certificates, expected);
 }
 
+#[test_log::test(tokio::test)]
+async fn pending_certificates_are_served_in_order() {
+    let certificates: Vec<CertificateDelivered> = create_certificate_chain(
+        topos_test_sdk::constants::SOURCE_SUBNET_ID_1,
+        &[topos_test_sdk::constants::TARGET_SUBNET_ID_1],
+        2,
+    );
+
+    let fullnode_store = create_fullnode_store(vec![]).await;
+    let validator_store =
+        create_validator_store(vec![], futures::future::ready(fullnode_store.clone())).await;
+
+    // The second certificate awaits the delivery of the first one in the precedence pool
+    for certificate in &certificates {
+        validator_store
+            .insert_pending_certificate(&certificate.certificate)
+            .unwrap();
+    }
+
+    let service = SynchronizerService::new(validator_store);
+    let certificate_ids = service
+        .fetch_pending_certificate_ids(tonic::Request::new(PendingCertificateIdsRequest {
+            request_id: None,
+        }))
+        .await
+        .unwrap()
+        .into_inner()
+        .certificate_ids;
+
+    let expected_ids: Vec<topos_core::api::grpc::shared::v1::CertificateId> = certificates
+        .iter()
+        .map(|certificate| certificate.certificate.id.into())
+        .collect();
+    assert_eq!(certificate_ids, expected_ids);
+
+    let request = FetchCertificatesRequest {
+        request_id: None,
+        certificates: certificate_ids,
+    };
+    let fetched = service
+        .fetch_pending_certificates(tonic::Request::new(request.clone()))
+        .await
+        .unwrap()
+        .into_inner()
+        .certificates;
+
+    let expected = certificates
+        .into_iter()
+        .map(|c| c.certificate.try_into().unwrap())
+        .collect::<Vec<topos_core::api::grpc::uci::v1::Certificate>>();
+    assert_eq!(fetched, expected);
+
+    let service = service.with_limits(SynchronizerServiceLimits {
+        max_certificates_per_request: 1,
+        ..Default::default()
+    });
+    let status = service
+        .fetch_pending_certificates(tonic::Request::new(request))
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+}
+
+#[test]
+fn reject_invalid_proofs_of_delivery() {
+    let subnet = topos_test_sdk::constants::SOURCE_SUBNET_ID_1;
+    let certificates: Vec<CertificateDelivered> =
+        create_certificate_chain(subnet, &[topos_test_sdk::constants::TARGET_SUBNET_ID_1], 4);
+
+    let signers: Vec<MessageSigner> = (1..=4)
+        .map(|i| MessageSigner::new(&[i; 32]).unwrap())
+        .collect();
+    let validators: HashSet<ValidatorId> = signers
+        .iter()
+        .map(|signer| ValidatorId::from(signer.public_address))
+        .collect();
+
+    let mut proofs: Vec<ProofOfDelivery> = certificates
+        .into_iter()
+        .map(|certificate| certificate.proof_of_delivery)
+        .collect();
+
+    sign_proof(&mut proofs[0], &signers);
+    sign_proof(&mut proofs[1], &signers[..3]);
+    // Not enough Ready messages
+    sign_proof(&mut proofs[2], &signers[..2]);
+    // Ready messages signed over another certificate
+    proofs[3].readies = proofs[0].readies.clone();
+
+    let expected_valid = vec![proofs[0].clone(), proofs[1].clone()];
+    let diff = HashMap::from([(subnet, proofs)]);
+
+    let (verified, rejected) = verify_checkpoint_diff(diff, &validators, 3);
+
+    assert_eq!(verified.get(&subnet), Some(&expected_valid));
+    assert_eq!(rejected.len(), 2);
+    assert!(matches!(
+        rejected[0].1,
+        ProofOfDeliveryError::ThresholdNotReached {
+            expected: 3,
+            got: 2
+        }
+    ));
+    assert!(matches!(
+        rejected[1].1,
+        ProofOfDeliveryError::InvalidSignature(_)
+    ));
+}
+
+#[test]
+fn peers_are_ranked_on_latency_and_failures() {
+    let (fast, slow, failing, unknown, malicious) = (
+        PeerId::random(),
+        PeerId::random(),
+        PeerId::random(),
+        PeerId::random(),
+        PeerId::random(),
+    );
+    let mut scores = PeerScores::default();
+
+    scores.record_success(fast, Duration::from_millis(10));
+    scores.record_success(slow, Duration::from_millis(150));
+    scores.record_success(failing, Duration::from_millis(100));
+    scores.record_failure(failing);
+    scores.record_success(malicious, Duration::from_millis(1));
+    scores.record_invalid(malicious, 1);
+
+    assert!(!scores.is_trusted(&malicious));
+    assert_eq!(
+        scores.rank([unknown, malicious, failing, slow, fast, fast]),
+        vec![fast, slow, unknown, failing]
+    );
+
+    // A slow peer getting faster moves up the ranking
+    for _ in 0..10 {
+        scores.record_success(slow, Duration::from_millis(1));
+    }
+    assert_eq!(scores.rank([fast, slow]), vec![slow, fast]);
+}
+
+#[test]
+fn peers_sending_invalid_data_are_banned_for_a_while() {
+    let peer = PeerId::random();
+    let mut scores = PeerScores::with_ban_duration(Duration::from_millis(50));
+
+    scores.record_success(peer, Duration::from_millis(10));
+    scores.record_invalid(peer, 1);
+    assert!(scores.rank([peer]).is_empty());
+
+    std::thread::sleep(Duration::from_millis(60));
+    assert!(scores.is_trusted(&peer));
+    assert_eq!(scores.rank([peer]), vec![peer]);
+
+    // The ban is longer on each new offense
+    scores.record_invalid(peer, 1);
+    std::thread::sleep(Duration::from_millis(60));
+    assert!(!scores.is_trusted(&peer));
+    std::thread::sleep(Duration::from_millis(50));
+    assert!(scores.is_trusted(&peer));
+}
+
+#[test]
+fn checkpoint_diffs_are_merged_without_duplicates() {
+    let subnet = topos_test_sdk::constants::SOURCE_SUBNET_ID_1;
+    let other_subnet = topos_test_sdk::constants::SOURCE_SUBNET_ID_2;
+    let proofs: Vec<ProofOfDelivery> =
+        create_certificate_chain(subnet, &[topos_test_sdk::constants::TARGET_SUBNET_ID_1], 3)
+            .into_iter()
+            .map(|certificate| certificate.proof_of_delivery)
+            .collect();
+    let other_proof = create_certificate_chain(
+        other_subnet,
+        &[topos_test_sdk::constants::TARGET_SUBNET_ID_1],
+        1,
+    )
+    .remove(0)
+    .proof_of_delivery;
+
+    // Merging earlier proofs keeps them in position order
+    let mut diff = HashMap::from([(subnet, proofs[1..].to_vec())]);
+    merge_checkpoint_diff(
+        &mut diff,
+        HashMap::from([
+            (subnet, proofs[..2].to_vec()),
+            (other_subnet, vec![other_proof.clone()]),
+        ]),
+    );
+
+    assert_eq!(diff.get(&subnet), Some(&proofs));
+    assert_eq!(diff.get(&other_subnet), Some(&vec![other_proof]));
+}
+
+#[test]
+fn sync_progress_reports_when_caught_up() {
+    let subnet = topos_test_sdk::constants::SOURCE_SUBNET_ID_1;
+    let peer = PeerId::random();
+
+    let mut progress = SyncProgress {
+        peers: vec![peer],
+        subnet_lags: HashMap::from([(subnet, 2)]),
+        pending_proofs: 2,
+        certificates_fetched: 3,
+    };
+    assert!(!progress.is_caught_up());
+
+    progress.subnet_lags.insert(subnet, 0);
+    assert!(!progress.is_caught_up());
+
+    progress.pending_proofs = 0;
+    assert!(progress.is_caught_up());
+
+    let status: SyncStatus = progress.into();
+    assert!(status.caught_up);
+    assert_eq!(status.certificates_fetched, 3);
+    assert_eq!(status.peers, vec![peer.to_string()]);
+    assert_eq!(status.subnet_lags[0].subnet_id, Some(subnet.into()));
+    assert_eq!(status.subnet_lags[0].missing_certificates, 0);
+}
+
+#[test]
+fn trusted_checkpoint_keeps_the_highest_verified_head_per_subnet() {
+    let subnet = topos_test_sdk::constants::SOURCE_SUBNET_ID_1;
+    let signers: Vec<MessageSigner> = (1..=4)
+        .map(|i| MessageSigner::new(&[i; 32]).unwrap())
+        .collect();
+    let validators: HashSet<ValidatorId> = signers
+        .iter()
+        .map(|signer| ValidatorId::from(signer.public_address))
+        .collect();
+
+    let mut proofs: Vec<ProofOfDelivery> =
+        create_certificate_chain(subnet, &[topos_test_sdk::constants::TARGET_SUBNET_ID_1], 3)
+            .into_iter()
+            .map(|certificate| certificate.proof_of_delivery)
+            .collect();
+    for proof in &mut proofs {
+        sign_proof(proof, &signers[..3]);
+    }
+
+    let trusted_heads =
+        verify_trusted_checkpoint(vec![proofs[2].clone(), proofs[1].clone()], &validators, 3)
+            .unwrap();
+    assert_eq!(trusted_heads, HashMap::from([(subnet, proofs[2].clone())]));
+
+    sign_proof(&mut proofs[0], &signers[..2]);
+    assert!(matches!(
+        verify_trusted_checkpoint(proofs.clone(), &validators, 3),
+        Err(CheckpointsCollectorError::InvalidTrustedCheckpoint { certificate_id, .. })
+            if certificate_id == proofs[0].certificate_id
+    ));
+}
+
+#[test]
+fn trusted_heads_replace_the_local_heads_behind_them() {
+    let subnet = topos_test_sdk::constants::SOURCE_SUBNET_ID_1;
+    let other_subnet = topos_test_sdk::constants::SOURCE_SUBNET_ID_2;
+    let target = topos_test_sdk::constants::TARGET_SUBNET_ID_1;
+    let proofs: Vec<ProofOfDelivery> = create_certificate_chain(subnet, &[target], 3)
+        .into_iter()
+        .map(|certificate| certificate.proof_of_delivery)
+        .collect();
+    let other_proofs: Vec<ProofOfDelivery> = create_certificate_chain(other_subnet, &[target], 2)
+        .into_iter()
+        .map(|certificate| certificate.proof_of_delivery)
+        .collect();
+
+    // Nothing delivered locally, the trusted heads are used
+    let trusted_heads = HashMap::from([(subnet, proofs[1].clone())]);
+    assert_eq!(
+        with_trusted_heads(vec![], &trusted_heads),
+        vec![proofs[1].clone()]
+    );
+
+    // The local heads behind the trusted ones are replaced
+    let mut checkpoint = with_trusted_heads(
+        vec![proofs[0].clone(), other_proofs[1].clone()],
+        &trusted_heads,
+    );
+    checkpoint.sort_by_key(|proof| proof.delivery_position.subnet_id);
+    let mut expected = vec![proofs[1].clone(), other_proofs[1].clone()];
+    expected.sort_by_key(|proof| proof.delivery_position.subnet_id);
+    assert_eq!(checkpoint, expected);
+
+    // The local heads ahead of the trusted ones are kept
+    assert_eq!(
+        with_trusted_heads(vec![proofs[2].clone()], &trusted_heads),
+        vec![proofs[2].clone()]
+    );
+}
+
 #[test]
 fn sync_unordered_certificates() {}
 
@@ -132,3 +457,75 @@ fn fetch_certificate_failure() {}
 
 #[test]
 fn missing_certificate_for_pod() {}
+
+#[test]
+fn rate_limit_is_per_peer_and_per_window() {
+    let limits = SynchronizerServiceLimits {
+        max_requests_per_window: 2,
+        ..Default::default()
+    };
+    let limiter = PeerRateLimiter::default();
+    let (peer_a, peer_b) = (PeerId::random(), PeerId::random());
+    let now = Instant::now();
+
+    assert!(limiter.check(Some(peer_a), &limits, now));
+    assert!(limiter.check(Some(peer_a), &limits, now));
+    assert!(!limiter.check(Some(peer_a), &limits, now));
+
+    assert!(limiter.check(Some(peer_b), &limits, now));
+    assert!(limiter.check(None, &limits, now));
+
+    let later = now + limits.rate_limit_window;
+    assert!(limiter.check(Some(peer_a), &limits, later));
+}
+
+#[test]
+fn checkpoint_page_size_is_capped() {
+    let limits = SynchronizerServiceLimits::default();
+
+    assert_eq!(
+        limits.checkpoint_page_size(0),
+        limits.max_checkpoint_diff_size
+    );
+    assert_eq!(limits.checkpoint_page_size(10), 10);
+    assert_eq!(
+        limits.checkpoint_page_size(u32::MAX),
+        limits.max_checkpoint_diff_size
+    );
+}
+
+#[test]
+fn default_requests_fit_the_default_limits() {
+    let config = CheckpointsCollectorConfig::default();
+    let limits = SynchronizerServiceLimits::default();
+
+    assert!(config.certificates_chunk_size <= limits.max_certificates_per_request);
+    assert!(config.checkpoint_page_size as usize <= limits.max_checkpoint_diff_size);
+}
+
+#[test]
+fn synchronizer_requires_validators_and_a_delivery_threshold() {
+    let builder = || Synchronizer::builder().with_shutdown(CancellationToken::new());
+    let validators: HashSet<ValidatorId> = (1..=4)
+        .map(|i| ValidatorId::from(MessageSigner::new(&[i; 32]).unwrap().public_address))
+        .collect();
+
+    assert!(matches!(
+        builder().build(),
+        Err(SynchronizerError::CheckpointsCollectorError(
+            CheckpointsCollectorError::NoValidators
+        ))
+    ));
+
+    for threshold in [0, 5] {
+        assert!(matches!(
+            builder()
+                .with_validators(validators.clone())
+                .with_delivery_threshold(threshold)
+                .build(),
+            Err(SynchronizerError::CheckpointsCollectorError(
+                CheckpointsCollectorError::InvalidDeliveryThreshold { .. }
+            ))
+        ));
+    }
+}
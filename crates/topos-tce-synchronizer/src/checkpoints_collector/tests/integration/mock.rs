@@ -2,6 +2,7 @@ use async_trait::async_trait;
 use tonic::Request;
 use topos_core::api::grpc::tce::v1::{
     synchronizer_service_server::SynchronizerService, CheckpointRequest, FetchCertificatesRequest,
+    PendingCertificateIdsRequest,
 };
 
 struct MockSynchronizerServer {}
@@ -25,4 +26,24 @@ impl SynchronizerService for MockSynchronizerServer {
     > {
         todo!()
     }
+
+    async fn fetch_pending_certificate_ids(
+        &self,
+        _request: Request<PendingCertificateIdsRequest>,
+    ) -> Result<
+        tonic::Response<topos_core::api::grpc::tce::v1::PendingCertificateIdsResponse>,
+        tonic::Status,
+    > {
+        todo!()
+    }
+
+    async fn fetch_pending_certificates(
+        &self,
+        _request: Request<FetchCertificatesRequest>,
+    ) -> Result<
+        tonic::Response<topos_core::api::grpc::tce::v1::FetchCertificatesResponse>,
+        tonic::Status,
+    > {
+        todo!()
+    }
 }
@@ -1,4 +1,4 @@
-use std::{future::IntoFuture, sync::Arc};
+use std::{collections::HashMap, future::IntoFuture, sync::Arc, time::Instant};
 
 use builder::SynchronizerBuilder;
 use checkpoints_collector::{CheckpointsCollectorError, CheckpointsCollectorEvent};
@@ -12,27 +12,30 @@ use tokio_stream::StreamExt;
 
 mod builder;
 mod checkpoints_collector;
+mod limits;
 
+pub use limits::SynchronizerServiceLimits;
+
+use limits::PeerRateLimiter;
 use tokio_stream::wrappers::ReceiverStream;
 use tokio_util::sync::CancellationToken;
 use tonic::{Request, Response, Status};
 use topos_core::{
-    api::grpc::{
-        shared::v1::positions::SourceStreamPosition,
-        tce::v1::{
-            synchronizer_service_server::SynchronizerService as GrpcSynchronizerService,
-            CheckpointMapFieldEntry, CheckpointRequest, CheckpointResponse,
-            FetchCertificatesRequest, FetchCertificatesResponse, ProofOfDelivery, SignedReady,
-        },
+    api::grpc::tce::v1::{
+        synchronizer_service_server::SynchronizerService as GrpcSynchronizerService,
+        CheckpointMapFieldEntry, CheckpointRequest, CheckpointResponse, FetchCertificatesRequest,
+        FetchCertificatesResponse, PendingCertificateIdsRequest, PendingCertificateIdsResponse,
+        ProofOfDelivery, SubnetLag, SyncStatus,
     },
-    uci::CertificateId,
+    errors::ProofOfDeliveryError,
+    uci::{Certificate, CertificateId, SubnetId},
 };
-use topos_tce_storage::{store::ReadStore, validator::ValidatorStore};
+use topos_p2p::PeerId;
+use topos_tce_storage::{store::ReadStore, types::CheckpointDiffCursor, validator::ValidatorStore};
 use tracing::{error, info, warn};
 
 pub struct Synchronizer {
     pub(crate) shutdown: CancellationToken,
-    #[allow(dead_code)]
     pub(crate) events: mpsc::Sender<SynchronizerEvent>,
 
     pub(crate) checkpoints_collector_stream: ReceiverStream<CheckpointsCollectorEvent>,
@@ -51,7 +54,9 @@ impl IntoFuture for Synchronizer {
                         break None
                     }
 
-                    _checkpoint_event = self.checkpoints_collector_stream.next() => {}
+                    Some(event) = self.checkpoints_collector_stream.next() => {
+                        self.on_checkpoints_collector_event(event).await;
+                    }
                 }
             };
 
@@ -71,6 +76,43 @@ impl Synchronizer {
     pub fn builder() -> SynchronizerBuilder {
         SynchronizerBuilder::default()
     }
+
+    async fn on_checkpoints_collector_event(&mut self, event: CheckpointsCollectorEvent) {
+        match event {
+            CheckpointsCollectorEvent::ProofOfDeliveryRejected {
+                peer,
+                certificate_id,
+                error,
+            } => {
+                _ = self
+                    .events
+                    .send(SynchronizerEvent::ProofOfDeliveryRejected {
+                        peer,
+                        certificate_id,
+                        error,
+                    })
+                    .await;
+            }
+            CheckpointsCollectorEvent::CertificatesFetched { peer, count } => {
+                _ = self
+                    .events
+                    .send(SynchronizerEvent::CertificatesFetched { peer, count })
+                    .await;
+            }
+            CheckpointsCollectorEvent::Progress(progress) => {
+                _ = self
+                    .events
+                    .send(SynchronizerEvent::Progress(progress))
+                    .await;
+            }
+            CheckpointsCollectorEvent::PendingCertificatesFetched { peer, certificates } => {
+                _ = self
+                    .events
+                    .send(SynchronizerEvent::PendingCertificatesFetched { peer, certificates })
+                    .await;
+            }
+        }
+    }
 }
 
 #[derive(Error, Debug)]
@@ -97,11 +139,117 @@ pub enum SynchronizerError {
     NoProtocolReceiver,
 }
 
-pub enum SynchronizerEvent {}
+#[derive(Debug)]
+pub enum SynchronizerEvent {
+    /// A Proof of Delivery sent by a peer has been rejected and wasn't persisted
+    ProofOfDeliveryRejected {
+        peer: PeerId,
+        certificate_id: CertificateId,
+        error: ProofOfDeliveryError,
+    },
+    /// Certificates were fetched from a peer
+    CertificatesFetched { peer: PeerId, count: usize },
+    /// Progress of the current synchronization round
+    Progress(SyncProgress),
+    /// Certificates still in broadcast were fetched from the pending pools of a peer
+    PendingCertificatesFetched {
+        peer: PeerId,
+        certificates: Vec<Certificate>,
+    },
+}
+
+/// Progress of the synchronization with the other nodes
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SyncProgress {
+    /// Peers the node is synchronizing from
+    pub peers: Vec<PeerId>,
+    /// Number of certificates missing per subnet, compared to the heads of the peers
+    pub subnet_lags: HashMap<SubnetId, u64>,
+    /// Number of synchronized Proofs of Delivery whose certificate isn't delivered yet
+    pub pending_proofs: u64,
+    /// Number of certificates fetched since the start of the node
+    pub certificates_fetched: u64,
+}
+
+impl SyncProgress {
+    /// Whether the node has every certificate known by the peers it synchronized with
+    pub fn is_caught_up(&self) -> bool {
+        self.pending_proofs == 0 && self.subnet_lags.values().all(|lag| *lag == 0)
+    }
+}
+
+impl From<SyncProgress> for SyncStatus {
+    fn from(progress: SyncProgress) -> Self {
+        Self {
+            caught_up: progress.is_caught_up(),
+            subnet_lags: progress
+                .subnet_lags
+                .into_iter()
+                .map(|(subnet_id, missing_certificates)| SubnetLag {
+                    subnet_id: Some(subnet_id.into()),
+                    missing_certificates,
+                })
+                .collect(),
+            pending_proofs: progress.pending_proofs,
+            certificates_fetched: progress.certificates_fetched,
+            peers: progress.peers.iter().map(ToString::to_string).collect(),
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct SynchronizerService {
     pub validator_store: Arc<ValidatorStore>,
+    limits: SynchronizerServiceLimits,
+    rate_limiter: PeerRateLimiter,
+}
+
+impl SynchronizerService {
+    pub fn new(validator_store: Arc<ValidatorStore>) -> Self {
+        Self {
+            validator_store,
+            limits: SynchronizerServiceLimits::default(),
+            rate_limiter: PeerRateLimiter::default(),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: SynchronizerServiceLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Parses the requested certificate ids, rejecting the requests asking for too many of them
+    fn requested_certificates(
+        &self,
+        request: &FetchCertificatesRequest,
+    ) -> Result<Vec<CertificateId>, Status> {
+        if request.certificates.len() > self.limits.max_certificates_per_request {
+            return Err(Status::invalid_argument(format!(
+                "Too many certificates requested, the maximum is {}",
+                self.limits.max_certificates_per_request
+            )));
+        }
+
+        request
+            .certificates
+            .iter()
+            .cloned()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| Status::invalid_argument("Unable to parse certificates"))
+    }
+
+    /// Rejects the request if its peer exceeded its request rate
+    fn check_rate_limit<T>(&self, request: &Request<T>) -> Result<(), Status> {
+        let peer = request.extensions().get::<PeerId>().copied();
+
+        if self.rate_limiter.check(peer, &self.limits, Instant::now()) {
+            Ok(())
+        } else {
+            warn!("Rate limiting synchronization requests of peer {:?}", peer);
+            Err(Status::resource_exhausted("Too many requests"))
+        }
+    }
 }
 
 #[async_trait::async_trait]
@@ -110,13 +258,10 @@ impl GrpcSynchronizerService for SynchronizerService {
         &self,
         request: Request<FetchCertificatesRequest>,
     ) -> Result<Response<FetchCertificatesResponse>, Status> {
+        self.check_rate_limit(&request)?;
+
         let request = request.into_inner();
-        let certificate_ids: Vec<CertificateId> = request
-            .certificates
-            .into_iter()
-            .map(|c| c.try_into())
-            .collect::<Result<Vec<_>, _>>()
-            .map_err(|_| Status::invalid_argument("Unable to parse certificates"))?;
+        let certificate_ids = self.requested_certificates(&request)?;
 
         let response =
             if let Ok(certs) = self.validator_store.get_certificates(&certificate_ids[..]) {
@@ -145,7 +290,19 @@ impl GrpcSynchronizerService for SynchronizerService {
         &self,
         request: Request<CheckpointRequest>,
     ) -> Result<Response<CheckpointResponse>, Status> {
+        self.check_rate_limit(&request)?;
+
         let request = request.into_inner();
+        let cursor = if request.continuation_token.is_empty() {
+            None
+        } else {
+            Some(
+                CheckpointDiffCursor::from_bytes(&request.continuation_token)
+                    .map_err(|_| Status::invalid_argument("Invalid continuation token"))?,
+            )
+        };
+        let limit = self.limits.checkpoint_page_size(request.limit);
+
         let res: Result<Vec<_>, _> = request
             .checkpoint
             .into_iter()
@@ -160,40 +317,86 @@ impl GrpcSynchronizerService for SynchronizerService {
             Ok(value) => value,
         };
 
-        let diff = if let Ok(diff) = self.validator_store.get_checkpoint_diff(res) {
-            diff.into_iter()
-                .map(|(key, value)| {
-                    let v: Vec<_> = value
-                        .into_iter()
-                        .map(|v| ProofOfDelivery {
-                            delivery_position: Some(SourceStreamPosition {
-                                source_subnet_id: Some(v.delivery_position.subnet_id.into()),
-                                position: *v.delivery_position.position,
-                                certificate_id: Some(v.certificate_id.into()),
-                            }),
-                            readies: v
-                                .readies
-                                .into_iter()
-                                .map(|(ready, signature)| SignedReady { ready, signature })
-                                .collect(),
-                            threshold: v.threshold,
-                        })
-                        .collect();
-                    CheckpointMapFieldEntry {
-                        key: key.to_string(),
-                        value: v,
-                    }
-                })
-                .collect()
-        } else {
-            Vec::new()
+        let (diff, next) = match self
+            .validator_store
+            .get_checkpoint_diff_page(res, cursor, limit)
+        {
+            Ok(page) => page,
+            Err(error) => {
+                error!("Unable to compute the checkpoint diff: {}", error);
+                (HashMap::new(), None)
+            }
         };
 
+        let diff: Vec<CheckpointMapFieldEntry> = diff
+            .into_iter()
+            .map(|(key, value)| {
+                let v: Vec<ProofOfDelivery> = value.into_iter().map(Into::into).collect();
+                CheckpointMapFieldEntry {
+                    key: key.to_string(),
+                    value: v,
+                }
+            })
+            .collect();
+
         let response = CheckpointResponse {
             request_id: request.request_id,
             checkpoint_diff: diff,
+            continuation_token: next.map(|cursor| cursor.to_bytes()).unwrap_or_default(),
         };
 
         Ok(Response::new(response))
     }
+
+    async fn fetch_pending_certificate_ids(
+        &self,
+        request: Request<PendingCertificateIdsRequest>,
+    ) -> Result<Response<PendingCertificateIdsResponse>, Status> {
+        self.check_rate_limit(&request)?;
+
+        let request = request.into_inner();
+        let certificate_ids = self
+            .validator_store
+            .get_pending_certificate_ids(self.limits.max_pending_certificate_ids)
+            .map_err(|error| {
+                Status::internal(format!("Can't get the pending certificates: {error}"))
+            })?
+            .into_iter()
+            .map(|certificate_id| (*certificate_id.as_array()).into())
+            .collect();
+
+        Ok(Response::new(PendingCertificateIdsResponse {
+            request_id: request.request_id,
+            certificate_ids,
+        }))
+    }
+
+    async fn fetch_pending_certificates(
+        &self,
+        request: Request<FetchCertificatesRequest>,
+    ) -> Result<Response<FetchCertificatesResponse>, Status> {
+        self.check_rate_limit(&request)?;
+
+        let request = request.into_inner();
+        let certificate_ids = self.requested_certificates(&request)?;
+
+        let certificates = self
+            .validator_store
+            .get_pool_certificates(&certificate_ids)
+            .map_err(|error| {
+                Status::internal(format!("Can't get the pending certificates: {error}"))
+            })?
+            .into_iter()
+            .flatten()
+            .map(TryInto::try_into)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| {
+                Status::internal("Storage certificates cannot be converted to gRPC type")
+            })?;
+
+        Ok(Response::new(FetchCertificatesResponse {
+            request_id: request.request_id,
+            certificates,
+        }))
+    }
 }
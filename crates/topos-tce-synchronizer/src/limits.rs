@@ -0,0 +1,85 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use serde::{Deserialize, Serialize};
+use topos_p2p::PeerId;
+
+/// Limits applied by the [`SynchronizerService`](crate::SynchronizerService) to the
+/// requests of the other peers. The defaults serve a peer catching up with the
+/// default settings of the checkpoints collector without rate limiting it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", default)]
+pub struct SynchronizerServiceLimits {
+    /// Maximum number of Proofs of Delivery returned per checkpoint page
+    pub max_checkpoint_diff_size: usize,
+    /// Maximum number of certificate ids accepted per `FetchCertificatesRequest`
+    pub max_certificates_per_request: usize,
+    /// Maximum number of pending certificate ids returned per request
+    pub max_pending_certificate_ids: usize,
+    /// Maximum number of requests served per peer during a `rate_limit_window`
+    pub max_requests_per_window: u32,
+    #[serde(skip)]
+    pub rate_limit_window: Duration,
+}
+
+impl SynchronizerServiceLimits {
+    const MAX_CHECKPOINT_DIFF_SIZE: usize = 1000;
+    const MAX_CERTIFICATES_PER_REQUEST: usize = 100;
+    const MAX_PENDING_CERTIFICATE_IDS: usize = 1000;
+    const MAX_REQUESTS_PER_WINDOW: u32 = 50;
+    const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(1);
+
+    /// Number of Proofs of Delivery to return for a requested `limit`, 0 meaning no preference
+    pub(crate) fn checkpoint_page_size(&self, limit: u32) -> usize {
+        match limit as usize {
+            0 => self.max_checkpoint_diff_size,
+            limit => limit.min(self.max_checkpoint_diff_size),
+        }
+    }
+}
+
+impl Default for SynchronizerServiceLimits {
+    fn default() -> Self {
+        Self {
+            max_checkpoint_diff_size: Self::MAX_CHECKPOINT_DIFF_SIZE,
+            max_certificates_per_request: Self::MAX_CERTIFICATES_PER_REQUEST,
+            max_pending_certificate_ids: Self::MAX_PENDING_CERTIFICATE_IDS,
+            max_requests_per_window: Self::MAX_REQUESTS_PER_WINDOW,
+            rate_limit_window: Self::RATE_LIMIT_WINDOW,
+        }
+    }
+}
+
+/// Fixed window request counter per peer.
+///
+/// Requests whose peer is unknown share the same counter.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PeerRateLimiter {
+    windows: Arc<Mutex<HashMap<Option<PeerId>, (Instant, u32)>>>,
+}
+
+impl PeerRateLimiter {
+    /// Accounts a request of `peer` and returns whether it is allowed
+    pub(crate) fn check(
+        &self,
+        peer: Option<PeerId>,
+        limits: &SynchronizerServiceLimits,
+        now: Instant,
+    ) -> bool {
+        let mut windows = self.windows.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Forget the peers whose window is over to keep the map bounded
+        windows.retain(|_, (started, _)| now.duration_since(*started) < limits.rate_limit_window);
+
+        let (_, count) = windows.entry(peer).or_insert((now, 0));
+        if *count >= limits.max_requests_per_window {
+            return false;
+        }
+        *count += 1;
+
+        true
+    }
+}
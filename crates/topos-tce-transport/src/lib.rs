@@ -4,7 +4,7 @@ use clap::Parser;
 use serde::{Deserialize, Serialize};
 use topos_core::{
     types::ValidatorId,
-    uci::{Certificate, CertificateId},
+    uci::{Certificate, CertificateId, SubnetId},
 };
 use topos_crypto::messages::Signature;
 
@@ -34,15 +34,35 @@ impl ReliableBroadcastParams {
     }
 }
 
+/// Reason of a broadcast failure
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BroadcastFailureReason {
+    /// The certificate didn't pass the checks done before broadcasting it
+    InvalidCertificate,
+    /// Not enough Echo and Ready messages were received before the deadline,
+    /// even after gossiping the certificate again
+    DeadlineExceeded,
+    /// The previous certificate wasn't delivered before the deadline
+    PrecedenceNotDelivered,
+}
+
 /// Protocol events
 #[derive(Clone, Debug)]
 pub enum ProtocolEvents {
     BroadcastFailed {
         certificate_id: CertificateId,
+        reason: BroadcastFailureReason,
     },
     AlreadyDelivered {
         certificate_id: CertificateId,
     },
+    /// Indicates that two different certificates were received for the same
+    /// position of a source stream, the conflicting one is not echoed
+    Equivocation {
+        subnet_id: SubnetId,
+        known_certificate_id: CertificateId,
+        conflicting_certificate_id: CertificateId,
+    },
     /// Emitted to get peers list, expected that Commands.ApplyPeers will come as reaction
     NeedPeers,
     /// (pb.Broadcast)
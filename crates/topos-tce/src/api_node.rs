@@ -0,0 +1,115 @@
+//! Node serving the API over the storage of a running node.
+//!
+//! The storage is opened as a read-only secondary instance which catches up
+//! periodically with the running node, so that heavy queries don't compete with
+//! the broadcast. The node doesn't take part in the broadcast: submitted
+//! certificates are refused and no certificate is delivered to the opened streams.
+
+use futures::StreamExt;
+use tokio::{
+    sync::{broadcast, mpsc},
+    time,
+};
+use tokio_util::sync::CancellationToken;
+use topos_tce_api::{RuntimeError, RuntimeEvent as ApiEvent};
+use topos_tce_storage::{constant::STORAGE_STATISTICS_INTERVAL, tuning, StorageClient};
+use tracing::{debug, error, info, warn};
+
+use crate::{
+    app_context::{
+        api::{get_last_pending_certificates, get_source_head},
+        export_storage_statistics,
+    },
+    config::ApiConfiguration,
+    open_secondary_validator_store, BROADCAST_CHANNEL_SIZE,
+};
+
+pub async fn run(
+    config: &ApiConfiguration,
+    shutdown: (CancellationToken, mpsc::Sender<()>),
+) -> Result<(), Box<dyn std::error::Error>> {
+    topos_metrics::init_metrics();
+
+    debug!("Opening the storage at {:?}", config.db_path);
+    if let Err(error) = tuning::configure(config.storage_tuning.clone()) {
+        warn!("Unable to tune the storage: {error}");
+    }
+
+    let validator_store =
+        open_secondary_validator_store(&config.db_path, &config.secondary_db_path);
+    let storage_client = StorageClient::new(validator_store.clone());
+
+    // Kept alive for the streams of the API even if nothing is ever delivered
+    let (_broadcast_sender, broadcast_receiver) = broadcast::channel(BROADCAST_CHANNEL_SIZE);
+
+    debug!("Starting gRPC api");
+    let (api_client, mut api_stream, _ctx) = topos_tce_api::Runtime::builder()
+        .with_broadcast_stream(broadcast_receiver)
+        .serve_grpc_addr(config.api_addr)
+        .serve_graphql_addr(config.graphql_api_addr)
+        .serve_metrics_addr(config.metrics_api_addr)
+        .store(validator_store.clone())
+        .storage(storage_client.clone())
+        .build_and_launch()
+        .await;
+    debug!("gRPC api started");
+
+    let mut catch_up = time::interval(config.catch_up_interval);
+    let mut storage_statistics = time::interval(*STORAGE_STATISTICS_INTERVAL);
+
+    loop {
+        tokio::select! {
+            _ = catch_up.tick() => {
+                if let Err(error) = validator_store.catch_up() {
+                    warn!("Unable to catch up with the storage of the node: {error}");
+                }
+            }
+
+            _ = storage_statistics.tick() => {
+                let validator_store = validator_store.clone();
+                tokio::task::spawn_blocking(move || export_storage_statistics(&validator_store));
+            }
+
+            Some(event) = api_stream.next() => {
+                on_api_event(&storage_client, event).await;
+            }
+
+            // Shutdown signal
+            _ = shutdown.0.cancelled() => {
+                info!("Shutting down the API node...");
+                if let Err(e) = api_client.shutdown().await {
+                    error!("Error shutting down the API: {e}");
+                }
+                // Drop the sender to notify the API node termination
+                drop(shutdown.1);
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn on_api_event(storage: &StorageClient, event: ApiEvent) {
+    match event {
+        ApiEvent::CertificateSubmitted {
+            certificate,
+            sender,
+        } => {
+            debug!(
+                "Refusing the submission of the certificate {}",
+                certificate.id
+            );
+
+            _ = sender.send(Err(RuntimeError::ReadOnly));
+        }
+
+        ApiEvent::GetSourceHead { subnet_id, sender } => {
+            _ = sender.send(get_source_head(storage, subnet_id).await);
+        }
+
+        ApiEvent::GetLastPendingCertificates { subnet_ids, sender } => {
+            _ = sender.send(Ok(get_last_pending_certificates(storage, subnet_ids).await));
+        }
+    }
+}
@@ -1,6 +1,7 @@
 //!
 //! Application logic glue
 //!
+use crate::checkpointer::Checkpointer;
 use crate::events::Events;
 use futures::{Stream, StreamExt};
 use prometheus::HistogramTimer;
@@ -10,21 +11,30 @@ use tce_transport::ProtocolEvents;
 use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 use topos_core::uci::CertificateId;
-use topos_metrics::CERTIFICATE_DELIVERED_TOTAL;
+use topos_metrics::{
+    CERTIFICATE_DELIVERED_TOTAL, STORAGE_COLUMN_FAMILY_ESTIMATED_KEYS,
+    STORAGE_COLUMN_FAMILY_MEMTABLE_SIZE_BYTES, STORAGE_COLUMN_FAMILY_PENDING_COMPACTION_BYTES,
+    STORAGE_COLUMN_FAMILY_SST_FILES_SIZE_BYTES,
+};
 use topos_p2p::{Event as NetEvent, NetworkClient};
 use topos_tce_api::RuntimeClient as ApiClient;
 use topos_tce_api::RuntimeEvent as ApiEvent;
 use topos_tce_broadcast::ReliableBroadcastClient;
 use topos_tce_gatekeeper::GatekeeperClient;
+use topos_tce_storage::constant::{
+    CHECKPOINT_INTERVAL, PENDING_POOL_CLEANUP_INTERVAL, STORAGE_STATISTICS_INTERVAL,
+};
 use topos_tce_storage::types::CertificateDeliveredWithPositions;
 use topos_tce_storage::validator::ValidatorStore;
 use topos_tce_storage::StorageClient;
 use topos_tce_synchronizer::SynchronizerEvent;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 
-mod api;
+pub(crate) mod api;
+mod checkpoint;
 mod network;
 mod protocol;
+mod synchronizer;
 
 /// Top-level transducer main app context & driver (alike)
 ///
@@ -45,6 +55,7 @@ pub struct AppContext {
     pub delivery_latency: HashMap<CertificateId, HistogramTimer>,
 
     pub validator_store: Arc<ValidatorStore>,
+    pub checkpointer: Checkpointer,
 }
 
 impl AppContext {
@@ -54,6 +65,7 @@ impl AppContext {
         CertificateId::from_array([0u8; topos_core::uci::CERTIFICATE_ID_LENGTH]);
 
     /// Factory
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         pending_storage: StorageClient,
         tce_cli: ReliableBroadcastClient,
@@ -61,6 +73,7 @@ impl AppContext {
         api_client: ApiClient,
         gatekeeper: GatekeeperClient,
         validator_store: Arc<ValidatorStore>,
+        checkpointer: Checkpointer,
     ) -> (Self, mpsc::Receiver<Events>) {
         let (events, receiver) = mpsc::channel(100);
         (
@@ -73,6 +86,7 @@ impl AppContext {
                 gatekeeper,
                 delivery_latency: Default::default(),
                 validator_store,
+                checkpointer,
             },
             receiver,
         )
@@ -89,6 +103,10 @@ impl AppContext {
         mut broadcast_stream: impl Stream<Item = CertificateDeliveredWithPositions> + Unpin,
         shutdown: (CancellationToken, mpsc::Sender<()>),
     ) {
+        let mut pool_cleanup = tokio::time::interval(*PENDING_POOL_CLEANUP_INTERVAL);
+        let mut checkpoint = tokio::time::interval(*CHECKPOINT_INTERVAL);
+        let mut storage_statistics = tokio::time::interval(*STORAGE_STATISTICS_INTERVAL);
+
         loop {
             tokio::select! {
 
@@ -118,7 +136,29 @@ impl AppContext {
                 }
 
                 // Synchronizer events
-                Some(_event) = synchronizer_stream.next() => {
+                Some(event) = synchronizer_stream.next() => {
+                    self.on_synchronizer_event(event).await;
+                }
+
+                // Pending pools retention
+                _ = pool_cleanup.tick() => {
+                    let validator_store = self.validator_store.clone();
+                    tokio::task::spawn_blocking(move || {
+                        evict_expired_pending_certificates(&validator_store)
+                    });
+                }
+
+                // Checkpoint chain
+                _ = checkpoint.tick() => {
+                    self.on_checkpoint_tick().await;
+                }
+
+                // Storage statistics
+                _ = storage_statistics.tick() => {
+                    let validator_store = self.validator_store.clone();
+                    tokio::task::spawn_blocking(move || {
+                        export_storage_statistics(&validator_store)
+                    });
                 }
 
                 // Shutdown signal
@@ -146,3 +186,46 @@ impl AppContext {
         Ok(())
     }
 }
+
+/// Evicts the certificates which stayed too long in the pending pools
+pub(crate) fn evict_expired_pending_certificates(validator_store: &ValidatorStore) {
+    match validator_store.evict_expired_pending_certificates() {
+        Ok(evicted) => {
+            for record in evicted {
+                debug!(
+                    "Certificate {} of subnet {} expired in the pending pools",
+                    record.certificate_id, record.subnet_id
+                );
+            }
+        }
+        Err(error) => error!("Unable to evict the expired pending certificates: {error:?}"),
+    }
+}
+
+/// Exports the statistics of the column families of the storage as metrics
+pub(crate) fn export_storage_statistics(validator_store: &ValidatorStore) {
+    match validator_store.get_storage_statistics() {
+        Ok(statistics) => {
+            for column_family in statistics {
+                let labels = [
+                    column_family.database.as_str(),
+                    column_family.column_family.as_str(),
+                ];
+
+                STORAGE_COLUMN_FAMILY_ESTIMATED_KEYS
+                    .with_label_values(&labels)
+                    .set(column_family.estimated_keys as i64);
+                STORAGE_COLUMN_FAMILY_SST_FILES_SIZE_BYTES
+                    .with_label_values(&labels)
+                    .set(column_family.sst_files_size as i64);
+                STORAGE_COLUMN_FAMILY_MEMTABLE_SIZE_BYTES
+                    .with_label_values(&labels)
+                    .set(column_family.memtable_size as i64);
+                STORAGE_COLUMN_FAMILY_PENDING_COMPACTION_BYTES
+                    .with_label_values(&labels)
+                    .set(column_family.pending_compaction_bytes as i64);
+            }
+        }
+        Err(error) => error!("Unable to get the statistics of the storage: {error:?}"),
+    }
+}
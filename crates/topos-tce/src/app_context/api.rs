@@ -1,11 +1,12 @@
 use crate::AppContext;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use topos_core::uci::{Certificate, SubnetId};
 use topos_metrics::CERTIFICATE_DELIVERY_LATENCY;
 use topos_tce_api::RuntimeError;
 use topos_tce_api::RuntimeEvent as ApiEvent;
 use topos_tce_storage::errors::{InternalStorageError, StorageError};
 use topos_tce_storage::types::PendingResult;
+use topos_tce_storage::StorageClient;
 use tracing::{error, warn};
 
 impl AppContext {
@@ -43,93 +44,106 @@ impl AppContext {
             }
 
             ApiEvent::GetSourceHead { subnet_id, sender } => {
-                // Get source head certificate
-                let mut result = self
-                    .pending_storage
-                    .get_source_head(subnet_id)
-                    .await
-                    .and_then(|result| match result {
-                        None => Err(StorageError::InternalStorage(
-                            InternalStorageError::MissingHeadForSubnet(subnet_id),
-                        )),
-                        value => Ok(value),
-                    })
-                    .map_err(|e| match e {
-                        StorageError::InternalStorage(internal) => {
-                            if let InternalStorageError::MissingHeadForSubnet(subnet_id) = internal
-                            {
-                                RuntimeError::UnknownSubnet(subnet_id)
-                            } else {
-                                RuntimeError::UnableToGetSourceHead(subnet_id, internal.to_string())
-                            }
-                        }
-                        e => RuntimeError::UnableToGetSourceHead(subnet_id, e.to_string()),
-                    });
+                _ = sender.send(get_source_head(&self.pending_storage, subnet_id).await);
+            }
 
-                // TODO: Initial genesis certificate eventually will be fetched from the topos subnet
-                // Currently, for subnet starting from scratch there are no certificates in the database
-                // So for MissingHeadForSubnet error we will return some default dummy certificate
-                if let Err(RuntimeError::UnknownSubnet(subnet_id)) = result {
-                    warn!("Returning dummy certificate as head certificate, to be fixed...");
-                    result = Ok(Some((
-                        0,
-                        topos_core::uci::Certificate {
-                            prev_id: AppContext::DUMMY_INITIAL_CERTIFICATE_ID,
-                            source_subnet_id: subnet_id,
-                            state_root: Default::default(),
-                            tx_root_hash: Default::default(),
-                            receipts_root_hash: Default::default(),
-                            target_subnets: vec![],
-                            verifier: 0,
-                            id: AppContext::DUMMY_INITIAL_CERTIFICATE_ID,
-                            proof: Default::default(),
-                            signature: Default::default(),
-                        },
-                    )));
-                };
+            ApiEvent::GetLastPendingCertificates { subnet_ids, sender } => {
+                _ = sender.send(Ok(get_last_pending_certificates(
+                    &self.pending_storage,
+                    subnet_ids,
+                )
+                .await));
+            }
+        }
+    }
+}
 
-                _ = sender.send(result);
+/// Returns the head of the source stream of the subnet
+pub(crate) async fn get_source_head(
+    storage: &StorageClient,
+    subnet_id: SubnetId,
+) -> Result<Option<(u64, Certificate)>, RuntimeError> {
+    // Get source head certificate
+    let result = storage
+        .get_source_head(subnet_id)
+        .await
+        .and_then(|result| match result {
+            None => Err(StorageError::InternalStorage(
+                InternalStorageError::MissingHeadForSubnet(subnet_id),
+            )),
+            value => Ok(value),
+        })
+        .map_err(|e| match e {
+            StorageError::InternalStorage(internal) => {
+                if let InternalStorageError::MissingHeadForSubnet(subnet_id) = internal {
+                    RuntimeError::UnknownSubnet(subnet_id)
+                } else {
+                    RuntimeError::UnableToGetSourceHead(subnet_id, internal.to_string())
+                }
             }
+            e => RuntimeError::UnableToGetSourceHead(subnet_id, e.to_string()),
+        });
 
-            ApiEvent::GetLastPendingCertificates {
-                mut subnet_ids,
-                sender,
-            } => {
-                let mut last_pending_certificates: HashMap<SubnetId, Option<(Certificate, u64)>> =
-                    subnet_ids
-                        .iter()
-                        .map(|subnet_id| (*subnet_id, None))
-                        .collect();
+    // TODO: Initial genesis certificate eventually will be fetched from the topos subnet
+    // Currently, for subnet starting from scratch there are no certificates in the database
+    // So for MissingHeadForSubnet error we will return some default dummy certificate
+    if let Err(RuntimeError::UnknownSubnet(subnet_id)) = result {
+        warn!("Returning dummy certificate as head certificate, to be fixed...");
+        return Ok(Some((
+            0,
+            topos_core::uci::Certificate {
+                prev_id: AppContext::DUMMY_INITIAL_CERTIFICATE_ID,
+                source_subnet_id: subnet_id,
+                state_root: Default::default(),
+                tx_root_hash: Default::default(),
+                receipts_root_hash: Default::default(),
+                target_subnets: vec![],
+                verifier: 0,
+                id: AppContext::DUMMY_INITIAL_CERTIFICATE_ID,
+                proof: Default::default(),
+                signature: Default::default(),
+            },
+        )));
+    };
 
-                if let Ok(pending_certificates) =
-                    self.pending_storage.get_pending_certificates().await
-                {
-                    // Count number of pending certificates for every subnet
-                    let mut indexes: HashMap<SubnetId, u64> = HashMap::new();
-                    for (_pending_certificate_id, cert) in pending_certificates.iter() {
-                        *indexes.entry(cert.source_subnet_id).or_insert(0) += 1;
-                    }
+    result
+}
 
-                    // Iterate through pending certificates and determine last one for every subnet
-                    // Last certificate in the subnet should be one with the highest index
-                    for (_pending_certificate_id, cert) in pending_certificates.into_iter().rev() {
-                        if let Some(subnet_id) = subnet_ids.take(&cert.source_subnet_id) {
-                            *last_pending_certificates.entry(subnet_id).or_insert(None) =
-                                Some((cert, indexes[&subnet_id]));
-                        }
-                        if subnet_ids.is_empty() {
-                            break;
-                        }
-                    }
-                }
+/// Returns the last pending certificate of each subnet along with the number
+/// of pending certificates of the subnet
+pub(crate) async fn get_last_pending_certificates(
+    storage: &StorageClient,
+    mut subnet_ids: HashSet<SubnetId>,
+) -> HashMap<SubnetId, Option<(Certificate, u64)>> {
+    let mut last_pending_certificates: HashMap<SubnetId, Option<(Certificate, u64)>> = subnet_ids
+        .iter()
+        .map(|subnet_id| (*subnet_id, None))
+        .collect();
 
-                // Add None pending certificate for any other requested subnet_id
-                subnet_ids.iter().for_each(|subnet_id| {
-                    last_pending_certificates.insert(*subnet_id, None);
-                });
+    if let Ok(pending_certificates) = storage.get_pending_certificates().await {
+        // Count number of pending certificates for every subnet
+        let mut indexes: HashMap<SubnetId, u64> = HashMap::new();
+        for (_pending_certificate_id, cert) in pending_certificates.iter() {
+            *indexes.entry(cert.source_subnet_id).or_insert(0) += 1;
+        }
 
-                _ = sender.send(Ok(last_pending_certificates));
+        // Iterate through pending certificates and determine last one for every subnet
+        // Last certificate in the subnet should be one with the highest index
+        for (_pending_certificate_id, cert) in pending_certificates.into_iter().rev() {
+            if let Some(subnet_id) = subnet_ids.take(&cert.source_subnet_id) {
+                *last_pending_certificates.entry(subnet_id).or_insert(None) =
+                    Some((cert, indexes[&subnet_id]));
+            }
+            if subnet_ids.is_empty() {
+                break;
             }
         }
     }
+
+    // Add None pending certificate for any other requested subnet_id
+    subnet_ids.iter().for_each(|subnet_id| {
+        last_pending_certificates.insert(*subnet_id, None);
+    });
+
+    last_pending_certificates
 }
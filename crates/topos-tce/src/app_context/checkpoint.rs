@@ -0,0 +1,88 @@
+use topos_core::api::grpc::tce::v1::{
+    double_echo_request, CheckpointSignature as GrpcCheckpointSignature, DoubleEchoRequest,
+};
+use topos_tce_storage::types::CheckpointSummary;
+use tracing::{error, warn};
+
+use crate::checkpointer::CheckpointSignature;
+use crate::AppContext;
+
+impl AppContext {
+    pub async fn on_checkpoint_tick(&mut self) {
+        for signature in self.checkpointer.on_tick().await {
+            self.publish_checkpoint_signature(signature).await;
+        }
+
+        self.notify_epoch_change().await;
+    }
+
+    pub(crate) async fn on_checkpoint_signature(&mut self, signature: GrpcCheckpointSignature) {
+        let GrpcCheckpointSignature {
+            summary: Some(summary),
+            signature: Some(signature),
+            validator_id: Some(validator_id),
+        } = signature
+        else {
+            return error!("Unable to process the checkpoint signature due to missing data");
+        };
+
+        let summary = match CheckpointSummary::try_from(summary) {
+            Ok(summary) => summary,
+            Err(e) => return error!("Invalid checkpoint summary: {e}"),
+        };
+
+        let validator_id = match validator_id.clone().try_into() {
+            Ok(validator_id) => validator_id,
+            Err(e) => {
+                return error!(
+                    "Invalid validator id, could not process the checkpoint signature: {e}, \
+                     validator_id: {validator_id}"
+                )
+            }
+        };
+
+        if let Some(signature) = self
+            .checkpointer
+            .on_signature(CheckpointSignature {
+                summary,
+                validator_id,
+                signature: signature.into(),
+            })
+            .await
+        {
+            self.publish_checkpoint_signature(signature).await;
+        }
+
+        self.notify_epoch_change().await;
+    }
+
+    /// Switches the double echo to the validator set of the epoch started by
+    /// the last certified checkpoint, if any
+    async fn notify_epoch_change(&mut self) {
+        if let Some(epoch) = self.checkpointer.take_epoch_change() {
+            if let Err(error) = self.tce_cli.change_epoch(epoch).await {
+                warn!("Unable to notify the epoch change: {error:?}");
+            }
+        }
+    }
+
+    async fn publish_checkpoint_signature(&self, signature: CheckpointSignature) {
+        let request = DoubleEchoRequest {
+            request: Some(double_echo_request::Request::CheckpointSignature(
+                GrpcCheckpointSignature {
+                    summary: Some(signature.summary.into()),
+                    signature: Some(signature.signature.into()),
+                    validator_id: Some(signature.validator_id.into()),
+                },
+            )),
+        };
+
+        if let Err(e) = self
+            .network_client
+            .publish(topos_p2p::TOPOS_GOSSIP, request)
+            .await
+        {
+            error!("Unable to send the checkpoint signature due to error: {e}");
+        }
+    }
+}
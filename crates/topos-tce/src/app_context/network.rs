@@ -137,6 +137,9 @@ impl AppContext {
                             }
                         });
                     }
+                    double_echo_request::Request::CheckpointSignature(signature) => {
+                        self.on_checkpoint_signature(signature).await;
+                    }
                     _ => {}
                 }
             }
@@ -1,6 +1,6 @@
 use tce_transport::ProtocolEvents;
 use topos_core::api::grpc::tce::v1::{double_echo_request, DoubleEchoRequest, Echo, Gossip, Ready};
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use crate::events::Events;
 use crate::AppContext;
@@ -20,6 +20,31 @@ impl AppContext {
                 info!("Broadcasting certificate {}", certificate_id);
             }
 
+            ProtocolEvents::BroadcastFailed {
+                certificate_id,
+                reason,
+            } => {
+                warn!(
+                    "Broadcast of the Certificate {} failed: {:?}",
+                    certificate_id, reason
+                );
+
+                if let Some(timer) = self.delivery_latency.remove(&certificate_id) {
+                    timer.stop_and_discard();
+                }
+            }
+
+            ProtocolEvents::Equivocation {
+                subnet_id,
+                known_certificate_id,
+                conflicting_certificate_id,
+            } => {
+                warn!(
+                    "Refused to echo the Certificate {} of subnet {}, conflicting with {}",
+                    conflicting_certificate_id, subnet_id, known_certificate_id
+                );
+            }
+
             ProtocolEvents::Gossip { cert } => {
                 let cert_id = cert.id;
 
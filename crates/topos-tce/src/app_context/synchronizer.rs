@@ -0,0 +1,98 @@
+use std::collections::hash_map;
+
+use tokio::spawn;
+use topos_metrics::{
+    CERTIFICATE_DELIVERY_LATENCY, SYNCHRONIZER_CAUGHT_UP, SYNCHRONIZER_CERTIFICATES_FETCHED_TOTAL,
+    SYNCHRONIZER_PEERS, SYNCHRONIZER_PENDING_PROOFS, SYNCHRONIZER_SUBNET_LAG,
+};
+use topos_tce_broadcast::DoubleEchoCommand;
+use topos_tce_synchronizer::{SyncProgress, SynchronizerEvent};
+use tracing::{debug, error, info, warn};
+
+use crate::AppContext;
+
+impl AppContext {
+    pub async fn on_synchronizer_event(&mut self, event: SynchronizerEvent) {
+        match event {
+            SynchronizerEvent::ProofOfDeliveryRejected {
+                peer,
+                certificate_id,
+                error,
+            } => {
+                warn!(
+                    "Rejected the Proof of Delivery of {} sent by {}: {}",
+                    certificate_id, peer, error
+                );
+            }
+
+            SynchronizerEvent::CertificatesFetched { peer, count } => {
+                debug!("Fetched {} certificates from {}", count, peer);
+                SYNCHRONIZER_CERTIFICATES_FETCHED_TOTAL
+                    .with_label_values(&[&peer.to_string()])
+                    .inc_by(count as u64);
+            }
+
+            SynchronizerEvent::Progress(progress) => {
+                export_sync_progress(&progress);
+                self.api_client.set_sync_status(progress.into()).await;
+            }
+
+            SynchronizerEvent::PendingCertificatesFetched { peer, certificates } => {
+                info!(
+                    "Fetched {} pending certificates from {}",
+                    certificates.len(),
+                    peer
+                );
+
+                for certificate in &certificates {
+                    if let hash_map::Entry::Vacant(entry) =
+                        self.delivery_latency.entry(certificate.id)
+                    {
+                        entry.insert(CERTIFICATE_DELIVERY_LATENCY.start_timer());
+                    }
+                }
+
+                // Broadcast as if received through gossip, keeping the order in which
+                // the certificates depend on each other
+                let channel = self.tce_cli.get_double_echo_channel();
+                spawn(async move {
+                    for cert in certificates {
+                        if channel
+                            .send(DoubleEchoCommand::Broadcast {
+                                cert,
+                                need_gossip: false,
+                            })
+                            .await
+                            .is_err()
+                        {
+                            error!(
+                                "Unable to send broadcast_new_certificate command, Receiver was \
+                                 dropped"
+                            );
+                            break;
+                        }
+                    }
+                });
+            }
+        }
+    }
+}
+
+fn export_sync_progress(progress: &SyncProgress) {
+    SYNCHRONIZER_SUBNET_LAG.reset();
+    for (subnet_id, lag) in &progress.subnet_lags {
+        SYNCHRONIZER_SUBNET_LAG
+            .with_label_values(&[&subnet_id.to_string()])
+            .set(*lag as i64);
+    }
+
+    SYNCHRONIZER_PEERS.reset();
+    for peer in &progress.peers {
+        SYNCHRONIZER_PEERS
+            .with_label_values(&[&peer.to_string()])
+            .set(1);
+    }
+
+    SYNCHRONIZER_PENDING_PROOFS.set(progress.pending_proofs as i64);
+    SYNCHRONIZER_CAUGHT_UP.set(progress.is_caught_up() as i64);
+}
@@ -0,0 +1,528 @@
+//! Agreement of the validators on the checkpoint chain
+//!
+//! Time is divided into slots of [`CHECKPOINT_INTERVAL`], each slot having one of the
+//! validators of the epoch as leader. When the source heads changed since the last
+//! checkpoint, the leader proposes the next checkpoint by signing it and gossiping its
+//! signature. The other validators sign a proposal of a slot leader once every head
+//! certificate is delivered locally. Each validator signs a single checkpoint per
+//! sequence number and epoch at a time: a checkpoint which doesn't reach the threshold
+//! within a few slots is superseded by the proposal of a later leader, which the
+//! validators having signed the stalled one can sign as well.
+//!
+//! Every validator collects the signatures, and the first checkpoint to reach the
+//! delivery threshold of its epoch is appended to the local checkpoint chain.
+//!
+//! The epoch only changes through the checkpoint chain: when the configured validator
+//! set differs from the one of the current epoch, the leader proposes it as the validator
+//! set of the next epoch. The validators configured with the same set sign the proposal,
+//! and the next epoch starts with this set once the checkpoint is certified.
+
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Instant, SystemTime, UNIX_EPOCH},
+};
+
+use tce_transport::ReliableBroadcastParams;
+use topos_core::types::ValidatorId;
+use topos_crypto::messages::{MessageSigner, Signature};
+use topos_tce_storage::{
+    constant::CHECKPOINT_INTERVAL,
+    store::ReadStore,
+    types::{
+        CheckpointDigest, CheckpointSequenceNumber, CheckpointSummary, EpochId,
+        ValidatorQuorumSignatureInfo, VerifiedCheckpointSummary,
+    },
+    validator::ValidatorStore,
+};
+use tracing::{debug, error, info, warn};
+
+/// Number of slots after which a validator can sign another checkpoint for the
+/// same sequence number, when the one it signed didn't reach the threshold
+const SIGNATURE_TIMEOUT_SLOTS: u32 = 2;
+
+/// Number of sequence numbers after the last checkpoint for which proposals are kept
+const MAX_SEQUENCE_NUMBERS_AHEAD: u64 = 2;
+
+/// Number of different checkpoints a validator can sign for the same sequence number
+const MAX_PROPOSALS_PER_VALIDATOR: usize = 4;
+
+/// Signature of a validator over a checkpoint, exchanged between the validators
+#[derive(Debug, Clone)]
+pub struct CheckpointSignature {
+    pub summary: CheckpointSummary,
+    pub validator_id: ValidatorId,
+    pub signature: Signature,
+}
+
+/// Checkpoint proposed for the next sequence number and the signatures collected so far
+struct Proposal {
+    summary: CheckpointSummary,
+    signatures: HashMap<ValidatorId, Signature>,
+}
+
+/// Checkpoint signed by the local validator for a sequence number
+struct SignedCheckpoint {
+    digest: CheckpointDigest,
+    signed_at: Instant,
+}
+
+pub struct Checkpointer {
+    validator_id: ValidatorId,
+    message_signer: Arc<MessageSigner>,
+    validator_store: Arc<ValidatorStore>,
+    /// Current epoch, following the last checkpoint of the chain
+    epoch: EpochId,
+    /// Epoch entered since the last call to [`Checkpointer::take_epoch_change`]
+    epoch_change: Option<EpochId>,
+    /// Configured validator set, agreeing with the configured parameters and proposed
+    /// as the validator set of the next epoch when it differs from the current one
+    validators: HashSet<ValidatorId>,
+    params: ReliableBroadcastParams,
+    proposals: HashMap<CheckpointDigest, Proposal>,
+    /// Last checkpoint signed by the local validator for each epoch and sequence number
+    signed: HashMap<(EpochId, CheckpointSequenceNumber), SignedCheckpoint>,
+}
+
+impl Checkpointer {
+    pub fn new(
+        validator_id: ValidatorId,
+        message_signer: Arc<MessageSigner>,
+        validator_store: Arc<ValidatorStore>,
+        epoch: EpochId,
+        validators: HashSet<ValidatorId>,
+        params: ReliableBroadcastParams,
+    ) -> Self {
+        Self {
+            validator_id,
+            message_signer,
+            validator_store,
+            epoch,
+            epoch_change: None,
+            validators,
+            params,
+            proposals: HashMap::new(),
+            signed: HashMap::new(),
+        }
+    }
+
+    /// Returns the epoch entered since the last call, if any
+    pub fn take_epoch_change(&mut self) -> Option<EpochId> {
+        self.epoch_change.take()
+    }
+
+    /// Proposes the next checkpoint when leading the current slot, and signs the
+    /// proposals which couldn't be attested when they were received
+    ///
+    /// Returns the signatures to gossip, the pending ones being gossiped again.
+    pub async fn on_tick(&mut self) -> Vec<CheckpointSignature> {
+        let Some(last) = self.last_checkpoint() else {
+            return Vec::new();
+        };
+        let next = last
+            .as_ref()
+            .map_or(0, |last| last.summary().sequence_number + 1);
+        let epoch = self.epoch;
+
+        self.proposals.retain(|_, proposal| {
+            proposal.summary.epoch >= epoch
+                && proposal.summary.sequence_number >= next
+                && proposal.summary.sequence_number <= next + MAX_SEQUENCE_NUMBERS_AHEAD
+        });
+        self.signed.retain(|(signed_epoch, sequence_number), _| {
+            *signed_epoch >= epoch && *sequence_number >= next
+        });
+
+        let Some((validators, _)) = self.validator_set(epoch) else {
+            return Vec::new();
+        };
+
+        if self.can_sign(epoch, next, None) && slot_leaders(&validators)[0] == self.validator_id {
+            let next_validators = self.validator_set_change(&validators);
+            self.propose(
+                epoch,
+                last.as_ref().map(|last| last.summary()),
+                next_validators,
+            );
+        }
+
+        let digests: Vec<_> = self.proposals.keys().copied().collect();
+        for digest in digests {
+            self.attest(&digest);
+        }
+
+        let mut signatures = Vec::new();
+        for signed in self.signed.values() {
+            if let Some(proposal) = self.proposals.get(&signed.digest) {
+                if let Some(signature) = proposal.signatures.get(&self.validator_id) {
+                    signatures.push(CheckpointSignature {
+                        summary: proposal.summary.clone(),
+                        validator_id: self.validator_id,
+                        signature: *signature,
+                    });
+                }
+            }
+        }
+
+        let digests: Vec<_> = self.proposals.keys().copied().collect();
+        for digest in digests {
+            self.try_certify(&digest).await;
+        }
+
+        signatures
+    }
+
+    /// Records the signature of a validator and signs the checkpoint if it can be
+    /// attested, returning the local signature to gossip if any
+    pub async fn on_signature(
+        &mut self,
+        CheckpointSignature {
+            summary,
+            validator_id,
+            signature,
+        }: CheckpointSignature,
+    ) -> Option<CheckpointSignature> {
+        let Some((validators, _)) = self.validator_set(summary.epoch) else {
+            return None;
+        };
+
+        if !validators.contains(&validator_id) {
+            warn!("Checkpoint signature from the non-validator {validator_id}");
+            return None;
+        }
+
+        if let Err(error) = self.message_signer.verify_signature(
+            signature,
+            &summary.signing_payload(&validator_id),
+            validator_id.address(),
+        ) {
+            warn!("Invalid checkpoint signature from {validator_id}: {error}");
+            return None;
+        }
+
+        let next = match self.last_checkpoint()? {
+            Some(last) => last.summary().sequence_number + 1,
+            None => 0,
+        };
+        if summary.sequence_number < next
+            || summary.sequence_number > next + MAX_SEQUENCE_NUMBERS_AHEAD
+        {
+            return None;
+        }
+
+        let digest = summary.digest();
+        if !self.proposals.contains_key(&digest) {
+            let signed = self
+                .proposals
+                .values()
+                .filter(|proposal| {
+                    proposal.summary.sequence_number == summary.sequence_number
+                        && proposal.signatures.contains_key(&validator_id)
+                })
+                .count();
+
+            if signed >= MAX_PROPOSALS_PER_VALIDATOR {
+                warn!(
+                    "Ignoring the checkpoint {} signed by {validator_id}, which already signed \
+                     {signed} others",
+                    summary.sequence_number
+                );
+                return None;
+            }
+        }
+
+        self.proposals
+            .entry(digest)
+            .or_insert_with(|| Proposal {
+                summary,
+                signatures: HashMap::new(),
+            })
+            .signatures
+            .insert(validator_id, signature);
+
+        let signature = self.attest(&digest);
+        self.try_certify(&digest).await;
+
+        signature
+    }
+
+    /// Proposes a checkpoint over the current source heads, unless they didn't
+    /// change since the last checkpoint and the validator set doesn't change either
+    fn propose(
+        &mut self,
+        epoch: EpochId,
+        last: Option<&CheckpointSummary>,
+        next_validators: Option<Vec<ValidatorId>>,
+    ) {
+        let heads = match self.validator_store.get_checkpoint() {
+            Ok(heads) => heads.into_values().collect(),
+            Err(error) => return error!("Unable to read the source heads: {error:?}"),
+        };
+
+        let mut summary = CheckpointSummary::new(epoch, last, heads);
+        if let Some(next_validators) = next_validators {
+            info!(
+                "Proposing {} validators for the epoch {}",
+                next_validators.len(),
+                epoch + 1
+            );
+            summary = summary.with_next_validators(next_validators);
+        } else if summary.checkpoint_data.is_empty()
+            || last.is_some_and(|last| last.checkpoint_data == summary.checkpoint_data)
+        {
+            return;
+        }
+
+        info!(
+            "Proposing the checkpoint {} of the epoch {epoch}",
+            summary.sequence_number
+        );
+
+        let digest = summary.digest();
+        self.proposals.entry(digest).or_insert_with(|| Proposal {
+            summary,
+            signatures: HashMap::new(),
+        });
+        self.sign(&digest);
+    }
+
+    /// Signs the proposal if it was proposed by a slot leader, follows the last
+    /// checkpoint, only contains delivered certificates and proposes the configured
+    /// validator set for the next epoch if any
+    fn attest(&mut self, digest: &CheckpointDigest) -> Option<CheckpointSignature> {
+        let proposal = self.proposals.get(digest)?;
+        let summary = &proposal.summary;
+        let epoch = self.epoch;
+
+        if summary.epoch != epoch || !self.can_sign(epoch, summary.sequence_number, Some(digest)) {
+            return None;
+        }
+
+        let (validators, _) = self.validator_set(epoch)?;
+        if !slot_leaders(&validators)
+            .iter()
+            .any(|leader| proposal.signatures.contains_key(leader))
+        {
+            return None;
+        }
+
+        if summary.next_validators.is_some()
+            && summary.next_validators != self.validator_set_change(&validators)
+        {
+            warn!(
+                "Refusing to sign the checkpoint {}, its validator set for the epoch {} doesn't \
+                 match the configured one",
+                summary.sequence_number,
+                epoch + 1
+            );
+            return None;
+        }
+
+        let last = self.last_checkpoint()?;
+        if summary.sequence_number
+            != last
+                .as_ref()
+                .map_or(0, |last| last.summary().sequence_number + 1)
+            || summary.previous_digest != last.map(|last| last.summary().digest())
+        {
+            return None;
+        }
+
+        for head in &summary.checkpoint_data {
+            match self.validator_store.get_source_head(&head.subnet_id) {
+                Ok(Some(local)) if local.position >= head.position => {}
+                Ok(_) => {
+                    debug!(
+                        "Unable to attest the checkpoint {}, the certificate {} isn't delivered \
+                         yet",
+                        summary.sequence_number, head.certificate_id
+                    );
+                    return None;
+                }
+                Err(error) => {
+                    error!(
+                        "Unable to read the source head of {}: {error:?}",
+                        head.subnet_id
+                    );
+                    return None;
+                }
+            }
+
+            match self.validator_store.get_certificate(&head.certificate_id) {
+                Ok(Some(certificate))
+                    if certificate.proof_of_delivery.delivery_position.position
+                        == head.position => {}
+                _ => {
+                    warn!(
+                        "Refusing to sign the checkpoint {}, the certificate {} doesn't match the \
+                         delivered one",
+                        summary.sequence_number, head.certificate_id
+                    );
+                    return None;
+                }
+            }
+        }
+
+        self.sign(digest)
+    }
+
+    fn sign(&mut self, digest: &CheckpointDigest) -> Option<CheckpointSignature> {
+        let proposal = self.proposals.get_mut(digest)?;
+        let summary = &proposal.summary;
+
+        let signature = match self
+            .message_signer
+            .sign_message(&summary.signing_payload(&self.validator_id))
+        {
+            Ok(signature) => signature,
+            Err(error) => {
+                error!("Unable to sign the checkpoint: {error}");
+                return None;
+            }
+        };
+
+        proposal.signatures.insert(self.validator_id, signature);
+        self.signed.insert(
+            (summary.epoch, summary.sequence_number),
+            SignedCheckpoint {
+                digest: *digest,
+                signed_at: Instant::now(),
+            },
+        );
+
+        Some(CheckpointSignature {
+            summary: summary.clone(),
+            validator_id: self.validator_id,
+            signature,
+        })
+    }
+
+    /// Appends the proposal to the checkpoint chain once it reaches the threshold
+    async fn try_certify(&mut self, digest: &CheckpointDigest) {
+        let Some(proposal) = self.proposals.get(digest) else {
+            return;
+        };
+        let Some((validators, threshold)) = self.validator_set(proposal.summary.epoch) else {
+            return;
+        };
+
+        if proposal.signatures.len() < threshold {
+            return;
+        }
+
+        let checkpoint = VerifiedCheckpointSummary(
+            proposal.summary.clone(),
+            ValidatorQuorumSignatureInfo {
+                epoch: proposal.summary.epoch,
+                signatures: proposal
+                    .signatures
+                    .iter()
+                    .map(|(validator_id, signature)| (*validator_id, signature.to_string()))
+                    .collect(),
+            },
+        );
+
+        if let Err(error) = checkpoint.verify(&validators.into_iter().collect(), threshold) {
+            return error!("Unable to certify the checkpoint: {error}");
+        }
+
+        let fullnode_store = self.validator_store.get_fullnode_store();
+        let sequence_number = checkpoint.summary().sequence_number;
+
+        match fullnode_store.insert_verified_checkpoint(&checkpoint).await {
+            Ok(()) => {
+                info!(
+                    "Checkpoint {sequence_number} of the epoch {} certified",
+                    checkpoint.summary().epoch
+                );
+                self.proposals
+                    .retain(|_, proposal| proposal.summary.sequence_number > sequence_number);
+
+                let next_epoch = checkpoint.summary().next_epoch();
+                if next_epoch != self.epoch {
+                    info!("Entering the epoch {next_epoch}");
+                    self.epoch = next_epoch;
+                    self.epoch_change = Some(next_epoch);
+                }
+            }
+            Err(error) => warn!("Unable to append the checkpoint {sequence_number}: {error}"),
+        }
+    }
+
+    /// Returns whether the local validator can sign a checkpoint for the sequence number,
+    /// which is the case when it didn't sign any other one or when the one it signed
+    /// timed out
+    fn can_sign(
+        &self,
+        epoch: EpochId,
+        sequence_number: CheckpointSequenceNumber,
+        digest: Option<&CheckpointDigest>,
+    ) -> bool {
+        match self.signed.get(&(epoch, sequence_number)) {
+            None => true,
+            Some(signed) if Some(&signed.digest) == digest => false,
+            Some(signed) => {
+                signed.signed_at.elapsed() >= *CHECKPOINT_INTERVAL * SIGNATURE_TIMEOUT_SLOTS
+            }
+        }
+    }
+
+    /// Returns the last checkpoint of the chain, `None` if it can't be read
+    fn last_checkpoint(&self) -> Option<Option<VerifiedCheckpointSummary>> {
+        self.validator_store
+            .get_fullnode_store()
+            .get_last_verified_checkpoint()
+            .map_err(|error| error!("Unable to read the last checkpoint: {error:?}"))
+            .ok()
+    }
+
+    /// Returns the configured validator set, ordered by id, if it differs from the
+    /// validators of the current epoch
+    fn validator_set_change(&self, current: &[ValidatorId]) -> Option<Vec<ValidatorId>> {
+        if self.validators.is_empty()
+            || current.iter().copied().collect::<HashSet<_>>() == self.validators
+        {
+            return None;
+        }
+
+        let mut validators: Vec<_> = self.validators.iter().copied().collect();
+        validators.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+
+        Some(validators)
+    }
+
+    /// Returns the validators of the epoch, ordered by id, and the delivery threshold
+    fn validator_set(&self, epoch: EpochId) -> Option<(Vec<ValidatorId>, usize)> {
+        let validators = match self.validator_store.get_epoch_validators(epoch) {
+            Ok(Some(validators)) if !validators.is_empty() => validators,
+            Ok(_) => return None,
+            Err(error) => {
+                error!("Unable to load the validator set of the epoch {epoch}: {error:?}");
+                return None;
+            }
+        };
+
+        let threshold = if validators == self.validators {
+            self.params.delivery_threshold
+        } else {
+            ReliableBroadcastParams::new(validators.len()).delivery_threshold
+        };
+
+        let mut validators: Vec<_> = validators.into_iter().collect();
+        validators.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+
+        Some((validators, threshold))
+    }
+}
+
+/// Leaders of the current and of the previous slot, to tolerate clock drifts
+fn slot_leaders(validators: &[ValidatorId]) -> [ValidatorId; 2] {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let slot = now / CHECKPOINT_INTERVAL.as_secs().max(1);
+    let leader = |slot: u64| validators[(slot % validators.len() as u64) as usize];
+
+    [leader(slot), leader(slot.saturating_sub(1))]
+}
@@ -1,11 +1,19 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 use std::time::Duration;
 
+use serde::Deserialize;
 use tce_transport::ReliableBroadcastParams;
-use topos_core::types::ValidatorId;
+use topos_core::types::{ProofOfDelivery, ValidatorId};
+use topos_core::uci::SubnetId;
 use topos_p2p::{Multiaddr, PeerId};
+use topos_tce_storage::{tuning::StorageTuning, types::EpochId};
+
+pub use topos_crypto::frost::GroupPublicKey;
+pub use topos_tce_broadcast::SubnetGroupKeys;
+pub use topos_tce_synchronizer::SynchronizerServiceLimits;
 
 pub use crate::AppContext;
 
@@ -22,6 +30,9 @@ pub struct TceConfiguration {
     pub tce_params: ReliableBroadcastParams,
     pub boot_peers: Vec<(PeerId, Multiaddr)>,
     pub validators: HashSet<ValidatorId>,
+    /// Group public keys of the subnets signing their certificates with a threshold signature,
+    /// the certificates of the other subnets are verified against their subnet key
+    pub subnet_group_keys: SubnetGroupKeys,
     pub api_addr: SocketAddr,
     pub graphql_api_addr: SocketAddr,
     pub metrics_api_addr: SocketAddr,
@@ -30,11 +41,112 @@ pub struct TceConfiguration {
     pub storage: StorageConfiguration,
     pub network_bootstrap_timeout: Duration,
     pub minimum_cluster_size: usize,
+    /// Tuning of the RocksDB databases of the storage
+    pub storage_tuning: StorageTuning,
+    /// Maximum number of certificates kept in the pending pools for specific source
+    /// subnets, the other ones are limited to `PENDING_POOL_MAX_CERTIFICATES_PER_SUBNET`
+    pub pending_pool_limits: HashMap<SubnetId, usize>,
+    /// Checkpoint to start the synchronization from,
+    /// the whole history is synchronized if not provided
+    pub trusted_checkpoint: Option<TrustedCheckpoint>,
+    /// Synchronize the history prior to the trusted checkpoint once the node is up to date
+    pub sync_backfill: bool,
+    /// Limits applied to the synchronization requests of the other nodes
+    pub synchronizer_limits: SynchronizerServiceLimits,
     pub version: &'static str,
 }
 
+/// Configuration of a node serving the API over the storage of a running node,
+/// without taking part in the broadcast
+#[derive(Debug)]
+pub struct ApiConfiguration {
+    /// Path of the storage of the running node
+    pub db_path: PathBuf,
+    /// Path where the read-only instances of the storage keep their own files
+    pub secondary_db_path: PathBuf,
+    pub api_addr: SocketAddr,
+    pub graphql_api_addr: SocketAddr,
+    pub metrics_api_addr: SocketAddr,
+    /// Interval between two catch ups with the changes of the running node
+    pub catch_up_interval: Duration,
+    pub storage_tuning: StorageTuning,
+}
+
 #[derive(Debug)]
 pub enum StorageConfiguration {
     RAM,
     RocksDB(Option<PathBuf>),
 }
+
+/// Parses a list of `<SubnetId>=<GroupPublicKey>` pairs, comma or space separated
+pub fn parse_subnet_group_keys(input: &str) -> Result<SubnetGroupKeys, String> {
+    input
+        .split(&[',', ' '])
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (subnet_id, group_public_key) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid subnet group key pair: {pair}"))?;
+
+            Ok((
+                SubnetId::from_str(subnet_id).map_err(|e| e.to_string())?,
+                GroupPublicKey::from_str(group_public_key).map_err(|e| e.to_string())?,
+            ))
+        })
+        .collect()
+}
+
+/// Parses a list of `<SubnetId>=<max certificates>` pairs, comma or space separated
+pub fn parse_pending_pool_limits(input: &str) -> Result<HashMap<SubnetId, usize>, String> {
+    input
+        .split(&[',', ' '])
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (subnet_id, limit) = pair
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid pending pool limit pair: {pair}"))?;
+
+            Ok((
+                SubnetId::from_str(subnet_id).map_err(|e| e.to_string())?,
+                limit
+                    .parse()
+                    .map_err(|e| format!("Invalid limit {limit}: {e}"))?,
+            ))
+        })
+        .collect()
+}
+
+/// Proofs of Delivery of the source heads to start the synchronization from
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+pub struct TrustedCheckpoint {
+    /// Epoch whose validator set signed the proofs
+    #[serde(default)]
+    pub epoch: EpochId,
+    /// Validator set of the epoch, only required if the node doesn't know it
+    #[serde(default)]
+    pub validators: Option<HashSet<ValidatorId>>,
+    pub proofs: Vec<ProofOfDelivery>,
+}
+
+/// Reads a trusted checkpoint, either a JSON object with the `epoch` of the checkpoint,
+/// its `validators` and the `proofs` of the source heads, or a JSON array of the proofs
+/// of the source heads delivered during the epoch 0
+pub fn read_trusted_checkpoint(path: &Path) -> Result<TrustedCheckpoint, String> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Format {
+        Checkpoint(TrustedCheckpoint),
+        Proofs(Vec<ProofOfDelivery>),
+    }
+
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| format!("Unable to read {}: {e}", path.display()))?;
+
+    match serde_json::from_str(&content).map_err(|e| e.to_string())? {
+        Format::Checkpoint(checkpoint) => Ok(checkpoint),
+        Format::Proofs(proofs) => Ok(TrustedCheckpoint {
+            proofs,
+            ..Default::default()
+        }),
+    }
+}
@@ -1,7 +1,8 @@
 use config::TceConfiguration;
 use futures::StreamExt;
 use opentelemetry::global;
-use std::{future::IntoFuture, sync::Arc};
+use std::{collections::HashSet, future::IntoFuture, path::Path, sync::Arc};
+use tce_transport::ReliableBroadcastParams;
 use tokio::{
     spawn,
     sync::{broadcast, mpsc},
@@ -9,28 +10,39 @@ use tokio::{
 use tokio_stream::wrappers::BroadcastStream;
 use tokio_util::sync::CancellationToken;
 use topos_core::api::grpc::tce::v1::synchronizer_service_server::SynchronizerServiceServer;
+use topos_core::{
+    types::ValidatorId,
+    uci::verifier::{VerifierRegistry, Verifiers},
+};
 use topos_crypto::messages::MessageSigner;
 use topos_p2p::{
     utils::{local_key_pair, local_key_pair_from_slice},
     GrpcContext, GrpcRouter, Multiaddr,
 };
-use topos_tce_broadcast::{ReliableBroadcastClient, ReliableBroadcastConfig};
+use topos_tce_broadcast::{
+    ReliableBroadcastClient, ReliableBroadcastConfig, SignatureVerification,
+};
 use topos_tce_storage::{
     epoch::{EpochValidatorsStore, ValidatorPerEpochStore},
     fullnode::FullNodeStore,
     index::IndexTables,
+    tuning,
+    types::PoolRetention,
     validator::{ValidatorPerpetualTables, ValidatorStore},
     StorageClient,
 };
 use topos_tce_synchronizer::SynchronizerService;
 use tracing::{debug, warn};
+pub mod api_node;
 mod app_context;
+pub mod checkpointer;
 pub mod config;
 pub mod events;
 
 pub use app_context::AppContext;
 
-use crate::config::{AuthKey, StorageConfiguration};
+use crate::checkpointer::Checkpointer;
+use crate::config::{AuthKey, StorageConfiguration, TrustedCheckpoint};
 
 // TODO: Estimate on the max broadcast throughput, could need to be override by config
 const BROADCAST_CHANNEL_SIZE: usize = 10_000;
@@ -73,40 +85,40 @@ pub async fn run(
 
     let peer_list = boot_peers.iter().map(|(p, _)| *p).collect::<Vec<_>>();
     debug!("Starting the Storage");
-    let path = if let StorageConfiguration::RocksDB(Some(ref path)) = config.storage {
-        path
-    } else {
-        return Err(Box::new(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("Unsupported storage type {:?}", config.storage),
-        )));
+    if let Err(error) = tuning::configure(config.storage_tuning.clone()) {
+        warn!("Unable to tune the storage: {error}");
+    }
+
+    let validator_store = match config.storage {
+        StorageConfiguration::RocksDB(Some(ref path)) => open_validator_store(path),
+        StorageConfiguration::RAM => {
+            warn!("Using the in-memory storage, nothing will be persisted");
+
+            let fullnode_store =
+                FullNodeStore::open_in_memory().expect("Unable to create full node store");
+
+            ValidatorStore::open_in_memory(fullnode_store)
+                .expect("Unable to create validator store")
+        }
+        StorageConfiguration::RocksDB(None) => {
+            return Err(Box::new(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!("Unsupported storage type {:?}", config.storage),
+            )));
+        }
     };
 
-    let perpetual_tables = Arc::new(ValidatorPerpetualTables::open(path.clone()));
-    let index_tables = Arc::new(IndexTables::open(path.clone()));
-
-    let validators_store =
-        EpochValidatorsStore::new(path.clone()).expect("Unable to create EpochValidators store");
-
-    let epoch_store =
-        ValidatorPerEpochStore::new(0, path.clone()).expect("Unable to create Per epoch store");
-
-    let fullnode_store = FullNodeStore::open(
-        epoch_store,
-        validators_store,
-        perpetual_tables,
-        index_tables,
-    )
-    .expect("Unable to create full node store");
-
-    let validator_store = ValidatorStore::open(path.clone(), fullnode_store.clone())
-        .expect("Unable to create validator store");
+    validator_store.set_pool_retention(PoolRetention {
+        subnet_limits: config.pending_pool_limits.clone(),
+        ..validator_store.pool_retention()
+    });
 
     let grpc_context = GrpcContext::default().with_router(
         GrpcRouter::new(tonic::transport::Server::builder()).add_service(
-            SynchronizerServiceServer::new(SynchronizerService {
-                validator_store: validator_store.clone(),
-            }),
+            SynchronizerServiceServer::new(
+                SynchronizerService::new(validator_store.clone())
+                    .with_limits(config.synchronizer_limits.clone()),
+            ),
         ),
     );
 
@@ -144,12 +156,50 @@ pub async fn run(
 
     debug!("Starting reliable broadcast");
 
+    let verifiers: Arc<dyn VerifierRegistry> = Arc::new(Verifiers::default());
+
+    // The configured validators form the genesis validator set, the next ones
+    // being agreed on through the checkpoint chain
+    if validator_store.get_epoch_validators(0)?.is_none() {
+        validator_store.insert_epoch_validators(0, &config.validators)?;
+    }
+
+    let epoch = validator_store.get_fullnode_store().get_current_epoch()?;
+    let validators = validator_store
+        .get_epoch_validators(epoch)?
+        .ok_or_else(|| format!("No validator set registered for the epoch {epoch}"))?;
+    let tce_params = if validators == config.validators {
+        config.tce_params.clone()
+    } else {
+        warn!("The configured validators differ from the validator set of the epoch {epoch}");
+
+        ReliableBroadcastParams::new(validators.len())
+    };
+    debug!(
+        "Starting at the epoch {epoch} with {} validators",
+        validators.len()
+    );
+
+    let checkpointer = Checkpointer::new(
+        message_signer.public_address.into(),
+        message_signer.clone(),
+        validator_store.clone(),
+        epoch,
+        config.validators.clone(),
+        config.tce_params.clone(),
+    );
+
     let (tce_cli, tce_stream) = ReliableBroadcastClient::new(
         ReliableBroadcastConfig {
-            tce_params: config.tce_params.clone(),
+            tce_params: tce_params.clone(),
             validator_id: message_signer.public_address.into(),
-            validators: config.validators.clone(),
+            validators: validators.clone(),
             message_signer,
+            verifiers: verifiers.clone(),
+            signature_verification: SignatureVerification::Enabled(
+                config.subnet_group_keys.clone(),
+            ),
+            epoch,
         },
         validator_store.clone(),
         broadcast_sender,
@@ -160,12 +210,33 @@ pub async fn run(
 
     debug!("Starting the Synchronizer");
 
+    let (trusted_proofs, trusted_validators) = match &config.trusted_checkpoint {
+        Some(checkpoint) => (
+            checkpoint.proofs.clone(),
+            trusted_checkpoint_validators(&validator_store, checkpoint)?,
+        ),
+        None => Default::default(),
+    };
+    let trusted_delivery_threshold = if trusted_validators == config.validators {
+        config.tce_params.delivery_threshold
+    } else {
+        ReliableBroadcastParams::new(trusted_validators.len()).delivery_threshold
+    } as u64;
+
     let (synchronizer_runtime, synchronizer_stream) =
         topos_tce_synchronizer::Synchronizer::builder()
             .with_shutdown(shutdown.0.child_token())
             .with_store(validator_store.clone())
             .with_gatekeeper_client(gatekeeper_client.clone())
             .with_network_client(network_client.clone())
+            .with_validators(validators)
+            .with_delivery_threshold(tce_params.delivery_threshold as u64)
+            .with_trusted_checkpoint(
+                trusted_proofs,
+                trusted_validators,
+                trusted_delivery_threshold,
+            )
+            .with_backfill(config.sync_backfill)
             .build()?;
 
     spawn(synchronizer_runtime.into_future());
@@ -179,6 +250,7 @@ pub async fn run(
         .serve_graphql_addr(config.graphql_api_addr)
         .serve_metrics_addr(config.metrics_api_addr)
         .store(validator_store.clone())
+        .verifiers(verifiers)
         .storage(storage_client.clone())
         .build_and_launch()
         .await;
@@ -192,6 +264,7 @@ pub async fn run(
         api_client,
         gatekeeper_client,
         validator_store,
+        checkpointer,
     );
 
     app_context
@@ -208,3 +281,97 @@ pub async fn run(
     global::shutdown_tracer_provider();
     Ok(())
 }
+
+/// Returns the validator set of the epoch of the trusted checkpoint, the one known
+/// locally or else the one declared by the checkpoint
+fn trusted_checkpoint_validators(
+    validator_store: &ValidatorStore,
+    checkpoint: &TrustedCheckpoint,
+) -> Result<HashSet<ValidatorId>, Box<dyn std::error::Error>> {
+    let epoch = checkpoint.epoch;
+
+    match (
+        validator_store.get_epoch_validators(epoch)?,
+        &checkpoint.validators,
+    ) {
+        (Some(known), Some(declared)) if known != *declared => Err(format!(
+            "The validators of the trusted checkpoint differ from the validator set of the epoch \
+             {epoch}"
+        )
+        .into()),
+        (Some(known), _) => Ok(known),
+        (None, Some(declared)) => Ok(declared.clone()),
+        (None, None) => Err(format!(
+            "Unknown validator set of the epoch {epoch} of the trusted checkpoint"
+        )
+        .into()),
+    }
+}
+
+/// Opens the validator store persisted at `path`
+pub fn open_validator_store(path: &Path) -> Arc<ValidatorStore> {
+    ValidatorStore::open(path.to_path_buf(), open_fullnode_store(path))
+        .expect("Unable to create validator store")
+}
+
+/// Opens the validator store persisted at `path` as a read-only secondary instance,
+/// keeping its own files at `secondary_path`
+pub fn open_secondary_validator_store(path: &Path, secondary_path: &Path) -> Arc<ValidatorStore> {
+    let (path, secondary_path) = (path.to_path_buf(), secondary_path.to_path_buf());
+
+    let perpetual_tables = Arc::new(ValidatorPerpetualTables::open_secondary(
+        path.clone(),
+        secondary_path.clone(),
+    ));
+    let index_tables = Arc::new(IndexTables::open_secondary(
+        path.clone(),
+        secondary_path.clone(),
+    ));
+
+    let validators_store =
+        EpochValidatorsStore::new_secondary(path.clone(), secondary_path.clone())
+            .expect("Unable to create EpochValidators store");
+
+    // Follows the epoch store the node opened last
+    let current_epoch = perpetual_tables
+        .current_epoch()
+        .expect("Unable to read the current epoch");
+    let epoch = ValidatorPerEpochStore::latest_epoch(current_epoch, path.clone()).unwrap_or(0);
+    let epoch_store =
+        ValidatorPerEpochStore::new_secondary(epoch, path.clone(), secondary_path.clone())
+            .expect("Unable to create Per epoch store");
+
+    let fullnode_store = FullNodeStore::open_secondary(
+        epoch_store,
+        validators_store,
+        perpetual_tables,
+        index_tables,
+    )
+    .expect("Unable to create full node store");
+
+    ValidatorStore::open_secondary(path, secondary_path, fullnode_store)
+        .expect("Unable to create validator store")
+}
+
+/// Opens the full node store persisted at `path`
+pub fn open_fullnode_store(path: &Path) -> Arc<FullNodeStore> {
+    let perpetual_tables = Arc::new(ValidatorPerpetualTables::open(path.to_path_buf()));
+    let index_tables = Arc::new(IndexTables::open(path.to_path_buf(), &perpetual_tables));
+
+    let validators_store = EpochValidatorsStore::new(path.to_path_buf())
+        .expect("Unable to create EpochValidators store");
+
+    let epoch = perpetual_tables
+        .current_epoch()
+        .expect("Unable to read the current epoch");
+    let epoch_store = ValidatorPerEpochStore::new(epoch, path.to_path_buf())
+        .expect("Unable to create Per epoch store");
+
+    FullNodeStore::open(
+        epoch_store,
+        validators_store,
+        perpetual_tables,
+        index_tables,
+    )
+    .expect("Unable to create full node store")
+}
@@ -77,7 +77,7 @@ pub async fn create_fullnode_store(certificates: Vec<CertificateDelivered>) -> A
     let temp_dir = create_folder::default();
 
     let perpetual_tables = Arc::new(ValidatorPerpetualTables::open(temp_dir.clone()));
-    let index_tables = Arc::new(IndexTables::open(temp_dir.clone()));
+    let index_tables = Arc::new(IndexTables::open(temp_dir.clone(), &perpetual_tables));
 
     let validators_store = EpochValidatorsStore::new(temp_dir.clone())
         .expect("Unable to create EpochValidators store");
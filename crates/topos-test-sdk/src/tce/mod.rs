@@ -26,6 +26,7 @@ use topos_core::api::grpc::tce::v1::{
 };
 use topos_core::api::grpc::tce::v1::{
     CheckpointRequest, CheckpointResponse, FetchCertificatesRequest, FetchCertificatesResponse,
+    PendingCertificateIdsRequest, PendingCertificateIdsResponse,
 };
 use topos_core::api::grpc::tce::v1::{StatusRequest, StatusResponse};
 use topos_core::types::CertificateDelivered;
@@ -33,7 +34,7 @@ use topos_core::types::ValidatorId;
 use topos_core::uci::SubnetId;
 use topos_crypto::messages::MessageSigner;
 use topos_p2p::{error::P2PError, Event, GrpcRouter, NetworkClient, Runtime};
-use topos_tce::{events::Events, AppContext};
+use topos_tce::{checkpointer::Checkpointer, events::Events, AppContext};
 use topos_tce_api::RuntimeContext;
 use topos_tce_storage::StorageClient;
 use topos_tce_synchronizer::SynchronizerService;
@@ -187,6 +188,20 @@ impl GrpcSynchronizerService for DummyService {
     ) -> Result<Response<CheckpointResponse>, Status> {
         Err(Status::unimplemented("fetch_checkpoint"))
     }
+
+    async fn fetch_pending_certificate_ids(
+        &self,
+        _request: Request<PendingCertificateIdsRequest>,
+    ) -> Result<Response<PendingCertificateIdsResponse>, Status> {
+        Err(Status::unimplemented("fetch_pending_certificate_ids"))
+    }
+
+    async fn fetch_pending_certificates(
+        &self,
+        _request: Request<FetchCertificatesRequest>,
+    ) -> Result<Response<FetchCertificatesResponse>, Status> {
+        Err(Status::unimplemented("fetch_pending_certificates"))
+    }
 }
 
 pub fn create_dummy_router() -> Router {
@@ -220,9 +235,7 @@ pub async fn start_node(
         .collect::<Vec<_>>();
 
     let router = GrpcRouter::new(tonic::transport::Server::builder()).add_service(
-        SynchronizerServiceServer::new(SynchronizerService {
-            validator_store: validator_store.clone(),
-        }),
+        SynchronizerServiceServer::new(SynchronizerService::new(validator_store.clone())),
     );
 
     let (network_client, network_stream, runtime_join_handle) = bootstrap_network(
@@ -236,8 +249,26 @@ pub async fn start_node(
     .await
     .expect("Unable to bootstrap tce network");
 
+    // Nodes started without validator set only trust the proofs they signed
+    let sync_validators = if validators.is_empty() {
+        HashSet::from([validator_id])
+    } else {
+        validators.clone()
+    };
+    let delivery_threshold = (create_reliable_broadcast_params(peers.len()).delivery_threshold
+        as u64)
+        .clamp(1, sync_validators.len() as u64);
+
     let storage_client = StorageClient::new(validator_store.clone());
     let (sender, receiver) = broadcast::channel(100);
+    let checkpointer = Checkpointer::new(
+        validator_id,
+        message_signer.clone(),
+        validator_store.clone(),
+        Default::default(),
+        validators.clone(),
+        create_reliable_broadcast_params(peers.len()),
+    );
     let (tce_cli, tce_stream) = create_reliable_broadcast_client(
         validator_id,
         validators,
@@ -264,6 +295,8 @@ pub async fn start_node(
         gatekeeper_client.clone(),
         network_client.clone(),
         validator_store.clone(),
+        sync_validators,
+        delivery_threshold,
     )
     .await;
 
@@ -274,6 +307,7 @@ pub async fn start_node(
         api_context.client,
         gatekeeper_client,
         validator_store,
+        checkpointer,
     );
 
     let shutdown_token = CancellationToken::new();
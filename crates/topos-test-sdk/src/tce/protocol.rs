@@ -3,8 +3,11 @@ use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::sync::broadcast;
 use topos_core::types::ValidatorId;
+use topos_core::uci::verifier::Verifiers;
 use topos_crypto::messages::MessageSigner;
-use topos_tce_broadcast::{ReliableBroadcastClient, ReliableBroadcastConfig};
+use topos_tce_broadcast::{
+    ReliableBroadcastClient, ReliableBroadcastConfig, SignatureVerification,
+};
 use topos_tce_storage::types::CertificateDeliveredWithPositions;
 use topos_tce_storage::validator::ValidatorStore;
 use topos_tce_transport::{ProtocolEvents, ReliableBroadcastParams};
@@ -25,6 +28,9 @@ pub async fn create_reliable_broadcast_client(
         validator_id,
         validators,
         message_signer,
+        verifiers: Arc::new(Verifiers::default()),
+        signature_verification: SignatureVerification::Disabled,
+        epoch: 0,
     };
 
     ReliableBroadcastClient::new(config, storage, sender).await
@@ -1,9 +1,11 @@
 use futures::Stream;
+use std::collections::HashSet;
 use std::future::IntoFuture;
 use std::sync::Arc;
 use tokio::{spawn, task::JoinHandle};
 use tokio_util::sync::CancellationToken;
 
+use topos_core::types::ValidatorId;
 use topos_p2p::NetworkClient;
 use topos_tce_gatekeeper::GatekeeperClient;
 use topos_tce_storage::validator::ValidatorStore;
@@ -14,6 +16,8 @@ pub async fn create_synchronizer(
     gatekeeper_client: GatekeeperClient,
     network_client: NetworkClient,
     store: Arc<ValidatorStore>,
+    validators: HashSet<ValidatorId>,
+    delivery_threshold: u64,
 ) -> (
     impl Stream<Item = SynchronizerEvent>,
     JoinHandle<Result<(), SynchronizerError>>,
@@ -25,6 +29,8 @@ pub async fn create_synchronizer(
             .with_store(store)
             .with_gatekeeper_client(gatekeeper_client)
             .with_network_client(network_client)
+            .with_validators(validators)
+            .with_delivery_threshold(delivery_threshold)
             .build()
             .expect("Can't create the Synchronizer");
 
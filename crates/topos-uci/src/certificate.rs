@@ -1,7 +1,9 @@
+use crate::verifier::VerifierRegistry;
 use crate::*;
 use serde::{Deserialize, Serialize};
 use std::borrow::Borrow;
 use std::fmt::Debug;
+use topos_crypto::frost::{GroupKeys, GroupPublicKey, SignatureShare, SigningCommitment};
 
 /// Certificate - main exchange item
 #[derive(Clone, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
@@ -111,16 +113,48 @@ impl Certificate {
         Ok(cert)
     }
 
+    /// Verifies that the id of the certificate is the hash of its content
+    pub fn verify_id(&self) -> Result<(), Error> {
+        if self.id != CertificateId::from(Self::calculate_cert_id(self)?) {
+            return Err(Error::ValidationError(format!(
+                "id {} doesn't match the certificate content",
+                self.id
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Verifies the ECDSA signature against the public key of the source subnet
     pub fn check_signature(&self) -> Result<(), Error> {
-        std::thread::sleep(DUMMY_FROST_VERIF_DELAY);
+        topos_crypto::signatures::verify(
+            &self.source_subnet_id.to_secp256k1_public_key(),
+            self.get_payload().as_slice(),
+            self.signature.as_slice(),
+        )?;
+
         Ok(())
     }
 
-    pub fn check_proof(&self) -> Result<(), Error> {
-        std::thread::sleep(DUMMY_STARK_DELAY);
+    /// Verifies the threshold signature against the group public key of the source subnet
+    pub fn check_threshold_signature(
+        &self,
+        group_public_key: &GroupPublicKey,
+    ) -> Result<(), Error> {
+        topos_crypto::frost::verify(
+            &group_public_key.to_bytes(),
+            self.get_payload().as_slice(),
+            self.signature.as_slice(),
+        )?;
+
         Ok(())
     }
 
+    /// Verifies the proof using the verifier registered for `self.verifier`
+    pub fn check_proof(&self, verifiers: &dyn VerifierRegistry) -> Result<(), Error> {
+        verifiers.verify(self)
+    }
+
     /// Signs the hash of the certificate payload
     pub fn update_signature(&mut self, private_key: &[u8]) -> Result<(), Error> {
         self.signature =
@@ -128,6 +162,24 @@ impl Certificate {
         Ok(())
     }
 
+    /// Sets the signature aggregated from the shares of the source subnet signers,
+    /// each share being produced over the certificate payload and verified against
+    /// the public keys of the group
+    pub fn update_threshold_signature(
+        &mut self,
+        group_keys: &GroupKeys,
+        commitments: &[SigningCommitment],
+        shares: &[SignatureShare],
+    ) -> Result<(), Error> {
+        self.signature = topos_crypto::frost::aggregate(
+            group_keys,
+            self.get_payload().as_slice(),
+            commitments,
+            shares,
+        )?;
+        Ok(())
+    }
+
     /// Get byte payload of the certificate
     /// Excludes frost signature
     pub fn get_payload(&self) -> Vec<u8> {
@@ -212,6 +264,17 @@ mod tests {
         .expect("valid signature check")
     }
 
+    #[test]
+    fn certificate_id_matches_its_content() {
+        let private_test_key = hex::decode(PRIVATE_TEST_KEY).unwrap();
+        let mut dummy_cert = generate_dummy_cert(&private_test_key);
+
+        assert!(dummy_cert.verify_id().is_ok());
+
+        dummy_cert.state_root[0] = 0xff;
+        assert!(dummy_cert.verify_id().is_err());
+    }
+
     #[test]
     #[should_panic]
     fn signature_verification_failed_corrupt_data() {
@@ -0,0 +1,171 @@
+//! Certificate proof verification
+//!
+//! The `verifier` field of a [`Certificate`] identifies the backend able to
+//! verify its `proof`. Backends are registered in a [`VerifierRegistry`]
+//! which dispatches each certificate to the right one.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::{Certificate, Error};
+
+/// Verifier id of the [`NoopVerifier`]
+pub const NOOP_VERIFIER_ID: u32 = 0;
+
+/// Verifier id of the [`HashCommitmentVerifier`]
+pub const HASH_COMMITMENT_VERIFIER_ID: u32 = 1;
+
+/// Backend able to verify the proof of a certificate
+pub trait ProofVerifier: Send + Sync {
+    fn verify_proof(&self, certificate: &Certificate) -> Result<(), Error>;
+}
+
+/// Maps verifier ids to their proof verification backend
+pub trait VerifierRegistry: Send + Sync {
+    /// Returns the backend registered for the given verifier id
+    fn get(&self, verifier_id: u32) -> Option<&dyn ProofVerifier>;
+
+    /// Verifies the proof of the certificate using the backend
+    /// matching its `verifier` field
+    fn verify(&self, certificate: &Certificate) -> Result<(), Error> {
+        self.get(certificate.verifier)
+            .ok_or(Error::UnknownVerifier(certificate.verifier))?
+            .verify_proof(certificate)
+    }
+}
+
+/// Accepts every proof
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoopVerifier;
+
+impl ProofVerifier for NoopVerifier {
+    fn verify_proof(&self, _certificate: &Certificate) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Expects the proof to be the keccak256 hash of the state root,
+/// the transactions root and the receipts root of the certificate
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HashCommitmentVerifier;
+
+impl HashCommitmentVerifier {
+    /// Computes the commitment expected as proof for the given certificate
+    pub fn commitment(certificate: &Certificate) -> [u8; 32] {
+        let mut buffer = Vec::new();
+        buffer.extend_from_slice(certificate.state_root.as_ref());
+        buffer.extend_from_slice(certificate.tx_root_hash.as_ref());
+        buffer.extend_from_slice(certificate.receipts_root_hash.as_ref());
+
+        topos_crypto::hash::calculate_hash(&buffer)
+    }
+}
+
+impl ProofVerifier for HashCommitmentVerifier {
+    fn verify_proof(&self, certificate: &Certificate) -> Result<(), Error> {
+        if certificate.proof.as_slice() != Self::commitment(certificate).as_slice() {
+            return Err(Error::InvalidProof(
+                "proof doesn't match the hash commitment".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Registry of proof verifiers
+///
+/// The default registry contains the [`NoopVerifier`] and the
+/// [`HashCommitmentVerifier`].
+#[derive(Clone)]
+pub struct Verifiers {
+    verifiers: HashMap<u32, Arc<dyn ProofVerifier>>,
+}
+
+impl Verifiers {
+    /// Creates a registry without any verifier
+    pub fn empty() -> Self {
+        Self {
+            verifiers: HashMap::new(),
+        }
+    }
+
+    /// Registers a verifier, replacing any verifier previously registered
+    /// with the same id
+    pub fn register<V: ProofVerifier + 'static>(mut self, verifier_id: u32, verifier: V) -> Self {
+        self.verifiers.insert(verifier_id, Arc::new(verifier));
+
+        self
+    }
+}
+
+impl Default for Verifiers {
+    fn default() -> Self {
+        Self::empty()
+            .register(NOOP_VERIFIER_ID, NoopVerifier)
+            .register(HASH_COMMITMENT_VERIFIER_ID, HashCommitmentVerifier)
+    }
+}
+
+impl VerifierRegistry for Verifiers {
+    fn get(&self, verifier_id: u32) -> Option<&dyn ProofVerifier> {
+        self.verifiers.get(&verifier_id).map(|verifier| &**verifier)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CertificateId, SubnetId, CERTIFICATE_ID_LENGTH, SUBNET_ID_LENGTH};
+
+    const PREV_CERTIFICATE_ID: CertificateId =
+        CertificateId::from_array([1u8; CERTIFICATE_ID_LENGTH]);
+    const SOURCE_SUBNET_ID: SubnetId = SubnetId::from_array([2u8; SUBNET_ID_LENGTH]);
+
+    fn certificate(verifier: u32, proof: Vec<u8>) -> Certificate {
+        Certificate::new(
+            PREV_CERTIFICATE_ID,
+            SOURCE_SUBNET_ID,
+            [4u8; 32],
+            [5u8; 32],
+            [6u8; 32],
+            &[],
+            verifier,
+            proof,
+        )
+        .expect("Dummy certificate")
+    }
+
+    #[test]
+    fn noop_verifier_accepts_any_proof() {
+        let verifiers = Verifiers::default();
+
+        assert!(verifiers
+            .verify(&certificate(NOOP_VERIFIER_ID, vec![1, 2, 3]))
+            .is_ok());
+    }
+
+    #[test]
+    fn hash_commitment_verifier() {
+        let verifiers = Verifiers::default();
+
+        let mut cert = certificate(HASH_COMMITMENT_VERIFIER_ID, Vec::new());
+        assert!(matches!(
+            verifiers.verify(&cert),
+            Err(Error::InvalidProof(_))
+        ));
+
+        cert.proof = HashCommitmentVerifier::commitment(&cert).to_vec();
+        assert!(verifiers.verify(&cert).is_ok());
+    }
+
+    #[test]
+    fn unknown_verifier_is_rejected() {
+        let verifiers = Verifiers::empty().register(NOOP_VERIFIER_ID, NoopVerifier);
+
+        assert!(matches!(
+            verifiers.verify(&certificate(HASH_COMMITMENT_VERIFIER_ID, Vec::new())),
+            Err(Error::UnknownVerifier(HASH_COMMITMENT_VERIFIER_ID))
+        ));
+    }
+}
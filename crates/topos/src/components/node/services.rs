@@ -9,7 +9,10 @@ use tokio::{spawn, sync::mpsc, task::JoinHandle};
 use tokio_util::sync::CancellationToken;
 use topos_p2p::config::NetworkConfig;
 use topos_sequencer::SequencerConfiguration;
-use topos_tce::config::{AuthKey, StorageConfiguration, TceConfiguration};
+use topos_tce::config::{
+    parse_pending_pool_limits, parse_subnet_group_keys, read_trusted_checkpoint, AuthKey,
+    StorageConfiguration, TceConfiguration,
+};
 use topos_tce_transport::ReliableBroadcastParams;
 use topos_wallet::SecretManager;
 use tracing::{debug, error, info};
@@ -66,6 +69,7 @@ pub(crate) fn spawn_sequencer_process(
         signing_key: keys.validator.clone().unwrap(),
         verifier: 0,
         start_block: config.start_block,
+        signing_coordinator: None,
     };
 
     debug!("Sequencer args: {config:?}");
@@ -90,6 +94,11 @@ pub(crate) fn spawn_tce_process(
             .chain(config.parse_boot_peers())
             .collect::<Vec<_>>(),
         validators: genesis.validators().expect("Cannot parse validators"),
+        subnet_group_keys: config
+            .subnet_group_keys
+            .as_deref()
+            .map(|keys| parse_subnet_group_keys(keys).expect("Cannot parse subnet group keys"))
+            .unwrap_or_default(),
         auth_key: keys.network.map(AuthKey::PrivateKey),
         signing_key: keys.validator.map(AuthKey::PrivateKey),
         tce_addr: format!("/ip4/{}", config.libp2p_api_addr.ip()),
@@ -98,11 +107,29 @@ pub(crate) fn spawn_tce_process(
         api_addr: config.grpc_api_addr,
         graphql_api_addr: config.graphql_api_addr,
         metrics_api_addr: config.metrics_api_addr,
-        storage: StorageConfiguration::RocksDB(Some(config.db_path)),
+        storage: if config.in_memory_storage {
+            StorageConfiguration::RAM
+        } else {
+            StorageConfiguration::RocksDB(Some(config.db_path))
+        },
         network_bootstrap_timeout: Duration::from_secs(180),
         minimum_cluster_size: config
             .minimum_tce_cluster_size
             .unwrap_or(NetworkConfig::MINIMUM_CLUSTER_SIZE),
+        trusted_checkpoint: config
+            .trusted_checkpoint
+            .as_deref()
+            .map(|path| read_trusted_checkpoint(path).expect("Cannot read the trusted checkpoint")),
+        sync_backfill: config.sync_backfill,
+        synchronizer_limits: config.sync_limits,
+        storage_tuning: config.storage,
+        pending_pool_limits: config
+            .pending_pool_limits
+            .as_deref()
+            .map(|limits| {
+                parse_pending_pool_limits(limits).expect("Cannot parse pending pool limits")
+            })
+            .unwrap_or_default(),
         version: env!("TOPOS_VERSION"),
     };
 
@@ -28,6 +28,7 @@ pub(crate) async fn handle_command(
                 signing_key: keys.validator.clone().unwrap(),
                 verifier: cmd.verifier,
                 start_block: cmd.start_block,
+                signing_coordinator: None,
             };
 
             // Setup instrumentation if both otlp agent and otlp service name are provided as arguments
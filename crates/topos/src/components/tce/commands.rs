@@ -2,14 +2,20 @@ use std::path::PathBuf;
 
 use clap::{Args, Subcommand};
 
+mod api;
 mod peer_id;
 mod push_certificate;
 mod run;
+pub(crate) mod snapshot;
 mod status;
+pub(crate) mod storage;
 
+pub(crate) use api::Api;
 pub(crate) use push_certificate::PushCertificate;
 pub(crate) use run::Run;
+pub(crate) use snapshot::{Snapshot, SnapshotCommands};
 pub(crate) use status::Status;
+pub(crate) use storage::{Storage, StorageCommands};
 
 use self::peer_id::Keys;
 
@@ -34,18 +40,36 @@ pub(crate) struct TceCommand {
 
 #[derive(Subcommand, Debug)]
 pub(crate) enum TceCommands {
+    Api(Api),
     PushCertificate(PushCertificate),
     Keys(Keys),
     Run(Box<Run>),
+    Snapshot(Snapshot),
     Status(Status),
+    Storage(Storage),
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_api() {
+        assert!(TceCommands::has_subcommand("api"));
+    }
+
     #[test]
     fn test_run() {
         assert!(TceCommands::has_subcommand("run"));
     }
+
+    #[test]
+    fn test_snapshot() {
+        assert!(TceCommands::has_subcommand("snapshot"));
+    }
+
+    #[test]
+    fn test_storage() {
+        assert!(TceCommands::has_subcommand("storage"));
+    }
 }
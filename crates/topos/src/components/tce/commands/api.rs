@@ -0,0 +1,66 @@
+use std::{net::SocketAddr, path::PathBuf};
+
+use clap::Args;
+
+use super::storage::StorageTuningArgs;
+
+/// Serve the API of a running node from its storage, without taking part in the broadcast
+#[derive(Args, Debug)]
+pub(crate) struct Api {
+    /// Storage database path of the running node
+    #[arg(long, env = "TCE_DB_PATH")]
+    pub(crate) db_path: PathBuf,
+
+    /// Path where the read-only instances of the storage keep their own files,
+    /// defaults to the `<db-path>-secondary` folder next to the storage
+    #[arg(long, env = "TCE_SECONDARY_DB_PATH")]
+    pub(crate) secondary_db_path: Option<PathBuf>,
+
+    /// Interval in milliseconds between two catch ups with the changes of the running node
+    #[arg(long, env = "TCE_CATCH_UP_INTERVAL_MS", default_value_t = 500)]
+    pub(crate) catch_up_interval_ms: u64,
+
+    /// gRPC API Addr
+    #[arg(long, env = "TCE_API_NODE_API_ADDR", default_value = "[::1]:1341")]
+    pub(crate) api_addr: SocketAddr,
+
+    /// GraphQL API Addr
+    #[arg(
+        long,
+        env = "TCE_API_NODE_GRAPHQL_API_ADDR",
+        default_value = "[::1]:4001"
+    )]
+    pub(crate) graphql_api_addr: SocketAddr,
+
+    /// Metrics server API Addr
+    #[arg(
+        long,
+        env = "TCE_API_NODE_METRICS_API_ADDR",
+        default_value = "[::1]:3001"
+    )]
+    pub(crate) metrics_api_addr: SocketAddr,
+
+    #[command(flatten)]
+    pub(crate) storage_tuning: StorageTuningArgs,
+}
+
+impl Api {
+    /// Path of the read-only instances, outside of the storage owned by the running node
+    pub(crate) fn secondary_db_path(&self) -> PathBuf {
+        self.secondary_db_path.clone().unwrap_or_else(|| {
+            // Resolved first so that a path such as `.` has a parent folder
+            let db_path = self
+                .db_path
+                .canonicalize()
+                .unwrap_or_else(|_| self.db_path.clone());
+
+            let mut name = db_path
+                .file_name()
+                .unwrap_or_else(|| "tce".as_ref())
+                .to_os_string();
+            name.push("-secondary");
+
+            db_path.with_file_name(name)
+        })
+    }
+}
@@ -1,12 +1,20 @@
 use clap::Args;
 use serde::Serialize;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::net::SocketAddr;
+use std::path::PathBuf;
 use std::str::FromStr;
 use topos_core::types::{ValidatorId, ValidatorIdConversionError};
+use topos_core::uci::SubnetId;
 use topos_p2p::{Multiaddr, PeerId};
+use topos_tce::config::{
+    parse_pending_pool_limits, parse_subnet_group_keys, read_trusted_checkpoint, SubnetGroupKeys,
+    SynchronizerServiceLimits, TrustedCheckpoint,
+};
 use topos_tce_transport::ReliableBroadcastParams;
 
+use super::storage::StorageTuningArgs;
+
 #[derive(Args, Debug, Serialize)]
 #[command(about = "Run a full TCE instance")]
 pub struct Run {
@@ -20,6 +28,12 @@ pub struct Run {
     #[arg(long, default_value = "", env = "TCE_VALIDATORS", default_value = "")]
     pub validators: String,
 
+    /// Group public keys of the subnets signing their certificates with a threshold
+    /// signature, pairs of <SubnetId>=<GroupPublicKey>, comma separated. The certificates
+    /// of the other subnets must be signed with the key their subnet id is derived from
+    #[arg(long, env = "TCE_SUBNET_GROUP_KEYS")]
+    pub subnet_group_keys: Option<String>,
+
     /// Advertised (externally visible) <host>,
     /// if empty this machine ip address(es) are used
     #[arg(long, env = "TCE_EXT_HOST", default_value = "/ip4/0.0.0.0")]
@@ -49,6 +63,10 @@ pub struct Run {
     #[clap(long, default_value = "./default_db/", env = "TCE_DB_PATH")]
     pub db_path: Option<String>,
 
+    /// Keep the storage in memory instead of using the database path, nothing is persisted
+    #[clap(long, env = "TCE_IN_MEMORY_STORAGE")]
+    pub in_memory_storage: bool,
+
     /// gRPC API Addr
     #[clap(long, env = "TCE_API_ADDR", default_value = "[::1]:1340")]
     pub api_addr: SocketAddr,
@@ -77,6 +95,70 @@ pub struct Run {
 
     #[arg(long, env = "TOPOS_MINIMUM_TCE_CLUSTER_SIZE")]
     pub minimum_tce_cluster_size: Option<usize>,
+
+    /// Path of a JSON file holding the Proofs of Delivery of the source heads to start the
+    /// synchronization from. If not provided the whole history is synchronized
+    #[arg(long, env = "TCE_TRUSTED_CHECKPOINT")]
+    pub trusted_checkpoint: Option<PathBuf>,
+
+    /// Synchronize the history prior to the trusted checkpoint once the node is up to date
+    #[arg(long, env = "TCE_SYNC_BACKFILL")]
+    pub sync_backfill: bool,
+
+    /// Maximum number of certificates kept in the pending pools for specific source subnets,
+    /// pairs of <SubnetId>=<max>, comma separated. The other subnets are limited to
+    /// TOPOS_PENDING_POOL_MAX_CERTIFICATES_PER_SUBNET
+    #[arg(long, env = "TCE_PENDING_POOL_LIMITS")]
+    pub pending_pool_limits: Option<String>,
+
+    #[command(flatten)]
+    pub(crate) storage_tuning: StorageTuningArgs,
+
+    #[command(flatten)]
+    pub(crate) sync_limits: SyncLimitsArgs,
+}
+
+/// Limits applied to the synchronization requests of the other nodes,
+/// the defaults of `SynchronizerServiceLimits` are kept for the ones not set
+#[derive(Args, Debug, Clone, Default, Serialize)]
+pub(crate) struct SyncLimitsArgs {
+    /// Maximum number of Proofs of Delivery returned per checkpoint page
+    #[arg(long, env = "TCE_SYNC_MAX_CHECKPOINT_DIFF_SIZE")]
+    pub(crate) sync_max_checkpoint_diff_size: Option<usize>,
+
+    /// Maximum number of certificates a node can ask for per request
+    #[arg(long, env = "TCE_SYNC_MAX_CERTIFICATES_PER_REQUEST")]
+    pub(crate) sync_max_certificates_per_request: Option<usize>,
+
+    /// Maximum number of pending certificate ids returned per request
+    #[arg(long, env = "TCE_SYNC_MAX_PENDING_CERTIFICATE_IDS")]
+    pub(crate) sync_max_pending_certificate_ids: Option<usize>,
+
+    /// Maximum number of synchronization requests served per node each second
+    #[arg(long, env = "TCE_SYNC_MAX_REQUESTS_PER_SECOND")]
+    pub(crate) sync_max_requests_per_second: Option<u32>,
+}
+
+impl From<SyncLimitsArgs> for SynchronizerServiceLimits {
+    fn from(args: SyncLimitsArgs) -> Self {
+        let defaults = Self::default();
+
+        Self {
+            max_checkpoint_diff_size: args
+                .sync_max_checkpoint_diff_size
+                .unwrap_or(defaults.max_checkpoint_diff_size),
+            max_certificates_per_request: args
+                .sync_max_certificates_per_request
+                .unwrap_or(defaults.max_certificates_per_request),
+            max_pending_certificate_ids: args
+                .sync_max_pending_certificate_ids
+                .unwrap_or(defaults.max_pending_certificate_ids),
+            max_requests_per_window: args
+                .sync_max_requests_per_second
+                .unwrap_or(defaults.max_requests_per_window),
+            ..defaults
+        }
+    }
 }
 
 impl Run {
@@ -110,4 +192,25 @@ impl Run {
 
         Ok(HashSet::new())
     }
+
+    pub fn parse_subnet_group_keys(&self) -> Result<SubnetGroupKeys, String> {
+        self.subnet_group_keys
+            .as_deref()
+            .map(parse_subnet_group_keys)
+            .unwrap_or_else(|| Ok(SubnetGroupKeys::new()))
+    }
+
+    pub fn parse_pending_pool_limits(&self) -> Result<HashMap<SubnetId, usize>, String> {
+        self.pending_pool_limits
+            .as_deref()
+            .map(parse_pending_pool_limits)
+            .unwrap_or_else(|| Ok(HashMap::new()))
+    }
+
+    pub fn parse_trusted_checkpoint(&self) -> Result<Option<TrustedCheckpoint>, String> {
+        self.trusted_checkpoint
+            .as_deref()
+            .map(read_trusted_checkpoint)
+            .transpose()
+    }
 }
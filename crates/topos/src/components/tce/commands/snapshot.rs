@@ -0,0 +1,48 @@
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+
+use super::storage::StorageTuningArgs;
+
+/// Export or import the delivered certificates of a stopped node
+#[derive(Args, Debug)]
+pub(crate) struct Snapshot {
+    #[command(subcommand)]
+    pub(crate) subcommands: SnapshotCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum SnapshotCommands {
+    Export(SnapshotExport),
+    Import(SnapshotImport),
+}
+
+/// Write the delivered certificates of the storage into a snapshot archive
+#[derive(Args, Debug)]
+pub(crate) struct SnapshotExport {
+    /// Storage database path of the node
+    #[arg(long, env = "TCE_DB_PATH")]
+    pub(crate) db_path: PathBuf,
+
+    /// Path of the snapshot archive to create
+    #[arg(long)]
+    pub(crate) output: PathBuf,
+
+    #[command(flatten)]
+    pub(crate) storage_tuning: StorageTuningArgs,
+}
+
+/// Restore the delivered certificates of a snapshot archive into an empty storage
+#[derive(Args, Debug)]
+pub(crate) struct SnapshotImport {
+    /// Storage database path of the node
+    #[arg(long, env = "TCE_DB_PATH")]
+    pub(crate) db_path: PathBuf,
+
+    /// Path of the snapshot archive to restore
+    #[arg(long)]
+    pub(crate) input: PathBuf,
+
+    #[command(flatten)]
+    pub(crate) storage_tuning: StorageTuningArgs,
+}
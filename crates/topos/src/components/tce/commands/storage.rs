@@ -0,0 +1,63 @@
+use std::path::PathBuf;
+
+use clap::{Args, Subcommand};
+use serde::Serialize;
+use topos_tce_storage::tuning::{Compression, StorageTuning};
+
+/// Inspect the storage of a stopped node
+#[derive(Args, Debug)]
+pub(crate) struct Storage {
+    #[command(subcommand)]
+    pub(crate) subcommands: StorageCommands,
+}
+
+#[derive(Subcommand, Debug)]
+pub(crate) enum StorageCommands {
+    Check(StorageCheck),
+}
+
+/// Look for inconsistencies between the certificates, streams, indexes and pending pools
+#[derive(Args, Debug)]
+pub(crate) struct StorageCheck {
+    /// Storage database path of the node
+    #[arg(long, env = "TCE_DB_PATH")]
+    pub(crate) db_path: PathBuf,
+
+    /// Fix the inconsistencies which can be repaired from the stored data
+    #[arg(long)]
+    pub(crate) repair: bool,
+
+    #[command(flatten)]
+    pub(crate) storage_tuning: StorageTuningArgs,
+}
+
+/// Tuning of the RocksDB databases, RocksDB defaults are kept for the ones not set
+#[derive(Args, Debug, Clone, Default, Serialize)]
+pub(crate) struct StorageTuningArgs {
+    /// Size in bytes of the block cache shared by all the databases
+    #[arg(long, env = "TCE_STORAGE_BLOCK_CACHE_SIZE")]
+    pub(crate) storage_block_cache_size: Option<usize>,
+
+    /// Compression of the blocks written on disk: none, snappy, lz4 or zstd
+    #[arg(long, env = "TCE_STORAGE_COMPRESSION")]
+    pub(crate) storage_compression: Option<Compression>,
+
+    /// Size in bytes a memtable reaches before being flushed on disk
+    #[arg(long, env = "TCE_STORAGE_WRITE_BUFFER_SIZE")]
+    pub(crate) storage_write_buffer_size: Option<usize>,
+
+    /// Maximum number of memtables kept in memory for each column family
+    #[arg(long, env = "TCE_STORAGE_MAX_WRITE_BUFFER_NUMBER")]
+    pub(crate) storage_max_write_buffer_number: Option<i32>,
+}
+
+impl From<StorageTuningArgs> for StorageTuning {
+    fn from(args: StorageTuningArgs) -> Self {
+        Self {
+            block_cache_size: args.storage_block_cache_size,
+            compression: args.storage_compression,
+            write_buffer_size: args.storage_write_buffer_size,
+            max_write_buffer_number: args.storage_max_write_buffer_number,
+        }
+    }
+}
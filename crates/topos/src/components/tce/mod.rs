@@ -11,13 +11,13 @@ use topos_core::api::grpc::tce::v1::{
     api_service_client::ApiServiceClient, console_service_client::ConsoleServiceClient,
 };
 use topos_p2p::config::NetworkConfig;
-use topos_tce::config::{AuthKey, StorageConfiguration, TceConfiguration};
+use topos_tce::config::{ApiConfiguration, AuthKey, StorageConfiguration, TceConfiguration};
 use tower::Service;
 use tracing::{debug, error, info, warn};
 
 use crate::tracing::setup_tracing;
 
-use self::commands::{TceCommand, TceCommands};
+use self::commands::{SnapshotCommands, StorageCommands, TceCommand, TceCommands};
 
 pub(crate) mod commands;
 pub(crate) mod parser;
@@ -80,6 +80,9 @@ pub(crate) async fn handle_command(
                 validators: cmd
                     .parse_validators()
                     .map_err(|_| Box::new(topos::Error::InvalidValidatorAddress))?,
+                subnet_group_keys: cmd
+                    .parse_subnet_group_keys()
+                    .map_err(|e| Box::new(topos::Error::InvalidSubnetGroupKey(e)))?,
                 auth_key: cmd
                     .local_key_seed
                     .clone()
@@ -99,15 +102,28 @@ pub(crate) async fn handle_command(
                 api_addr: cmd.api_addr,
                 graphql_api_addr: cmd.graphql_api_addr,
                 metrics_api_addr: cmd.metrics_api_addr,
-                storage: StorageConfiguration::RocksDB(
-                    cmd.db_path
-                        .as_ref()
-                        .and_then(|path| PathBuf::from_str(path).ok()),
-                ),
+                storage: if cmd.in_memory_storage {
+                    StorageConfiguration::RAM
+                } else {
+                    StorageConfiguration::RocksDB(
+                        cmd.db_path
+                            .as_ref()
+                            .and_then(|path| PathBuf::from_str(path).ok()),
+                    )
+                },
                 network_bootstrap_timeout: Duration::from_secs(10),
                 minimum_cluster_size: cmd
                     .minimum_tce_cluster_size
                     .unwrap_or(NetworkConfig::MINIMUM_CLUSTER_SIZE),
+                storage_tuning: cmd.storage_tuning.clone().into(),
+                pending_pool_limits: cmd
+                    .parse_pending_pool_limits()
+                    .map_err(|e| Box::new(topos::Error::InvalidPendingPoolLimit(e)))?,
+                trusted_checkpoint: cmd
+                    .parse_trusted_checkpoint()
+                    .map_err(|e| Box::new(topos::Error::InvalidTrustedCheckpoint(e)))?,
+                sync_backfill: cmd.sync_backfill,
+                synchronizer_limits: cmd.sync_limits.clone().into(),
                 version: env!("TOPOS_VERSION"),
             };
 
@@ -143,6 +159,53 @@ pub(crate) async fn handle_command(
             Ok(())
         }
 
+        Some(TceCommands::Api(cmd)) => {
+            let config = ApiConfiguration {
+                secondary_db_path: cmd.secondary_db_path(),
+                db_path: cmd.db_path,
+                api_addr: cmd.api_addr,
+                graphql_api_addr: cmd.graphql_api_addr,
+                metrics_api_addr: cmd.metrics_api_addr,
+                catch_up_interval: Duration::from_millis(cmd.catch_up_interval_ms),
+                storage_tuning: cmd.storage_tuning.into(),
+            };
+
+            info!(
+                "Serving the API of the node storage at {:?}",
+                config.db_path
+            );
+            warn!("API gRPC endpoint reachable at {}", config.api_addr);
+            info!(
+                "API GraphQL endpoint reachable at {}",
+                config.graphql_api_addr
+            );
+
+            let shutdown_token = CancellationToken::new();
+            let shutdown_trigger = shutdown_token.clone();
+
+            let (shutdown_sender, mut shutdown_receiver) = mpsc::channel(1);
+
+            tokio::select! {
+                _ = signal::ctrl_c() => {
+                    info!("Received ctrl_c, shutting down application...");
+                    shutdown_trigger.cancel();
+
+                    // Wait that all sender get dropped
+                    let _ = shutdown_receiver.recv().await;
+
+                    info!("Shutdown procedure finished, exiting...");
+                }
+                result = topos_tce::api_node::run(&config, (shutdown_token, shutdown_sender)) => {
+                    if let Err(ref error) = result {
+                        error!("API node terminated {:?}", error);
+                        std::process::exit(1);
+                    }
+                }
+            }
+
+            Ok(())
+        }
+
         Some(TceCommands::Keys(cmd)) => {
             if let Some(slice) = cmd.from_seed {
                 println!(
@@ -156,6 +219,20 @@ pub(crate) async fn handle_command(
             Ok(())
         }
 
+        Some(TceCommands::Snapshot(cmd)) => {
+            let result = match cmd.subcommands {
+                SnapshotCommands::Export(cmd) => services::snapshot::export(cmd),
+                SnapshotCommands::Import(cmd) => services::snapshot::import(cmd).await,
+            };
+
+            if let Err(error) = result {
+                error!("Snapshot failed: {error}");
+                std::process::exit(1);
+            }
+
+            Ok(())
+        }
+
         Some(TceCommands::Status(status)) => {
             debug!("Start executing Status command");
 
@@ -167,6 +244,17 @@ pub(crate) async fn handle_command(
             std::process::exit(exit_code);
         }
 
+        Some(TceCommands::Storage(cmd)) => match cmd.subcommands {
+            StorageCommands::Check(cmd) => match services::storage::check(cmd) {
+                Ok(true) => Ok(()),
+                Ok(false) => std::process::exit(1),
+                Err(error) => {
+                    error!("Storage check failed: {error}");
+                    std::process::exit(1);
+                }
+            },
+        },
+
         None => Ok(()),
     }
 }
@@ -174,8 +262,10 @@ pub(crate) async fn handle_command(
 pub fn print_node_info(config: &TceConfiguration) {
     tracing::warn!("Topos - version: {}", config.version);
 
-    if let StorageConfiguration::RocksDB(Some(ref path)) = config.storage {
-        info!("RocksDB at {:?}", path);
+    match config.storage {
+        StorageConfiguration::RocksDB(Some(ref path)) => info!("RocksDB at {:?}", path),
+        StorageConfiguration::RAM => warn!("In-memory storage, nothing will be persisted"),
+        StorageConfiguration::RocksDB(None) => {}
     }
 
     warn!("API gRPC endpoint reachable at {}", config.api_addr);
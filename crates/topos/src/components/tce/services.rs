@@ -1,2 +1,4 @@
 pub(crate) mod push_certificate;
+pub(crate) mod snapshot;
 mod status;
+pub(crate) mod storage;
@@ -0,0 +1,70 @@
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+};
+
+use topos_tce_storage::{errors::SnapshotError, snapshot, tuning};
+use tracing::{info, warn};
+
+use crate::components::tce::commands::snapshot::{SnapshotExport, SnapshotImport};
+
+pub(crate) fn export(
+    SnapshotExport {
+        db_path,
+        output,
+        storage_tuning,
+    }: SnapshotExport,
+) -> Result<(), SnapshotError> {
+    tuning::configure(storage_tuning.into())?;
+
+    // Opening the storage as a primary instance would create it when missing
+    if !db_path.join("perpetual").is_dir() {
+        return Err(SnapshotError::StoreNotFound(db_path));
+    }
+
+    // The storage is only read, through a secondary instance removed once exported
+    let secondary_path =
+        std::env::temp_dir().join(format!("topos-snapshot-{}", std::process::id()));
+    let store =
+        topos_tce::open_secondary_validator_store(&db_path, &secondary_path).get_fullnode_store();
+
+    let exported = File::create(&output)
+        .map_err(SnapshotError::from)
+        .and_then(|file| snapshot::export(&store, BufWriter::new(file)));
+
+    drop(store);
+    if let Err(error) = std::fs::remove_dir_all(&secondary_path) {
+        warn!("Unable to remove the secondary storage {secondary_path:?}: {error}");
+    }
+
+    let summary = exported?;
+
+    info!(
+        "{} certificates of {} subnets exported to {:?}",
+        summary.certificates, summary.subnets, output
+    );
+
+    Ok(())
+}
+
+pub(crate) async fn import(
+    SnapshotImport {
+        db_path,
+        input,
+        storage_tuning,
+    }: SnapshotImport,
+) -> Result<(), SnapshotError> {
+    tuning::configure(storage_tuning.into())?;
+
+    let store = topos_tce::open_fullnode_store(&db_path);
+    let reader = BufReader::new(File::open(&input)?);
+
+    let summary = snapshot::import(&store, reader).await?;
+
+    info!(
+        "{} certificates of {} subnets imported into {:?}",
+        summary.certificates, summary.subnets, db_path
+    );
+
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use topos_tce_storage::{check, errors::StorageError, tuning};
+use tracing::{info, warn};
+
+use crate::components::tce::commands::storage::StorageCheck;
+
+/// Runs the storage check, returning whether the storage is consistent
+pub(crate) fn check(
+    StorageCheck {
+        db_path,
+        repair,
+        storage_tuning,
+    }: StorageCheck,
+) -> Result<bool, StorageError> {
+    tuning::configure(storage_tuning.into())?;
+    let store = topos_tce::open_validator_store(&db_path);
+
+    let remaining = if repair {
+        let report = check::repair(&store)?;
+        for inconsistency in &report.repaired {
+            info!("Repaired: {inconsistency}");
+        }
+
+        report.remaining
+    } else {
+        let report = check::check(&store)?;
+        info!("{} delivered certificates checked", report.certificates);
+
+        report.inconsistencies
+    };
+
+    for inconsistency in &remaining {
+        if inconsistency.is_repairable() {
+            warn!("{inconsistency} (repairable)");
+        } else {
+            warn!("{inconsistency}");
+        }
+    }
+
+    if remaining.is_empty() {
+        info!("Storage at {:?} is consistent", db_path);
+    }
+
+    Ok(remaining.is_empty())
+}
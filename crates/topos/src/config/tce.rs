@@ -10,6 +10,8 @@ use serde::{Deserialize, Serialize};
 use crate::components::tce::commands::Run;
 use crate::config::Config;
 use topos_p2p::{Multiaddr, PeerId};
+use topos_tce::config::SynchronizerServiceLimits;
+use topos_tce_storage::tuning::StorageTuning;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "kebab-case")]
@@ -17,6 +19,9 @@ pub struct TceConfig {
     /// Storage database path, if not set RAM storage is used
     #[serde(default = "default_db_path")]
     pub db_path: PathBuf,
+    /// Keep the storage in memory instead of using the database path, nothing is persisted
+    #[serde(default)]
+    pub in_memory_storage: bool,
     /// Array of extra boot nodes to connect to
     pub extra_boot_peers: Option<String>,
     /// Ip for the p2p Multiaddr
@@ -25,6 +30,10 @@ pub struct TceConfig {
     pub tce_local_port: Option<u16>,
     /// Local peer secret key seed (optional, used for testing)
     pub local_key_seed: Option<String>,
+    /// Group public keys of the subnets signing their certificates with a threshold
+    /// signature, pairs of <SubnetId>=<GroupPublicKey>, comma separated. The certificates
+    /// of the other subnets must be signed with the key their subnet id is derived from
+    pub subnet_group_keys: Option<String>,
     /// Connection degree for the GossipSub overlay
     pub minimum_tce_cluster_size: Option<usize>,
     /// gRPC API Addr
@@ -45,6 +54,21 @@ pub struct TceConfig {
     /// Otlp service name
     /// If not provided open telemetry will not be used
     pub otlp_service_name: Option<String>,
+    /// Tuning of the RocksDB databases of the storage
+    #[serde(default)]
+    pub storage: StorageTuning,
+    /// Path of a JSON file holding the Proofs of Delivery of the source heads
+    /// to start the synchronization from
+    pub trusted_checkpoint: Option<PathBuf>,
+    /// Synchronize the history prior to the trusted checkpoint once the node is up to date
+    #[serde(default)]
+    pub sync_backfill: bool,
+    /// Limits applied to the synchronization requests of the other nodes
+    #[serde(default)]
+    pub sync_limits: SynchronizerServiceLimits,
+    /// Maximum number of certificates kept in the pending pools for specific source subnets,
+    /// pairs of <SubnetId>=<max>, comma separated
+    pub pending_pool_limits: Option<String>,
 }
 
 fn default_db_path() -> PathBuf {
@@ -25,6 +25,12 @@ pub enum Error {
     InvalidPrivateKey,
     #[error("Invalid Validator address")]
     InvalidValidatorAddress,
+    #[error("Invalid subnet group public key: {0}")]
+    InvalidSubnetGroupKey(String),
+    #[error("Invalid trusted checkpoint: {0}")]
+    InvalidTrustedCheckpoint(String),
+    #[error("Invalid pending pool limit: {0}")]
+    InvalidPendingPoolLimit(String),
 }
 
 fn map_arch(arch: &str) -> &str {
@@ -6,7 +6,8 @@ use assert_cmd::prelude::*;
 use tonic::{Request, Response, Status};
 
 use topos_core::api::grpc::tce::v1::{
-    console_service_server::ConsoleService, StatusRequest, StatusResponse,
+    console_service_server::ConsoleService, GetMisbehavioursRequest, GetMisbehavioursResponse,
+    GetStorageStatisticsRequest, GetStorageStatisticsResponse, StatusRequest, StatusResponse,
 };
 
 #[test]
@@ -44,4 +45,18 @@ impl ConsoleService for DummyServer {
     async fn status(&self, _: Request<StatusRequest>) -> Result<Response<StatusResponse>, Status> {
         unimplemented!()
     }
+
+    async fn get_misbehaviours(
+        &self,
+        _: Request<GetMisbehavioursRequest>,
+    ) -> Result<Response<GetMisbehavioursResponse>, Status> {
+        unimplemented!()
+    }
+
+    async fn get_storage_statistics(
+        &self,
+        _: Request<GetStorageStatisticsRequest>,
+    ) -> Result<Response<GetStorageStatisticsResponse>, Status> {
+        unimplemented!()
+    }
 }